@@ -0,0 +1,151 @@
+//! Renderer interface.
+//!
+//! A `Frontend` owns the render state and turns paths into vertices; a
+//! `RenderBackend` owns the textures and draws what the frontend hands
+//! it. The functions mirror the render callbacks in NanoVG's
+//! `NVGparams`, so porting one of its backends is mostly mechanical.
+//!
+//! All vertices are in window space. Backends are free to queue the
+//! draw calls and only render them on `flush`.
+
+use crate::math::Transform;
+use crate::path::Vertex;
+use crate::{CompositeOperationState, ImageFlags, ImageId, Paint};
+
+/// Pixel format of a texture.
+///
+/// Alpha textures sample as white with the stored alpha, so image
+/// patterns using them are tinted by the inner color of the paint the
+/// way text is. All other formats without alpha are opaque.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureType {
+    /// One byte of coverage per pixel.
+    Alpha     = 0x01,
+    /// Four bytes per pixel, red, green, blue and alpha.
+    Rgba      = 0x02,
+    /// Three bytes per pixel, red, green and blue.
+    Rgb       = 0x03,
+    /// One byte of gray per pixel.
+    Luminance = 0x04,
+    /// Four little endian 16-bit floats per pixel, red, green, blue and
+    /// alpha. Values are clamped to 0..1 when sampled.
+    RgbaF16   = 0x05,
+}
+
+impl TextureType {
+    /// Returns the size of one pixel in bytes.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureType::Alpha     => 1,
+            TextureType::Rgba      => 4,
+            TextureType::Rgb       => 3,
+            TextureType::Luminance => 1,
+            TextureType::RgbaF16   => 8,
+        }
+    }
+}
+
+/// Rectangle which clips rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct Scissor {
+    /// Transform from the space of the rectangle, with the origin at its
+    /// center, to window space.
+    pub xform:  Transform,
+    /// Half of the width and height of the rectangle.
+    pub extent: [f32; 2],
+}
+
+impl Scissor {
+    /// Creates the scissor of the rectangle at (x,y) with size (w,h) in
+    /// the space of `xform`. Negative sizes are clamped to zero.
+    pub fn new(x: f32, y: f32, w: f32, h: f32, xform: &Transform) -> Self {
+        let w = w.max(0.0);
+        let h = h.max(0.0);
+        let mut t = Transform::identity();
+        t.set_translate(x + w * 0.5, y + h * 0.5);
+        t.set_multiply(xform);
+        Scissor{
+            xform:  t,
+            extent: [w * 0.5, h * 0.5],
+        }
+    }
+
+    /// Returns the transform from window space to the space of the
+    /// rectangle, where it spans `-extent..extent` around the origin.
+    pub fn inverse_xform(&self) -> Transform {
+        self.xform.invert().unwrap_or_else(Transform::identity)
+    }
+
+    /// Returns how many fringe widths one unit of the rectangle spans
+    /// along each of its axes. Scaling the distance to the edge by this
+    /// antialiases the scissor the way NanoVG's shaders do.
+    pub fn scale(&self, fringe: f32) -> [f32; 2] {
+        let m = &self.xform.m;
+        [
+            f32::sqrt(m[0] * m[0] + m[2] * m[2]) / fringe,
+            f32::sqrt(m[1] * m[1] + m[3] * m[3]) / fringe,
+        ]
+    }
+}
+
+/// The vertices of one flattened path.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderPath<'a> {
+    /// Triangle fan covering the interior of the path. Empty for strokes.
+    pub fill:   &'a [Vertex],
+    /// Triangle strip of the stroke, or of the antialiasing fringe of a
+    /// fill.
+    pub stroke: &'a [Vertex],
+    /// True when the path is convex and can be filled without a stencil.
+    pub convex: bool,
+}
+
+pub trait RenderBackend {
+    /// Creates a texture and returns its handle, or `None` on failure.
+    /// When `data` is `None` the contents are left undefined. Backends
+    /// usually hand out the handles from an `image::ImageRegistry`.
+    fn create_texture(&mut self, texture_type: TextureType, width: usize, height: usize,
+                      image_flags: ImageFlags, data: Option<&[u8]>) -> Option<ImageId>;
+
+    /// Deletes a texture. Returns false if the handle is not valid.
+    fn delete_texture(&mut self, image: ImageId) -> bool;
+
+    /// Copies pixels into a region of the texture. `data` holds the rows of
+    /// the region, starting at its top-left pixel, `stride` bytes apart.
+    /// The frontend checks the region against the texture size. Returns
+    /// false if the handle is not valid.
+    fn update_texture(&mut self, image: ImageId, x: usize, y: usize, width: usize, height: usize,
+                      data: &[u8], stride: usize) -> bool;
+
+    /// Returns the width and height of a texture, `None` if the handle is
+    /// not valid.
+    fn texture_size(&self, image: ImageId) -> Option<(usize, usize)>;
+
+    /// Returns the pixel format of a texture, `None` if the handle is not
+    /// valid.
+    fn texture_type(&self, image: ImageId) -> Option<TextureType>;
+
+    /// Called at the beginning of a frame with the window size and the
+    /// number of pixels per window unit.
+    fn viewport(&mut self, width: f32, height: f32, device_pixel_ratio: f32);
+
+    /// Drops the draw calls of the current frame.
+    fn cancel(&mut self);
+
+    /// Renders the draw calls of the current frame.
+    fn flush(&mut self);
+
+    /// Fills paths with the non-zero winding rule. The fringe strips are
+    /// drawn only outside of the filled area.
+    fn fill(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+            fringe: f32, bounds: [f32; 4], paths: &[RenderPath]);
+
+    /// Draws the stroke strips of paths.
+    fn stroke(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+              fringe: f32, stroke_width: f32, paths: &[RenderPath]);
+
+    /// Draws a list of triangles, sampling the image of the paint at the
+    /// texture coordinates of the vertices.
+    fn triangles(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+                 verts: &[Vertex], fringe: f32);
+}
@@ -0,0 +1,728 @@
+//! The Unicode Bidirectional Algorithm, UAX #9.
+//!
+//! `BidiInfo` splits a string into paragraphs and resolves the embedding
+//! level of each of its characters: even levels are left to right, odd
+//! levels right to left. `BidiInfo::visual_runs` then orders the runs of
+//! one line of a paragraph for display.
+//!
+//! Levels are kept per byte, so that they can be looked up by the byte
+//! offsets the text layout works with.
+
+mod tables;
+
+use crate::TextDirection;
+
+/// Embedding levels go at most this deep.
+const MAX_DEPTH: u8 = 125;
+
+/// Bracket pairs nest at most this deep.
+const MAX_BRACKETS: usize = 63;
+
+/// Bidirectional character types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidiClass {
+    /// Left to right letters.
+    L,
+    /// Right to left letters.
+    R,
+    /// Arabic letters.
+    AL,
+    /// European digits.
+    EN,
+    /// European number separators, plus and minus.
+    ES,
+    /// European number terminators, such as currency signs.
+    ET,
+    /// Arabic digits.
+    AN,
+    /// Number separators shared by both, such as comma and colon.
+    CS,
+    /// Non-spacing marks.
+    NSM,
+    /// Boundary neutrals, control and format characters.
+    BN,
+    /// Paragraph separators.
+    B,
+    /// Segment separators, tabs.
+    S,
+    /// White space.
+    WS,
+    /// Other neutrals.
+    ON,
+    LRE,
+    LRO,
+    RLE,
+    RLO,
+    PDF,
+    LRI,
+    RLI,
+    FSI,
+    PDI,
+}
+
+use self::BidiClass::*;
+
+/// Returns the bidi class of a character.
+pub fn bidi_class(c: char) -> BidiClass {
+    let c = c as u32;
+    match tables::BIDI_CLASSES.binary_search_by(|&(start, end, _)| {
+        if end < c {
+            std::cmp::Ordering::Less
+        } else if start > c {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }) {
+        Ok(i) => tables::BIDI_CLASSES[i].2,
+        Err(_) => L,
+    }
+}
+
+/// Returns the mirrored form of a character, which right to left text
+/// shows in its place, such as `)` for `(`.
+pub fn mirror(c: char) -> Option<char> {
+    let c = c as u32;
+    tables::MIRRORS.binary_search_by_key(&c, |&(from, _)| from).ok()
+        .and_then(|i| std::char::from_u32(tables::MIRRORS[i].1))
+}
+
+/// Returns the closing bracket of an opening bracket.
+fn closing_bracket(c: char) -> Option<char> {
+    let c = c as u32;
+    tables::BRACKETS.binary_search_by_key(&c, |&(open, _)| open).ok()
+        .and_then(|i| std::char::from_u32(tables::BRACKETS[i].1))
+}
+
+/// Maps brackets to their canonical equivalents for pairing.
+fn canonical_bracket(c: char) -> char {
+    match c {
+        '\u{2329}' => '\u{3008}',
+        '\u{232a}' => '\u{3009}',
+        c => c,
+    }
+}
+
+fn is_isolate_initiator(class: BidiClass) -> bool {
+    class == LRI || class == RLI || class == FSI
+}
+
+/// Returns true for the classes X9 removes.
+fn is_removed(class: BidiClass) -> bool {
+    matches!(class, RLE | LRE | RLO | LRO | PDF | BN)
+}
+
+/// Returns true for neutral and isolate classes, which N1 and N2 resolve.
+fn is_neutral(class: BidiClass) -> bool {
+    matches!(class, B | S | WS | ON | LRI | RLI | FSI | PDI)
+}
+
+/// Returns the strong direction of a class for the neutral rules, which
+/// count numbers as right to left.
+fn strong_direction(class: BidiClass) -> Option<BidiClass> {
+    match class {
+        L => Some(L),
+        R | AL | EN | AN => Some(R),
+        _ => None,
+    }
+}
+
+/// Direction of a level.
+fn direction(level: u8) -> BidiClass {
+    if level.is_multiple_of(2) { L } else { R }
+}
+
+/// A paragraph of a string with its base level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Paragraph {
+    /// Byte range of the paragraph, including its separator.
+    pub start: usize,
+    pub end:   usize,
+    pub level: u8,
+}
+
+/// A run of characters with one level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    /// Byte range of the run.
+    pub start: usize,
+    pub end:   usize,
+    pub level: u8,
+}
+
+impl Run {
+    /// Returns true if the run is written from right to left.
+    pub fn is_rtl(&self) -> bool {
+        self.level % 2 == 1
+    }
+}
+
+/// The resolved levels of a string.
+#[derive(Debug, Clone, Default)]
+pub struct BidiInfo {
+    /// Bidi class of each byte.
+    classes:        Vec<BidiClass>,
+    /// Resolved level of each byte.
+    pub levels:     Vec<u8>,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+impl BidiInfo {
+    /// Resolves the levels of a string. The base level of each paragraph
+    /// comes from `direction`, or from its first strong character if the
+    /// direction is `Auto`.
+    pub fn new(text: &str, direction: TextDirection) -> BidiInfo {
+        let mut info = BidiInfo{
+            classes:    Vec::with_capacity(text.len()),
+            levels:     Vec::with_capacity(text.len()),
+            paragraphs: Vec::new(),
+        };
+        for c in text.chars() {
+            let class = bidi_class(c);
+            for _ in 0..c.len_utf8() {
+                info.classes.push(class);
+            }
+        }
+
+        // Text which is left to right throughout needs no resolving.
+        if direction != TextDirection::RightToLeft && !info.classes.iter().any(|&class| needs_resolving(class)) {
+            info.levels.resize(text.len(), 0);
+            info.paragraphs.push(Paragraph{ start: 0, end: text.len(), level: 0 });
+            return info;
+        }
+
+        let mut start = 0;
+        while start < text.len() || info.paragraphs.is_empty() {
+            // P1: Split into paragraphs, the separator ends a paragraph.
+            let end = text[start..].char_indices()
+                .find(|&(i, _)| info.classes[start + i] == B)
+                .map_or(text.len(), |(i, c)| start + i + c.len_utf8());
+            let chars: Vec<char> = text[start..end].chars().collect();
+            let classes: Vec<BidiClass> = chars.iter().map(|&c| bidi_class(c)).collect();
+            let level = match direction {
+                TextDirection::LeftToRight => 0,
+                TextDirection::RightToLeft => 1,
+                TextDirection::Auto => {
+                    let matching = matching_pdis(&classes);
+                    first_strong(&classes, &matching, 0, classes.len()).unwrap_or(0)
+                }
+            };
+            let levels = resolve_paragraph(&chars, &classes, level);
+            for (c, level) in chars.iter().zip(levels) {
+                for _ in 0..c.len_utf8() {
+                    info.levels.push(level);
+                }
+            }
+            info.paragraphs.push(Paragraph{ start: start, end: end, level: level });
+            start = end;
+        }
+        info
+    }
+
+    /// Returns true if any character is right to left.
+    pub fn has_rtl(&self) -> bool {
+        self.levels.iter().any(|&level| level % 2 == 1)
+    }
+
+    /// Returns the level of the paragraph containing a byte offset.
+    pub fn paragraph_level(&self, offset: usize) -> u8 {
+        self.paragraphs.iter()
+            .find(|paragraph| offset < paragraph.end)
+            .or_else(|| self.paragraphs.last())
+            .map_or(0, |paragraph| paragraph.level)
+    }
+
+    /// Returns the runs of the line `start..end` in visual order, from
+    /// left to right. The line must lie within one paragraph.
+    pub fn visual_runs(&self, text: &str, start: usize, end: usize) -> Vec<Run> {
+        let mut runs = Vec::new();
+        if start >= end {
+            return runs;
+        }
+        let para_level = self.paragraph_level(start);
+
+        // L1: Separators and the white space before them or at the end of
+        // the line go back to the paragraph level.
+        let mut levels: Vec<u8> = self.levels[start..end].to_vec();
+        let mut trailing = true;
+        for (i, c) in text[start..end].char_indices().rev() {
+            let class = self.classes[start + i];
+            let len = c.len_utf8();
+            if class == S || class == B {
+                trailing = true;
+                levels[i..i + len].iter_mut().for_each(|level| *level = para_level);
+            } else if trailing && (class == WS || is_isolate_initiator(class) || class == PDI || is_removed(class)) {
+                levels[i..i + len].iter_mut().for_each(|level| *level = para_level);
+            } else {
+                trailing = false;
+            }
+        }
+
+        for (i, &level) in levels.iter().enumerate() {
+            match runs.last_mut() {
+                Some(run) if run.level == level => run.end = start + i + 1,
+                _ => runs.push(Run{ start: start + i, end: start + i + 1, level: level }),
+            }
+        }
+
+        // L2: From the highest level to the lowest odd level, reverse every
+        // sequence of runs at that level or higher.
+        let highest = runs.iter().map(|run| run.level).max().unwrap_or(0);
+        let lowest_odd = runs.iter().map(|run| run.level | 1).min().unwrap_or(1);
+        let mut level = highest;
+        while level >= lowest_odd && level > 0 {
+            let mut i = 0;
+            while i < runs.len() {
+                if runs[i].level >= level {
+                    let mut j = i;
+                    while j < runs.len() && runs[j].level >= level {
+                        j += 1;
+                    }
+                    runs[i..j].reverse();
+                    i = j;
+                } else {
+                    i += 1;
+                }
+            }
+            level -= 1;
+        }
+        runs
+    }
+}
+
+/// Returns true for the classes which can make text right to left.
+fn needs_resolving(class: BidiClass) -> bool {
+    matches!(class, R | AL | AN | RLE | RLO | RLI | FSI)
+}
+
+/// BD9: Returns the matching PDI of each isolate initiator.
+fn matching_pdis(classes: &[BidiClass]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; classes.len()];
+    let mut open = Vec::new();
+    for (i, &class) in classes.iter().enumerate() {
+        if is_isolate_initiator(class) {
+            open.push(i);
+        } else if class == PDI {
+            if let Some(initiator) = open.pop() {
+                matching[initiator] = Some(i);
+            }
+        }
+    }
+    matching
+}
+
+/// P2, P3: Returns the level of the first strong character in `start..end`,
+/// skipping isolates.
+fn first_strong(classes: &[BidiClass], matching: &[Option<usize>], start: usize, end: usize) -> Option<u8> {
+    let mut i = start;
+    while i < end {
+        match classes[i] {
+            L => return Some(0),
+            R | AL => return Some(1),
+            class if is_isolate_initiator(class) => match matching[i] {
+                Some(pdi) => i = pdi,
+                None => return None,
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// An entry of the directional status stack.
+#[derive(Debug, Clone, Copy)]
+struct Status {
+    level:     u8,
+    override_: Option<BidiClass>,
+    isolate:   bool,
+}
+
+/// Resolves the levels of the characters of one paragraph.
+fn resolve_paragraph(chars: &[char], original: &[BidiClass], para_level: u8) -> Vec<u8> {
+    let n = chars.len();
+    let matching = matching_pdis(original);
+    let mut classes = original.to_vec();
+    let mut levels = vec![para_level; n];
+
+    // X1 - X8: Explicit embeddings, overrides and isolates.
+    let mut stack = vec![Status{ level: para_level, override_: None, isolate: false }];
+    let mut overflow_isolates = 0;
+    let mut overflow_embeddings = 0;
+    let mut valid_isolates = 0;
+    for i in 0..n {
+        let last = stack[stack.len() - 1];
+        match original[i] {
+            RLE | LRE | RLO | LRO => {
+                let class = original[i];
+                let level = if class == RLE || class == RLO { (last.level + 1) | 1 } else { (last.level + 2) & !1 };
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    let override_ = match class {
+                        RLO => Some(R),
+                        LRO => Some(L),
+                        _ => None,
+                    };
+                    stack.push(Status{ level: level, override_: override_, isolate: false });
+                } else if overflow_isolates == 0 {
+                    overflow_embeddings += 1;
+                }
+                levels[i] = last.level;
+            }
+            RLI | LRI | FSI => {
+                levels[i] = last.level;
+                if let Some(class) = last.override_ {
+                    classes[i] = class;
+                }
+                let rtl = match original[i] {
+                    RLI => true,
+                    FSI => first_strong(original, &matching, i + 1, matching[i].unwrap_or(n)) == Some(1),
+                    _ => false,
+                };
+                let level = if rtl { (last.level + 1) | 1 } else { (last.level + 2) & !1 };
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    valid_isolates += 1;
+                    stack.push(Status{ level: level, override_: None, isolate: true });
+                } else {
+                    overflow_isolates += 1;
+                }
+            }
+            PDI => {
+                if overflow_isolates > 0 {
+                    overflow_isolates -= 1;
+                } else if valid_isolates > 0 {
+                    overflow_embeddings = 0;
+                    while !stack[stack.len() - 1].isolate {
+                        stack.pop();
+                    }
+                    stack.pop();
+                    valid_isolates -= 1;
+                }
+                let last = stack[stack.len() - 1];
+                levels[i] = last.level;
+                if let Some(class) = last.override_ {
+                    classes[i] = class;
+                }
+            }
+            PDF => {
+                if overflow_isolates > 0 {
+                    // Ignored
+                } else if overflow_embeddings > 0 {
+                    overflow_embeddings -= 1;
+                } else if !last.isolate && stack.len() >= 2 {
+                    stack.pop();
+                }
+                levels[i] = last.level;
+            }
+            B => levels[i] = para_level,
+            BN => levels[i] = last.level,
+            _ => {
+                levels[i] = last.level;
+                if let Some(class) = last.override_ {
+                    classes[i] = class;
+                }
+            }
+        }
+    }
+
+    // X9: Embedding and override characters and boundary neutrals are
+    // ignored from here on.
+    let removed: Vec<bool> = original.iter().map(|&class| is_removed(class)).collect();
+
+    // X10: Split into level runs and chain them into isolating run
+    // sequences across isolates.
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    let mut prev_level = None;
+    for i in (0..n).filter(|&i| !removed[i]) {
+        if prev_level == Some(levels[i]) {
+            let last = runs.len() - 1;
+            runs[last].push(i);
+        } else {
+            runs.push(vec![i]);
+        }
+        prev_level = Some(levels[i]);
+    }
+    let mut run_of = vec![0; n];
+    for (r, run) in runs.iter().enumerate() {
+        for &i in run {
+            run_of[i] = r;
+        }
+    }
+    let mut is_matched_pdi = vec![false; n];
+    for &pdi in matching.iter().flatten() {
+        is_matched_pdi[pdi] = true;
+    }
+    for run in &runs {
+        if is_matched_pdi[run[0]] {
+            continue;
+        }
+        let mut sequence = run.clone();
+        loop {
+            let last = sequence[sequence.len() - 1];
+            match matching[last] {
+                Some(pdi) if is_isolate_initiator(original[last]) && !removed[pdi] => {
+                    sequence.extend_from_slice(&runs[run_of[pdi]]);
+                }
+                _ => break,
+            }
+        }
+        resolve_sequence(chars, original, &mut classes, &mut levels, &removed, &matching, &sequence, para_level);
+    }
+
+    // Removed characters take the level of the character before them.
+    for i in 0..n {
+        if removed[i] {
+            levels[i] = if i > 0 { levels[i - 1] } else { para_level };
+        }
+    }
+    levels
+}
+
+/// Resolves the weak and neutral types and the implicit levels of an
+/// isolating run sequence, given by the indices of its characters.
+fn resolve_sequence(chars: &[char], original: &[BidiClass], classes: &mut [BidiClass], levels: &mut [u8],
+                    removed: &[bool], matching: &[Option<usize>], sequence: &[usize], para_level: u8) {
+    let n = classes.len();
+    let first = sequence[0];
+    let last = sequence[sequence.len() - 1];
+    let level = levels[first];
+
+    // The start and end of sequence types come from the levels on either
+    // side of it.
+    let before = (0..first).rev().find(|&i| !removed[i]).map_or(para_level, |i| levels[i]);
+    let after = if is_isolate_initiator(original[last]) && matching[last].is_none() {
+        para_level
+    } else {
+        (last + 1..n).find(|&i| !removed[i]).map_or(para_level, |i| levels[i])
+    };
+    let sos = direction(level.max(before));
+    let eos = direction(level.max(after));
+
+    let mut types: Vec<BidiClass> = sequence.iter().map(|&i| classes[i]).collect();
+    let len = types.len();
+
+    // W1: Marks take the type of the character before them.
+    for k in 0..len {
+        if types[k] == NSM {
+            types[k] = match k {
+                0 => sos,
+                _ if is_isolate_initiator(types[k - 1]) || types[k - 1] == PDI => ON,
+                _ => types[k - 1],
+            };
+        }
+    }
+
+    // W2: European digits after Arabic letters are Arabic digits.
+    let mut strong = sos;
+    for t in types.iter_mut() {
+        match *t {
+            L | R | AL => strong = *t,
+            EN if strong == AL => *t = AN,
+            _ => {}
+        }
+    }
+
+    // W3: Arabic letters are right to left.
+    for t in types.iter_mut() {
+        if *t == AL {
+            *t = R;
+        }
+    }
+
+    // W4: A single separator between two numbers of one kind joins them.
+    for k in 1..len.saturating_sub(1) {
+        let (prev, next) = (types[k - 1], types[k + 1]);
+        if types[k] == ES && prev == EN && next == EN {
+            types[k] = EN;
+        } else if types[k] == CS && prev == next && (prev == EN || prev == AN) {
+            types[k] = prev;
+        }
+    }
+
+    // W5: Terminators next to European digits are European digits.
+    let mut k = 0;
+    while k < len {
+        if types[k] == ET {
+            let end = (k..len).find(|&j| types[j] != ET).unwrap_or(len);
+            let digits = (k > 0 && types[k - 1] == EN) || (end < len && types[end] == EN);
+            if digits {
+                types[k..end].iter_mut().for_each(|t| *t = EN);
+            }
+            k = end;
+        } else {
+            k += 1;
+        }
+    }
+
+    // W6: Other separators and terminators are neutral.
+    for t in types.iter_mut() {
+        if *t == ES || *t == ET || *t == CS {
+            *t = ON;
+        }
+    }
+
+    // W7: European digits after left to right text are left to right.
+    let mut strong = sos;
+    for t in types.iter_mut() {
+        match *t {
+            L | R => strong = *t,
+            EN if strong == L => *t = L,
+            _ => {}
+        }
+    }
+
+    // N0: Paired brackets take the direction of their content or context.
+    let embedding = direction(level);
+    for (open, close) in bracket_pairs(chars, sequence, &types) {
+        let mut inside = None;
+        for &t in &types[open + 1..close] {
+            match strong_direction(t) {
+                Some(dir) if dir == embedding => {
+                    inside = Some(dir);
+                    break;
+                }
+                Some(dir) => inside = Some(dir),
+                None => {}
+            }
+        }
+        let resolved = match inside {
+            None => continue,
+            Some(dir) if dir == embedding => embedding,
+            Some(opposite) => {
+                let context = types[..open].iter().rev()
+                    .filter_map(|&t| strong_direction(t))
+                    .next()
+                    .unwrap_or(sos);
+                if context == opposite { opposite } else { embedding }
+            }
+        };
+        for &k in &[open, close] {
+            types[k] = resolved;
+            // Marks after a bracket follow it.
+            let mut j = k + 1;
+            while j < len && original[sequence[j]] == NSM {
+                types[j] = resolved;
+                j += 1;
+            }
+        }
+    }
+
+    // N1, N2: Neutrals between characters of one direction take that
+    // direction, others the embedding direction.
+    let mut k = 0;
+    while k < len {
+        if !is_neutral(types[k]) {
+            k += 1;
+            continue;
+        }
+        let end = (k..len).find(|&j| !is_neutral(types[j])).unwrap_or(len);
+        let prev = if k == 0 { sos } else { strong_direction(types[k - 1]).unwrap_or(embedding) };
+        let next = if end == len { eos } else { strong_direction(types[end]).unwrap_or(embedding) };
+        let resolved = if prev == next { prev } else { embedding };
+        types[k..end].iter_mut().for_each(|t| *t = resolved);
+        k = end;
+    }
+
+    // I1, I2: Implicit levels.
+    for (k, &i) in sequence.iter().enumerate() {
+        classes[i] = types[k];
+        levels[i] = match (level % 2, types[k]) {
+            (0, R) => level + 1,
+            (0, AN) | (0, EN) => level + 2,
+            (1, L) | (1, EN) | (1, AN) => level + 1,
+            _ => level,
+        };
+    }
+}
+
+/// BD16: Returns the bracket pairs of a sequence as positions in it,
+/// sorted by their opening bracket.
+fn bracket_pairs(chars: &[char], sequence: &[usize], types: &[BidiClass]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut stack: Vec<(char, usize)> = Vec::new();
+    for (k, &i) in sequence.iter().enumerate() {
+        if types[k] != ON {
+            continue;
+        }
+        let c = chars[i];
+        if let Some(close) = closing_bracket(c) {
+            if stack.len() == MAX_BRACKETS {
+                break;
+            }
+            stack.push((canonical_bracket(close), k));
+        } else if let Some(depth) = stack.iter().rposition(|&(close, _)| close == canonical_bracket(c)) {
+            pairs.push((stack[depth].1, k));
+            stack.truncate(depth);
+        }
+    }
+    pairs.sort();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a line the way it is displayed, from left to right.
+    fn visual(text: &str, direction: TextDirection) -> String {
+        let info = BidiInfo::new(text, direction);
+        info.visual_runs(text, 0, text.len()).iter().map(|run| {
+            let chars = text[run.start..run.end].chars();
+            if run.is_rtl() { chars.rev().collect() } else { chars.collect::<String>() }
+        }).collect()
+    }
+
+    #[test]
+    fn left_to_right_text_is_unchanged() {
+        let info = BidiInfo::new("abc 123", TextDirection::Auto);
+        assert!(!info.has_rtl());
+        assert_eq!(info.paragraphs, [Paragraph{ start: 0, end: 7, level: 0 }]);
+        assert_eq!(visual("abc 123", TextDirection::Auto), "abc 123");
+    }
+
+    #[test]
+    fn reorders_mixed_directions() {
+        assert_eq!(visual("abc \u{5d0}\u{5d1}\u{5d2} def", TextDirection::Auto), "abc \u{5d2}\u{5d1}\u{5d0} def");
+        // The first strong character makes the paragraph right to left.
+        assert_eq!(visual("\u{5d0}\u{5d1}\u{5d2} abc \u{5d3}\u{5d4}", TextDirection::Auto), "\u{5d4}\u{5d3} abc \u{5d2}\u{5d1}\u{5d0}");
+        assert_eq!(visual("\u{5d0}\u{5d1} abc", TextDirection::LeftToRight), "\u{5d1}\u{5d0} abc");
+        assert_eq!(visual("abc \u{5d0}\u{5d1}", TextDirection::RightToLeft), "\u{5d1}\u{5d0} abc");
+    }
+
+    #[test]
+    fn numbers_keep_their_order() {
+        // Numbers in right to left text are still read left to right.
+        assert_eq!(visual("\u{5d0}\u{5d1} 123 \u{5d2}", TextDirection::Auto), "\u{5d2} 123 \u{5d1}\u{5d0}");
+        // A number after right to left text in a left to right paragraph
+        // joins the right to left text.
+        let text = "abc \u{5d0}\u{5d1} 123";
+        let info = BidiInfo::new(text, TextDirection::Auto);
+        let levels: Vec<u8> = text.char_indices().map(|(i, _)| info.levels[i]).collect();
+        assert_eq!(levels, [0, 0, 0, 0, 1, 1, 1, 2, 2, 2]);
+        assert_eq!(visual(text, TextDirection::Auto), "abc 123 \u{5d1}\u{5d0}");
+        // Arabic digits after Arabic letters.
+        assert_eq!(visual("\u{644}\u{627} \u{661}\u{662}", TextDirection::Auto), "\u{661}\u{662} \u{627}\u{644}");
+        // European digits after Arabic letters are Arabic numbers.
+        assert_eq!(visual("\u{644}\u{627} 12.5", TextDirection::Auto), "12.5 \u{627}\u{644}");
+    }
+
+    #[test]
+    fn splits_paragraphs() {
+        let text = "abc\n\u{5d0}\u{5d1}";
+        let info = BidiInfo::new(text, TextDirection::Auto);
+        assert_eq!(info.paragraphs, [Paragraph{ start: 0, end: 4, level: 0 }, Paragraph{ start: 4, end: 8, level: 1 }]);
+        assert_eq!(info.paragraph_level(5), 1);
+        let runs = info.visual_runs(text, 4, 8);
+        assert_eq!(runs, [Run{ start: 4, end: 8, level: 1 }]);
+    }
+
+    #[test]
+    fn trailing_white_space_takes_the_paragraph_level() {
+        // The spaces between the Hebrew letters are right to left, unless
+        // the line ends after them.
+        let text = "abc \u{5d0}  \u{5d1}";
+        let info = BidiInfo::new(text, TextDirection::Auto);
+        assert_eq!(&info.levels[6..8], [1, 1]);
+        let runs = info.visual_runs(text, 0, 8);
+        assert_eq!(runs, [Run{ start: 0, end: 4, level: 0 }, Run{ start: 4, end: 6, level: 1 }, Run{ start: 6, end: 8, level: 0 }]);
+    }
+}
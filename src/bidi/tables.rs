@@ -0,0 +1,302 @@
+//! Character data of the bidirectional algorithm, generated from the
+//! Unicode Character Database 14.0.0.
+
+use super::BidiClass;
+use super::BidiClass::*;
+
+/// Ranges of characters with their bidi class, sorted. Characters not in
+/// the table are `L`.
+pub const BIDI_CLASSES: &[(u32, u32, BidiClass)] = &[
+    (0x0000, 0x0008, BN), (0x0009, 0x0009, S), (0x000a, 0x000a, B), (0x000b, 0x000b, S),
+    (0x000c, 0x000c, WS), (0x000d, 0x000d, B), (0x000e, 0x001b, BN), (0x001c, 0x001e, B),
+    (0x001f, 0x001f, S), (0x0020, 0x0020, WS), (0x0021, 0x0022, ON), (0x0023, 0x0025, ET),
+    (0x0026, 0x002a, ON), (0x002b, 0x002b, ES), (0x002c, 0x002c, CS), (0x002d, 0x002d, ES),
+    (0x002e, 0x002f, CS), (0x0030, 0x0039, EN), (0x003a, 0x003a, CS), (0x003b, 0x0040, ON),
+    (0x005b, 0x0060, ON), (0x007b, 0x007e, ON), (0x007f, 0x0084, BN), (0x0085, 0x0085, B),
+    (0x0086, 0x009f, BN), (0x00a0, 0x00a0, CS), (0x00a1, 0x00a1, ON), (0x00a2, 0x00a5, ET),
+    (0x00a6, 0x00a9, ON), (0x00ab, 0x00ac, ON), (0x00ad, 0x00ad, BN), (0x00ae, 0x00af, ON),
+    (0x00b0, 0x00b1, ET), (0x00b2, 0x00b3, EN), (0x00b4, 0x00b4, ON), (0x00b6, 0x00b8, ON),
+    (0x00b9, 0x00b9, EN), (0x00bb, 0x00bf, ON), (0x00d7, 0x00d7, ON), (0x00f7, 0x00f7, ON),
+    (0x02b9, 0x02ba, ON), (0x02c2, 0x02cf, ON), (0x02d2, 0x02df, ON), (0x02e5, 0x02ed, ON),
+    (0x02ef, 0x02ff, ON), (0x0300, 0x036f, NSM), (0x0374, 0x0375, ON), (0x037e, 0x037e, ON),
+    (0x0384, 0x0385, ON), (0x0387, 0x0387, ON), (0x03f6, 0x03f6, ON), (0x0483, 0x0489, NSM),
+    (0x058a, 0x058a, ON), (0x058d, 0x058e, ON), (0x058f, 0x058f, ET), (0x0590, 0x0590, R),
+    (0x0591, 0x05bd, NSM), (0x05be, 0x05be, R), (0x05bf, 0x05bf, NSM), (0x05c0, 0x05c0, R),
+    (0x05c1, 0x05c2, NSM), (0x05c3, 0x05c3, R), (0x05c4, 0x05c5, NSM), (0x05c6, 0x05c6, R),
+    (0x05c7, 0x05c7, NSM), (0x05c8, 0x05ff, R), (0x0600, 0x0605, AN), (0x0606, 0x0607, ON),
+    (0x0608, 0x0608, AL), (0x0609, 0x060a, ET), (0x060b, 0x060b, AL), (0x060c, 0x060c, CS),
+    (0x060d, 0x060d, AL), (0x060e, 0x060f, ON), (0x0610, 0x061a, NSM), (0x061b, 0x064a, AL),
+    (0x064b, 0x065f, NSM), (0x0660, 0x0669, AN), (0x066a, 0x066a, ET), (0x066b, 0x066c, AN),
+    (0x066d, 0x066f, AL), (0x0670, 0x0670, NSM), (0x0671, 0x06d5, AL), (0x06d6, 0x06dc, NSM),
+    (0x06dd, 0x06dd, AN), (0x06de, 0x06de, ON), (0x06df, 0x06e4, NSM), (0x06e5, 0x06e6, AL),
+    (0x06e7, 0x06e8, NSM), (0x06e9, 0x06e9, ON), (0x06ea, 0x06ed, NSM), (0x06ee, 0x06ef, AL),
+    (0x06f0, 0x06f9, EN), (0x06fa, 0x0710, AL), (0x0711, 0x0711, NSM), (0x0712, 0x072f, AL),
+    (0x0730, 0x074a, NSM), (0x074b, 0x07a5, AL), (0x07a6, 0x07b0, NSM), (0x07b1, 0x07bf, AL),
+    (0x07c0, 0x07ea, R), (0x07eb, 0x07f3, NSM), (0x07f4, 0x07f5, R), (0x07f6, 0x07f9, ON),
+    (0x07fa, 0x07fc, R), (0x07fd, 0x07fd, NSM), (0x07fe, 0x0815, R), (0x0816, 0x0819, NSM),
+    (0x081a, 0x081a, R), (0x081b, 0x0823, NSM), (0x0824, 0x0824, R), (0x0825, 0x0827, NSM),
+    (0x0828, 0x0828, R), (0x0829, 0x082d, NSM), (0x082e, 0x0858, R), (0x0859, 0x085b, NSM),
+    (0x085c, 0x085f, R), (0x0860, 0x088f, AL), (0x0890, 0x0891, AN), (0x0892, 0x0897, AL),
+    (0x0898, 0x089f, NSM), (0x08a0, 0x08c9, AL), (0x08ca, 0x08e1, NSM), (0x08e2, 0x08e2, AN),
+    (0x08e3, 0x0902, NSM), (0x093a, 0x093a, NSM), (0x093c, 0x093c, NSM), (0x0941, 0x0948, NSM),
+    (0x094d, 0x094d, NSM), (0x0951, 0x0957, NSM), (0x0962, 0x0963, NSM), (0x0981, 0x0981, NSM),
+    (0x09bc, 0x09bc, NSM), (0x09c1, 0x09c4, NSM), (0x09cd, 0x09cd, NSM), (0x09e2, 0x09e3, NSM),
+    (0x09f2, 0x09f3, ET), (0x09fb, 0x09fb, ET), (0x09fe, 0x09fe, NSM), (0x0a01, 0x0a02, NSM),
+    (0x0a3c, 0x0a3c, NSM), (0x0a41, 0x0a42, NSM), (0x0a47, 0x0a48, NSM), (0x0a4b, 0x0a4d, NSM),
+    (0x0a51, 0x0a51, NSM), (0x0a70, 0x0a71, NSM), (0x0a75, 0x0a75, NSM), (0x0a81, 0x0a82, NSM),
+    (0x0abc, 0x0abc, NSM), (0x0ac1, 0x0ac5, NSM), (0x0ac7, 0x0ac8, NSM), (0x0acd, 0x0acd, NSM),
+    (0x0ae2, 0x0ae3, NSM), (0x0af1, 0x0af1, ET), (0x0afa, 0x0aff, NSM), (0x0b01, 0x0b01, NSM),
+    (0x0b3c, 0x0b3c, NSM), (0x0b3f, 0x0b3f, NSM), (0x0b41, 0x0b44, NSM), (0x0b4d, 0x0b4d, NSM),
+    (0x0b55, 0x0b56, NSM), (0x0b62, 0x0b63, NSM), (0x0b82, 0x0b82, NSM), (0x0bc0, 0x0bc0, NSM),
+    (0x0bcd, 0x0bcd, NSM), (0x0bf3, 0x0bf8, ON), (0x0bf9, 0x0bf9, ET), (0x0bfa, 0x0bfa, ON),
+    (0x0c00, 0x0c00, NSM), (0x0c04, 0x0c04, NSM), (0x0c3c, 0x0c3c, NSM), (0x0c3e, 0x0c40, NSM),
+    (0x0c46, 0x0c48, NSM), (0x0c4a, 0x0c4d, NSM), (0x0c55, 0x0c56, NSM), (0x0c62, 0x0c63, NSM),
+    (0x0c78, 0x0c7e, ON), (0x0c81, 0x0c81, NSM), (0x0cbc, 0x0cbc, NSM), (0x0ccc, 0x0ccd, NSM),
+    (0x0ce2, 0x0ce3, NSM), (0x0d00, 0x0d01, NSM), (0x0d3b, 0x0d3c, NSM), (0x0d41, 0x0d44, NSM),
+    (0x0d4d, 0x0d4d, NSM), (0x0d62, 0x0d63, NSM), (0x0d81, 0x0d81, NSM), (0x0dca, 0x0dca, NSM),
+    (0x0dd2, 0x0dd4, NSM), (0x0dd6, 0x0dd6, NSM), (0x0e31, 0x0e31, NSM), (0x0e34, 0x0e3a, NSM),
+    (0x0e3f, 0x0e3f, ET), (0x0e47, 0x0e4e, NSM), (0x0eb1, 0x0eb1, NSM), (0x0eb4, 0x0ebc, NSM),
+    (0x0ec8, 0x0ecd, NSM), (0x0f18, 0x0f19, NSM), (0x0f35, 0x0f35, NSM), (0x0f37, 0x0f37, NSM),
+    (0x0f39, 0x0f39, NSM), (0x0f3a, 0x0f3d, ON), (0x0f71, 0x0f7e, NSM), (0x0f80, 0x0f84, NSM),
+    (0x0f86, 0x0f87, NSM), (0x0f8d, 0x0f97, NSM), (0x0f99, 0x0fbc, NSM), (0x0fc6, 0x0fc6, NSM),
+    (0x102d, 0x1030, NSM), (0x1032, 0x1037, NSM), (0x1039, 0x103a, NSM), (0x103d, 0x103e, NSM),
+    (0x1058, 0x1059, NSM), (0x105e, 0x1060, NSM), (0x1071, 0x1074, NSM), (0x1082, 0x1082, NSM),
+    (0x1085, 0x1086, NSM), (0x108d, 0x108d, NSM), (0x109d, 0x109d, NSM), (0x135d, 0x135f, NSM),
+    (0x1390, 0x1399, ON), (0x1400, 0x1400, ON), (0x1680, 0x1680, WS), (0x169b, 0x169c, ON),
+    (0x1712, 0x1714, NSM), (0x1732, 0x1733, NSM), (0x1752, 0x1753, NSM), (0x1772, 0x1773, NSM),
+    (0x17b4, 0x17b5, NSM), (0x17b7, 0x17bd, NSM), (0x17c6, 0x17c6, NSM), (0x17c9, 0x17d3, NSM),
+    (0x17db, 0x17db, ET), (0x17dd, 0x17dd, NSM), (0x17f0, 0x17f9, ON), (0x1800, 0x180a, ON),
+    (0x180b, 0x180d, NSM), (0x180e, 0x180e, BN), (0x180f, 0x180f, NSM), (0x1885, 0x1886, NSM),
+    (0x18a9, 0x18a9, NSM), (0x1920, 0x1922, NSM), (0x1927, 0x1928, NSM), (0x1932, 0x1932, NSM),
+    (0x1939, 0x193b, NSM), (0x1940, 0x1940, ON), (0x1944, 0x1945, ON), (0x19de, 0x19ff, ON),
+    (0x1a17, 0x1a18, NSM), (0x1a1b, 0x1a1b, NSM), (0x1a56, 0x1a56, NSM), (0x1a58, 0x1a5e, NSM),
+    (0x1a60, 0x1a60, NSM), (0x1a62, 0x1a62, NSM), (0x1a65, 0x1a6c, NSM), (0x1a73, 0x1a7c, NSM),
+    (0x1a7f, 0x1a7f, NSM), (0x1ab0, 0x1ace, NSM), (0x1b00, 0x1b03, NSM), (0x1b34, 0x1b34, NSM),
+    (0x1b36, 0x1b3a, NSM), (0x1b3c, 0x1b3c, NSM), (0x1b42, 0x1b42, NSM), (0x1b6b, 0x1b73, NSM),
+    (0x1b80, 0x1b81, NSM), (0x1ba2, 0x1ba5, NSM), (0x1ba8, 0x1ba9, NSM), (0x1bab, 0x1bad, NSM),
+    (0x1be6, 0x1be6, NSM), (0x1be8, 0x1be9, NSM), (0x1bed, 0x1bed, NSM), (0x1bef, 0x1bf1, NSM),
+    (0x1c2c, 0x1c33, NSM), (0x1c36, 0x1c37, NSM), (0x1cd0, 0x1cd2, NSM), (0x1cd4, 0x1ce0, NSM),
+    (0x1ce2, 0x1ce8, NSM), (0x1ced, 0x1ced, NSM), (0x1cf4, 0x1cf4, NSM), (0x1cf8, 0x1cf9, NSM),
+    (0x1dc0, 0x1dff, NSM), (0x1fbd, 0x1fbd, ON), (0x1fbf, 0x1fc1, ON), (0x1fcd, 0x1fcf, ON),
+    (0x1fdd, 0x1fdf, ON), (0x1fed, 0x1fef, ON), (0x1ffd, 0x1ffe, ON), (0x2000, 0x200a, WS),
+    (0x200b, 0x200d, BN), (0x200f, 0x200f, R), (0x2010, 0x2027, ON), (0x2028, 0x2028, WS),
+    (0x2029, 0x2029, B), (0x202a, 0x202a, LRE), (0x202b, 0x202b, RLE), (0x202c, 0x202c, PDF),
+    (0x202d, 0x202d, LRO), (0x202e, 0x202e, RLO), (0x202f, 0x202f, CS), (0x2030, 0x2034, ET),
+    (0x2035, 0x2043, ON), (0x2044, 0x2044, CS), (0x2045, 0x205e, ON), (0x205f, 0x205f, WS),
+    (0x2060, 0x2064, BN), (0x2066, 0x2066, LRI), (0x2067, 0x2067, RLI), (0x2068, 0x2068, FSI),
+    (0x2069, 0x2069, PDI), (0x206a, 0x206f, BN), (0x2070, 0x2070, EN), (0x2074, 0x2079, EN),
+    (0x207a, 0x207b, ES), (0x207c, 0x207e, ON), (0x2080, 0x2089, EN), (0x208a, 0x208b, ES),
+    (0x208c, 0x208e, ON), (0x20a0, 0x20cf, ET), (0x20d0, 0x20f0, NSM), (0x2100, 0x2101, ON),
+    (0x2103, 0x2106, ON), (0x2108, 0x2109, ON), (0x2114, 0x2114, ON), (0x2116, 0x2118, ON),
+    (0x211e, 0x2123, ON), (0x2125, 0x2125, ON), (0x2127, 0x2127, ON), (0x2129, 0x2129, ON),
+    (0x212e, 0x212e, ET), (0x213a, 0x213b, ON), (0x2140, 0x2144, ON), (0x214a, 0x214d, ON),
+    (0x2150, 0x215f, ON), (0x2189, 0x218b, ON), (0x2190, 0x2211, ON), (0x2212, 0x2212, ES),
+    (0x2213, 0x2213, ET), (0x2214, 0x2335, ON), (0x237b, 0x2394, ON), (0x2396, 0x2426, ON),
+    (0x2440, 0x244a, ON), (0x2460, 0x2487, ON), (0x2488, 0x249b, EN), (0x24ea, 0x26ab, ON),
+    (0x26ad, 0x27ff, ON), (0x2900, 0x2b73, ON), (0x2b76, 0x2b95, ON), (0x2b97, 0x2bff, ON),
+    (0x2ce5, 0x2cea, ON), (0x2cef, 0x2cf1, NSM), (0x2cf9, 0x2cff, ON), (0x2d7f, 0x2d7f, NSM),
+    (0x2de0, 0x2dff, NSM), (0x2e00, 0x2e5d, ON), (0x2e80, 0x2e99, ON), (0x2e9b, 0x2ef3, ON),
+    (0x2f00, 0x2fd5, ON), (0x2ff0, 0x2ffb, ON), (0x3000, 0x3000, WS), (0x3001, 0x3004, ON),
+    (0x3008, 0x3020, ON), (0x302a, 0x302d, NSM), (0x3030, 0x3030, ON), (0x3036, 0x3037, ON),
+    (0x303d, 0x303f, ON), (0x3099, 0x309a, NSM), (0x309b, 0x309c, ON), (0x30a0, 0x30a0, ON),
+    (0x30fb, 0x30fb, ON), (0x31c0, 0x31e3, ON), (0x321d, 0x321e, ON), (0x3250, 0x325f, ON),
+    (0x327c, 0x327e, ON), (0x32b1, 0x32bf, ON), (0x32cc, 0x32cf, ON), (0x3377, 0x337a, ON),
+    (0x33de, 0x33df, ON), (0x33ff, 0x33ff, ON), (0x4dc0, 0x4dff, ON), (0xa490, 0xa4c6, ON),
+    (0xa60d, 0xa60f, ON), (0xa66f, 0xa672, NSM), (0xa673, 0xa673, ON), (0xa674, 0xa67d, NSM),
+    (0xa67e, 0xa67f, ON), (0xa69e, 0xa69f, NSM), (0xa6f0, 0xa6f1, NSM), (0xa700, 0xa721, ON),
+    (0xa788, 0xa788, ON), (0xa802, 0xa802, NSM), (0xa806, 0xa806, NSM), (0xa80b, 0xa80b, NSM),
+    (0xa825, 0xa826, NSM), (0xa828, 0xa82b, ON), (0xa82c, 0xa82c, NSM), (0xa838, 0xa839, ET),
+    (0xa874, 0xa877, ON), (0xa8c4, 0xa8c5, NSM), (0xa8e0, 0xa8f1, NSM), (0xa8ff, 0xa8ff, NSM),
+    (0xa926, 0xa92d, NSM), (0xa947, 0xa951, NSM), (0xa980, 0xa982, NSM), (0xa9b3, 0xa9b3, NSM),
+    (0xa9b6, 0xa9b9, NSM), (0xa9bc, 0xa9bd, NSM), (0xa9e5, 0xa9e5, NSM), (0xaa29, 0xaa2e, NSM),
+    (0xaa31, 0xaa32, NSM), (0xaa35, 0xaa36, NSM), (0xaa43, 0xaa43, NSM), (0xaa4c, 0xaa4c, NSM),
+    (0xaa7c, 0xaa7c, NSM), (0xaab0, 0xaab0, NSM), (0xaab2, 0xaab4, NSM), (0xaab7, 0xaab8, NSM),
+    (0xaabe, 0xaabf, NSM), (0xaac1, 0xaac1, NSM), (0xaaec, 0xaaed, NSM), (0xaaf6, 0xaaf6, NSM),
+    (0xab6a, 0xab6b, ON), (0xabe5, 0xabe5, NSM), (0xabe8, 0xabe8, NSM), (0xabed, 0xabed, NSM),
+    (0xfb1d, 0xfb1d, R), (0xfb1e, 0xfb1e, NSM), (0xfb1f, 0xfb28, R), (0xfb29, 0xfb29, ES),
+    (0xfb2a, 0xfb4f, R), (0xfb50, 0xfd3d, AL), (0xfd3e, 0xfd4f, ON), (0xfd50, 0xfdce, AL),
+    (0xfdcf, 0xfdcf, ON), (0xfdd0, 0xfdef, BN), (0xfdf0, 0xfdfc, AL), (0xfdfd, 0xfdff, ON),
+    (0xfe00, 0xfe0f, NSM), (0xfe10, 0xfe19, ON), (0xfe20, 0xfe2f, NSM), (0xfe30, 0xfe4f, ON),
+    (0xfe50, 0xfe50, CS), (0xfe51, 0xfe51, ON), (0xfe52, 0xfe52, CS), (0xfe54, 0xfe54, ON),
+    (0xfe55, 0xfe55, CS), (0xfe56, 0xfe5e, ON), (0xfe5f, 0xfe5f, ET), (0xfe60, 0xfe61, ON),
+    (0xfe62, 0xfe63, ES), (0xfe64, 0xfe66, ON), (0xfe68, 0xfe68, ON), (0xfe69, 0xfe6a, ET),
+    (0xfe6b, 0xfe6b, ON), (0xfe70, 0xfefe, AL), (0xfeff, 0xfeff, BN), (0xff01, 0xff02, ON),
+    (0xff03, 0xff05, ET), (0xff06, 0xff0a, ON), (0xff0b, 0xff0b, ES), (0xff0c, 0xff0c, CS),
+    (0xff0d, 0xff0d, ES), (0xff0e, 0xff0f, CS), (0xff10, 0xff19, EN), (0xff1a, 0xff1a, CS),
+    (0xff1b, 0xff20, ON), (0xff3b, 0xff40, ON), (0xff5b, 0xff65, ON), (0xffe0, 0xffe1, ET),
+    (0xffe2, 0xffe4, ON), (0xffe5, 0xffe6, ET), (0xffe8, 0xffee, ON), (0xfff9, 0xfffd, ON),
+    (0xfffe, 0xffff, BN), (0x10101, 0x10101, ON), (0x10140, 0x1018c, ON), (0x10190, 0x1019c, ON),
+    (0x101a0, 0x101a0, ON), (0x101fd, 0x101fd, NSM), (0x102e0, 0x102e0, NSM),
+    (0x102e1, 0x102fb, EN), (0x10376, 0x1037a, NSM), (0x10800, 0x1091e, R), (0x1091f, 0x1091f, ON),
+    (0x10920, 0x10a00, R), (0x10a01, 0x10a03, NSM), (0x10a04, 0x10a04, R), (0x10a05, 0x10a06, NSM),
+    (0x10a07, 0x10a0b, R), (0x10a0c, 0x10a0f, NSM), (0x10a10, 0x10a37, R), (0x10a38, 0x10a3a, NSM),
+    (0x10a3b, 0x10a3e, R), (0x10a3f, 0x10a3f, NSM), (0x10a40, 0x10ae4, R), (0x10ae5, 0x10ae6, NSM),
+    (0x10ae7, 0x10b38, R), (0x10b39, 0x10b3f, ON), (0x10b40, 0x10cff, R), (0x10d00, 0x10d23, AL),
+    (0x10d24, 0x10d27, NSM), (0x10d28, 0x10d2f, R), (0x10d30, 0x10d39, AN), (0x10d3a, 0x10e5f, R),
+    (0x10e60, 0x10e7e, AN), (0x10e7f, 0x10eaa, R), (0x10eab, 0x10eac, NSM), (0x10ead, 0x10f2f, R),
+    (0x10f30, 0x10f45, AL), (0x10f46, 0x10f50, NSM), (0x10f51, 0x10f59, AL), (0x10f5a, 0x10f81, R),
+    (0x10f82, 0x10f85, NSM), (0x10f86, 0x10fff, R), (0x11001, 0x11001, NSM),
+    (0x11038, 0x11046, NSM), (0x11052, 0x11065, ON), (0x11070, 0x11070, NSM),
+    (0x11073, 0x11074, NSM), (0x1107f, 0x11081, NSM), (0x110b3, 0x110b6, NSM),
+    (0x110b9, 0x110ba, NSM), (0x110c2, 0x110c2, NSM), (0x11100, 0x11102, NSM),
+    (0x11127, 0x1112b, NSM), (0x1112d, 0x11134, NSM), (0x11173, 0x11173, NSM),
+    (0x11180, 0x11181, NSM), (0x111b6, 0x111be, NSM), (0x111c9, 0x111cc, NSM),
+    (0x111cf, 0x111cf, NSM), (0x1122f, 0x11231, NSM), (0x11234, 0x11234, NSM),
+    (0x11236, 0x11237, NSM), (0x1123e, 0x1123e, NSM), (0x112df, 0x112df, NSM),
+    (0x112e3, 0x112ea, NSM), (0x11300, 0x11301, NSM), (0x1133b, 0x1133c, NSM),
+    (0x11340, 0x11340, NSM), (0x11366, 0x1136c, NSM), (0x11370, 0x11374, NSM),
+    (0x11438, 0x1143f, NSM), (0x11442, 0x11444, NSM), (0x11446, 0x11446, NSM),
+    (0x1145e, 0x1145e, NSM), (0x114b3, 0x114b8, NSM), (0x114ba, 0x114ba, NSM),
+    (0x114bf, 0x114c0, NSM), (0x114c2, 0x114c3, NSM), (0x115b2, 0x115b5, NSM),
+    (0x115bc, 0x115bd, NSM), (0x115bf, 0x115c0, NSM), (0x115dc, 0x115dd, NSM),
+    (0x11633, 0x1163a, NSM), (0x1163d, 0x1163d, NSM), (0x1163f, 0x11640, NSM),
+    (0x11660, 0x1166c, ON), (0x116ab, 0x116ab, NSM), (0x116ad, 0x116ad, NSM),
+    (0x116b0, 0x116b5, NSM), (0x116b7, 0x116b7, NSM), (0x1171d, 0x1171f, NSM),
+    (0x11722, 0x11725, NSM), (0x11727, 0x1172b, NSM), (0x1182f, 0x11837, NSM),
+    (0x11839, 0x1183a, NSM), (0x1193b, 0x1193c, NSM), (0x1193e, 0x1193e, NSM),
+    (0x11943, 0x11943, NSM), (0x119d4, 0x119d7, NSM), (0x119da, 0x119db, NSM),
+    (0x119e0, 0x119e0, NSM), (0x11a01, 0x11a06, NSM), (0x11a09, 0x11a0a, NSM),
+    (0x11a33, 0x11a38, NSM), (0x11a3b, 0x11a3e, NSM), (0x11a47, 0x11a47, NSM),
+    (0x11a51, 0x11a56, NSM), (0x11a59, 0x11a5b, NSM), (0x11a8a, 0x11a96, NSM),
+    (0x11a98, 0x11a99, NSM), (0x11c30, 0x11c36, NSM), (0x11c38, 0x11c3d, NSM),
+    (0x11c92, 0x11ca7, NSM), (0x11caa, 0x11cb0, NSM), (0x11cb2, 0x11cb3, NSM),
+    (0x11cb5, 0x11cb6, NSM), (0x11d31, 0x11d36, NSM), (0x11d3a, 0x11d3a, NSM),
+    (0x11d3c, 0x11d3d, NSM), (0x11d3f, 0x11d45, NSM), (0x11d47, 0x11d47, NSM),
+    (0x11d90, 0x11d91, NSM), (0x11d95, 0x11d95, NSM), (0x11d97, 0x11d97, NSM),
+    (0x11ef3, 0x11ef4, NSM), (0x11fd5, 0x11fdc, ON), (0x11fdd, 0x11fe0, ET), (0x11fe1, 0x11ff1, ON),
+    (0x16af0, 0x16af4, NSM), (0x16b30, 0x16b36, NSM), (0x16f4f, 0x16f4f, NSM),
+    (0x16f8f, 0x16f92, NSM), (0x16fe2, 0x16fe2, ON), (0x16fe4, 0x16fe4, NSM),
+    (0x1bc9d, 0x1bc9e, NSM), (0x1bca0, 0x1bca3, BN), (0x1cf00, 0x1cf2d, NSM),
+    (0x1cf30, 0x1cf46, NSM), (0x1d167, 0x1d169, NSM), (0x1d173, 0x1d17a, BN),
+    (0x1d17b, 0x1d182, NSM), (0x1d185, 0x1d18b, NSM), (0x1d1aa, 0x1d1ad, NSM),
+    (0x1d1e9, 0x1d1ea, ON), (0x1d200, 0x1d241, ON), (0x1d242, 0x1d244, NSM), (0x1d245, 0x1d245, ON),
+    (0x1d300, 0x1d356, ON), (0x1d6db, 0x1d6db, ON), (0x1d715, 0x1d715, ON), (0x1d74f, 0x1d74f, ON),
+    (0x1d789, 0x1d789, ON), (0x1d7c3, 0x1d7c3, ON), (0x1d7ce, 0x1d7ff, EN), (0x1da00, 0x1da36, NSM),
+    (0x1da3b, 0x1da6c, NSM), (0x1da75, 0x1da75, NSM), (0x1da84, 0x1da84, NSM),
+    (0x1da9b, 0x1da9f, NSM), (0x1daa1, 0x1daaf, NSM), (0x1e000, 0x1e006, NSM),
+    (0x1e008, 0x1e018, NSM), (0x1e01b, 0x1e021, NSM), (0x1e023, 0x1e024, NSM),
+    (0x1e026, 0x1e02a, NSM), (0x1e130, 0x1e136, NSM), (0x1e2ae, 0x1e2ae, NSM),
+    (0x1e2ec, 0x1e2ef, NSM), (0x1e2ff, 0x1e2ff, ET), (0x1e800, 0x1e8cf, R), (0x1e8d0, 0x1e8d6, NSM),
+    (0x1e8d7, 0x1e943, R), (0x1e944, 0x1e94a, NSM), (0x1e94b, 0x1ec70, R), (0x1ec71, 0x1ecb4, AL),
+    (0x1ecb5, 0x1ed00, R), (0x1ed01, 0x1ed3d, AL), (0x1ed3e, 0x1edff, R), (0x1ee00, 0x1ee03, AL),
+    (0x1ee04, 0x1ee04, R), (0x1ee05, 0x1ee1f, AL), (0x1ee20, 0x1ee20, R), (0x1ee21, 0x1ee22, AL),
+    (0x1ee23, 0x1ee23, R), (0x1ee24, 0x1ee24, AL), (0x1ee25, 0x1ee26, R), (0x1ee27, 0x1ee27, AL),
+    (0x1ee28, 0x1ee28, R), (0x1ee29, 0x1ee32, AL), (0x1ee33, 0x1ee33, R), (0x1ee34, 0x1ee37, AL),
+    (0x1ee38, 0x1ee38, R), (0x1ee39, 0x1ee39, AL), (0x1ee3a, 0x1ee3a, R), (0x1ee3b, 0x1ee3b, AL),
+    (0x1ee3c, 0x1ee41, R), (0x1ee42, 0x1ee42, AL), (0x1ee43, 0x1ee46, R), (0x1ee47, 0x1ee47, AL),
+    (0x1ee48, 0x1ee48, R), (0x1ee49, 0x1ee49, AL), (0x1ee4a, 0x1ee4a, R), (0x1ee4b, 0x1ee4b, AL),
+    (0x1ee4c, 0x1ee4c, R), (0x1ee4d, 0x1ee4f, AL), (0x1ee50, 0x1ee50, R), (0x1ee51, 0x1ee52, AL),
+    (0x1ee53, 0x1ee53, R), (0x1ee54, 0x1ee54, AL), (0x1ee55, 0x1ee56, R), (0x1ee57, 0x1ee57, AL),
+    (0x1ee58, 0x1ee58, R), (0x1ee59, 0x1ee59, AL), (0x1ee5a, 0x1ee5a, R), (0x1ee5b, 0x1ee5b, AL),
+    (0x1ee5c, 0x1ee5c, R), (0x1ee5d, 0x1ee5d, AL), (0x1ee5e, 0x1ee5e, R), (0x1ee5f, 0x1ee5f, AL),
+    (0x1ee60, 0x1ee60, R), (0x1ee61, 0x1ee62, AL), (0x1ee63, 0x1ee63, R), (0x1ee64, 0x1ee64, AL),
+    (0x1ee65, 0x1ee66, R), (0x1ee67, 0x1ee6a, AL), (0x1ee6b, 0x1ee6b, R), (0x1ee6c, 0x1ee72, AL),
+    (0x1ee73, 0x1ee73, R), (0x1ee74, 0x1ee77, AL), (0x1ee78, 0x1ee78, R), (0x1ee79, 0x1ee7c, AL),
+    (0x1ee7d, 0x1ee7d, R), (0x1ee7e, 0x1ee7e, AL), (0x1ee7f, 0x1ee7f, R), (0x1ee80, 0x1ee89, AL),
+    (0x1ee8a, 0x1ee8a, R), (0x1ee8b, 0x1ee9b, AL), (0x1ee9c, 0x1eea0, R), (0x1eea1, 0x1eea3, AL),
+    (0x1eea4, 0x1eea4, R), (0x1eea5, 0x1eea9, AL), (0x1eeaa, 0x1eeaa, R), (0x1eeab, 0x1eebb, AL),
+    (0x1eebc, 0x1eeef, R), (0x1eef0, 0x1eef1, ON), (0x1eef2, 0x1efff, R), (0x1f000, 0x1f02b, ON),
+    (0x1f030, 0x1f093, ON), (0x1f0a0, 0x1f0ae, ON), (0x1f0b1, 0x1f0bf, ON), (0x1f0c1, 0x1f0cf, ON),
+    (0x1f0d1, 0x1f0f5, ON), (0x1f100, 0x1f10a, EN), (0x1f10b, 0x1f10f, ON), (0x1f12f, 0x1f12f, ON),
+    (0x1f16a, 0x1f16f, ON), (0x1f1ad, 0x1f1ad, ON), (0x1f260, 0x1f265, ON), (0x1f300, 0x1f6d7, ON),
+    (0x1f6dd, 0x1f6ec, ON), (0x1f6f0, 0x1f6fc, ON), (0x1f700, 0x1f773, ON), (0x1f780, 0x1f7d8, ON),
+    (0x1f7e0, 0x1f7eb, ON), (0x1f7f0, 0x1f7f0, ON), (0x1f800, 0x1f80b, ON), (0x1f810, 0x1f847, ON),
+    (0x1f850, 0x1f859, ON), (0x1f860, 0x1f887, ON), (0x1f890, 0x1f8ad, ON), (0x1f8b0, 0x1f8b1, ON),
+    (0x1f900, 0x1fa53, ON), (0x1fa60, 0x1fa6d, ON), (0x1fa70, 0x1fa74, ON), (0x1fa78, 0x1fa7c, ON),
+    (0x1fa80, 0x1fa86, ON), (0x1fa90, 0x1faac, ON), (0x1fab0, 0x1faba, ON), (0x1fac0, 0x1fac5, ON),
+    (0x1fad0, 0x1fad9, ON), (0x1fae0, 0x1fae7, ON), (0x1faf0, 0x1faf6, ON), (0x1fb00, 0x1fb92, ON),
+    (0x1fb94, 0x1fbca, ON), (0x1fbf0, 0x1fbf9, EN), (0x1fffe, 0x1ffff, BN), (0x2fffe, 0x2ffff, BN),
+    (0x3fffe, 0x3ffff, BN), (0x4fffe, 0x4ffff, BN), (0x5fffe, 0x5ffff, BN), (0x6fffe, 0x6ffff, BN),
+    (0x7fffe, 0x7ffff, BN), (0x8fffe, 0x8ffff, BN), (0x9fffe, 0x9ffff, BN), (0xafffe, 0xaffff, BN),
+    (0xbfffe, 0xbffff, BN), (0xcfffe, 0xcffff, BN), (0xdfffe, 0xe00ff, BN), (0xe0100, 0xe01ef, NSM),
+    (0xe01f0, 0xe0fff, BN), (0xefffe, 0xeffff, BN), (0xffffe, 0xfffff, BN),
+    (0x10fffe, 0x10ffff, BN),
+];
+
+/// Characters with their mirrored counterparts, sorted.
+pub const MIRRORS: &[(u32, u32)] = &[
+    (0x0028, 0x0029), (0x0029, 0x0028), (0x003c, 0x003e), (0x003e, 0x003c), (0x005b, 0x005d),
+    (0x005d, 0x005b), (0x007b, 0x007d), (0x007d, 0x007b), (0x00ab, 0x00bb), (0x00bb, 0x00ab),
+    (0x0f3a, 0x0f3b), (0x0f3b, 0x0f3a), (0x0f3c, 0x0f3d), (0x0f3d, 0x0f3c), (0x169b, 0x169c),
+    (0x169c, 0x169b), (0x2039, 0x203a), (0x203a, 0x2039), (0x2045, 0x2046), (0x2046, 0x2045),
+    (0x207d, 0x207e), (0x207e, 0x207d), (0x208d, 0x208e), (0x208e, 0x208d), (0x2208, 0x220b),
+    (0x220a, 0x220d), (0x220b, 0x2208), (0x220d, 0x220a), (0x223c, 0x223d), (0x223d, 0x223c),
+    (0x2264, 0x2265), (0x2265, 0x2264), (0x2266, 0x2267), (0x2267, 0x2266), (0x2268, 0x2269),
+    (0x2269, 0x2268), (0x226a, 0x226b), (0x226b, 0x226a), (0x226e, 0x226f), (0x226f, 0x226e),
+    (0x2270, 0x2271), (0x2271, 0x2270), (0x2272, 0x2273), (0x2273, 0x2272), (0x2274, 0x2275),
+    (0x2275, 0x2274), (0x2276, 0x2277), (0x2277, 0x2276), (0x2278, 0x2279), (0x2279, 0x2278),
+    (0x227a, 0x227b), (0x227b, 0x227a), (0x227c, 0x227d), (0x227d, 0x227c), (0x227e, 0x227f),
+    (0x227f, 0x227e), (0x2282, 0x2283), (0x2283, 0x2282), (0x2284, 0x2285), (0x2285, 0x2284),
+    (0x2286, 0x2287), (0x2287, 0x2286), (0x2288, 0x2289), (0x2289, 0x2288), (0x228a, 0x228b),
+    (0x228b, 0x228a), (0x22a2, 0x22a3), (0x22a3, 0x22a2), (0x22ab, 0x2ae5), (0x22b0, 0x22b1),
+    (0x22b1, 0x22b0), (0x22c9, 0x22ca), (0x22ca, 0x22c9), (0x22cb, 0x22cc), (0x22cc, 0x22cb),
+    (0x22d0, 0x22d1), (0x22d1, 0x22d0), (0x22d6, 0x22d7), (0x22d7, 0x22d6), (0x22d8, 0x22d9),
+    (0x22d9, 0x22d8), (0x22da, 0x22db), (0x22db, 0x22da), (0x22dc, 0x22dd), (0x22dd, 0x22dc),
+    (0x22de, 0x22df), (0x22df, 0x22de), (0x22e6, 0x22e7), (0x22e7, 0x22e6), (0x22e8, 0x22e9),
+    (0x22e9, 0x22e8), (0x2308, 0x2309), (0x2309, 0x2308), (0x230a, 0x230b), (0x230b, 0x230a),
+    (0x2329, 0x232a), (0x232a, 0x2329), (0x2768, 0x2769), (0x2769, 0x2768), (0x276a, 0x276b),
+    (0x276b, 0x276a), (0x276c, 0x276d), (0x276d, 0x276c), (0x276e, 0x276f), (0x276f, 0x276e),
+    (0x2770, 0x2771), (0x2771, 0x2770), (0x2772, 0x2773), (0x2773, 0x2772), (0x2774, 0x2775),
+    (0x2775, 0x2774), (0x27c3, 0x27c4), (0x27c4, 0x27c3), (0x27c5, 0x27c6), (0x27c6, 0x27c5),
+    (0x27d5, 0x27d6), (0x27d6, 0x27d5), (0x27dd, 0x27de), (0x27de, 0x27dd), (0x27e2, 0x27e3),
+    (0x27e3, 0x27e2), (0x27e4, 0x27e5), (0x27e5, 0x27e4), (0x27e6, 0x27e7), (0x27e7, 0x27e6),
+    (0x27e8, 0x27e9), (0x27e9, 0x27e8), (0x27ea, 0x27eb), (0x27eb, 0x27ea), (0x27ec, 0x27ed),
+    (0x27ed, 0x27ec), (0x27ee, 0x27ef), (0x27ef, 0x27ee), (0x2983, 0x2984), (0x2984, 0x2983),
+    (0x2985, 0x2986), (0x2986, 0x2985), (0x2987, 0x2988), (0x2988, 0x2987), (0x2989, 0x298a),
+    (0x298a, 0x2989), (0x298b, 0x298c), (0x298c, 0x298b), (0x298d, 0x2990), (0x298e, 0x298f),
+    (0x298f, 0x298e), (0x2990, 0x298d), (0x2991, 0x2992), (0x2992, 0x2991), (0x2997, 0x2998),
+    (0x2998, 0x2997), (0x29a8, 0x29a9), (0x29a9, 0x29a8), (0x29aa, 0x29ab), (0x29ab, 0x29aa),
+    (0x29ac, 0x29ad), (0x29ad, 0x29ac), (0x29ae, 0x29af), (0x29af, 0x29ae), (0x29c0, 0x29c1),
+    (0x29c1, 0x29c0), (0x29d1, 0x29d2), (0x29d2, 0x29d1), (0x29d4, 0x29d5), (0x29d5, 0x29d4),
+    (0x29d8, 0x29d9), (0x29d9, 0x29d8), (0x29da, 0x29db), (0x29db, 0x29da), (0x29e8, 0x29e9),
+    (0x29e9, 0x29e8), (0x29fc, 0x29fd), (0x29fd, 0x29fc), (0x2a2d, 0x2a2e), (0x2a2e, 0x2a2d),
+    (0x2a34, 0x2a35), (0x2a35, 0x2a34), (0x2a79, 0x2a7a), (0x2a7a, 0x2a79), (0x2a7b, 0x2a7c),
+    (0x2a7c, 0x2a7b), (0x2a7d, 0x2a7e), (0x2a7e, 0x2a7d), (0x2a7f, 0x2a80), (0x2a80, 0x2a7f),
+    (0x2a81, 0x2a82), (0x2a82, 0x2a81), (0x2a85, 0x2a86), (0x2a86, 0x2a85), (0x2a87, 0x2a88),
+    (0x2a88, 0x2a87), (0x2a89, 0x2a8a), (0x2a8a, 0x2a89), (0x2a8b, 0x2a8c), (0x2a8c, 0x2a8b),
+    (0x2a8d, 0x2a8e), (0x2a8e, 0x2a8d), (0x2a8f, 0x2a90), (0x2a90, 0x2a8f), (0x2a91, 0x2a92),
+    (0x2a92, 0x2a91), (0x2a93, 0x2a94), (0x2a94, 0x2a93), (0x2a95, 0x2a96), (0x2a96, 0x2a95),
+    (0x2a97, 0x2a98), (0x2a98, 0x2a97), (0x2a99, 0x2a9a), (0x2a9a, 0x2a99), (0x2a9b, 0x2a9c),
+    (0x2a9c, 0x2a9b), (0x2a9d, 0x2a9e), (0x2a9e, 0x2a9d), (0x2a9f, 0x2aa0), (0x2aa0, 0x2a9f),
+    (0x2aa1, 0x2aa2), (0x2aa2, 0x2aa1), (0x2aa6, 0x2aa7), (0x2aa7, 0x2aa6), (0x2aa8, 0x2aa9),
+    (0x2aa9, 0x2aa8), (0x2aaf, 0x2ab0), (0x2ab0, 0x2aaf), (0x2ab1, 0x2ab2), (0x2ab2, 0x2ab1),
+    (0x2ab3, 0x2ab4), (0x2ab4, 0x2ab3), (0x2ab5, 0x2ab6), (0x2ab6, 0x2ab5), (0x2ab7, 0x2ab8),
+    (0x2ab8, 0x2ab7), (0x2ab9, 0x2aba), (0x2aba, 0x2ab9), (0x2abb, 0x2abc), (0x2abc, 0x2abb),
+    (0x2abd, 0x2abe), (0x2abe, 0x2abd), (0x2abf, 0x2ac0), (0x2ac0, 0x2abf), (0x2ac1, 0x2ac2),
+    (0x2ac2, 0x2ac1), (0x2ac3, 0x2ac4), (0x2ac4, 0x2ac3), (0x2ac5, 0x2ac6), (0x2ac6, 0x2ac5),
+    (0x2ac7, 0x2ac8), (0x2ac8, 0x2ac7), (0x2ac9, 0x2aca), (0x2aca, 0x2ac9), (0x2acb, 0x2acc),
+    (0x2acc, 0x2acb), (0x2acd, 0x2ace), (0x2ace, 0x2acd), (0x2acf, 0x2ad0), (0x2ad0, 0x2acf),
+    (0x2ad1, 0x2ad2), (0x2ad2, 0x2ad1), (0x2ad3, 0x2ad4), (0x2ad4, 0x2ad3), (0x2ad5, 0x2ad6),
+    (0x2ad6, 0x2ad5), (0x2ae5, 0x22ab), (0x2af7, 0x2af8), (0x2af8, 0x2af7), (0x2af9, 0x2afa),
+    (0x2afa, 0x2af9), (0x2e02, 0x2e03), (0x2e03, 0x2e02), (0x2e04, 0x2e05), (0x2e05, 0x2e04),
+    (0x2e09, 0x2e0a), (0x2e0a, 0x2e09), (0x2e0c, 0x2e0d), (0x2e0d, 0x2e0c), (0x2e1c, 0x2e1d),
+    (0x2e1d, 0x2e1c), (0x2e20, 0x2e21), (0x2e21, 0x2e20), (0x2e22, 0x2e23), (0x2e23, 0x2e22),
+    (0x2e24, 0x2e25), (0x2e25, 0x2e24), (0x2e26, 0x2e27), (0x2e27, 0x2e26), (0x2e28, 0x2e29),
+    (0x2e29, 0x2e28), (0x2e55, 0x2e56), (0x2e56, 0x2e55), (0x2e57, 0x2e58), (0x2e58, 0x2e57),
+    (0x2e59, 0x2e5a), (0x2e5a, 0x2e59), (0x2e5b, 0x2e5c), (0x2e5c, 0x2e5b), (0x3008, 0x3009),
+    (0x3009, 0x3008), (0x300a, 0x300b), (0x300b, 0x300a), (0x300c, 0x300d), (0x300d, 0x300c),
+    (0x300e, 0x300f), (0x300f, 0x300e), (0x3010, 0x3011), (0x3011, 0x3010), (0x3014, 0x3015),
+    (0x3015, 0x3014), (0x3016, 0x3017), (0x3017, 0x3016), (0x3018, 0x3019), (0x3019, 0x3018),
+    (0x301a, 0x301b), (0x301b, 0x301a), (0xfe59, 0xfe5a), (0xfe5a, 0xfe59), (0xfe5b, 0xfe5c),
+    (0xfe5c, 0xfe5b), (0xfe5d, 0xfe5e), (0xfe5e, 0xfe5d), (0xfe64, 0xfe65), (0xfe65, 0xfe64),
+    (0xff08, 0xff09), (0xff09, 0xff08), (0xff1c, 0xff1e), (0xff1e, 0xff1c), (0xff3b, 0xff3d),
+    (0xff3d, 0xff3b), (0xff5b, 0xff5d), (0xff5d, 0xff5b), (0xff5f, 0xff60), (0xff60, 0xff5f),
+    (0xff62, 0xff63), (0xff63, 0xff62),
+];
+
+/// Opening brackets with the closing brackets they pair with, sorted.
+pub const BRACKETS: &[(u32, u32)] = &[
+    (0x0028, 0x0029), (0x005b, 0x005d), (0x007b, 0x007d), (0x0f3a, 0x0f3b), (0x0f3c, 0x0f3d),
+    (0x169b, 0x169c), (0x2045, 0x2046), (0x207d, 0x207e), (0x208d, 0x208e), (0x2308, 0x2309),
+    (0x230a, 0x230b), (0x2329, 0x232a), (0x2768, 0x2769), (0x276a, 0x276b), (0x276c, 0x276d),
+    (0x276e, 0x276f), (0x2770, 0x2771), (0x2772, 0x2773), (0x2774, 0x2775), (0x27c5, 0x27c6),
+    (0x27e6, 0x27e7), (0x27e8, 0x27e9), (0x27ea, 0x27eb), (0x27ec, 0x27ed), (0x27ee, 0x27ef),
+    (0x2983, 0x2984), (0x2985, 0x2986), (0x2987, 0x2988), (0x2989, 0x298a), (0x298b, 0x298c),
+    (0x298d, 0x2990), (0x298f, 0x298e), (0x2991, 0x2992), (0x2997, 0x2998), (0x29d8, 0x29d9),
+    (0x29da, 0x29db), (0x29fc, 0x29fd), (0x2e22, 0x2e23), (0x2e24, 0x2e25), (0x2e26, 0x2e27),
+    (0x2e28, 0x2e29), (0x2e55, 0x2e56), (0x2e57, 0x2e58), (0x2e59, 0x2e5a), (0x2e5b, 0x2e5c),
+    (0x3008, 0x3009), (0x300a, 0x300b), (0x300c, 0x300d), (0x300e, 0x300f), (0x3010, 0x3011),
+    (0x3014, 0x3015), (0x3016, 0x3017), (0x3018, 0x3019), (0x301a, 0x301b), (0xfe59, 0xfe5a),
+    (0xfe5b, 0xfe5c), (0xfe5d, 0xfe5e), (0xff08, 0xff09), (0xff3b, 0xff3d), (0xff5b, 0xff5d),
+    (0xff5f, 0xff60), (0xff62, 0xff63),
+];
@@ -50,7 +50,14 @@ impl Color {
 
     /// Linearly interpolates from color c0 to c1, and returns resulting color value.
     pub fn lerp_rgba(c0: Color, c1: Color, u: f32) -> Self {
-        unimplemented!();
+        let u = u.clamp(0.0, 1.0);
+        let oneminu = 1.0 - u;
+        Color{
+            r: c0.r * oneminu + c1.r * u,
+            g: c0.g * oneminu + c1.g * u,
+            b: c0.b * oneminu + c1.b * u,
+            a: c0.a * oneminu + c1.a * u,
+        }
     }
 
     /// Sets transparency of a color value.
@@ -72,12 +79,103 @@ impl Color {
     /// Returns color value specified by hue, saturation and lightness.
     /// HSL values are all in range [0..1], alpha will be set to 255.
     pub fn hsl(h: f32, s: f32, l: f32) -> Self {
-        unimplemented!();
+        Color::hsla(h, s, l, 255)
     }
 
     /// Returns color value specified by hue, saturation and lightness and alpha.
     /// HSL values are all in range [0..1], alpha in range [0..255]
     pub fn hsla(h: f32, s: f32, l: f32, a: u8) -> Self {
-        unimplemented!();
+        let mut h = h % 1.0;
+        if h < 0.0 {
+            h += 1.0;
+        }
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let m2 = if l <= 0.5 { l * (1.0 + s) } else { l + s - l * s };
+        let m1 = 2.0 * l - m2;
+        Color{
+            r: hue(h + 1.0 / 3.0, m1, m2).clamp(0.0, 1.0),
+            g: hue(h, m1, m2).clamp(0.0, 1.0),
+            b: hue(h - 1.0 / 3.0, m1, m2).clamp(0.0, 1.0),
+            a: (a as f32) / 255.0,
+        }
+    }
+}
+
+/// Returns one channel of an HSL color, with the hue offset for the channel.
+fn hue(h: f32, m1: f32, m2: f32) -> f32 {
+    let h = if h < 0.0 { h + 1.0 } else if h > 1.0 { h - 1.0 } else { h };
+    if h < 1.0 / 6.0 {
+        m1 + (m2 - m1) * h * 6.0
+    } else if h < 3.0 / 6.0 {
+        m2
+    } else if h < 4.0 / 6.0 {
+        m1 + (m2 - m1) * (2.0 / 3.0 - h) * 6.0
+    } else {
+        m1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color(c: Color, expected: [f32; 4]) {
+        let actual = [c.r, c.g, c.b, c.a];
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn hsla_matches_nanovg() {
+        // Values from nvgHSLA in nanovg.c.
+        let cases: [(f32, f32, f32, u8, [f32; 4]); 12] = [
+            (0.0, 1.0, 0.5, 255, [1.0, 0.0, 3.576279e-7, 1.0]),
+            (1.0 / 3.0, 1.0, 0.5, 255, [0.0, 1.0, 0.0, 1.0]),
+            (2.0 / 3.0, 1.0, 0.5, 255, [0.0, 0.0, 1.0, 1.0]),
+            (0.55, 0.3, 0.75, 255, [0.6750001, 0.78, 0.8249999, 1.0]),
+            // Hue wraps around below 0 and above 1.
+            (-0.25, 0.6, 0.4, 128, [0.4000001, 0.16, 0.64, 0.5019608]),
+            (1.75, 0.6, 0.4, 128, [0.4000001, 0.16, 0.64, 0.5019608]),
+            (-1.9, 0.8, 0.6, 0, [0.9200001, 0.6640002, 0.28, 0.0]),
+            (3.0, 0.5, 0.5, 255, [0.75, 0.25, 0.2500002, 1.0]),
+            // Saturation and lightness are clamped.
+            (0.1, 1.5, 0.3, 255, [0.6, 0.36, 0.0, 1.0]),
+            (0.9, -0.5, 0.2, 64, [0.2, 0.2, 0.2, 0.2509804]),
+            (0.45, 0.7, 1.2, 255, [1.0, 1.0, 1.0, 1.0]),
+            (0.45, 0.7, -0.1, 255, [0.0, 0.0, 0.0, 1.0]),
+        ];
+        for &(h, s, l, a, expected) in &cases {
+            assert_color(Color::hsla(h, s, l, a), expected);
+        }
+        assert_color(Color::hsl(0.55, 0.3, 0.75), [0.6750001, 0.78, 0.8249999, 1.0]);
+    }
+
+    #[test]
+    fn hue_wraps_offsets() {
+        assert_eq!(hue(-0.25, 0.0, 1.0), 0.0);
+        assert_eq!(hue(1.25, 0.0, 1.0), 1.0);
+        assert!((hue(0.1, 0.2, 0.8) - 0.56).abs() < 1e-6);
+        assert!((hue(0.6, 0.2, 0.8) - 0.44).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lerp_rgba_matches_nanovg() {
+        // Values from nvgLerpRGBA in nanovg.c, u is clamped to [0..1].
+        let c0 = Color::rgba(10, 20, 30, 40);
+        let c1 = Color::rgba(200, 150, 100, 250);
+        let cases: [(f32, [f32; 4]); 7] = [
+            (-0.5, [0.03921569, 0.07843138, 0.1176471, 0.1568628]),
+            (0.0, [0.03921569, 0.07843138, 0.1176471, 0.1568628]),
+            (0.25, [0.2254902, 0.2058824, 0.1862745, 0.3627451]),
+            (0.5, [0.4117647, 0.3333333, 0.254902, 0.5686275]),
+            (0.7, [0.5607843, 0.4352941, 0.3098039, 0.7333333]),
+            (1.0, [0.7843137, 0.5882353, 0.3921569, 0.9803922]),
+            (1.5, [0.7843137, 0.5882353, 0.3921569, 0.9803922]),
+        ];
+        for &(u, expected) in &cases {
+            assert_color(Color::lerp_rgba(c0, c1, u), expected);
+        }
     }
 }
@@ -0,0 +1,140 @@
+//! Composite operations and blending.
+//!
+//! Composite operations are expressed as OpenGL style blend functions
+//! working on premultiplied colors: the result is the source scaled by
+//! the source factor plus the destination scaled by the destination
+//! factor. A GL backend maps the factors to its own enums, CPU backends
+//! can use `CompositeOperationState::blend` directly.
+
+use crate::{BlendFactor, CompositeOperation, CompositeOperationState};
+
+impl BlendFactor {
+    /// Returns the per-channel factor [r, g, b, a] for premultiplied
+    /// source and destination colors.
+    pub fn weights(self, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        match self {
+            BlendFactor::Zero                     => [0.0; 4],
+            BlendFactor::One                      => [1.0; 4],
+            BlendFactor::SourceColor              => src,
+            BlendFactor::OneMinusSourceColor      => [1.0 - src[0], 1.0 - src[1], 1.0 - src[2], 1.0 - src[3]],
+            BlendFactor::DestinationColor         => dst,
+            BlendFactor::OneMinusDestinationColor => [1.0 - dst[0], 1.0 - dst[1], 1.0 - dst[2], 1.0 - dst[3]],
+            BlendFactor::SourceAlpha              => [src[3]; 4],
+            BlendFactor::OneMinusSourceAlpha      => [1.0 - src[3]; 4],
+            BlendFactor::DestinationAlpha         => [dst[3]; 4],
+            BlendFactor::OneMinusDestinationAlpha => [1.0 - dst[3]; 4],
+            BlendFactor::SourceAlphaSaturate      => {
+                let f = src[3].min(1.0 - dst[3]);
+                [f, f, f, 1.0]
+            }
+        }
+    }
+}
+
+impl From<CompositeOperation> for CompositeOperationState {
+    fn from(op: CompositeOperation) -> Self {
+        let (sfactor, dfactor) = match op {
+            CompositeOperation::SourceOver      => (BlendFactor::One, BlendFactor::OneMinusSourceAlpha),
+            CompositeOperation::SourceIn        => (BlendFactor::DestinationAlpha, BlendFactor::Zero),
+            CompositeOperation::SourceOut       => (BlendFactor::OneMinusDestinationAlpha, BlendFactor::Zero),
+            CompositeOperation::Atop            => (BlendFactor::DestinationAlpha, BlendFactor::OneMinusSourceAlpha),
+            CompositeOperation::DestinationOver => (BlendFactor::OneMinusDestinationAlpha, BlendFactor::One),
+            CompositeOperation::DestinationIn   => (BlendFactor::Zero, BlendFactor::SourceAlpha),
+            CompositeOperation::DestinationOut  => (BlendFactor::Zero, BlendFactor::OneMinusSourceAlpha),
+            CompositeOperation::DestinationAtop => (BlendFactor::OneMinusDestinationAlpha, BlendFactor::SourceAlpha),
+            CompositeOperation::Lighter         => (BlendFactor::One, BlendFactor::One),
+            CompositeOperation::Copy            => (BlendFactor::One, BlendFactor::Zero),
+            CompositeOperation::Xor             => (BlendFactor::OneMinusDestinationAlpha, BlendFactor::OneMinusSourceAlpha),
+        };
+        CompositeOperationState::blend_func(sfactor, dfactor)
+    }
+}
+
+impl CompositeOperationState {
+    /// Returns the state which uses the same factors for color and alpha.
+    pub fn blend_func(sfactor: BlendFactor, dfactor: BlendFactor) -> Self {
+        CompositeOperationState{
+            src_rgb:   sfactor,
+            dst_rgb:   dfactor,
+            src_alpha: sfactor,
+            dst_alpha: dfactor,
+        }
+    }
+
+    /// Blends a premultiplied source color into a premultiplied
+    /// destination color and returns the result, clamped to 0..1 like a
+    /// fixed point framebuffer.
+    pub fn blend(&self, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        let src_rgb = self.src_rgb.weights(src, dst);
+        let dst_rgb = self.dst_rgb.weights(src, dst);
+        let src_alpha = self.src_alpha.weights(src, dst);
+        let dst_alpha = self.dst_alpha.weights(src, dst);
+        let mut out = [0.0; 4];
+        for c in 0..3 {
+            out[c] = src[c] * src_rgb[c] + dst[c] * dst_rgb[c];
+        }
+        out[3] = src[3] * src_alpha[3] + dst[3] * dst_alpha[3];
+        for c in &mut out {
+            *c = c.clamp(0.0, 1.0);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_map_to_nanovg_factors() {
+        // The table in nvg__compositeOperationState in nanovg.c.
+        use crate::BlendFactor::*;
+        let cases = [
+            (CompositeOperation::SourceOver,      One,                      OneMinusSourceAlpha),
+            (CompositeOperation::SourceIn,        DestinationAlpha,         Zero),
+            (CompositeOperation::SourceOut,       OneMinusDestinationAlpha, Zero),
+            (CompositeOperation::Atop,            DestinationAlpha,         OneMinusSourceAlpha),
+            (CompositeOperation::DestinationOver, OneMinusDestinationAlpha, One),
+            (CompositeOperation::DestinationIn,   Zero,                     SourceAlpha),
+            (CompositeOperation::DestinationOut,  Zero,                     OneMinusSourceAlpha),
+            (CompositeOperation::DestinationAtop, OneMinusDestinationAlpha, SourceAlpha),
+            (CompositeOperation::Lighter,         One,                      One),
+            (CompositeOperation::Copy,            One,                      Zero),
+            (CompositeOperation::Xor,             OneMinusDestinationAlpha, OneMinusSourceAlpha),
+        ];
+        for &(op, sfactor, dfactor) in &cases {
+            let state = CompositeOperationState::from(op);
+            assert_eq!((state.src_rgb, state.dst_rgb), (sfactor, dfactor), "{:?}", op);
+            assert_eq!((state.src_alpha, state.dst_alpha), (sfactor, dfactor), "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn blends_premultiplied_colors() {
+        // Half transparent red over opaque blue.
+        let src = [0.5, 0.0, 0.0, 0.5];
+        let dst = [0.0, 0.0, 1.0, 1.0];
+        let cases = [
+            (CompositeOperation::SourceOver,      [0.5, 0.0, 0.5, 1.0]),
+            (CompositeOperation::SourceIn,        [0.5, 0.0, 0.0, 0.5]),
+            (CompositeOperation::SourceOut,       [0.0, 0.0, 0.0, 0.0]),
+            (CompositeOperation::Atop,            [0.5, 0.0, 0.5, 1.0]),
+            (CompositeOperation::DestinationOver, [0.0, 0.0, 1.0, 1.0]),
+            (CompositeOperation::DestinationIn,   [0.0, 0.0, 0.5, 0.5]),
+            (CompositeOperation::DestinationOut,  [0.0, 0.0, 0.5, 0.5]),
+            (CompositeOperation::DestinationAtop, [0.0, 0.0, 0.5, 0.5]),
+            (CompositeOperation::Lighter,         [0.5, 0.0, 1.0, 1.0]),
+            (CompositeOperation::Copy,            [0.5, 0.0, 0.0, 0.5]),
+            (CompositeOperation::Xor,             [0.0, 0.0, 0.5, 0.5]),
+        ];
+        for &(op, expected) in &cases {
+            assert_eq!(CompositeOperationState::from(op).blend(src, dst), expected, "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn source_alpha_saturate_keeps_alpha() {
+        let weights = BlendFactor::SourceAlphaSaturate.weights([0.5, 0.5, 0.5, 0.5], [0.0, 0.0, 0.0, 0.75]);
+        assert_eq!(weights, [0.25, 0.25, 0.25, 1.0]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Fill expansion.
+//!
+//! Turns the flattened paths of a `PathCache` into a triangle fan for
+//! the interior of every path, plus a triangle strip for the
+//! antialiasing fringe along its outline.
+//!
+//! A single convex path gets only half a fringe outside its inset fan,
+//! so it can be drawn directly. Anything else has to be rendered by
+//! filling the fans into a stencil with the non-zero rule, drawing the
+//! fringes where the stencil is empty and then covering the stencil.
+
+use crate::path::{Path, PathCache, Vertex, PT_BEVEL, PT_INNERBEVEL, PT_LEFT};
+use crate::stroke::bevel_join;
+use crate::LineJoin;
+
+impl PathCache {
+    /// Returns true when the fill can be drawn without a stencil.
+    pub fn is_convex(&self) -> bool {
+        self.paths.len() == 1 && self.paths[0].convex
+    }
+
+    /// Returns the fill triangle fan of a path.
+    pub fn fill_verts(&self, path: &Path) -> &[Vertex] {
+        &self.verts[path.fill.clone()]
+    }
+
+    /// Expands every flattened path into a fill fan and, when `w` is
+    /// positive, a fringe strip of width `fringe` centered on the outline.
+    pub fn expand_fill(&mut self, w: f32, fringe: f32, line_join: LineJoin, miter_limit: f32) {
+        let aa = fringe;
+        let has_fringe = w > 0.0;
+
+        self.calculate_joins(w, line_join, miter_limit);
+        self.verts.clear();
+
+        let convex = self.is_convex();
+
+        for i in 0..self.paths.len() {
+            let path = self.paths[i].clone();
+            let pts = &self.points[path.first..path.first + path.count];
+            let dst = &mut self.verts;
+            let n = pts.len();
+
+            // Calculate shape vertices.
+            let woff = 0.5 * aa;
+            let fill_start = dst.len();
+            if has_fringe {
+                for j in 0..n {
+                    let p0 = &pts[(j + n - 1) % n];
+                    let p1 = &pts[j];
+                    if p1.flags & PT_BEVEL != 0 {
+                        let dlx0 = p0.dy;
+                        let dly0 = -p0.dx;
+                        let dlx1 = p1.dy;
+                        let dly1 = -p1.dx;
+                        if p1.flags & PT_LEFT != 0 {
+                            let lx = p1.x + p1.dmx * woff;
+                            let ly = p1.y + p1.dmy * woff;
+                            dst.push(Vertex::new(lx, ly, 0.5, 1.0));
+                        } else {
+                            let lx0 = p1.x + dlx0 * woff;
+                            let ly0 = p1.y + dly0 * woff;
+                            let lx1 = p1.x + dlx1 * woff;
+                            let ly1 = p1.y + dly1 * woff;
+                            dst.push(Vertex::new(lx0, ly0, 0.5, 1.0));
+                            dst.push(Vertex::new(lx1, ly1, 0.5, 1.0));
+                        }
+                    } else {
+                        dst.push(Vertex::new(p1.x + p1.dmx * woff, p1.y + p1.dmy * woff, 0.5, 1.0));
+                    }
+                }
+            } else {
+                for p in pts {
+                    dst.push(Vertex::new(p.x, p.y, 0.5, 1.0));
+                }
+            }
+            let fill_end = dst.len();
+
+            // Calculate fringe
+            let stroke_start = dst.len();
+            if has_fringe && n > 0 {
+                let mut lw = w + woff;
+                let rw = w - woff;
+                let mut lu = 0.0;
+                let ru = 1.0;
+
+                // Create only half a fringe for convex shapes so that
+                // the shape can be rendered without stenciling.
+                if convex {
+                    lw = woff; // This should generate the same vertex as fill inset above.
+                    lu = 0.5; // Set outline fade at middle.
+                }
+
+                for j in 0..n {
+                    let p0 = &pts[(j + n - 1) % n];
+                    let p1 = &pts[j];
+                    if p1.flags & (PT_BEVEL | PT_INNERBEVEL) != 0 {
+                        bevel_join(dst, p0, p1, lw, rw, lu, ru);
+                    } else {
+                        dst.push(Vertex::new(p1.x + p1.dmx * lw, p1.y + p1.dmy * lw, lu, 1.0));
+                        dst.push(Vertex::new(p1.x - p1.dmx * rw, p1.y - p1.dmy * rw, ru, 1.0));
+                    }
+                }
+
+                // Loop it
+                let (v0, v1) = (dst[stroke_start], dst[stroke_start + 1]);
+                dst.push(Vertex::new(v0.x, v0.y, lu, 1.0));
+                dst.push(Vertex::new(v1.x, v1.y, ru, 1.0));
+            }
+            let stroke_end = dst.len();
+
+            self.paths[i].fill = fill_start..fill_end;
+            self.paths[i].stroke = stroke_start..stroke_end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Command;
+
+    /// Flattens closed polygons and expands their fill.
+    fn fill(polygons: &[&[(f32, f32)]], w: f32) -> PathCache {
+        let mut commands = Vec::new();
+        for points in polygons {
+            commands.push(Command::MoveTo(points[0].0, points[0].1));
+            commands.extend(points[1..].iter().map(|&(x, y)| Command::LineTo(x, y)));
+            commands.push(Command::Close);
+        }
+        let mut cache = PathCache::new();
+        cache.flatten(&commands, 0.25, 0.01);
+        cache.expand_fill(w, 1.0, LineJoin::Miter, 2.4);
+        cache
+    }
+
+    fn coords(verts: &[Vertex]) -> Vec<(f32, f32, f32)> {
+        verts.iter().map(|v| (v.x, v.y, v.u)).collect()
+    }
+
+    const SQUARE: [(f32, f32); 4] = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+
+    #[test]
+    fn detects_convex_fills() {
+        assert!(fill(&[&SQUARE], 1.0).is_convex());
+        // Clockwise points are reversed, which keeps the path convex.
+        let mut reversed = SQUARE;
+        reversed.reverse();
+        assert!(fill(&[&reversed], 1.0).is_convex());
+        // A concave path or more than one path needs the stencil.
+        let l_shape = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 5.0), (5.0, 5.0), (5.0, 0.0)];
+        assert!(!fill(&[&l_shape], 1.0).is_convex());
+        let cache = fill(&[&SQUARE, &[(20.0, 0.0), (20.0, 10.0), (30.0, 10.0), (30.0, 0.0)]], 1.0);
+        assert!(cache.paths.iter().all(|path| path.convex));
+        assert!(!cache.is_convex());
+    }
+
+    #[test]
+    fn convex_fills_get_half_a_fringe() {
+        // The fan is inset by half the fringe, and the fringe fades from
+        // there to half the fringe outside the outline.
+        let cache = fill(&[&SQUARE], 1.0);
+        let path = &cache.paths[0];
+        assert_eq!(coords(cache.fill_verts(path)), [(0.5, 0.5, 0.5), (0.5, 9.5, 0.5), (9.5, 9.5, 0.5), (9.5, 0.5, 0.5)]);
+        let fringe = coords(cache.stroke_verts(path));
+        assert_eq!(fringe.len(), 4 * 2 + 2);
+        assert_eq!(fringe[..4], [(0.5, 0.5, 0.5), (-0.5, -0.5, 1.0), (0.5, 9.5, 0.5), (-0.5, 10.5, 1.0)]);
+        assert_eq!(fringe[8..], fringe[..2]);
+    }
+
+    #[test]
+    fn stencil_fills_get_a_full_fringe() {
+        let cache = fill(&[&SQUARE, &[(20.0, 0.0), (20.0, 10.0), (30.0, 10.0), (30.0, 0.0)]], 1.0);
+        let path = &cache.paths[0];
+        assert_eq!(coords(cache.fill_verts(path)), [(0.5, 0.5, 0.5), (0.5, 9.5, 0.5), (9.5, 9.5, 0.5), (9.5, 0.5, 0.5)]);
+        let fringe = coords(cache.stroke_verts(path));
+        assert_eq!(fringe.len(), 4 * 2 + 2);
+        assert_eq!(fringe[..4], [(1.5, 1.5, 0.0), (-0.5, -0.5, 1.0), (1.5, 8.5, 0.0), (-0.5, 10.5, 1.0)]);
+    }
+
+    #[test]
+    fn aliased_fills_have_no_fringe() {
+        for cache in &[fill(&[&SQUARE], 0.0), fill(&[&SQUARE, &SQUARE], 0.0)] {
+            for path in &cache.paths {
+                assert_eq!(coords(cache.fill_verts(path)), [(0.0, 0.0, 0.5), (0.0, 10.0, 0.5), (10.0, 10.0, 0.5), (10.0, 0.0, 0.5)]);
+                assert!(cache.stroke_verts(path).is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Joining forms of Arabic, Syriac and N'Ko letters.
+//!
+//! Letters of these scripts connect to their neighbours. Depending on which
+//! sides connect, a letter takes its isolated, initial, medial or final
+//! form, which fonts select with the `isol`, `init`, `medi` and `fina`
+//! features.
+
+use super::script;
+use super::shape::{Buffer, FINA, INIT, ISOL, MEDI};
+
+/// How a character joins to its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Joining {
+    /// Does not join.
+    None,
+    /// Joins to the character before it only.
+    Right,
+    /// Joins on both sides.
+    Dual,
+    /// Joins on both sides without changing its own shape, like ZWJ.
+    Causing,
+    /// Is skipped when joining, like combining marks.
+    Transparent,
+}
+
+fn joining(c: char) -> Joining {
+    if script::is_mark(c) && c != '\u{200c}' && c != '\u{200d}' {
+        return Joining::Transparent;
+    }
+    match c as u32 {
+        0x200d | 0x640 | 0x7fa => Joining::Causing,
+        0x622..=0x625 | 0x627 | 0x629 | 0x62f..=0x632 | 0x648 | 0x671..=0x673 | 0x675..=0x677 |
+        0x688..=0x699 | 0x6c0 | 0x6c3..=0x6cb | 0x6cd | 0x6cf | 0x6d2..=0x6d3 | 0x6d5 | 0x6ee..=0x6ef |
+        0x759..=0x75b | 0x76b..=0x76c | 0x771 | 0x773..=0x774 | 0x778..=0x779 | 0x8aa..=0x8ac | 0x8ae |
+        0x8b1..=0x8b2 | 0x8b9 => Joining::Right,
+        0x620 | 0x626 | 0x628 | 0x62a..=0x62e | 0x633..=0x63f | 0x641..=0x647 | 0x649..=0x64a |
+        0x66e..=0x66f | 0x678..=0x687 | 0x69a..=0x6bf | 0x6c1..=0x6c2 | 0x6cc | 0x6ce | 0x6d0..=0x6d1 |
+        0x6fa..=0x6fc | 0x6ff | 0x750..=0x758 | 0x75c..=0x76a | 0x76d..=0x770 | 0x772 | 0x775..=0x777 |
+        0x77a..=0x77f | 0x8a0..=0x8a9 | 0x8af..=0x8b0 | 0x8b3..=0x8b8 | 0x8ba..=0x8c7 => Joining::Dual,
+        // Syriac.
+        0x710 | 0x715..=0x719 | 0x71e | 0x728 | 0x72a | 0x72c | 0x72f | 0x74d => Joining::Right,
+        0x712..=0x714 | 0x71a..=0x71d | 0x71f..=0x727 | 0x729 | 0x72b | 0x72d..=0x72e | 0x74e..=0x74f => {
+            Joining::Dual
+        }
+        // N'Ko.
+        0x7ca..=0x7ea => Joining::Dual,
+        _ => Joining::None,
+    }
+}
+
+/// Sets the masks of the joining form features on every letter.
+pub fn setup_masks(buffer: &mut Buffer) {
+    let mut forms = vec![0u32; buffer.info.len()];
+    // The last letter which is not transparent, and whether it can join to
+    // the letter after it.
+    let mut prev: Option<(usize, bool)> = None;
+    for i in 0..buffer.info.len() {
+        let joining = joining(buffer.info[i].codepoint);
+        if joining == Joining::Transparent {
+            continue;
+        }
+        let joins_before = joining == Joining::Right || joining == Joining::Dual || joining == Joining::Causing;
+        let connected = joins_before && prev.is_some_and(|(_, joins_after)| joins_after);
+        if connected {
+            let (p, _) = prev.unwrap();
+            forms[p] = match forms[p] {
+                ISOL => INIT,
+                FINA => MEDI,
+                form => form,
+            };
+        }
+        forms[i] = match joining {
+            Joining::Right | Joining::Dual => if connected { FINA } else { ISOL },
+            _ => 0,
+        };
+        let joins_after = joining == Joining::Dual || joining == Joining::Causing;
+        prev = Some((i, joins_after));
+    }
+    for (info, form) in buffer.info.iter_mut().zip(forms) {
+        info.mask |= form;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::tests::{layout_font, layout_table, ligature_subst, lookup, shaped, shaped_glyphs, single_subst};
+    use crate::font::{Font, Script};
+
+    /// Returns a font for beh, alef, lam and fatha with the joining forms
+    /// of the letters and lam-alef ligatures.
+    fn joining_font() -> Font {
+        let lookups = [
+            lookup(1, 0, &[single_subst(&[(1, 10), (2, 14), (3, 16)])]),
+            lookup(1, 0, &[single_subst(&[(1, 13), (2, 15), (3, 19)])]),
+            lookup(1, 0, &[single_subst(&[(1, 12), (3, 18)])]),
+            lookup(1, 0, &[single_subst(&[(1, 11), (3, 17)])]),
+            lookup(4, 0, &[ligature_subst(&[(&[17, 15], 20), (&[18, 15], 21)])]),
+        ];
+        let features: [(&[u8; 4], &[u16]); 5] = [
+            (b"isol", &[0]),
+            (b"fina", &[1]),
+            (b"medi", &[2]),
+            (b"init", &[3]),
+            (b"rlig", &[4]),
+        ];
+        layout_font("\u{628}\u{627}\u{644}\u{64e}", vec![(b"GSUB", layout_table(&features, &lookups))])
+    }
+
+    #[test]
+    fn picks_joining_forms() {
+        let font = joining_font();
+        let forms = |text| shaped_glyphs(&font, text, Script::Arabic, true);
+        assert_eq!(forms("\u{628}"), vec![10]);
+        assert_eq!(forms("\u{628}\u{628}"), vec![11, 13]);
+        assert_eq!(forms("\u{628}\u{628}\u{628}"), vec![11, 12, 13]);
+        // Alef does not join the letter after it.
+        assert_eq!(forms("\u{628}\u{627}"), vec![11, 15]);
+        assert_eq!(forms("\u{627}\u{628}"), vec![14, 10]);
+        assert_eq!(forms("\u{628}\u{627}\u{628}\u{628}"), vec![11, 15, 11, 13]);
+        // Marks are transparent.
+        assert_eq!(forms("\u{628}\u{64e}\u{628}"), vec![11, 4, 13]);
+    }
+
+    #[test]
+    fn forms_lam_alef() {
+        let font = joining_font();
+        let glyphs = shaped(&font, "\u{644}\u{627}", Script::Arabic, true);
+        assert_eq!(glyphs.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(20, 0)]);
+        assert_eq!(shaped_glyphs(&font, "\u{628}\u{644}\u{627}", Script::Arabic, true), vec![11, 21]);
+        assert_eq!(shaped_glyphs(&font, "\u{644}\u{644}", Script::Arabic, true), vec![17, 19]);
+    }
+}
@@ -0,0 +1,580 @@
+//! PostScript glyph outlines from the `CFF ` table.
+//!
+//! Only what is needed to draw glyphs is read: the charstrings, the
+//! global and local subroutines, and for CID keyed fonts the font dict
+//! each glyph uses. Charstrings must be Type 2, which is all OpenType
+//! allows.
+
+use super::{read_u16, read_u8, FontError, OutlineCommand};
+
+/// Subroutine calls nested deeper than this fail the glyph.
+const MAX_CALL_DEPTH: usize = 10;
+/// Type 2 charstrings have at most 48 arguments.
+const MAX_STACK: usize = 48;
+
+/// An INDEX structure, with absolute offsets into the font file.
+#[derive(Debug, Clone, Copy, Default)]
+struct Index {
+    count:    usize,
+    off_size: usize,
+    /// Start of the offset array.
+    offsets:  usize,
+    /// Byte before the first object, which offsets are relative to.
+    base:     usize,
+    /// First byte after the INDEX.
+    end:      usize,
+}
+
+impl Index {
+    fn parse(data: &[u8], pos: usize) -> Option<Index> {
+        let count = read_u16(data, pos)? as usize;
+        if count == 0 {
+            return Some(Index{ end: pos + 2, ..Index::default() });
+        }
+        let off_size = read_u8(data, pos + 2)? as usize;
+        if !(1..=4).contains(&off_size) {
+            return None;
+        }
+        let offsets = pos + 3;
+        let base = offsets + (count + 1) * off_size - 1;
+        let index = Index{ count: count, off_size: off_size, offsets: offsets, base: base, end: 0 };
+        let last = index.offset(data, count)?;
+        if base + last > data.len() {
+            return None;
+        }
+        Some(Index{ end: base + last, ..index })
+    }
+
+    fn offset(&self, data: &[u8], i: usize) -> Option<usize> {
+        let pos = self.offsets + i * self.off_size;
+        let bytes = data.get(pos..pos + self.off_size)?;
+        Some(bytes.iter().fold(0, |v, &b| v << 8 | b as usize))
+    }
+
+    /// Returns the byte range of object `i`.
+    fn get(&self, data: &[u8], i: usize) -> Option<(usize, usize)> {
+        if i >= self.count {
+            return None;
+        }
+        let start = self.offset(data, i)?;
+        let end = self.offset(data, i + 1)?;
+        if start < 1 || start > end || self.base + end > data.len() {
+            return None;
+        }
+        Some((self.base + start, self.base + end))
+    }
+
+    /// Returns the number added to subroutine numbers.
+    fn bias(&self) -> isize {
+        if self.count < 1240 {
+            107
+        } else if self.count < 33900 {
+            1131
+        } else {
+            32768
+        }
+    }
+}
+
+/// Parses a DICT into (operator, operands) pairs. Two byte operators
+/// are returned as 1200 + the second byte.
+fn parse_dict(data: &[u8], start: usize, end: usize) -> Option<Vec<(u16, Vec<f64>)>> {
+    let mut entries = Vec::new();
+    let mut operands = Vec::new();
+    let mut pos = start;
+    while pos < end {
+        let b = read_u8(data, pos)?;
+        pos += 1;
+        match b {
+            0..=21 => {
+                let op = if b == 12 {
+                    pos += 1;
+                    1200 + read_u8(data, pos - 1)? as u16
+                } else {
+                    b as u16
+                };
+                entries.push((op, std::mem::take(&mut operands)));
+            }
+            28 => {
+                operands.push(read_u16(data, pos)? as i16 as f64);
+                pos += 2;
+            }
+            29 => {
+                let v = (read_u16(data, pos)? as u32) << 16 | read_u16(data, pos + 2)? as u32;
+                operands.push(v as i32 as f64);
+                pos += 4;
+            }
+            30 => {
+                // Real numbers are packed in nibbles, which only matter
+                // for font matrices, so they are skipped.
+                loop {
+                    let b = read_u8(data, pos)?;
+                    pos += 1;
+                    if b & 0x0f == 0x0f || b >> 4 == 0x0f {
+                        break;
+                    }
+                }
+                operands.push(0.0);
+            }
+            32..=246 => operands.push(b as f64 - 139.0),
+            247..=250 => {
+                operands.push((b as f64 - 247.0) * 256.0 + read_u8(data, pos)? as f64 + 108.0);
+                pos += 1;
+            }
+            251..=254 => {
+                operands.push(-(b as f64 - 251.0) * 256.0 - read_u8(data, pos)? as f64 - 108.0);
+                pos += 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(entries)
+}
+
+fn dict_get(dict: &[(u16, Vec<f64>)], op: u16) -> Option<&[f64]> {
+    dict.iter().find(|&&(o, _)| o == op).map(|(_, operands)| &operands[..])
+}
+
+/// Reads the local subroutines of a Private DICT given by its [size, offset]
+/// operands, relative to the start of the CFF table.
+fn private_subrs(data: &[u8], table: usize, private: Option<&[f64]>) -> Option<Index> {
+    let private = match private {
+        Some(p) if p.len() >= 2 => p,
+        _ => return Some(Index::default()),
+    };
+    let start = table + private[1] as usize;
+    let dict = parse_dict(data, start, start + private[0] as usize)?;
+    match dict_get(&dict, 19) {
+        Some(subrs) if !subrs.is_empty() => Index::parse(data, start + subrs[0] as usize),
+        _ => Some(Index::default()),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cff {
+    charstrings: Index,
+    global_subrs: Index,
+    local_subrs: Index,
+    /// FDSelect offset and the local subroutines of every font dict, for
+    /// CID keyed fonts.
+    cid: Option<(usize, Vec<Index>)>,
+}
+
+impl Cff {
+    /// Parses the CFF table found at `offset` in the font file.
+    pub fn parse(data: &[u8], offset: usize) -> Result<Cff, FontError> {
+        Cff::parse_table(data, offset).ok_or(FontError::Corrupt("invalid CFF table"))
+    }
+
+    fn parse_table(data: &[u8], table: usize) -> Option<Cff> {
+        let header_size = read_u8(data, table + 2)? as usize;
+        let names = Index::parse(data, table + header_size)?;
+        let top_dicts = Index::parse(data, names.end)?;
+        let strings = Index::parse(data, top_dicts.end)?;
+        let global_subrs = Index::parse(data, strings.end)?;
+        let (start, end) = top_dicts.get(data, 0)?;
+        let top = parse_dict(data, start, end)?;
+
+        if dict_get(&top, 1206).map_or(2.0, |t| t.first().cloned().unwrap_or(2.0)) != 2.0 {
+            return None;
+        }
+        let charstrings = Index::parse(data, table + *dict_get(&top, 17)?.first()? as usize)?;
+
+        let cid = if dict_get(&top, 1230).is_some() {
+            // CID keyed: every font dict has its own Private DICT.
+            let fd_array = Index::parse(data, table + *dict_get(&top, 1236)?.first()? as usize)?;
+            let fd_select = table + *dict_get(&top, 1237)?.first()? as usize;
+            let mut subrs = Vec::with_capacity(fd_array.count);
+            for i in 0..fd_array.count {
+                let (start, end) = fd_array.get(data, i)?;
+                let fd = parse_dict(data, start, end)?;
+                subrs.push(private_subrs(data, table, dict_get(&fd, 18))?);
+            }
+            Some((fd_select, subrs))
+        } else {
+            None
+        };
+        let local_subrs = private_subrs(data, table, dict_get(&top, 18))?;
+        Some(Cff{
+            charstrings:  charstrings,
+            global_subrs: global_subrs,
+            local_subrs:  local_subrs,
+            cid:          cid,
+        })
+    }
+
+    /// Returns the local subroutines used by a glyph.
+    fn subrs_for(&self, data: &[u8], glyph: u16) -> Option<Index> {
+        let (fd_select, fds) = match self.cid {
+            Some(ref cid) => (cid.0, &cid.1),
+            None => return Some(self.local_subrs),
+        };
+        let fd = match read_u8(data, fd_select)? {
+            0 => read_u8(data, fd_select + 1 + glyph as usize)? as usize,
+            3 => {
+                let ranges = read_u16(data, fd_select + 1)? as usize;
+                let mut found = None;
+                for i in 0..ranges {
+                    let range = fd_select + 3 + i * 3;
+                    let first = read_u16(data, range)?;
+                    let next = read_u16(data, range + 3)?;
+                    if glyph >= first && glyph < next {
+                        found = Some(read_u8(data, range + 2)? as usize);
+                        break;
+                    }
+                }
+                found?
+            }
+            _ => return None,
+        };
+        fds.get(fd).cloned()
+    }
+
+    pub fn outline(&self, data: &[u8], glyph: u16, out: &mut Vec<OutlineCommand>) -> bool {
+        self.run(data, glyph, out).is_some()
+    }
+
+    fn run(&self, data: &[u8], glyph: u16, out: &mut Vec<OutlineCommand>) -> Option<()> {
+        let (start, end) = self.charstrings.get(data, glyph as usize)?;
+        let mut interp = Interpreter{
+            data:         data,
+            global_subrs: self.global_subrs,
+            local_subrs:  self.subrs_for(data, glyph)?,
+            stack:        Vec::with_capacity(MAX_STACK),
+            stems:        0,
+            x:            0.0,
+            y:            0.0,
+            open:         false,
+            done:         false,
+            out:          out,
+        };
+        interp.run(start, end, 0)?;
+        interp.close();
+        Some(())
+    }
+}
+
+struct Interpreter<'a> {
+    data:         &'a [u8],
+    global_subrs: Index,
+    local_subrs:  Index,
+    stack:        Vec<f32>,
+    stems:        usize,
+    x:            f32,
+    y:            f32,
+    /// A contour has been started and not closed yet.
+    open:         bool,
+    done:         bool,
+    out:          &'a mut Vec<OutlineCommand>,
+}
+
+impl<'a> Interpreter<'a> {
+    fn close(&mut self) {
+        if self.open {
+            self.out.push(OutlineCommand::Close);
+            self.open = false;
+        }
+    }
+
+    fn move_to(&mut self, dx: f32, dy: f32) {
+        self.close();
+        self.x += dx;
+        self.y += dy;
+        self.out.push(OutlineCommand::MoveTo(self.x, self.y));
+        self.open = true;
+    }
+
+    fn line_to(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.out.push(OutlineCommand::LineTo(self.x, self.y));
+    }
+
+    fn curve_to(&mut self, dx1: f32, dy1: f32, dx2: f32, dy2: f32, dx3: f32, dy3: f32) {
+        let (x1, y1) = (self.x + dx1, self.y + dy1);
+        let (x2, y2) = (x1 + dx2, y1 + dy2);
+        self.x = x2 + dx3;
+        self.y = y2 + dy3;
+        self.out.push(OutlineCommand::CubicTo(x1, y1, x2, y2, self.x, self.y));
+    }
+
+    /// Counts the stem hints, whose arguments come in pairs after an
+    /// optional width.
+    fn stem_hints(&mut self) {
+        self.stems += self.stack.len() / 2;
+        self.stack.clear();
+    }
+
+    fn run(&mut self, mut pos: usize, end: usize, depth: usize) -> Option<()> {
+        while pos < end && !self.done {
+            let b = read_u8(self.data, pos)?;
+            pos += 1;
+            let n = self.stack.len();
+            match b {
+                1 | 3 | 18 | 23 => self.stem_hints(),
+                19 | 20 => {
+                    // Arguments before a hint mask are implicit vstems.
+                    self.stem_hints();
+                    pos += self.stems.div_ceil(8);
+                }
+                21 => {
+                    if n < 2 {
+                        return None;
+                    }
+                    let (dx, dy) = (self.stack[n - 2], self.stack[n - 1]);
+                    self.move_to(dx, dy);
+                    self.stack.clear();
+                }
+                22 | 4 => {
+                    let d = *self.stack.last()?;
+                    if b == 22 {
+                        self.move_to(d, 0.0);
+                    } else {
+                        self.move_to(0.0, d);
+                    }
+                    self.stack.clear();
+                }
+                5 => {
+                    for i in 0..n / 2 {
+                        let (dx, dy) = (self.stack[i * 2], self.stack[i * 2 + 1]);
+                        self.line_to(dx, dy);
+                    }
+                    self.stack.clear();
+                }
+                6 | 7 => {
+                    let mut horizontal = b == 6;
+                    for i in 0..n {
+                        let d = self.stack[i];
+                        if horizontal {
+                            self.line_to(d, 0.0);
+                        } else {
+                            self.line_to(0.0, d);
+                        }
+                        horizontal = !horizontal;
+                    }
+                    self.stack.clear();
+                }
+                8 | 24 | 25 => {
+                    let s = self.stack.clone();
+                    let mut i = 0;
+                    // rlinecurve starts with lines and ends with one curve.
+                    if b == 25 {
+                        while i + 6 < n {
+                            self.line_to(s[i], s[i + 1]);
+                            i += 2;
+                        }
+                    }
+                    while i + 6 <= n {
+                        self.curve_to(s[i], s[i + 1], s[i + 2], s[i + 3], s[i + 4], s[i + 5]);
+                        i += 6;
+                    }
+                    // rcurveline ends with a line.
+                    if b == 24 && i + 2 <= n {
+                        self.line_to(s[i], s[i + 1]);
+                    }
+                    self.stack.clear();
+                }
+                26 | 27 => {
+                    let s = self.stack.clone();
+                    let mut i = n % 2;
+                    let mut d1 = if i == 1 { s[0] } else { 0.0 };
+                    while i + 4 <= n {
+                        if b == 26 {
+                            self.curve_to(d1, s[i], s[i + 1], s[i + 2], 0.0, s[i + 3]);
+                        } else {
+                            self.curve_to(s[i], d1, s[i + 1], s[i + 2], s[i + 3], 0.0);
+                        }
+                        d1 = 0.0;
+                        i += 4;
+                    }
+                    self.stack.clear();
+                }
+                30 | 31 => {
+                    let s = self.stack.clone();
+                    let mut horizontal = b == 31;
+                    let mut i = 0;
+                    while i + 4 <= n {
+                        // The last curve may end with an extra coordinate.
+                        let last = if n - i == 5 { s[i + 4] } else { 0.0 };
+                        if horizontal {
+                            self.curve_to(s[i], 0.0, s[i + 1], s[i + 2], last, s[i + 3]);
+                        } else {
+                            self.curve_to(0.0, s[i], s[i + 1], s[i + 2], s[i + 3], last);
+                        }
+                        horizontal = !horizontal;
+                        i += 4;
+                    }
+                    self.stack.clear();
+                }
+                10 | 29 => {
+                    if depth >= MAX_CALL_DEPTH {
+                        return None;
+                    }
+                    let subrs = if b == 10 { self.local_subrs } else { self.global_subrs };
+                    let index = self.stack.pop()? as isize + subrs.bias();
+                    if index < 0 {
+                        return None;
+                    }
+                    let (start, end) = subrs.get(self.data, index as usize)?;
+                    self.run(start, end, depth + 1)?;
+                }
+                11 => return Some(()),
+                14 => {
+                    self.close();
+                    self.done = true;
+                }
+                12 => {
+                    let op = read_u8(self.data, pos)?;
+                    pos += 1;
+                    self.flex(op)?;
+                    self.stack.clear();
+                }
+                28 => {
+                    let v = read_u16(self.data, pos)? as i16;
+                    pos += 2;
+                    self.push(v as f32)?;
+                }
+                255 => {
+                    let v = (read_u16(self.data, pos)? as u32) << 16 | read_u16(self.data, pos + 2)? as u32;
+                    pos += 4;
+                    self.push(v as i32 as f32 / 65536.0)?;
+                }
+                32..=246 => self.push(b as f32 - 139.0)?,
+                247..=250 => {
+                    let v = (b as f32 - 247.0) * 256.0 + read_u8(self.data, pos)? as f32 + 108.0;
+                    pos += 1;
+                    self.push(v)?;
+                }
+                251..=254 => {
+                    let v = -(b as f32 - 251.0) * 256.0 - read_u8(self.data, pos)? as f32 - 108.0;
+                    pos += 1;
+                    self.push(v)?;
+                }
+                _ => return None,
+            }
+        }
+        Some(())
+    }
+
+    fn push(&mut self, v: f32) -> Option<()> {
+        if self.stack.len() >= MAX_STACK {
+            return None;
+        }
+        self.stack.push(v);
+        Some(())
+    }
+
+    /// Runs one of the flex operators, which draw two curves.
+    fn flex(&mut self, op: u8) -> Option<()> {
+        let s = self.stack.clone();
+        match op {
+            35 => {
+                if s.len() < 12 {
+                    return None;
+                }
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                self.curve_to(s[6], s[7], s[8], s[9], s[10], s[11]);
+            }
+            34 => {
+                if s.len() < 7 {
+                    return None;
+                }
+                self.curve_to(s[0], 0.0, s[1], s[2], s[3], 0.0);
+                self.curve_to(s[4], 0.0, s[5], -s[2], s[6], 0.0);
+            }
+            36 => {
+                if s.len() < 9 {
+                    return None;
+                }
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], 0.0);
+                self.curve_to(s[5], 0.0, s[6], s[7], s[8], -(s[1] + s[3] + s[7]));
+            }
+            37 => {
+                if s.len() < 11 {
+                    return None;
+                }
+                let dx = s[0] + s[2] + s[4] + s[6] + s[8];
+                let dy = s[1] + s[3] + s[5] + s[7] + s[9];
+                let (dx6, dy6) = if dx.abs() > dy.abs() { (s[10], -dy) } else { (-dx, s[10]) };
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                self.curve_to(s[6], s[7], s[8], s[9], dx6, dy6);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::cff_table;
+    use crate::font::OutlineCommand::*;
+
+    /// Returns a charstring number between -107 and 107.
+    fn n(v: i32) -> u8 {
+        (v + 139) as u8
+    }
+
+    /// Returns the outline of a glyph, `None` if its charstring fails.
+    fn outline_of(data: &[u8], glyph: u16) -> Option<Vec<OutlineCommand>> {
+        let mut out = Vec::new();
+        if Cff::parse(data, 0).unwrap().outline(data, glyph, &mut out) { Some(out) } else { None }
+    }
+
+    #[test]
+    fn runs_charstrings() {
+        // A width, a square of lines and a second contour of a curve.
+        let glyph = [
+            vec![n(50), n(10), n(20), 21, 247, 0, 6, 247, 0, 7, n(-100), 6],
+            vec![n(0), n(-100), 21, n(10), n(20), n(30), n(40), n(50), n(60), 8, 14],
+        ].concat();
+        let data = cff_table(&[vec![14], glyph], &[], &[]);
+        assert_eq!(outline_of(&data, 0), Some(vec![]));
+        assert_eq!(outline_of(&data, 1).unwrap(), [
+            MoveTo(10.0, 20.0), LineTo(118.0, 20.0), LineTo(118.0, 128.0), LineTo(18.0, 128.0), Close,
+            MoveTo(18.0, 28.0), CubicTo(28.0, 48.0, 58.0, 88.0, 108.0, 148.0), Close,
+        ]);
+        assert_eq!(outline_of(&data, 2), None);
+    }
+
+    #[test]
+    fn calls_subroutines() {
+        // Subroutine numbers are biased by 107 for small INDEXes.
+        let local = vec![vec![n(100), n(0), 5, 11], vec![n(-106), 10, 11]];
+        let global = vec![vec![n(0), n(100), 5, n(-107), 10, 11]];
+        let glyphs = [
+            vec![14],
+            vec![n(0), n(0), 21, n(-107), 29, n(-107), 10, 14],
+            // Subroutine 1 calls itself until the depth limit.
+            vec![n(0), n(0), 21, n(-106), 10, 14],
+            // There is no subroutine 2.
+            vec![n(0), n(0), 21, n(-105), 10, 14],
+        ];
+        let data = cff_table(&glyphs, &global, &local);
+        assert_eq!(outline_of(&data, 1).unwrap(),
+                   [MoveTo(0.0, 0.0), LineTo(0.0, 100.0), LineTo(100.0, 100.0), LineTo(200.0, 100.0), Close]);
+        assert_eq!(outline_of(&data, 2), None);
+        assert_eq!(outline_of(&data, 3), None);
+    }
+
+    #[test]
+    fn limits_the_stack() {
+        let mut glyph = vec![n(1); MAX_STACK];
+        glyph.push(5);
+        let data = cff_table(&[glyph.clone()], &[], &[]);
+        assert!(outline_of(&data, 0).is_some());
+        glyph.insert(0, n(1));
+        let data = cff_table(&[glyph], &[], &[]);
+        assert_eq!(outline_of(&data, 0), None);
+    }
+
+    #[test]
+    fn rejects_truncated_tables() {
+        let data = cff_table(&[vec![n(0), n(0), 21, 14]], &[], &[vec![11]]);
+        assert!(Cff::parse(&data, 0).is_ok());
+        for len in 0..data.len() {
+            assert!(Cff::parse(&data[..len], 0).is_err(), "{} bytes", len);
+        }
+    }
+}
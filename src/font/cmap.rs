@@ -0,0 +1,193 @@
+//! Character to glyph mapping.
+
+use super::{read_u16, read_u32, Table};
+
+/// A Unicode subtable of the `cmap` table.
+#[derive(Debug, Clone, Copy)]
+pub struct Subtable {
+    offset: usize,
+    format: u16,
+}
+
+impl Subtable {
+    /// Picks the best supported Unicode subtable: full repertoire tables
+    /// first, then BMP only ones.
+    pub fn find(data: &[u8], cmap: Table) -> Option<Subtable> {
+        let num = read_u16(data, cmap.offset + 2)? as usize;
+        let mut best: Option<(u32, Subtable)> = None;
+        for i in 0..num {
+            let record = cmap.offset + 4 + i * 8;
+            let platform = read_u16(data, record)?;
+            let encoding = read_u16(data, record + 2)?;
+            let offset = cmap.offset + read_u32(data, record + 4)? as usize;
+            let format = match read_u16(data, offset) {
+                Some(format) => format,
+                None => continue,
+            };
+            if ![0, 4, 6, 12].contains(&format) {
+                continue;
+            }
+            let rank = match (platform, encoding) {
+                (3, 10) | (0, 4) => 3,
+                (3, 1) | (0, _) => 2,
+                // Symbol fonts map their glyphs into the private use area.
+                (3, 0) => 1,
+                _ => continue,
+            };
+            // Prefer tables covering more than the BMP at the same rank.
+            let rank = rank * 2 + if format >= 12 { 1 } else { 0 };
+            if best.is_none_or(|(r, _)| rank > r) {
+                best = Some((rank, Subtable{ offset: offset, format: format }));
+            }
+        }
+        best.map(|(_, subtable)| subtable)
+    }
+
+    /// Returns the glyph of a code point, `None` if it is not mapped.
+    pub fn lookup(&self, data: &[u8], c: u32) -> Option<u16> {
+        let o = self.offset;
+        match self.format {
+            0 => {
+                if c < 256 {
+                    data.get(o + 6 + c as usize).map(|&g| g as u16)
+                } else {
+                    None
+                }
+            }
+            4 => {
+                if c > 0xffff {
+                    return None;
+                }
+                let c = c as u16;
+                let seg_count = read_u16(data, o + 6)? as usize / 2;
+                let ends = o + 14;
+                let starts = ends + seg_count * 2 + 2;
+                let deltas = starts + seg_count * 2;
+                let range_offsets = deltas + seg_count * 2;
+                // Binary search for the first segment ending at or after c.
+                let (mut lo, mut hi) = (0, seg_count);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    if read_u16(data, ends + mid * 2)? < c {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                if lo >= seg_count {
+                    return None;
+                }
+                let start = read_u16(data, starts + lo * 2)?;
+                if c < start {
+                    return None;
+                }
+                let delta = read_u16(data, deltas + lo * 2)?;
+                let range_offset = read_u16(data, range_offsets + lo * 2)? as usize;
+                if range_offset == 0 {
+                    return Some(c.wrapping_add(delta));
+                }
+                let at = range_offsets + lo * 2 + range_offset + (c - start) as usize * 2;
+                let glyph = read_u16(data, at)?;
+                if glyph == 0 {
+                    None
+                } else {
+                    Some(glyph.wrapping_add(delta))
+                }
+            }
+            6 => {
+                let first = read_u16(data, o + 6)? as u32;
+                let count = read_u16(data, o + 8)? as u32;
+                if c >= first && c < first + count {
+                    read_u16(data, o + 10 + (c - first) as usize * 2)
+                } else {
+                    None
+                }
+            }
+            12 => {
+                let groups = read_u32(data, o + 12)? as usize;
+                let (mut lo, mut hi) = (0, groups);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    let group = o + 16 + mid * 12;
+                    let start = read_u32(data, group)?;
+                    let end = read_u32(data, group + 4)?;
+                    if c < start {
+                        hi = mid;
+                    } else if c > end {
+                        lo = mid + 1;
+                    } else {
+                        let glyph = read_u32(data, group + 8)?.checked_add(c - start)?;
+                        return if glyph > 0xffff { None } else { Some(glyph as u16) };
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::{cmap_format12, cmap_format4, cmap_table};
+
+    /// Returns the subtable picked from a cmap table and the table.
+    fn find(subtables: &[(u16, u16, Vec<u8>)]) -> (Option<Subtable>, Vec<u8>) {
+        let data = cmap_table(subtables);
+        (Subtable::find(&data, Table{ offset: 0, len: data.len() }), data)
+    }
+
+    #[test]
+    fn maps_segments_of_format_4() {
+        // 'A' to 'C' by delta, 'x' to 'z' through the glyph array, where
+        // 'y' is explicitly unmapped.
+        let segments: [(u16, u16, u16, &[u16]); 2] = [(0x41, 0x43, 1u16.wrapping_sub(0x41), &[]), (0x78, 0x7a, 0, &[4, 0, 5])];
+        let (subtable, data) = find(&[(3, 1, cmap_format4(&segments))]);
+        let subtable = subtable.unwrap();
+        let cases = [(0x40, None), (0x41, Some(1)), (0x43, Some(3)), (0x44, None), (0x78, Some(4)), (0x79, None),
+                     (0x7a, Some(5)), (0xfffe, None), (0x1_0041, None)];
+        for &(c, glyph) in &cases {
+            assert_eq!(subtable.lookup(&data, c), glyph, "{:x}", c);
+        }
+    }
+
+    #[test]
+    fn maps_groups_of_format_12() {
+        let groups = [(0x20, 0x7e, 1), (0x1_f600, 0x1_f64f, 200), (0x10_fffd, 0x10_fffd, 0x1_0000)];
+        let (subtable, data) = find(&[(3, 10, cmap_format12(&groups))]);
+        let subtable = subtable.unwrap();
+        let cases = [(0x1f, None), (0x20, Some(1)), (0x7e, Some(95)), (0x7f, None), (0x1_f600, Some(200)),
+                     (0x1_f64f, Some(279)), (0x1_f650, None), (0x10_fffd, None)];
+        for &(c, glyph) in &cases {
+            assert_eq!(subtable.lookup(&data, c), glyph, "{:x}", c);
+        }
+    }
+
+    #[test]
+    fn prefers_full_unicode_subtables() {
+        let bmp = cmap_format4(&[(0x41, 0x41, 1u16.wrapping_sub(0x41), &[])]);
+        let full = cmap_format12(&[(0x41, 0x41, 2)]);
+        let (subtable, data) = find(&[(3, 1, bmp.clone()), (3, 10, full.clone())]);
+        assert_eq!(subtable.unwrap().lookup(&data, 0x41), Some(2));
+        // BMP Unicode tables beat symbol tables of any format.
+        let (subtable, data) = find(&[(3, 0, full), (0, 3, bmp.clone())]);
+        assert_eq!(subtable.unwrap().lookup(&data, 0x41), Some(1));
+        // Mac Roman and unsupported formats are skipped.
+        assert!(find(&[(1, 0, bmp)]).0.is_none());
+        assert!(find(&[(3, 1, vec![0, 2, 0, 0])]).0.is_none());
+    }
+
+    #[test]
+    fn truncated_subtables_map_nothing() {
+        let segments: [(u16, u16, u16, &[u16]); 1] = [(0x78, 0x7a, 0, &[4, 5, 6])];
+        let (subtable, data) = find(&[(3, 1, cmap_format4(&segments))]);
+        let subtable = subtable.unwrap();
+        assert_eq!(subtable.lookup(&data, 0x7a), Some(6));
+        assert_eq!(subtable.lookup(&data[..data.len() - 2], 0x7a), None);
+        assert_eq!(subtable.lookup(&data[..20], 0x78), None);
+
+        let (subtable, data) = find(&[(3, 10, cmap_format12(&[(0x41, 0x5a, 1)]))]);
+        assert_eq!(subtable.unwrap().lookup(&data[..data.len() - 1], 0x41), None);
+    }
+}
@@ -0,0 +1,308 @@
+//! TrueType glyph outlines from the `glyf` table.
+
+use super::{read_i16, read_u16, read_u32, OutlineCommand, Table};
+
+/// Composite glyphs nested deeper than this are ignored.
+const MAX_DEPTH: usize = 8;
+
+/// Returns the byte range of a glyph in the `glyf` table, `None` for
+/// glyphs without data.
+fn glyph_range(data: &[u8], glyf: Table, loca: Table, long_offsets: bool, glyph: u16) -> Option<(usize, usize)> {
+    let g = glyph as usize;
+    let (start, end) = if long_offsets {
+        (read_u32(data, loca.offset + g * 4)? as usize, read_u32(data, loca.offset + g * 4 + 4)? as usize)
+    } else {
+        (read_u16(data, loca.offset + g * 2)? as usize * 2, read_u16(data, loca.offset + g * 2 + 2)? as usize * 2)
+    };
+    if start >= end || end > glyf.len {
+        return None;
+    }
+    Some((glyf.offset + start, glyf.offset + end))
+}
+
+/// Returns the bounds stored in the glyph header.
+pub fn bounds(data: &[u8], glyf: Table, loca: Table, long_offsets: bool, glyph: u16) -> Option<[f32; 4]> {
+    let (start, _) = glyph_range(data, glyf, loca, long_offsets, glyph)?;
+    Some([
+        read_i16(data, start + 2)? as f32,
+        read_i16(data, start + 4)? as f32,
+        read_i16(data, start + 6)? as f32,
+        read_i16(data, start + 8)? as f32,
+    ])
+}
+
+/// Appends the outline of a glyph. Returns false for corrupt glyphs.
+pub fn outline(data: &[u8], glyf: Table, loca: Table, long_offsets: bool, glyph: u16,
+               out: &mut Vec<OutlineCommand>) -> bool {
+    append(data, glyf, loca, long_offsets, glyph, &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0], 0, out).is_some()
+}
+
+fn append(data: &[u8], glyf: Table, loca: Table, long_offsets: bool, glyph: u16, xform: &[f32; 6],
+          depth: usize, out: &mut Vec<OutlineCommand>) -> Option<()> {
+    let (start, end) = match glyph_range(data, glyf, loca, long_offsets, glyph) {
+        Some(range) => range,
+        None => return Some(()),
+    };
+    let contours = read_i16(data, start)?;
+    if contours >= 0 {
+        simple(&data[..end], start, contours as usize, xform, out)
+    } else if depth < MAX_DEPTH {
+        composite(data, glyf, loca, long_offsets, start + 10, end, xform, depth, out)
+    } else {
+        Some(())
+    }
+}
+
+struct Point {
+    x:        f32,
+    y:        f32,
+    on_curve: bool,
+}
+
+fn simple(data: &[u8], start: usize, contours: usize, xform: &[f32; 6], out: &mut Vec<OutlineCommand>) -> Option<()> {
+    let mut ends = Vec::with_capacity(contours);
+    for i in 0..contours {
+        ends.push(read_u16(data, start + 10 + i * 2)? as usize);
+    }
+    let num_points = match ends.last() {
+        Some(&last) => last + 1,
+        None => return Some(()),
+    };
+    let instructions = read_u16(data, start + 10 + contours * 2)? as usize;
+    let mut pos = start + 12 + contours * 2 + instructions;
+
+    let mut flags = Vec::with_capacity(num_points);
+    while flags.len() < num_points {
+        let flag = *data.get(pos)?;
+        pos += 1;
+        flags.push(flag);
+        if flag & 8 != 0 {
+            let repeat = *data.get(pos)?;
+            pos += 1;
+            for _ in 0..repeat {
+                flags.push(flag);
+            }
+        }
+    }
+    flags.truncate(num_points);
+
+    // Coordinates are deltas, either one unsigned byte with the sign in
+    // the flags, a repeat of the previous value, or a signed word.
+    let mut read_coords = |short: u8, same: u8| -> Option<Vec<i32>> {
+        let mut coords = Vec::with_capacity(num_points);
+        let mut v = 0i32;
+        for &flag in &flags {
+            if flag & short != 0 {
+                let d = *data.get(pos)? as i32;
+                pos += 1;
+                v += if flag & same != 0 { d } else { -d };
+            } else if flag & same == 0 {
+                v += read_i16(data, pos)? as i32;
+                pos += 2;
+            }
+            coords.push(v);
+        }
+        Some(coords)
+    };
+    let xs = read_coords(2, 16)?;
+    let ys = read_coords(4, 32)?;
+
+    let mut first = 0;
+    for &end in &ends {
+        if end < first || end >= num_points {
+            return None;
+        }
+        let points: Vec<Point> = (first..=end).map(|i| {
+            let (x, y) = (xs[i] as f32, ys[i] as f32);
+            Point{
+                x:        xform[0] * x + xform[2] * y + xform[4],
+                y:        xform[1] * x + xform[3] * y + xform[5],
+                on_curve: flags[i] & 1 != 0,
+            }
+        }).collect();
+        contour(&points, out);
+        first = end + 1;
+    }
+    Some(())
+}
+
+/// Converts a contour of on and off curve points to commands. Two off
+/// curve points in a row have an implied on curve point between them.
+fn contour(points: &[Point], out: &mut Vec<OutlineCommand>) {
+    let n = points.len();
+    if n == 0 {
+        return;
+    }
+    let mid = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+    // Start at an on curve point, or between the last and the first point
+    // if there is none.
+    let (start, first, count) = match points.iter().position(|p| p.on_curve) {
+        Some(i) => ((points[i].x, points[i].y), i + 1, n - 1),
+        None => (mid((points[n - 1].x, points[n - 1].y), (points[0].x, points[0].y)), 0, n),
+    };
+    out.push(OutlineCommand::MoveTo(start.0, start.1));
+    let mut control: Option<(f32, f32)> = None;
+    for k in 0..count {
+        let p = &points[(first + k) % n];
+        let pt = (p.x, p.y);
+        if p.on_curve {
+            match control.take() {
+                Some(c) => out.push(OutlineCommand::QuadTo(c.0, c.1, pt.0, pt.1)),
+                None => out.push(OutlineCommand::LineTo(pt.0, pt.1)),
+            }
+        } else {
+            if let Some(c) = control {
+                let m = mid(c, pt);
+                out.push(OutlineCommand::QuadTo(c.0, c.1, m.0, m.1));
+            }
+            control = Some(pt);
+        }
+    }
+    match control {
+        Some(c) => out.push(OutlineCommand::QuadTo(c.0, c.1, start.0, start.1)),
+        None => out.push(OutlineCommand::LineTo(start.0, start.1)),
+    }
+    out.push(OutlineCommand::Close);
+}
+
+fn composite(data: &[u8], glyf: Table, loca: Table, long_offsets: bool, mut pos: usize, end: usize,
+             xform: &[f32; 6], depth: usize, out: &mut Vec<OutlineCommand>) -> Option<()> {
+    // Components are read from the glyph only, other glyphs from the
+    // whole file.
+    let bytes = &data[..end];
+    loop {
+        if pos + 4 > end {
+            return None;
+        }
+        let flags = read_u16(bytes, pos)?;
+        let glyph = read_u16(bytes, pos + 2)?;
+        pos += 4;
+        let (dx, dy) = if flags & 1 != 0 {
+            let d = (read_i16(bytes, pos)? as f32, read_i16(bytes, pos + 2)? as f32);
+            pos += 4;
+            d
+        } else {
+            let d = (*bytes.get(pos)? as i8 as f32, *bytes.get(pos + 1)? as i8 as f32);
+            pos += 2;
+            d
+        };
+        // Components positioned by matching points are placed at the
+        // origin, like stb_truetype does.
+        let (dx, dy) = if flags & 2 != 0 { (dx, dy) } else { (0.0, 0.0) };
+        let f2dot14 = |pos: usize| read_i16(bytes, pos).map(|v| v as f32 / 16384.0);
+        let (a, b, c, d) = if flags & 8 != 0 {
+            let s = f2dot14(pos)?;
+            pos += 2;
+            (s, 0.0, 0.0, s)
+        } else if flags & 0x40 != 0 {
+            let m = (f2dot14(pos)?, 0.0, 0.0, f2dot14(pos + 2)?);
+            pos += 4;
+            m
+        } else if flags & 0x80 != 0 {
+            let m = (f2dot14(pos)?, f2dot14(pos + 2)?, f2dot14(pos + 4)?, f2dot14(pos + 6)?);
+            pos += 8;
+            m
+        } else {
+            (1.0, 0.0, 0.0, 1.0)
+        };
+        // Apply the component transform first, then the parent's.
+        let child = [
+            xform[0] * a + xform[2] * b,
+            xform[1] * a + xform[3] * b,
+            xform[0] * c + xform[2] * d,
+            xform[1] * c + xform[3] * d,
+            xform[0] * dx + xform[2] * dy + xform[4],
+            xform[1] * dx + xform[3] * dy + xform[5],
+        ];
+        append(data, glyf, loca, long_offsets, glyph, &child, depth + 1, out)?;
+        if flags & 0x20 == 0 {
+            return Some(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::{glyf_tables, square_glyph, words};
+    use crate::font::OutlineCommand::*;
+
+    /// Returns the outline of a glyph among `glyphs`, `None` if it is
+    /// corrupt.
+    fn outline_of(glyphs: &[Vec<u8>], glyph: u16) -> Option<Vec<OutlineCommand>> {
+        let tables = glyf_tables(glyphs);
+        let data = [&tables[0].1[..], &tables[1].1[..]].concat();
+        let glyf = Table{ offset: 0, len: tables[0].1.len() };
+        let loca = Table{ offset: glyf.len, len: tables[1].1.len() };
+        let mut out = Vec::new();
+        if outline(&data, glyf, loca, false, glyph, &mut out) { Some(out) } else { None }
+    }
+
+    /// Returns a composite glyph header followed by components.
+    fn composite(components: &[Vec<u8>]) -> Vec<u8> {
+        [words(&[-1, 0, 0, 0, 0]), components.concat()].concat()
+    }
+
+    #[test]
+    fn reads_simple_glyphs() {
+        let square = square_glyph(10, 20, 110, 220);
+        assert_eq!(outline_of(&[square], 0).unwrap(),
+                   [MoveTo(10.0, 20.0), LineTo(110.0, 20.0), LineTo(110.0, 220.0), LineTo(10.0, 220.0),
+                    LineTo(10.0, 20.0), Close]);
+
+        // Off curve points are controls, with an on curve point implied
+        // between two of them. Coordinates are short positive and
+        // negative deltas, and repeats of the previous value.
+        let flags = [1 | 2 | 4 | 16 | 32, 2 | 16 | 32, 2 | 4 | 16 | 32, 1 | 2 | 4];
+        let glyph = [words(&[1, 0, 0, 0, 0, 3, 0]), flags.to_vec(), vec![0, 100, 100, 100, 0, 100, 100]].concat();
+        assert_eq!(outline_of(&[glyph], 0).unwrap(),
+                   [MoveTo(0.0, 0.0), QuadTo(100.0, 0.0, 150.0, 50.0), QuadTo(200.0, 100.0, 100.0, 0.0),
+                    LineTo(0.0, 0.0), Close]);
+    }
+
+    #[test]
+    fn transforms_composite_components() {
+        // A square moved by words, and one moved by bytes and scaled by a
+        // half.
+        let glyph = composite(&[
+            words(&[0x01 | 0x02 | 0x20, 1, 300, -50]),
+            [words(&[0x02 | 0x08, 1]), vec![10, 0xf6], words(&[0x2000])].concat(),
+        ]);
+        let square = square_glyph(0, 0, 100, 100);
+        let expected = [
+            MoveTo(300.0, -50.0), LineTo(400.0, -50.0), LineTo(400.0, 50.0), LineTo(300.0, 50.0),
+            LineTo(300.0, -50.0), Close,
+            MoveTo(10.0, -10.0), LineTo(60.0, -10.0), LineTo(60.0, 40.0), LineTo(10.0, 40.0), LineTo(10.0, -10.0),
+            Close,
+        ];
+        assert_eq!(outline_of(&[glyph.clone(), square.clone()], 0).unwrap(), expected);
+
+        // Nested components apply their own transform first: a square
+        // scaled by a half in x and two in y, then moved.
+        let scaled = [words(&[-1, 0, 0, 0, 0, 0x02 | 0x40, 2]), vec![0, 0], words(&[0x2000, 0x7fff])].concat();
+        let moved = composite(&[[words(&[0x02, 1]), vec![4, 8]].concat()]);
+        let outline = outline_of(&[moved, scaled, square], 0).unwrap();
+        assert_eq!(outline[1], LineTo(54.0, 8.0));
+        assert!(matches!(outline[2], LineTo(x, y) if x == 54.0 && (y - 208.0).abs() < 0.01));
+    }
+
+    #[test]
+    fn limits_composite_depth() {
+        // A composite including itself ends after MAX_DEPTH levels.
+        let glyph = composite(&[words(&[0x02, 0]), vec![0, 0]]);
+        assert_eq!(outline_of(&[glyph], 0), Some(vec![]));
+    }
+
+    #[test]
+    fn rejects_truncated_glyphs() {
+        let square = square_glyph(0, 0, 100, 100);
+        for len in 10..square.len() - 1 {
+            assert_eq!(outline_of(&[square[..len].to_vec()], 0), None, "{} bytes", len);
+        }
+        let glyph = composite(&[words(&[0x01 | 0x02, 1, 300])]);
+        assert_eq!(outline_of(&[glyph, square.clone()], 0), None);
+        // Glyphs without data, or past the end of loca, have no outline.
+        assert_eq!(outline_of(&[vec![], square], 0), Some(vec![]));
+        assert_eq!(outline_of(&[], 5), Some(vec![]));
+    }
+}
@@ -0,0 +1,356 @@
+//! Glyph positioning lookups of the GPOS table.
+
+use super::layout::{self, ContextMatch, Glyphs, Lookup};
+use super::{read_i16, read_u16};
+use super::shape::{Attach, Buffer, GlyphPos};
+
+/// Nested lookups of contextual lookups go at most this deep.
+const MAX_NESTING: usize = 8;
+
+/// Applies lookup `index` of the GPOS table at `table` to every glyph with
+/// one of the features of `mask`.
+pub fn apply(buffer: &mut Buffer, table: usize, index: u16, mask: u32) {
+    let lookup = match layout::lookup(buffer.data(), table, index, layout::GPOS_EXTENSION) {
+        Some(lookup) => lookup,
+        None => return,
+    };
+    let mut i = 0;
+    while i < buffer.len() {
+        if buffer.info[i].mask & mask != 0 && !buffer.skip(i, &lookup) {
+            if let Some(next) = apply_at(buffer, table, &lookup, i, 0) {
+                i = next.max(i + 1);
+                continue;
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Applies a lookup to glyph `i`. Returns the glyph to continue with, or
+/// `None` if no subtable applies.
+fn apply_at(buffer: &mut Buffer, table: usize, lookup: &Lookup, i: usize, depth: usize) -> Option<usize> {
+    for &subtable in &lookup.subtables {
+        let next = match lookup.kind {
+            1 => single(buffer, subtable, i),
+            2 => pair(buffer, lookup, subtable, i),
+            3 => cursive(buffer, lookup, subtable, i),
+            4 => mark_to_base(buffer, subtable, i),
+            5 => mark_to_ligature(buffer, subtable, i),
+            6 => mark_to_mark(buffer, lookup, subtable, i),
+            7 | 8 if depth < MAX_NESTING => {
+                let m = layout::match_context(buffer.data(), subtable, lookup.kind == 8,
+                                              &buffer.lookup_glyphs(lookup), i);
+                m.map(|m| apply_nested(buffer, table, m, depth))
+            }
+            _ => None,
+        };
+        if next.is_some() {
+            return next;
+        }
+    }
+    None
+}
+
+/// Applies the lookups of a matched context rule to its input glyphs.
+fn apply_nested(buffer: &mut Buffer, table: usize, m: ContextMatch, depth: usize) -> usize {
+    let data = buffer.data();
+    for r in 0..m.count {
+        let record = m.records + r * 4;
+        let (seq, index) = match (read_u16(data, record), read_u16(data, record + 2)) {
+            (Some(seq), Some(index)) => (seq as usize, index),
+            _ => break,
+        };
+        if seq >= m.positions.len() {
+            continue;
+        }
+        if let Some(lookup) = layout::lookup(data, table, index, layout::GPOS_EXTENSION) {
+            apply_at(buffer, table, &lookup, m.positions[seq], depth + 1);
+        }
+    }
+    m.positions[m.positions.len() - 1] + 1
+}
+
+/// Adds a value record to a position.
+fn apply_value(data: &[u8], record: usize, format: u16, pos: &mut GlyphPos) {
+    let mut at = record;
+    let mut read = |bit: u16| -> i32 {
+        if format & bit == 0 {
+            return 0;
+        }
+        let v = read_i16(data, at).unwrap_or(0) as i32;
+        at += 2;
+        v
+    };
+    pos.x_offset += read(1);
+    pos.y_offset += read(2);
+    pos.x_advance += read(4);
+    pos.y_advance += read(8);
+}
+
+/// Reads an anchor point.
+fn anchor(data: &[u8], offset: usize) -> Option<(i32, i32)> {
+    match read_u16(data, offset)? {
+        1..=3 => Some((read_i16(data, offset + 2)? as i32, read_i16(data, offset + 4)? as i32)),
+        _ => None,
+    }
+}
+
+fn single(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                       buffer.info[i].glyph)? as usize;
+    let format = read_u16(data, subtable + 4)?;
+    let record = match read_u16(data, subtable)? {
+        1 => subtable + 6,
+        2 => {
+            if index >= read_u16(data, subtable + 6)? as usize {
+                return None;
+            }
+            subtable + 8 + index * layout::value_record_size(format)
+        }
+        _ => return None,
+    };
+    apply_value(data, record, format, &mut buffer.pos[i]);
+    Some(i + 1)
+}
+
+fn pair(buffer: &mut Buffer, lookup: &Lookup, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                       buffer.info[i].glyph)? as usize;
+    let j = buffer.lookup_glyphs(lookup).next(i)?;
+    let second = buffer.info[j].glyph;
+    let format1 = read_u16(data, subtable + 4)?;
+    let format2 = read_u16(data, subtable + 6)?;
+    let size1 = layout::value_record_size(format1);
+    let size2 = layout::value_record_size(format2);
+    let record = match read_u16(data, subtable)? {
+        1 => {
+            if index >= read_u16(data, subtable + 8)? as usize {
+                return None;
+            }
+            let set = subtable + read_u16(data, subtable + 10 + index * 2)? as usize;
+            let count = read_u16(data, set)? as usize;
+            let record_size = 2 + size1 + size2;
+            let (mut lo, mut hi) = (0, count);
+            let mut found = None;
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let record = set + 2 + mid * record_size;
+                let glyph = read_u16(data, record)?;
+                if second < glyph {
+                    hi = mid;
+                } else if second > glyph {
+                    lo = mid + 1;
+                } else {
+                    found = Some(record + 2);
+                    break;
+                }
+            }
+            found?
+        }
+        2 => {
+            let class1 = layout::glyph_class(data, subtable + read_u16(data, subtable + 8)? as usize,
+                                             buffer.info[i].glyph);
+            let class2 = layout::glyph_class(data, subtable + read_u16(data, subtable + 10)? as usize, second);
+            let class1_count = read_u16(data, subtable + 12)?;
+            let class2_count = read_u16(data, subtable + 14)?;
+            if class1 >= class1_count || class2 >= class2_count {
+                return None;
+            }
+            subtable + 16 + (class1 as usize * class2_count as usize + class2 as usize) * (size1 + size2)
+        }
+        _ => return None,
+    };
+    apply_value(data, record, format1, &mut buffer.pos[i]);
+    apply_value(data, record + size1, format2, &mut buffer.pos[j]);
+    Some(if format2 != 0 { j + 1 } else { j })
+}
+
+/// Connects the exit point of the glyph before `i` to the entry point of
+/// glyph `i`.
+fn cursive(buffer: &mut Buffer, lookup: &Lookup, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let coverage = subtable + read_u16(data, subtable + 2)? as usize;
+    let count = read_u16(data, subtable + 4)?;
+    let record = |glyph: u16, at: usize| -> Option<(i32, i32)> {
+        let index = layout::coverage_index(data, coverage, glyph)?;
+        if index >= count {
+            return None;
+        }
+        match read_u16(data, subtable + 6 + index as usize * 4 + at)? {
+            0 => None,
+            offset => anchor(data, subtable + offset as usize),
+        }
+    };
+    let (entry_x, entry_y) = record(buffer.info[i].glyph, 0)?;
+    let p = buffer.lookup_glyphs(lookup).prev(i)?;
+    let (exit_x, exit_y) = record(buffer.info[p].glyph, 2)?;
+
+    if buffer.rtl {
+        let d = exit_x + buffer.pos[p].x_offset;
+        buffer.pos[p].x_advance -= d;
+        buffer.pos[p].x_offset -= d;
+        buffer.pos[i].x_advance = entry_x + buffer.pos[i].x_offset;
+    } else {
+        buffer.pos[p].x_advance = exit_x + buffer.pos[p].x_offset;
+        let d = entry_x + buffer.pos[i].x_offset;
+        buffer.pos[i].x_advance -= d;
+        buffer.pos[i].x_offset -= d;
+    }
+    // The glyph before is moved unless the lookup goes from right to left.
+    let (child, parent, dy) = if lookup.flags & layout::RIGHT_TO_LEFT != 0 {
+        (p, i, entry_y - exit_y)
+    } else {
+        (i, p, exit_y - entry_y)
+    };
+    buffer.pos[child].attach = Some(Attach::Cursive{ parent: parent, dy: dy });
+    Some(i + 1)
+}
+
+/// Returns the mark class and anchor of glyph `i` from a mark array.
+fn mark_record(buffer: &Buffer, coverage: usize, array: usize, i: usize) -> Option<(u16, (i32, i32))> {
+    let data = buffer.data();
+    let index = layout::coverage_index(data, coverage, buffer.info[i].glyph)? as usize;
+    if index >= read_u16(data, array)? as usize {
+        return None;
+    }
+    let record = array + 2 + index * 4;
+    let class = read_u16(data, record)?;
+    let point = anchor(data, array + read_u16(data, record + 2)? as usize)?;
+    Some((class, point))
+}
+
+/// Attaches mark `i` to glyph `base` by their anchors.
+fn attach_mark(buffer: &mut Buffer, i: usize, base: usize, mark: (i32, i32), anchor: (i32, i32)) -> usize {
+    buffer.pos[i].attach = Some(Attach::Mark{ base: base, dx: anchor.0 - mark.0, dy: anchor.1 - mark.1 });
+    i + 1
+}
+
+/// Returns the glyph before `i` which is not a mark.
+fn prev_base(buffer: &Buffer, i: usize) -> Option<usize> {
+    (0..i).rev().find(|&j| buffer.info[j].class != layout::MARK_GLYPH)
+}
+
+fn mark_to_base(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let mark_coverage = subtable + read_u16(data, subtable + 2)? as usize;
+    let base_coverage = subtable + read_u16(data, subtable + 4)? as usize;
+    let class_count = read_u16(data, subtable + 6)? as usize;
+    let mark_array = subtable + read_u16(data, subtable + 8)? as usize;
+    let base_array = subtable + read_u16(data, subtable + 10)? as usize;
+
+    let (class, mark) = mark_record(buffer, mark_coverage, mark_array, i)?;
+    let base = prev_base(buffer, i)?;
+    let index = layout::coverage_index(data, base_coverage, buffer.info[base].glyph)? as usize;
+    if index >= read_u16(data, base_array)? as usize || class as usize >= class_count {
+        return None;
+    }
+    let offset = read_u16(data, base_array + 2 + (index * class_count + class as usize) * 2)?;
+    if offset == 0 {
+        return None;
+    }
+    let point = anchor(data, base_array + offset as usize)?;
+    Some(attach_mark(buffer, i, base, mark, point))
+}
+
+fn mark_to_ligature(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let mark_coverage = subtable + read_u16(data, subtable + 2)? as usize;
+    let lig_coverage = subtable + read_u16(data, subtable + 4)? as usize;
+    let class_count = read_u16(data, subtable + 6)? as usize;
+    let mark_array = subtable + read_u16(data, subtable + 8)? as usize;
+    let lig_array = subtable + read_u16(data, subtable + 10)? as usize;
+
+    let (class, mark) = mark_record(buffer, mark_coverage, mark_array, i)?;
+    let lig = prev_base(buffer, i)?;
+    let index = layout::coverage_index(data, lig_coverage, buffer.info[lig].glyph)? as usize;
+    if index >= read_u16(data, lig_array)? as usize || class as usize >= class_count {
+        return None;
+    }
+    let attach = lig_array + read_u16(data, lig_array + 2 + index * 2)? as usize;
+    let components = read_u16(data, attach)? as usize;
+    if components == 0 {
+        return None;
+    }
+    // Marks between the components of the ligature attach to the component
+    // before them, other marks to the last one.
+    let (m, l) = (buffer.info[i], buffer.info[lig]);
+    let component = if m.lig_id != 0 && m.lig_id == l.lig_id && m.lig_comp > 0 {
+        (m.lig_comp as usize).min(components) - 1
+    } else {
+        components - 1
+    };
+    let offset = read_u16(data, attach + 2 + (component * class_count + class as usize) * 2)?;
+    if offset == 0 {
+        return None;
+    }
+    let point = anchor(data, attach + offset as usize)?;
+    Some(attach_mark(buffer, i, lig, mark, point))
+}
+
+fn mark_to_mark(buffer: &mut Buffer, lookup: &Lookup, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let mark1_coverage = subtable + read_u16(data, subtable + 2)? as usize;
+    let mark2_coverage = subtable + read_u16(data, subtable + 4)? as usize;
+    let class_count = read_u16(data, subtable + 6)? as usize;
+    let mark1_array = subtable + read_u16(data, subtable + 8)? as usize;
+    let mark2_array = subtable + read_u16(data, subtable + 10)? as usize;
+
+    let (class, mark) = mark_record(buffer, mark1_coverage, mark1_array, i)?;
+    let j = buffer.lookup_glyphs(lookup).prev(i)?;
+    let (m1, m2) = (buffer.info[i], buffer.info[j]);
+    // Both marks have to be on the same base or ligature component.
+    if m2.class != layout::MARK_GLYPH || m1.lig_id != m2.lig_id || m1.lig_comp != m2.lig_comp {
+        return None;
+    }
+    let index = layout::coverage_index(data, mark2_coverage, m2.glyph)? as usize;
+    if index >= read_u16(data, mark2_array)? as usize || class as usize >= class_count {
+        return None;
+    }
+    let offset = read_u16(data, mark2_array + 2 + (index * class_count + class as usize) * 2)?;
+    if offset == 0 {
+        return None;
+    }
+    let point = anchor(data, mark2_array + offset as usize)?;
+    Some(attach_mark(buffer, i, j, mark, point))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::tests::{layout_font, layout_table, lookup, mark_base_pos, pair_pos, shaped};
+    use crate::font::Script;
+
+    #[test]
+    fn kerns_pairs() {
+        let lookups = [lookup(2, 0, &[pair_pos(&[(1, 2, -50), (2, 2, 30)])])];
+        let font = layout_font("ab", vec![(b"GPOS", layout_table(&[(b"kern", &[0])], &lookups))]);
+        let advances = |text| shaped(&font, text, Script::Latin, false).iter().map(|g| g.x_advance).collect::<Vec<_>>();
+        assert_eq!(advances("ab"), vec![450, 500]);
+        assert_eq!(advances("ba"), vec![500, 500]);
+        assert_eq!(advances("abb"), vec![450, 530, 500]);
+        assert_eq!(font.kerning(1, 2), -50);
+        assert_eq!(font.kerning(2, 1), 0);
+    }
+
+    #[test]
+    fn attaches_marks_to_bases() {
+        let lookups = [lookup(4, 0, &[mark_base_pos(&[(2, (-100, 50))], &[(1, (250, 600))])])];
+        let font = layout_font("a\u{301}", vec![(b"GPOS", layout_table(&[(b"mark", &[0])], &lookups))]);
+        let glyphs = shaped(&font, "a\u{301}a", Script::Latin, false);
+        let placed: Vec<_> = glyphs.iter().map(|g| (g.glyph, g.cluster, g.x_advance, g.x_offset, g.y_offset)).collect();
+        assert_eq!(placed, vec![(1, 0, 500, 0, 0), (2, 0, 0, -150, 550), (1, 3, 500, 0, 0)]);
+    }
+}
@@ -0,0 +1,329 @@
+//! Glyph substitution lookups of the GSUB table.
+
+use super::layout::{self, ContextMatch, Glyphs, Lookup};
+use super::read_u16;
+use super::shape::Buffer;
+
+/// Lookup type of extension subtables in GSUB.
+const EXTENSION: u16 = 7;
+
+/// Nested lookups of contextual lookups go at most this deep.
+const MAX_NESTING: usize = 8;
+
+/// Applies lookup `index` of the GSUB table at `table` to every glyph with
+/// one of the features of `mask`.
+pub fn apply(buffer: &mut Buffer, table: usize, index: u16, mask: u32) {
+    let lookup = match layout::lookup(buffer.data(), table, index, EXTENSION) {
+        Some(lookup) => lookup,
+        None => return,
+    };
+    if lookup.kind == 8 {
+        // Reverse chaining substitutions go from the end to the start.
+        for i in (0..buffer.len()).rev() {
+            if buffer.info[i].mask & mask != 0 && !buffer.skip(i, &lookup) {
+                reverse_chain(buffer, &lookup, i);
+            }
+        }
+        return;
+    }
+    let mut i = 0;
+    while i < buffer.len() {
+        if buffer.info[i].mask & mask != 0 && !buffer.skip(i, &lookup) {
+            let len = buffer.len();
+            if let Some(next) = apply_at(buffer, table, &lookup, i, 0) {
+                // Make progress unless glyphs were removed.
+                if next > i || buffer.len() < len {
+                    i = next;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Applies a lookup to glyph `i`. Returns the glyph to continue with, or
+/// `None` if no subtable applies.
+fn apply_at(buffer: &mut Buffer, table: usize, lookup: &Lookup, i: usize, depth: usize) -> Option<usize> {
+    for &subtable in &lookup.subtables {
+        let next = match lookup.kind {
+            1 => single(buffer, subtable, i),
+            2 => multiple(buffer, subtable, i),
+            3 => alternate(buffer, subtable, i),
+            4 => ligature(buffer, lookup, subtable, i),
+            5 | 6 if depth < MAX_NESTING => {
+                let m = layout::match_context(buffer.data(), subtable, lookup.kind == 6,
+                                              &buffer.lookup_glyphs(lookup), i);
+                m.map(|m| apply_nested(buffer, table, m, depth))
+            }
+            _ => None,
+        };
+        if next.is_some() {
+            return next;
+        }
+    }
+    None
+}
+
+/// Applies the lookups of a matched context rule to its input glyphs.
+fn apply_nested(buffer: &mut Buffer, table: usize, m: ContextMatch, depth: usize) -> usize {
+    let mut positions = m.positions;
+    let mut end = positions[positions.len() - 1] + 1;
+    for r in 0..m.count {
+        let data = buffer.data();
+        let record = m.records + r * 4;
+        let (seq, index) = match (read_u16(data, record), read_u16(data, record + 2)) {
+            (Some(seq), Some(index)) => (seq as usize, index),
+            _ => break,
+        };
+        if seq >= positions.len() || positions[seq] >= buffer.len() {
+            continue;
+        }
+        let lookup = match layout::lookup(data, table, index, EXTENSION) {
+            Some(lookup) => lookup,
+            None => continue,
+        };
+        let len = buffer.len();
+        apply_at(buffer, table, &lookup, positions[seq], depth + 1);
+        // Keep the following input glyphs in place if glyphs were added or
+        // removed.
+        let delta = buffer.len() as isize - len as isize;
+        if delta != 0 {
+            for p in &mut positions[seq + 1..] {
+                *p = (*p as isize + delta).max(0) as usize;
+            }
+            end = (end as isize + delta).max(0) as usize;
+        }
+    }
+    end
+}
+
+fn single(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    let glyph = buffer.info[i].glyph;
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize, glyph)?;
+    let substitute = match read_u16(data, subtable)? {
+        1 => glyph.wrapping_add(read_u16(data, subtable + 4)?),
+        2 => {
+            if index >= read_u16(data, subtable + 4)? {
+                return None;
+            }
+            read_u16(data, subtable + 6 + index as usize * 2)?
+        }
+        _ => return None,
+    };
+    buffer.set_glyph(i, substitute);
+    Some(i + 1)
+}
+
+fn multiple(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                       buffer.info[i].glyph)?;
+    if index >= read_u16(data, subtable + 4)? {
+        return None;
+    }
+    let sequence = subtable + read_u16(data, subtable + 6 + index as usize * 2)? as usize;
+    let count = read_u16(data, sequence)? as usize;
+    let glyphs: Vec<u16> = (0..count).filter_map(|k| read_u16(data, sequence + 2 + k * 2)).collect();
+    if glyphs.is_empty() {
+        buffer.remove(i);
+        return Some(i);
+    }
+    buffer.set_glyph(i, glyphs[0]);
+    for (k, &glyph) in glyphs.iter().enumerate().skip(1) {
+        let info = buffer.info[i];
+        buffer.insert(i + k, info);
+        buffer.set_glyph(i + k, glyph);
+    }
+    Some(i + glyphs.len())
+}
+
+fn alternate(buffer: &mut Buffer, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                       buffer.info[i].glyph)?;
+    if index >= read_u16(data, subtable + 4)? {
+        return None;
+    }
+    let set = subtable + read_u16(data, subtable + 6 + index as usize * 2)? as usize;
+    // Without a way to choose, the first alternate is used.
+    if read_u16(data, set)? == 0 {
+        return None;
+    }
+    let glyph = read_u16(data, set + 2)?;
+    buffer.set_glyph(i, glyph);
+    Some(i + 1)
+}
+
+fn ligature(buffer: &mut Buffer, lookup: &Lookup, subtable: usize, i: usize) -> Option<usize> {
+    let data = buffer.data();
+    if read_u16(data, subtable)? != 1 {
+        return None;
+    }
+    let index = layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                       buffer.info[i].glyph)?;
+    if index >= read_u16(data, subtable + 4)? {
+        return None;
+    }
+    let set = subtable + read_u16(data, subtable + 6 + index as usize * 2)? as usize;
+    let count = read_u16(data, set)? as usize;
+    for l in 0..count {
+        let lig = set + read_u16(data, set + 2 + l * 2)? as usize;
+        let glyph = read_u16(data, lig)?;
+        let components = read_u16(data, lig + 2)? as usize;
+        if components == 0 {
+            continue;
+        }
+        // Match the other components.
+        let positions = {
+            let glyphs = buffer.lookup_glyphs(lookup);
+            let mut positions = vec![i];
+            let mut j = i;
+            for k in 1..components {
+                match glyphs.next(j) {
+                    Some(next) if Some(glyphs.glyph(next)) == read_u16(data, lig + 4 + (k - 1) * 2) => {
+                        j = next;
+                        positions.push(j);
+                    }
+                    _ => break,
+                }
+            }
+            positions
+        };
+        if positions.len() != components {
+            continue;
+        }
+        form_ligature(buffer, glyph, &positions);
+        return Some(i + 1);
+    }
+    None
+}
+
+/// Replaces the glyphs at `positions` with a ligature. Marks between the
+/// components stay where they are and remember the component before them.
+fn form_ligature(buffer: &mut Buffer, glyph: u16, positions: &[usize]) {
+    let first = positions[0];
+    let last = positions[positions.len() - 1];
+    buffer.merge_clusters(first, last + 1);
+    let lig_id = buffer.new_lig_id();
+    let mut component = 0;
+    for j in first..=last {
+        if positions.contains(&j) {
+            component += 1;
+        } else {
+            buffer.info[j].lig_id = lig_id;
+            buffer.info[j].lig_comp = component as u8;
+        }
+    }
+    buffer.info[first].lig_id = lig_id;
+    buffer.info[first].lig_comp = 0;
+    buffer.info[first].lig_comps = positions.len() as u8;
+    buffer.set_glyph(first, glyph);
+    for &j in positions[1..].iter().rev() {
+        buffer.remove(j);
+    }
+}
+
+fn reverse_chain(buffer: &mut Buffer, lookup: &Lookup, i: usize) -> Option<()> {
+    let data = buffer.data();
+    for &subtable in &lookup.subtables {
+        if read_u16(data, subtable)? != 1 {
+            continue;
+        }
+        let index = match layout::coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize,
+                                                 buffer.info[i].glyph) {
+            Some(index) => index,
+            None => continue,
+        };
+        let backtrack = read_u16(data, subtable + 4)? as usize;
+        let lookahead_at = subtable + 6 + backtrack * 2;
+        let lookahead = read_u16(data, lookahead_at)? as usize;
+        let substitutes_at = lookahead_at + 2 + lookahead * 2;
+        let matched = {
+            let glyphs = buffer.lookup_glyphs(lookup);
+            let covered = |array: usize, k: usize, j: usize| match read_u16(data, array + k * 2) {
+                Some(offset) => layout::coverage_index(data, subtable + offset as usize, glyphs.glyph(j)).is_some(),
+                None => false,
+            };
+            let mut ok = true;
+            let mut j = i;
+            for k in 0..backtrack {
+                match glyphs.prev(j) {
+                    Some(prev) if covered(subtable + 6, k, prev) => j = prev,
+                    _ => {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            let mut j = i;
+            for k in 0..lookahead {
+                if !ok {
+                    break;
+                }
+                match glyphs.next(j) {
+                    Some(next) if covered(lookahead_at + 2, k, next) => j = next,
+                    _ => ok = false,
+                }
+            }
+            ok
+        };
+        if !matched || index >= read_u16(data, substitutes_at)? {
+            continue;
+        }
+        let glyph = read_u16(data, substitutes_at + 2 + index as usize * 2)?;
+        buffer.set_glyph(i, glyph);
+        return Some(());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::tests::{chain_context, layout_font, layout_table, ligature_subst, lookup, shaped, shaped_glyphs,
+                             single_subst};
+    use crate::font::{Font, Script};
+
+    /// Returns a font for "fil" with a GSUB table.
+    fn font_with_gsub(features: &[(&[u8; 4], &[u16])], lookups: &[Vec<u8>]) -> Font {
+        layout_font("fil", vec![(b"GSUB", layout_table(features, lookups))])
+    }
+
+    #[test]
+    fn forms_ligatures() {
+        let ligatures = lookup(4, 0, &[ligature_subst(&[(&[1, 1, 2], 11), (&[1, 2], 10)])]);
+        let font = font_with_gsub(&[(b"liga", &[0])], &[ligatures]);
+        assert_eq!(shaped_glyphs(&font, "fi", Script::Latin, false), vec![10]);
+        assert_eq!(shaped_glyphs(&font, "ffi", Script::Latin, false), vec![11]);
+        assert_eq!(shaped_glyphs(&font, "ffl", Script::Latin, false), vec![1, 1, 3]);
+        let glyphs = shaped(&font, "lfi", Script::Latin, false);
+        assert_eq!(glyphs.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(3, 0), (10, 1)]);
+    }
+
+    #[test]
+    fn substitutes_single_glyphs_before_ligatures() {
+        let lookups = [lookup(4, 0, &[ligature_subst(&[(&[1, 2], 10)])]), lookup(1, 0, &[single_subst(&[(2, 20)])])];
+        let font = font_with_gsub(&[(b"liga", &[0]), (b"locl", &[1])], &lookups);
+        assert_eq!(shaped_glyphs(&font, "fil", Script::Latin, false), vec![1, 20, 3]);
+        let font = font_with_gsub(&[(b"liga", &[0])], &lookups);
+        assert_eq!(shaped_glyphs(&font, "fil", Script::Latin, false), vec![10, 3]);
+    }
+
+    #[test]
+    fn substitutes_in_context() {
+        let context = chain_context(&[&[3]], &[&[2]], &[&[3]], &[(0, 1)]);
+        let lookups = [lookup(6, 0, &[context]), lookup(1, 0, &[single_subst(&[(2, 21)])])];
+        let font = font_with_gsub(&[(b"calt", &[0])], &lookups);
+        assert_eq!(shaped_glyphs(&font, "lil", Script::Latin, false), vec![3, 21, 3]);
+        assert_eq!(shaped_glyphs(&font, "lili", Script::Latin, false), vec![3, 21, 3, 2]);
+        assert_eq!(shaped_glyphs(&font, "fil", Script::Latin, false), vec![1, 2, 3]);
+        assert_eq!(shaped_glyphs(&font, "lii", Script::Latin, false), vec![3, 2, 2]);
+    }
+}
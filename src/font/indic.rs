@@ -0,0 +1,406 @@
+//! Reordering of Indic syllables.
+//!
+//! The scripts of India write some vowel signs before the consonant they
+//! follow in text, and turn a leading Ra with virama into a reph mark over
+//! the end of the syllable. The text is split into syllables, whose
+//! characters are sorted into the order they are drawn in, before the
+//! font's substitutions form conjuncts. After the basic forms are
+//! substituted the reph moves to its place.
+//!
+//! This follows the OpenType specification for the second generation of
+//! Indic fonts, with the base consonant found by position rather than by
+//! asking the font which consonants have special forms.
+
+use super::script::Script;
+use super::shape::{Buffer, GlyphInfo, HALF, RPHF};
+
+/// Categories of characters.
+const X: u8 = 0;
+const C: u8 = 1;
+const V: u8 = 2;
+const N: u8 = 3;
+const H: u8 = 4;
+const ZWNJ: u8 = 5;
+const ZWJ: u8 = 6;
+const M: u8 = 7;
+const SM: u8 = 8;
+const RA: u8 = 9;
+const PLACEHOLDER: u8 = 10;
+
+/// Positions of characters within a syllable, in drawing order.
+const POS_RA_TO_BECOME_REPH: u8 = 1;
+const POS_PRE_M: u8 = 2;
+const POS_PRE_C: u8 = 3;
+const POS_BASE_C: u8 = 4;
+const POS_BELOW_C: u8 = 6;
+const POS_ABOVE_M: u8 = 8;
+const POS_BELOW_M: u8 = 9;
+const POS_POST_M: u8 = 10;
+const POS_SMVD: u8 = 11;
+const POS_END: u8 = 12;
+
+/// Where the reph goes in the syllable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RephPosition {
+    /// Right after the base consonant.
+    AfterMain,
+    /// After the below-base forms.
+    AfterSub,
+    /// Before the post-base vowel signs.
+    BeforePost,
+    /// After the post-base vowel signs.
+    AfterPost,
+}
+
+/// The dotted circle that stands in for a missing base.
+const DOTTED_CIRCLE: char = '\u{25cc}';
+
+fn block(script: Script) -> u32 {
+    match script {
+        Script::Devanagari => 0x900,
+        Script::Bengali => 0x980,
+        Script::Gurmukhi => 0xa00,
+        Script::Gujarati => 0xa80,
+        Script::Oriya => 0xb00,
+        Script::Tamil => 0xb80,
+        Script::Telugu => 0xc00,
+        Script::Kannada => 0xc80,
+        _ => 0xd00,
+    }
+}
+
+fn reph_position(script: Script) -> Option<RephPosition> {
+    match script {
+        Script::Devanagari | Script::Gujarati => Some(RephPosition::BeforePost),
+        Script::Bengali => Some(RephPosition::AfterSub),
+        Script::Oriya => Some(RephPosition::AfterMain),
+        Script::Telugu | Script::Kannada => Some(RephPosition::AfterPost),
+        _ => None,
+    }
+}
+
+fn category(script: Script, c: char) -> u8 {
+    match c {
+        '\u{200c}' => return ZWNJ,
+        '\u{200d}' => return ZWJ,
+        DOTTED_CIRCLE | '\u{a0}' => return PLACEHOLDER,
+        _ => {}
+    }
+    let c = c as u32;
+    let base = block(script);
+    if c < base || c >= base + 0x80 {
+        return X;
+    }
+    match c - base {
+        0x30 => RA,
+        0x70 if script == Script::Bengali => RA,
+        0x00..=0x03 => SM,
+        0x04..=0x14 | 0x60..=0x61 | 0x72..=0x77 => V,
+        0x15..=0x39 | 0x58..=0x5f | 0x71 | 0x78..=0x7f => C,
+        0x3c => N,
+        0x4d => H,
+        0x3a..=0x3b | 0x3e..=0x4c | 0x4e..=0x4f | 0x55..=0x57 | 0x62..=0x63 => M,
+        0x51..=0x54 => SM,
+        _ => X,
+    }
+}
+
+fn is_consonant(category: u8) -> bool {
+    category == C || category == RA || category == PLACEHOLDER
+}
+
+fn matra_position(script: Script, c: char) -> u8 {
+    let offset = (c as u32).wrapping_sub(block(script));
+    let pre: &[u32] = match script {
+        Script::Devanagari => &[0x3f, 0x4e],
+        Script::Bengali => &[0x3f, 0x47, 0x48],
+        Script::Gurmukhi | Script::Gujarati => &[0x3f],
+        Script::Oriya => &[0x47],
+        Script::Tamil | Script::Malayalam => &[0x46, 0x47, 0x48],
+        _ => &[],
+    };
+    let above: &[u32] = match script {
+        Script::Devanagari => &[0x3a, 0x45, 0x46, 0x47, 0x48, 0x55],
+        Script::Gurmukhi => &[0x47, 0x48, 0x4b, 0x4c],
+        Script::Gujarati => &[0x45, 0x47, 0x48],
+        Script::Oriya => &[0x3f, 0x56],
+        Script::Tamil => &[0x40],
+        Script::Telugu => &[0x3e, 0x3f, 0x40, 0x46, 0x47, 0x48, 0x4a, 0x4b, 0x4c, 0x55],
+        Script::Kannada => &[0x3f, 0x46],
+        _ => &[],
+    };
+    if pre.contains(&offset) {
+        POS_PRE_M
+    } else if above.contains(&offset) {
+        POS_ABOVE_M
+    } else if (0x41..=0x44).contains(&offset) || (0x62..=0x63).contains(&offset) || offset == 0x56 || offset == 0x57 {
+        match script {
+            Script::Telugu | Script::Kannada => POS_POST_M,
+            _ => POS_BELOW_M,
+        }
+    } else {
+        POS_POST_M
+    }
+}
+
+/// Returns the two parts of a vowel sign written on both sides of its
+/// consonant.
+fn split_matra(c: char) -> Option<(char, char)> {
+    let parts = match c as u32 {
+        0x9cb => (0x9c7, 0x9be),
+        0x9cc => (0x9c7, 0x9d7),
+        0xb48 => (0xb47, 0xb56),
+        0xb4b => (0xb47, 0xb3e),
+        0xb4c => (0xb47, 0xb57),
+        0xbca => (0xbc6, 0xbbe),
+        0xbcb => (0xbc7, 0xbbe),
+        0xbcc => (0xbc6, 0xbd7),
+        0xd4a => (0xd46, 0xd3e),
+        0xd4b => (0xd47, 0xd3e),
+        0xd4c => (0xd46, 0xd57),
+        _ => return None,
+    };
+    Some((::std::char::from_u32(parts.0)?, ::std::char::from_u32(parts.1)?))
+}
+
+/// Kinds of syllables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Syllable {
+    Consonant,
+    Vowel,
+    /// Signs without a base, which get a dotted circle.
+    Broken,
+    Other,
+}
+
+/// Returns the end and kind of the syllable starting at `start`.
+fn find_syllable(info: &[GlyphInfo], start: usize) -> (usize, Syllable) {
+    let cat = |i: usize| info.get(i).map_or(X, |info| info.category);
+    let mut i = start;
+    let kind = match cat(i) {
+        c if is_consonant(c) => {
+            loop {
+                i += 1;
+                if cat(i) == N {
+                    i += 1;
+                }
+                if cat(i) != H {
+                    break;
+                }
+                i += 1;
+                if cat(i) == ZWJ || cat(i) == ZWNJ {
+                    i += 1;
+                }
+                if !is_consonant(cat(i)) {
+                    break;
+                }
+            }
+            Syllable::Consonant
+        }
+        V => {
+            i += 1;
+            if cat(i) == N {
+                i += 1;
+            }
+            Syllable::Vowel
+        }
+        N | H | M | SM => Syllable::Broken,
+        _ => return (start + 1, Syllable::Other),
+    };
+    while cat(i) == M || cat(i) == N || cat(i) == H || ((cat(i) == ZWJ || cat(i) == ZWNJ) && cat(i + 1) == M) {
+        i += 1;
+    }
+    while cat(i) == SM {
+        i += 1;
+    }
+    (i.max(start + 1), kind)
+}
+
+/// Splits the text into syllables, reorders them and sets the masks of the
+/// features which only apply to parts of a syllable.
+pub fn setup(buffer: &mut Buffer, script: Script) {
+    // Decompose split vowel signs if the font has both parts.
+    let mut i = 0;
+    while i < buffer.len() {
+        let info = buffer.info[i];
+        if let Some((pre, post)) = split_matra(info.codepoint) {
+            if buffer.font.glyph_index(pre).is_some() && buffer.font.glyph_index(post).is_some() {
+                buffer.info[i] = buffer.glyph_info(pre, info.cluster);
+                let post = buffer.glyph_info(post, info.cluster);
+                buffer.insert(i + 1, post);
+            }
+        }
+        i += 1;
+    }
+    for info in &mut buffer.info {
+        info.category = category(script, info.codepoint);
+    }
+
+    let mut start = 0;
+    let mut number = 0;
+    while start < buffer.len() {
+        let (mut end, kind) = find_syllable(&buffer.info, start);
+        number += 1;
+        if kind == Syllable::Broken {
+            let cluster = buffer.info[start].cluster;
+            let mut circle = buffer.glyph_info(DOTTED_CIRCLE, cluster);
+            circle.category = PLACEHOLDER;
+            buffer.insert(start, circle);
+            end += 1;
+        }
+        for info in &mut buffer.info[start..end] {
+            info.syllable = number;
+        }
+        buffer.merge_clusters(start, end);
+        if kind == Syllable::Consonant || kind == Syllable::Broken {
+            initial_reordering(buffer, script, start, end);
+        }
+        start = end;
+    }
+}
+
+/// Finds the base consonant of a syllable, sets the positions of all its
+/// characters and sorts them by position.
+fn initial_reordering(buffer: &mut Buffer, script: Script, start: usize, end: usize) {
+    let info = &mut buffer.info[start..end];
+    let n = info.len();
+
+    // A syllable starting with Ra and virama gets a reph, unless the Ra is
+    // all there is or ZWJ asks for the half form.
+    let has_reph = reph_position(script).is_some() && n > 2 && info[0].category == RA && info[1].category == H &&
+        info[2].category != ZWJ && info[2..].iter().any(|i| is_consonant(i.category));
+    let limit = if has_reph { 2 } else { 0 };
+
+    // The base is the last consonant, except that Ra after a virama takes
+    // its below-base form. Telugu and Kannada put all consonants after the
+    // first below it.
+    let consonants: Vec<usize> = (limit..n).filter(|&k| is_consonant(info[k].category)).collect();
+    let base = match script {
+        Script::Telugu | Script::Kannada => consonants.first().cloned(),
+        _ => consonants.iter().rev().cloned().find(|&k| {
+            let below_ra = info[k].category == RA && k > limit && info[k - 1].category == H &&
+                consonants.first() != Some(&k);
+            !below_ra
+        }).or_else(|| consonants.first().cloned()),
+    };
+    let base = match base {
+        Some(base) => base,
+        None => return,
+    };
+
+    // Positions, with nukta, virama and joiners taking the position of the
+    // character before them.
+    let mut last = POS_PRE_C;
+    for (k, glyph) in info.iter_mut().enumerate() {
+        let position = match glyph.category {
+            _ if k < limit => POS_RA_TO_BECOME_REPH,
+            c if is_consonant(c) => {
+                if k < base {
+                    POS_PRE_C
+                } else if k == base {
+                    POS_BASE_C
+                } else {
+                    POS_BELOW_C
+                }
+            }
+            M => matra_position(script, glyph.codepoint),
+            SM => POS_SMVD,
+            X | V => POS_END,
+            _ => last,
+        };
+        glyph.position = position;
+        last = position;
+    }
+    // Masks of the features for parts of the syllable.
+    for (k, glyph) in info.iter_mut().enumerate() {
+        if k < limit {
+            glyph.mask |= RPHF;
+        } else if k < base {
+            glyph.mask |= HALF;
+        }
+    }
+
+    info.sort_by_key(|glyph| glyph.position);
+}
+
+/// Moves the reph of each syllable to its place once the font has formed it.
+pub fn final_reordering(buffer: &mut Buffer, script: Script) {
+    let reph_position = match reph_position(script) {
+        Some(position) => position,
+        None => return,
+    };
+    let mut start = 0;
+    while start < buffer.len() {
+        let syllable = buffer.info[start].syllable;
+        let mut end = start + 1;
+        while end < buffer.len() && buffer.info[end].syllable == syllable {
+            end += 1;
+        }
+        let first = buffer.info[start];
+        let formed = first.position == POS_RA_TO_BECOME_REPH && first.substituted &&
+            (end == start + 1 || buffer.info[start + 1].position != POS_RA_TO_BECOME_REPH);
+        if syllable != 0 && formed && end - start > 1 {
+            let limit = match reph_position {
+                RephPosition::AfterMain => POS_BASE_C,
+                RephPosition::AfterSub => POS_BELOW_C,
+                RephPosition::BeforePost => POS_BELOW_M,
+                RephPosition::AfterPost => POS_POST_M,
+            };
+            let mut target = start + 1;
+            while target < end && buffer.info[target].position <= limit {
+                target += 1;
+            }
+            let reph = buffer.info[start];
+            let pos = buffer.pos[start];
+            buffer.info.remove(start);
+            buffer.pos.remove(start);
+            buffer.info.insert(target - 1, reph);
+            buffer.pos.insert(target - 1, pos);
+        }
+        start = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::{layout_font, layout_table, ligature_subst, lookup, shaped, shaped_glyphs};
+    use crate::font::Font;
+
+    /// Devanagari ka, kha, ra, virama, sign i, sign aa and the dotted
+    /// circle, as glyphs 1 to 7.
+    const CHARS: &str = "\u{915}\u{916}\u{930}\u{94d}\u{93f}\u{93e}\u{25cc}";
+
+    /// Returns a font whose rphf feature makes ra and virama a reph.
+    fn reph_font() -> Font {
+        let lookups = [lookup(4, 0, &[ligature_subst(&[(&[3, 4], 10)])])];
+        layout_font(CHARS, vec![(b"GSUB", layout_table(&[(b"rphf", &[0])], &lookups))])
+    }
+
+    #[test]
+    fn moves_pre_base_matras() {
+        let font = layout_font(CHARS, vec![]);
+        let glyphs = shaped(&font, "\u{915}\u{93f}", Script::Devanagari, false);
+        assert_eq!(glyphs.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(5, 0), (1, 0)]);
+        // The matra goes before the whole syllable.
+        assert_eq!(shaped_glyphs(&font, "\u{915}\u{94d}\u{916}\u{93f}", Script::Devanagari, false), vec![5, 1, 4, 2]);
+        assert_eq!(shaped_glyphs(&font, "\u{915}\u{93e}", Script::Devanagari, false), vec![1, 6]);
+        // A matra without a consonant gets a dotted circle.
+        assert_eq!(shaped_glyphs(&font, "\u{93f}", Script::Devanagari, false), vec![5, 7]);
+    }
+
+    #[test]
+    fn places_the_reph() {
+        let font = reph_font();
+        assert_eq!(shaped_glyphs(&font, "\u{930}\u{94d}\u{915}", Script::Devanagari, false), vec![1, 10]);
+        // Devanagari puts the reph before post-base matras.
+        let glyphs = shaped(&font, "\u{930}\u{94d}\u{915}\u{93e}", Script::Devanagari, false);
+        assert_eq!(glyphs.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(1, 0), (10, 0), (6, 0)]);
+        assert_eq!(shaped_glyphs(&font, "\u{930}\u{94d}\u{915}\u{93f}", Script::Devanagari, false), vec![5, 1, 10]);
+        // Ra and virama without a consonant after them are no reph.
+        assert_eq!(shaped_glyphs(&font, "\u{915}\u{930}\u{94d}", Script::Devanagari, false), vec![1, 3, 4]);
+        // Nor are they when the font has no reph form.
+        let font = layout_font(CHARS, vec![]);
+        assert_eq!(shaped_glyphs(&font, "\u{930}\u{94d}\u{915}", Script::Devanagari, false), vec![3, 4, 1]);
+    }
+}
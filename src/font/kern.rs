@@ -0,0 +1,130 @@
+//! Pair kerning from the GPOS and `kern` tables.
+
+use super::{read_u16, read_u32, Table};
+use super::layout;
+
+/// Lookup type of pair adjustments in GPOS.
+const PAIR_ADJUSTMENT: u16 = 2;
+
+/// Returns the pair adjustment lookups of the `kern` feature of a GPOS
+/// table, each as the offsets of its subtables.
+pub fn pair_lookups(data: &[u8], gpos: Table) -> Vec<Vec<usize>> {
+    let table = gpos.offset;
+    let mut lookups = Vec::new();
+    for index in layout::feature_lookups(data, table, &[b"kern"]) {
+        let lookup = match layout::lookup_offset(data, table, index) {
+            Some(lookup) => lookup,
+            None => continue,
+        };
+        if let Some((PAIR_ADJUSTMENT, subtables)) = layout::lookup_subtables(data, lookup, layout::GPOS_EXTENSION) {
+            lookups.push(subtables);
+        }
+    }
+    lookups
+}
+
+/// Returns the x advance adjustment of a pair from the subtables of one
+/// pair adjustment lookup. The first subtable covering the pair is used.
+pub fn gpos_pair(data: &[u8], subtables: &[usize], left: u16, right: u16) -> Option<i16> {
+    for &subtable in subtables {
+        let coverage = subtable + read_u16(data, subtable + 2)? as usize;
+        let index = match layout::coverage_index(data, coverage, left) {
+            Some(index) => index as usize,
+            None => continue,
+        };
+        let format1 = read_u16(data, subtable + 4)?;
+        let format2 = read_u16(data, subtable + 6)?;
+        let size1 = layout::value_record_size(format1);
+        let size2 = layout::value_record_size(format2);
+        match read_u16(data, subtable)? {
+            1 => {
+                if index >= read_u16(data, subtable + 8)? as usize {
+                    continue;
+                }
+                let set = subtable + read_u16(data, subtable + 10 + index * 2)? as usize;
+                let count = read_u16(data, set)? as usize;
+                let record_size = 2 + size1 + size2;
+                let (mut lo, mut hi) = (0, count);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    let record = set + 2 + mid * record_size;
+                    let second = read_u16(data, record)?;
+                    if right < second {
+                        hi = mid;
+                    } else if right > second {
+                        lo = mid + 1;
+                    } else {
+                        return Some(layout::value_record_x_advance(data, record + 2, format1));
+                    }
+                }
+            }
+            2 => {
+                let class1 = layout::glyph_class(data, subtable + read_u16(data, subtable + 8)? as usize, left);
+                let class2 = layout::glyph_class(data, subtable + read_u16(data, subtable + 10)? as usize, right);
+                let class1_count = read_u16(data, subtable + 12)?;
+                let class2_count = read_u16(data, subtable + 14)?;
+                if class1 >= class1_count || class2 >= class2_count {
+                    continue;
+                }
+                let record = subtable + 16
+                    + (class1 as usize * class2_count as usize + class2 as usize) * (size1 + size2);
+                return Some(layout::value_record_x_advance(data, record, format1));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the kerning of a pair from the horizontal format 0 subtables of
+/// a `kern` table, in either the Microsoft or the Apple layout.
+pub fn kern_pair(data: &[u8], kern: Table, left: u16, right: u16) -> i16 {
+    let table = kern.offset;
+    let (apple, count, mut subtable) = match read_u16(data, table) {
+        Some(0) => (false, read_u16(data, table + 2).unwrap_or(0) as usize, table + 4),
+        Some(1) => (true, read_u32(data, table + 4).unwrap_or(0) as usize, table + 8),
+        _ => return 0,
+    };
+    let end = table + kern.len;
+    let key = (left as u32) << 16 | right as u32;
+    let mut value = 0i16;
+    for _ in 0..count {
+        let (len, format, horizontal, header) = if apple {
+            let len = read_u32(data, subtable).unwrap_or(0) as usize;
+            let coverage = read_u16(data, subtable + 4).unwrap_or(0);
+            // Vertical, cross-stream and variation subtables are skipped.
+            (len, coverage & 0xff, coverage & 0xe000 == 0, 8)
+        } else {
+            let len = read_u16(data, subtable + 2).unwrap_or(0) as usize;
+            let coverage = read_u16(data, subtable + 4).unwrap_or(0);
+            // Horizontal, not minimum values and not cross-stream.
+            (len, coverage >> 8, coverage & 0x7 == 1, 6)
+        };
+        if len == 0 || subtable + header > end {
+            break;
+        }
+        if format == 0 && horizontal {
+            let pairs = subtable + header;
+            let n = read_u16(data, pairs).unwrap_or(0) as usize;
+            let (mut lo, mut hi) = (0, n);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let record = pairs + 8 + mid * 6;
+                let pair = match read_u32(data, record) {
+                    Some(pair) => pair,
+                    None => break,
+                };
+                if key < pair {
+                    hi = mid;
+                } else if key > pair {
+                    lo = mid + 1;
+                } else {
+                    value = value.wrapping_add(read_u16(data, record + 4).unwrap_or(0) as i16);
+                    break;
+                }
+            }
+        }
+        subtable += len;
+    }
+    value
+}
@@ -0,0 +1,513 @@
+//! Common tables of the OpenType layout tables, GSUB and GPOS.
+
+use super::{read_u16, read_u32};
+
+/// Lookup type of extension subtables in GPOS.
+pub const GPOS_EXTENSION: u16 = 9;
+
+/// Returns the coverage index of a glyph in the coverage table at `offset`.
+pub fn coverage_index(data: &[u8], offset: usize, glyph: u16) -> Option<u16> {
+    match read_u16(data, offset)? {
+        1 => {
+            let count = read_u16(data, offset + 2)? as usize;
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let g = read_u16(data, offset + 4 + mid * 2)?;
+                if glyph < g {
+                    hi = mid;
+                } else if glyph > g {
+                    lo = mid + 1;
+                } else {
+                    return Some(mid as u16);
+                }
+            }
+            None
+        }
+        2 => {
+            let count = read_u16(data, offset + 2)? as usize;
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let record = offset + 4 + mid * 6;
+                let start = read_u16(data, record)?;
+                let end = read_u16(data, record + 2)?;
+                if glyph < start {
+                    hi = mid;
+                } else if glyph > end {
+                    lo = mid + 1;
+                } else {
+                    return Some(read_u16(data, record + 4)?.wrapping_add(glyph - start));
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Returns the class of a glyph in the class definition table at `offset`,
+/// 0 for glyphs without a class.
+pub fn glyph_class(data: &[u8], offset: usize, glyph: u16) -> u16 {
+    let class = match read_u16(data, offset) {
+        Some(1) => {
+            let start = read_u16(data, offset + 2).unwrap_or(0);
+            let count = read_u16(data, offset + 4).unwrap_or(0);
+            if glyph >= start && glyph - start < count {
+                read_u16(data, offset + 6 + (glyph - start) as usize * 2)
+            } else {
+                None
+            }
+        }
+        Some(2) => {
+            let count = read_u16(data, offset + 2).unwrap_or(0) as usize;
+            let (mut lo, mut hi) = (0, count);
+            let mut class = None;
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let record = offset + 4 + mid * 6;
+                let (start, end) = match (read_u16(data, record), read_u16(data, record + 2)) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => break,
+                };
+                if glyph < start {
+                    hi = mid;
+                } else if glyph > end {
+                    lo = mid + 1;
+                } else {
+                    class = read_u16(data, record + 4);
+                    break;
+                }
+            }
+            class
+        }
+        _ => None,
+    };
+    class.unwrap_or(0)
+}
+
+/// Returns the lookup indices of every feature with one of the given tags,
+/// in lookup list order and without duplicates. Features of all scripts
+/// and languages are used.
+pub fn feature_lookups(data: &[u8], table: usize, tags: &[&[u8; 4]]) -> Vec<u16> {
+    let mut lookups = Vec::new();
+    let features = match read_u16(data, table + 6) {
+        Some(offset) => table + offset as usize,
+        None => return lookups,
+    };
+    let count = read_u16(data, features).unwrap_or(0) as usize;
+    for i in 0..count {
+        let record = features + 2 + i * 6;
+        let tag = match read_u32(data, record) {
+            Some(tag) => tag.to_be_bytes(),
+            None => break,
+        };
+        if !tags.iter().any(|t| **t == tag) {
+            continue;
+        }
+        let feature = features + read_u16(data, record + 4).unwrap_or(0) as usize;
+        let n = read_u16(data, feature + 2).unwrap_or(0) as usize;
+        for j in 0..n {
+            if let Some(index) = read_u16(data, feature + 4 + j * 2) {
+                lookups.push(index);
+            }
+        }
+    }
+    lookups.sort();
+    lookups.dedup();
+    lookups
+}
+
+/// Returns the offset of a lookup table from the lookup list of a GSUB or
+/// GPOS table.
+pub fn lookup_offset(data: &[u8], table: usize, index: u16) -> Option<usize> {
+    let list = table + read_u16(data, table + 8)? as usize;
+    if index >= read_u16(data, list)? {
+        return None;
+    }
+    Some(list + read_u16(data, list + 2 + index as usize * 2)? as usize)
+}
+
+/// Returns the type and subtable offsets of a lookup, resolving extension
+/// subtables to the subtables they wrap.
+pub fn lookup_subtables(data: &[u8], lookup: usize, extension: u16) -> Option<(u16, Vec<usize>)> {
+    let lookup_type = read_u16(data, lookup)?;
+    let count = read_u16(data, lookup + 4)? as usize;
+    let mut kind = lookup_type;
+    let mut subtables = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut subtable = lookup + read_u16(data, lookup + 6 + i * 2)? as usize;
+        if lookup_type == extension {
+            kind = read_u16(data, subtable + 2)?;
+            subtable += read_u32(data, subtable + 4)? as usize;
+        }
+        subtables.push(subtable);
+    }
+    Some((kind, subtables))
+}
+
+/// Returns the size in bytes of a GPOS value record of the given format.
+pub fn value_record_size(format: u16) -> usize {
+    format.count_ones() as usize * 2
+}
+
+/// Returns the x advance adjustment of a GPOS value record.
+pub fn value_record_x_advance(data: &[u8], offset: usize, format: u16) -> i16 {
+    if format & 4 == 0 {
+        return 0;
+    }
+    let skip = (format & 3).count_ones() as usize * 2;
+    read_u16(data, offset + skip).map_or(0, |v| v as i16)
+}
+
+/// A lookup of a GSUB or GPOS table, with extension subtables resolved.
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    pub kind:      u16,
+    pub flags:     u16,
+    /// Mark filtering set, used if the flags say so.
+    pub mark_set:  u16,
+    pub subtables: Vec<usize>,
+}
+
+/// Lookup flags.
+pub const RIGHT_TO_LEFT: u16 = 0x1;
+pub const IGNORE_BASE_GLYPHS: u16 = 0x2;
+pub const IGNORE_LIGATURES: u16 = 0x4;
+pub const IGNORE_MARKS: u16 = 0x8;
+pub const USE_MARK_FILTERING_SET: u16 = 0x10;
+
+/// Reads lookup `index` of a GSUB or GPOS table.
+pub fn lookup(data: &[u8], table: usize, index: u16, extension: u16) -> Option<Lookup> {
+    let offset = lookup_offset(data, table, index)?;
+    let flags = read_u16(data, offset + 2)?;
+    let (kind, subtables) = lookup_subtables(data, offset, extension)?;
+    let mark_set = if flags & USE_MARK_FILTERING_SET != 0 {
+        read_u16(data, offset + 6 + subtables.len() * 2)?
+    } else {
+        0
+    };
+    Some(Lookup{ kind: kind, flags: flags, mark_set: mark_set, subtables: subtables })
+}
+
+/// Returns the lookup indices of a feature for the first of `scripts` the
+/// table has, using the default language of the script. Falls back to the
+/// default script and then to Latin, like other shapers do.
+pub fn script_lookups(data: &[u8], table: usize, scripts: &[&[u8; 4]], feature: &[u8; 4]) -> Vec<u16> {
+    let mut lookups = Vec::new();
+    let lang_sys = match find_lang_sys(data, table, scripts) {
+        Some(lang_sys) => lang_sys,
+        None => return lookups,
+    };
+    let features = match read_u16(data, table + 6) {
+        Some(offset) => table + offset as usize,
+        None => return lookups,
+    };
+    let feature_count = read_u16(data, features).unwrap_or(0);
+    let required = read_u16(data, lang_sys + 2).unwrap_or(0xffff);
+    let count = read_u16(data, lang_sys + 4).unwrap_or(0) as usize;
+    let indices = (0..count).filter_map(|i| read_u16(data, lang_sys + 6 + i * 2)).chain(Some(required));
+    for index in indices {
+        if index >= feature_count {
+            continue;
+        }
+        let record = features + 2 + index as usize * 6;
+        if read_u32(data, record).map(|tag| tag.to_be_bytes()) != Some(*feature) {
+            continue;
+        }
+        let offset = features + read_u16(data, record + 4).unwrap_or(0) as usize;
+        let n = read_u16(data, offset + 2).unwrap_or(0) as usize;
+        lookups.extend((0..n).filter_map(|j| read_u16(data, offset + 4 + j * 2)));
+    }
+    lookups.sort();
+    lookups.dedup();
+    lookups
+}
+
+fn find_lang_sys(data: &[u8], table: usize, scripts: &[&[u8; 4]]) -> Option<usize> {
+    let list = table + read_u16(data, table + 4)? as usize;
+    let count = read_u16(data, list)? as usize;
+    let find = |want: &&[u8; 4]| {
+        (0..count).find(|i| read_u32(data, list + 2 + i * 6).map(|tag| tag.to_be_bytes()) == Some(**want))
+            .and_then(|i| read_u16(data, list + 6 + i * 6))
+    };
+    let fallbacks: [&[u8; 4]; 3] = [b"DFLT", b"dflt", b"latn"];
+    let script = scripts.iter().chain(&fallbacks).filter_map(find).next()?;
+    let script = list + script as usize;
+    match read_u16(data, script)? {
+        0 if read_u16(data, script + 2)? > 0 => Some(script + read_u16(data, script + 8)? as usize),
+        0 => None,
+        offset => Some(script + offset as usize),
+    }
+}
+
+/// Glyph definitions from the GDEF table.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gdef {
+    classes:      usize,
+    mark_classes: usize,
+    mark_sets:    usize,
+}
+
+/// Glyph classes of the GDEF table.
+pub const BASE_GLYPH: u16 = 1;
+pub const LIGATURE_GLYPH: u16 = 2;
+pub const MARK_GLYPH: u16 = 3;
+
+impl Gdef {
+    pub fn parse(data: &[u8], table: usize) -> Gdef {
+        let offset = |at: usize| match read_u16(data, table + at) {
+            Some(0) | None => 0,
+            Some(offset) => table + offset as usize,
+        };
+        let minor = read_u16(data, table + 2).unwrap_or(0);
+        Gdef{
+            classes:      offset(4),
+            mark_classes: offset(10),
+            mark_sets:    if minor >= 2 { offset(12) } else { 0 },
+        }
+    }
+
+    /// Returns true if the font classifies its glyphs.
+    pub fn has_classes(&self) -> bool {
+        self.classes != 0
+    }
+
+    /// Returns the class of a glyph, 0 if it has none.
+    pub fn glyph_class(&self, data: &[u8], glyph: u16) -> u16 {
+        if self.classes == 0 { 0 } else { glyph_class(data, self.classes, glyph) }
+    }
+
+    /// Returns the mark attachment class of a glyph.
+    pub fn mark_class(&self, data: &[u8], glyph: u16) -> u16 {
+        if self.mark_classes == 0 { 0 } else { glyph_class(data, self.mark_classes, glyph) }
+    }
+
+    /// Returns true if a glyph is in a mark filtering set.
+    pub fn in_mark_set(&self, data: &[u8], set: u16, glyph: u16) -> bool {
+        if self.mark_sets == 0 || set >= read_u16(data, self.mark_sets + 2).unwrap_or(0) {
+            return false;
+        }
+        let coverage = read_u32(data, self.mark_sets + 4 + set as usize * 4).unwrap_or(0) as usize;
+        coverage_index(data, self.mark_sets + coverage, glyph).is_some()
+    }
+}
+
+/// How the glyphs of a context rule are given.
+#[derive(Debug, Clone, Copy)]
+pub enum Sequence {
+    /// An array of glyph ids.
+    Glyphs(usize),
+    /// An array of classes of a class definition table.
+    Classes(usize, usize),
+    /// An array of offsets to coverage tables, relative to a subtable.
+    Coverages(usize, usize),
+}
+
+impl Sequence {
+    /// Returns true if the glyph matches item `k` of the sequence.
+    pub fn matches(&self, data: &[u8], k: usize, glyph: u16) -> bool {
+        match *self {
+            Sequence::Glyphs(array) => read_u16(data, array + k * 2) == Some(glyph),
+            Sequence::Classes(class_def, array) => read_u16(data, array + k * 2) == Some(glyph_class(data, class_def, glyph)),
+            Sequence::Coverages(base, array) => match read_u16(data, array + k * 2) {
+                Some(offset) => coverage_index(data, base + offset as usize, glyph).is_some(),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Input glyphs matched by a context rule and the lookups to apply to them.
+#[derive(Debug, Clone)]
+pub struct ContextMatch {
+    /// Buffer positions of the input glyphs.
+    pub positions: Vec<usize>,
+    /// Offset and number of the sequence lookup records.
+    pub records:   usize,
+    pub count:     usize,
+}
+
+/// The glyphs a lookup is applied to.
+pub trait Glyphs {
+    fn len(&self) -> usize;
+    fn glyph(&self, i: usize) -> u16;
+    /// Returns true if the lookup ignores glyph `i`.
+    fn skip(&self, i: usize) -> bool;
+
+    /// Returns the next glyph after `i` which is not skipped.
+    fn next(&self, i: usize) -> Option<usize> {
+        (i + 1..self.len()).find(|&j| !self.skip(j))
+    }
+
+    /// Returns the previous glyph before `i` which is not skipped.
+    fn prev(&self, i: usize) -> Option<usize> {
+        (0..i).rev().find(|&j| !self.skip(j))
+    }
+}
+
+/// Matches a contextual or chained contextual subtable at glyph `i`.
+pub fn match_context<G: Glyphs>(data: &[u8], subtable: usize, chained: bool, glyphs: &G, i: usize)
+                                -> Option<ContextMatch> {
+    let glyph = glyphs.glyph(i);
+    let format = read_u16(data, subtable)?;
+    if format == 3 {
+        let (rule, records) = if chained {
+            let backtrack = read_u16(data, subtable + 2)? as usize;
+            let input_at = subtable + 4 + backtrack * 2;
+            let input = read_u16(data, input_at)? as usize;
+            let lookahead_at = input_at + 2 + input * 2;
+            let lookahead = read_u16(data, lookahead_at)? as usize;
+            let rule = Rule{
+                backtrack:       Sequence::Coverages(subtable, subtable + 4),
+                backtrack_count: backtrack,
+                input:           Sequence::Coverages(subtable, input_at + 2),
+                input_count:     input,
+                input_first:     true,
+                lookahead:       Sequence::Coverages(subtable, lookahead_at + 2),
+                lookahead_count: lookahead,
+            };
+            let count_at = lookahead_at + 2 + lookahead * 2;
+            (rule, (count_at + 2, read_u16(data, count_at)?))
+        } else {
+            let input = read_u16(data, subtable + 2)? as usize;
+            let rule = Rule{
+                backtrack:       Sequence::Glyphs(0),
+                backtrack_count: 0,
+                input:           Sequence::Coverages(subtable, subtable + 6),
+                input_count:     input,
+                input_first:     true,
+                lookahead:       Sequence::Glyphs(0),
+                lookahead_count: 0,
+            };
+            (rule, (subtable + 6 + input * 2, read_u16(data, subtable + 4)?))
+        };
+        return rule.matches(data, glyphs, i).map(|positions| ContextMatch{
+            positions: positions,
+            records:   records.0,
+            count:     records.1 as usize,
+        });
+    }
+
+    let index = coverage_index(data, subtable + read_u16(data, subtable + 2)? as usize, glyph)?;
+    let (class_defs, sets_at) = match (format, chained) {
+        (1, _) => ([0; 3], subtable + 4),
+        (2, false) => {
+            let input = subtable + read_u16(data, subtable + 4)? as usize;
+            ([0, input, 0], subtable + 6)
+        }
+        (2, true) => {
+            let mut defs = [0; 3];
+            for (k, def) in defs.iter_mut().enumerate() {
+                *def = subtable + read_u16(data, subtable + 4 + k * 2)? as usize;
+            }
+            (defs, subtable + 10)
+        }
+        _ => return None,
+    };
+    let set_index = if format == 1 { index } else { glyph_class(data, class_defs[1], glyph) } as usize;
+    if set_index >= read_u16(data, sets_at)? as usize {
+        return None;
+    }
+    let set = match read_u16(data, sets_at + 2 + set_index * 2)? {
+        0 => return None,
+        offset => subtable + offset as usize,
+    };
+    let seq = |k: usize, array: usize| match format {
+        1 => Sequence::Glyphs(array),
+        _ => Sequence::Classes(class_defs[k], array),
+    };
+    let rule_count = read_u16(data, set)? as usize;
+    for r in 0..rule_count {
+        let rule_at = set + read_u16(data, set + 2 + r * 2)? as usize;
+        let (rule, count_at, records) = if chained {
+            let backtrack = read_u16(data, rule_at)? as usize;
+            let input_at = rule_at + 2 + backtrack * 2;
+            let input = read_u16(data, input_at)? as usize;
+            let lookahead_at = input_at + 2 + input.saturating_sub(1) * 2;
+            let lookahead = read_u16(data, lookahead_at)? as usize;
+            let rule = Rule{
+                backtrack:       seq(0, rule_at + 2),
+                backtrack_count: backtrack,
+                input:           seq(1, input_at + 2),
+                input_count:     input,
+                input_first:     false,
+                lookahead:       seq(2, lookahead_at + 2),
+                lookahead_count: lookahead,
+            };
+            let count_at = lookahead_at + 2 + lookahead * 2;
+            (rule, count_at, count_at + 2)
+        } else {
+            let input = read_u16(data, rule_at)? as usize;
+            let rule = Rule{
+                backtrack:       Sequence::Glyphs(0),
+                backtrack_count: 0,
+                input:           seq(1, rule_at + 4),
+                input_count:     input,
+                input_first:     false,
+                lookahead:       Sequence::Glyphs(0),
+                lookahead_count: 0,
+            };
+            (rule, rule_at + 2, rule_at + 4 + input.saturating_sub(1) * 2)
+        };
+        if let Some(positions) = rule.matches(data, glyphs, i) {
+            return Some(ContextMatch{
+                positions: positions,
+                records:   records,
+                count:     read_u16(data, count_at)? as usize,
+            });
+        }
+    }
+    None
+}
+
+/// The glyph sequences of one context rule.
+struct Rule {
+    backtrack:       Sequence,
+    backtrack_count: usize,
+    input:           Sequence,
+    input_count:     usize,
+    /// Whether the input sequence includes the first glyph. Only coverage
+    /// based rules list it, the others matched it by their coverage.
+    input_first:     bool,
+    lookahead:       Sequence,
+    lookahead_count: usize,
+}
+
+impl Rule {
+    /// Matches the rule at glyph `i` and returns the positions of the input
+    /// glyphs.
+    fn matches<G: Glyphs>(&self, data: &[u8], glyphs: &G, i: usize) -> Option<Vec<usize>> {
+        if self.input_count == 0 {
+            return None;
+        }
+        let first = if self.input_first { 1 } else { 0 };
+        if self.input_first && !self.input.matches(data, 0, glyphs.glyph(i)) {
+            return None;
+        }
+        let mut positions = vec![i];
+        let mut j = i;
+        for k in 1..self.input_count {
+            j = glyphs.next(j)?;
+            if !self.input.matches(data, k - 1 + first, glyphs.glyph(j)) {
+                return None;
+            }
+            positions.push(j);
+        }
+        let mut j = i;
+        for k in 0..self.backtrack_count {
+            j = glyphs.prev(j)?;
+            if !self.backtrack.matches(data, k, glyphs.glyph(j)) {
+                return None;
+            }
+        }
+        let mut j = positions[positions.len() - 1];
+        for k in 0..self.lookahead_count {
+            j = glyphs.next(j)?;
+            if !self.lookahead.matches(data, k, glyphs.glyph(j)) {
+                return None;
+            }
+        }
+        Some(positions)
+    }
+}
@@ -0,0 +1,1021 @@
+//! Loading of TrueType and OpenType fonts.
+//!
+//! `Font` parses one face of a TTF or OTF file, or of a TrueType
+//! collection, and gives access to what text rendering needs: the
+//! character map, horizontal metrics and glyph outlines. Outlines are
+//! read from the `glyf` table of TrueType fonts or from the `CFF ` table
+//! of OpenType fonts with PostScript outlines.
+//!
+//! `Font::shape` turns a run of text into positioned glyphs with the GSUB
+//! and GPOS tables, for scripts and fonts which need more than one glyph
+//! per character.
+//!
+//! All values are in font units, with y pointing up. Use
+//! `Font::scale_for_em` to convert them to pixels.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+mod arabic;
+mod cff;
+mod cmap;
+mod glyf;
+mod gpos;
+mod gsub;
+mod indic;
+mod kern;
+mod layout;
+mod raster;
+mod script;
+mod shape;
+
+pub use self::script::{is_default_ignorable, is_mark, Script};
+pub use self::shape::ShapedGlyph;
+
+/// Errors from loading a font.
+#[derive(Debug)]
+pub enum FontError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The data is not a TrueType or OpenType font.
+    UnknownFormat,
+    /// The collection has no face with the requested index.
+    FaceIndex(usize),
+    /// A table every font needs is missing.
+    MissingTable(&'static str),
+    /// The font uses a feature which is not supported.
+    Unsupported(&'static str),
+    /// The data is truncated or otherwise invalid.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FontError::Io(ref err) => write!(f, "{}", err),
+            FontError::UnknownFormat => write!(f, "unknown font format"),
+            FontError::FaceIndex(index) => write!(f, "font has no face {}", index),
+            FontError::MissingTable(tag) => write!(f, "font has no {} table", tag),
+            FontError::Unsupported(what) => write!(f, "unsupported font: {}", what),
+            FontError::Corrupt(what) => write!(f, "corrupt font: {}", what),
+        }
+    }
+}
+
+impl Error for FontError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FontError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(err: io::Error) -> Self {
+        FontError::Io(err)
+    }
+}
+
+/// The bytes of a font file.
+///
+/// Fonts keep their file in memory, since glyphs are read from it on
+/// demand. Passing an `Arc` lets several contexts share one copy.
+#[derive(Debug, Clone)]
+pub enum FontData {
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl Deref for FontData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            FontData::Owned(ref data) => data,
+            FontData::Shared(ref data) => data,
+        }
+    }
+}
+
+impl From<Vec<u8>> for FontData {
+    fn from(data: Vec<u8>) -> Self {
+        FontData::Owned(data)
+    }
+}
+
+impl From<Arc<[u8]>> for FontData {
+    fn from(data: Arc<[u8]>) -> Self {
+        FontData::Shared(data)
+    }
+}
+
+/// One step of a glyph outline, in font units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineCommand {
+    /// Starts a new contour.
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// Quadratic bezier with one control point.
+    QuadTo(f32, f32, f32, f32),
+    /// Cubic bezier with two control points.
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    /// Closes the current contour.
+    Close,
+}
+
+/// A line drawn along text, such as an underline, in font units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the baseline to the top of the line, negative below
+    /// the baseline.
+    pub position:  i16,
+    pub thickness: i16,
+}
+
+/// Big endian reads which fail on truncated data.
+pub(crate) fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).cloned()
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let b = data.get(offset..offset.checked_add(2)?)?;
+    Some((b[0] as u16) << 8 | b[1] as u16)
+}
+
+pub(crate) fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|v| v as i16)
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let b = data.get(offset..offset.checked_add(4)?)?;
+    Some((b[0] as u32) << 24 | (b[1] as u32) << 16 | (b[2] as u32) << 8 | b[3] as u32)
+}
+
+/// Byte range of a table in the file.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Table {
+    pub offset: usize,
+    pub len:    usize,
+}
+
+impl Table {
+    pub fn data<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.offset..self.offset + self.len]
+    }
+}
+
+/// Returns the number of faces in a font file, 1 unless it is a
+/// TrueType collection, 0 if it is not a font.
+pub fn face_count(data: &[u8]) -> usize {
+    match read_u32(data, 0) {
+        Some(0x7474_6366) => read_u32(data, 8).unwrap_or(0) as usize,
+        Some(tag) if is_sfnt(tag) => 1,
+        _ => 0,
+    }
+}
+
+fn is_sfnt(tag: u32) -> bool {
+    // TrueType 1.0, 'true', 'OTTO' and the old Apple 'typ1'.
+    tag == 0x0001_0000 || tag == 0x7472_7565 || tag == 0x4f54_544f || tag == 0x7479_7031
+}
+
+/// One face of a font file.
+#[derive(Debug, Clone)]
+pub struct Font {
+    data:          FontData,
+    num_glyphs:    u16,
+    units_per_em:  u16,
+    ascender:      i16,
+    descender:     i16,
+    line_gap:      i16,
+    num_hmetrics:  u16,
+    hmtx:          Table,
+    cmap:          Option<cmap::Subtable>,
+    outlines:      Outlines,
+    kern:          Option<Table>,
+    /// Subtables of the GPOS pair adjustment lookups used for kerning.
+    pair_lookups:  Vec<Vec<usize>>,
+    gsub:          Option<Table>,
+    gpos:          Option<Table>,
+    gdef:          layout::Gdef,
+    underline:     Option<LineMetrics>,
+    strikeout:     Option<LineMetrics>,
+}
+
+#[derive(Debug, Clone)]
+enum Outlines {
+    Glyf{ glyf: Table, loca: Table, long_offsets: bool },
+    Cff(cff::Cff),
+}
+
+impl Font {
+    /// Parses face `index` of a font file. The index is 0 for files which
+    /// are not collections.
+    pub fn new<D: Into<FontData>>(data: D, index: usize) -> Result<Font, FontError> {
+        let data = data.into();
+        let offset = {
+            let bytes: &[u8] = &data;
+            let tag = read_u32(bytes, 0).ok_or(FontError::UnknownFormat)?;
+            if tag == 0x7474_6366 {
+                if index >= face_count(bytes) {
+                    return Err(FontError::FaceIndex(index));
+                }
+                read_u32(bytes, 12 + index * 4).ok_or(FontError::Corrupt("truncated collection header"))? as usize
+            } else if is_sfnt(tag) {
+                if index != 0 {
+                    return Err(FontError::FaceIndex(index));
+                }
+                0
+            } else {
+                return Err(FontError::UnknownFormat);
+            }
+        };
+        Font::parse(data, offset)
+    }
+
+    /// Reads and parses face `index` of a font file.
+    pub fn load<P: AsRef<Path>>(path: P, index: usize) -> Result<Font, FontError> {
+        Font::new(fs::read(path)?, index)
+    }
+
+    fn parse(data: FontData, offset: usize) -> Result<Font, FontError> {
+        let bytes: &[u8] = &data;
+        let tag = read_u32(bytes, offset).ok_or(FontError::Corrupt("truncated header"))?;
+        if !is_sfnt(tag) {
+            return Err(FontError::UnknownFormat);
+        }
+        let num_tables = read_u16(bytes, offset + 4).ok_or(FontError::Corrupt("truncated header"))? as usize;
+        let find = |name: &[u8; 4]| -> Result<Option<Table>, FontError> {
+            let want = (name[0] as u32) << 24 | (name[1] as u32) << 16 | (name[2] as u32) << 8 | name[3] as u32;
+            for i in 0..num_tables {
+                let record = offset + 12 + i * 16;
+                let tag = read_u32(bytes, record).ok_or(FontError::Corrupt("truncated table directory"))?;
+                if tag == want {
+                    let start = read_u32(bytes, record + 8).unwrap_or(0) as usize;
+                    let len = read_u32(bytes, record + 12).unwrap_or(0) as usize;
+                    if start.checked_add(len).is_none_or(|end| end > bytes.len()) {
+                        return Err(FontError::Corrupt("table out of bounds"));
+                    }
+                    return Ok(Some(Table{ offset: start, len: len }));
+                }
+            }
+            Ok(None)
+        };
+        let head = find(b"head")?.ok_or(FontError::MissingTable("head"))?;
+        let hhea = find(b"hhea")?.ok_or(FontError::MissingTable("hhea"))?;
+        let hmtx = find(b"hmtx")?.ok_or(FontError::MissingTable("hmtx"))?;
+        let maxp = find(b"maxp")?.ok_or(FontError::MissingTable("maxp"))?;
+        let cmap = find(b"cmap")?.ok_or(FontError::MissingTable("cmap"))?;
+
+        let truncated = FontError::Corrupt("truncated table");
+        let head_data = head.data(bytes);
+        let units_per_em = read_u16(head_data, 18).ok_or(FontError::Corrupt("truncated head table"))?;
+        let long_offsets = read_i16(head_data, 50).ok_or(FontError::Corrupt("truncated head table"))? != 0;
+        let hhea_data = hhea.data(bytes);
+        if hhea_data.len() < 36 {
+            return Err(truncated);
+        }
+        let ascender = read_i16(hhea_data, 4).unwrap_or(0);
+        let descender = read_i16(hhea_data, 6).unwrap_or(0);
+        let line_gap = read_i16(hhea_data, 8).unwrap_or(0);
+        let num_hmetrics = read_u16(hhea_data, 34).unwrap_or(0);
+        let num_glyphs = read_u16(maxp.data(bytes), 4).ok_or(FontError::Corrupt("truncated maxp table"))?;
+        if units_per_em == 0 {
+            return Err(FontError::Corrupt("zero units per em"));
+        }
+        if num_hmetrics == 0 || hmtx.len < num_hmetrics as usize * 4 {
+            return Err(FontError::Corrupt("truncated hmtx table"));
+        }
+
+        let outlines = match (find(b"glyf")?, find(b"loca")?, find(b"CFF ")?) {
+            (Some(glyf), Some(loca), _) => {
+                let entry = if long_offsets { 4 } else { 2 };
+                if loca.len < (num_glyphs as usize + 1) * entry {
+                    return Err(FontError::Corrupt("truncated loca table"));
+                }
+                Outlines::Glyf{ glyf: glyf, loca: loca, long_offsets: long_offsets }
+            }
+            (_, _, Some(table)) => Outlines::Cff(cff::Cff::parse(bytes, table.offset)?),
+            _ => {
+                if find(b"CFF2")?.is_some() {
+                    return Err(FontError::Unsupported("CFF2 outlines"));
+                }
+                return Err(FontError::MissingTable("glyf"));
+            }
+        };
+
+        let gpos = find(b"GPOS")?;
+        let pair_lookups = match gpos {
+            Some(gpos) => kern::pair_lookups(bytes, gpos),
+            None => Vec::new(),
+        };
+        let gdef = match find(b"GDEF")? {
+            Some(gdef) => layout::Gdef::parse(bytes, gdef.offset),
+            None => layout::Gdef::default(),
+        };
+        // The underline is in the post table, the strikeout in OS/2.
+        let line = |table: Option<Table>, position: usize, thickness: usize| {
+            let data = table?.data(bytes);
+            let line = LineMetrics{
+                position:  read_i16(data, position)?,
+                thickness: read_i16(data, thickness)?,
+            };
+            if line.thickness > 0 { Some(line) } else { None }
+        };
+        let underline = line(find(b"post")?, 8, 10);
+        let strikeout = line(find(b"OS/2")?, 28, 26);
+
+        let font = Font{
+            cmap:          cmap::Subtable::find(bytes, cmap),
+            num_glyphs:    num_glyphs,
+            units_per_em:  units_per_em,
+            ascender:      ascender,
+            descender:     descender,
+            line_gap:      line_gap,
+            num_hmetrics:  num_hmetrics,
+            hmtx:          hmtx,
+            outlines:      outlines,
+            kern:          find(b"kern")?,
+            pair_lookups:  pair_lookups,
+            gsub:          find(b"GSUB")?,
+            gpos:          gpos,
+            gdef:          gdef,
+            underline:     underline,
+            strikeout:     strikeout,
+            data:          data,
+        };
+        if font.cmap.is_none() {
+            return Err(FontError::Unsupported("no Unicode character map"));
+        }
+        Ok(font)
+    }
+
+    /// Returns the bytes of the font file.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the number of glyphs in the face.
+    pub fn num_glyphs(&self) -> u16 {
+        self.num_glyphs
+    }
+
+    /// Returns the size of the em square in font units.
+    pub fn units_per_em(&self) -> u16 {
+        self.units_per_em
+    }
+
+    /// Returns the scale from font units to pixels for a font size in
+    /// pixels per em.
+    pub fn scale_for_em(&self, size: f32) -> f32 {
+        size / self.units_per_em as f32
+    }
+
+    /// Returns the distance from the baseline to the top of the line.
+    pub fn ascender(&self) -> i16 {
+        self.ascender
+    }
+
+    /// Returns the distance from the baseline to the bottom of the line,
+    /// usually negative.
+    pub fn descender(&self) -> i16 {
+        self.descender
+    }
+
+    /// Returns the gap between the bottom of a line and the top of the
+    /// next one.
+    pub fn line_gap(&self) -> i16 {
+        self.line_gap
+    }
+
+    /// Returns the underline the font suggests, `None` if it has no `post`
+    /// table.
+    pub fn underline(&self) -> Option<LineMetrics> {
+        self.underline
+    }
+
+    /// Returns the strikeout the font suggests, `None` if it has no `OS/2`
+    /// table.
+    pub fn strikeout(&self) -> Option<LineMetrics> {
+        self.strikeout
+    }
+
+    /// Returns the glyph of a character, `None` if the font does not have
+    /// one.
+    pub fn glyph_index(&self, c: char) -> Option<u16> {
+        let glyph = self.cmap.as_ref()?.lookup(&self.data, c as u32)?;
+        if glyph == 0 || glyph >= self.num_glyphs {
+            None
+        } else {
+            Some(glyph)
+        }
+    }
+
+    /// Returns the horizontal advance of a glyph.
+    pub fn advance_width(&self, glyph: u16) -> u16 {
+        let data = self.hmtx.data(&self.data);
+        let last = self.num_hmetrics as usize - 1;
+        read_u16(data, glyph.min(last as u16) as usize * 4).unwrap_or(0)
+    }
+
+    /// Returns the distance from the origin to the left edge of a glyph.
+    pub fn left_side_bearing(&self, glyph: u16) -> i16 {
+        let data = self.hmtx.data(&self.data);
+        let n = self.num_hmetrics as usize;
+        let glyph = glyph as usize;
+        if glyph < n {
+            read_i16(data, glyph * 4 + 2).unwrap_or(0)
+        } else {
+            read_i16(data, n * 4 + (glyph - n) * 2).unwrap_or(0)
+        }
+    }
+
+    /// Returns the adjustment of the advance of `left` when it is followed
+    /// by `right`. Kerning comes from the `kern` feature of the GPOS table,
+    /// or from the `kern` table in fonts without one.
+    pub fn kerning(&self, left: u16, right: u16) -> i16 {
+        if self.pair_lookups.is_empty() {
+            return match self.kern {
+                Some(kern) => kern::kern_pair(&self.data, kern, left, right),
+                None => 0,
+            };
+        }
+        self.pair_lookups.iter()
+            .filter_map(|subtables| kern::gpos_pair(&self.data, subtables, left, right))
+            .fold(0i16, |sum, v| sum.wrapping_add(v))
+    }
+
+    /// Appends the outline of a glyph to `out`. Returns false if the glyph
+    /// has no outline, like a space.
+    pub fn outline(&self, glyph: u16, out: &mut Vec<OutlineCommand>) -> bool {
+        if glyph >= self.num_glyphs {
+            return false;
+        }
+        let start = out.len();
+        let ok = match self.outlines {
+            Outlines::Glyf{ glyf, loca, long_offsets } => {
+                glyf::outline(&self.data, glyf, loca, long_offsets, glyph, out)
+            }
+            Outlines::Cff(ref cff) => cff.outline(&self.data, glyph, out),
+        };
+        if !ok {
+            out.truncate(start);
+        }
+        ok && out.len() > start
+    }
+
+    /// Returns the bounds of a glyph outline as [xmin, ymin, xmax, ymax],
+    /// `None` for glyphs without an outline.
+    pub fn glyph_bounds(&self, glyph: u16) -> Option<[f32; 4]> {
+        if let Outlines::Glyf{ glyf, loca, long_offsets } = self.outlines {
+            return glyf::bounds(&self.data, glyf, loca, long_offsets, glyph);
+        }
+        let mut outline = Vec::new();
+        if !self.outline(glyph, &mut outline) {
+            return None;
+        }
+        let mut b = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        let mut add = |x: f32, y: f32| {
+            b[0] = b[0].min(x);
+            b[1] = b[1].min(y);
+            b[2] = b[2].max(x);
+            b[3] = b[3].max(y);
+        };
+        for cmd in &outline {
+            match *cmd {
+                OutlineCommand::MoveTo(x, y) | OutlineCommand::LineTo(x, y) => add(x, y),
+                OutlineCommand::QuadTo(cx, cy, x, y) => {
+                    add(cx, cy);
+                    add(x, y);
+                }
+                OutlineCommand::CubicTo(c1x, c1y, c2x, c2y, x, y) => {
+                    add(c1x, c1y);
+                    add(c2x, c2y);
+                    add(x, y);
+                }
+                OutlineCommand::Close => {}
+            }
+        }
+        Some(b)
+    }
+
+    /// Returns the scale from font units to pixels for a font size given
+    /// as the distance from the descender to the ascender, which is how
+    /// NanoVG measures font sizes.
+    pub fn scale_for_pixel_height(&self, size: f32) -> f32 {
+        let height = self.ascender as f32 - self.descender as f32;
+        if height > 0.0 {
+            size / height
+        } else {
+            self.scale_for_em(size)
+        }
+    }
+
+    /// Returns the pixels covered by a glyph drawn at `scale` as
+    /// [x0, y0, x1, y1], relative to the pen position with y pointing down.
+    /// Glyphs without an outline have an empty box.
+    pub fn glyph_bitmap_box(&self, glyph: u16, scale: f32) -> [i32; 4] {
+        match self.glyph_bounds(glyph) {
+            Some(b) => [
+                (b[0] * scale).floor() as i32,
+                (-b[3] * scale).floor() as i32,
+                (b[2] * scale).ceil() as i32,
+                (-b[1] * scale).ceil() as i32,
+            ],
+            None => [0; 4],
+        }
+    }
+
+    /// Rasterizes a glyph at `scale` into `width` by `height` alpha pixels
+    /// at the start of `out`, which has rows of `stride` bytes. The top
+    /// left of the bitmap is the top left of the glyph's bitmap box.
+    pub fn render_glyph_bitmap(&self, glyph: u16, scale: f32, out: &mut [u8], width: usize, height: usize,
+                               stride: usize) {
+        if width == 0 || height == 0 || out.len() < (height - 1) * stride + width {
+            return;
+        }
+        let mut outline = Vec::new();
+        if !self.outline(glyph, &mut outline) {
+            return;
+        }
+        let bbox = self.glyph_bitmap_box(glyph, scale);
+        let (dx, dy) = (bbox[0] as f32, bbox[1] as f32);
+        let pt = |x: f32, y: f32| (x * scale - dx, -y * scale - dy);
+        let mut raster = raster::Rasterizer::new(width, height);
+        let mut start = (0.0, 0.0);
+        let mut last = (0.0, 0.0);
+        for cmd in &outline {
+            match *cmd {
+                OutlineCommand::MoveTo(x, y) => {
+                    // Contours are closed even when the outline does not say so.
+                    raster.line(last.0, last.1, start.0, start.1);
+                    start = pt(x, y);
+                    last = start;
+                }
+                OutlineCommand::LineTo(x, y) => {
+                    let p = pt(x, y);
+                    raster.line(last.0, last.1, p.0, p.1);
+                    last = p;
+                }
+                OutlineCommand::QuadTo(cx, cy, x, y) => {
+                    let (c, p) = (pt(cx, cy), pt(x, y));
+                    raster.quad(last.0, last.1, c.0, c.1, p.0, p.1);
+                    last = p;
+                }
+                OutlineCommand::CubicTo(c1x, c1y, c2x, c2y, x, y) => {
+                    let (c1, c2, p) = (pt(c1x, c1y), pt(c2x, c2y), pt(x, y));
+                    raster.cubic(last.0, last.1, c1.0, c1.1, c2.0, c2.1, p.0, p.1);
+                    last = p;
+                }
+                OutlineCommand::Close => {
+                    raster.line(last.0, last.1, start.0, start.1);
+                    last = start;
+                }
+            }
+        }
+        raster.line(last.0, last.1, start.0, start.1);
+        raster.accumulate(out, stride);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Returns 16 bit values as big endian bytes. Negative values are
+    /// stored as two's complement.
+    pub(crate) fn words<T: Copy + Into<i32>>(values: &[T]) -> Vec<u8> {
+        values.iter().flat_map(|&v| (v.into() as u16).to_be_bytes()).collect()
+    }
+
+    /// Builds a font file out of tables.
+    pub(crate) fn sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = words(&[1, 0, tables.len() as i32, 0, 0, 0]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, table) in tables {
+            data.extend_from_slice(&tag[..]);
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += (table.len() + 3) & !3;
+        }
+        // Tables are padded to 4 bytes, except the last one, so that any
+        // truncation of the file cuts into a table.
+        for (i, (_, table)) in tables.iter().enumerate() {
+            data.extend_from_slice(table);
+            if i + 1 < tables.len() {
+                data.resize((data.len() + 3) & !3, 0);
+            }
+        }
+        data
+    }
+
+    /// Builds a format 4 cmap subtable from segments of (start, end,
+    /// delta, glyphs). Segments with glyphs map through them instead of
+    /// adding the delta to the character.
+    pub(crate) fn cmap_format4(segments: &[(u16, u16, u16, &[u16])]) -> Vec<u8> {
+        let mut segments = segments.to_vec();
+        segments.push((0xffff, 0xffff, 1, &[]));
+        let n = segments.len();
+        let (mut ends, mut starts, mut deltas, mut range_offsets, mut glyphs) = (vec![], vec![], vec![], vec![], vec![]);
+        for (i, &(start, end, delta, ids)) in segments.iter().enumerate() {
+            ends.push(end as i32);
+            starts.push(start as i32);
+            deltas.push(delta as i32);
+            range_offsets.push(if ids.is_empty() { 0 } else { ((n - i) + glyphs.len()) as i32 * 2 });
+            glyphs.extend(ids.iter().map(|&g| g as i32));
+        }
+        let len = 16 + n * 8 + glyphs.len() * 2;
+        [
+            words(&[4, len as i32, 0, n as i32 * 2, 0, 0, 0]),
+            words(&ends),
+            words(&[0]),
+            words(&starts),
+            words(&deltas),
+            words(&range_offsets),
+            words(&glyphs),
+        ].concat()
+    }
+
+    /// Builds a format 12 cmap subtable from groups of (start, end, glyph).
+    pub(crate) fn cmap_format12(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut data = words(&[12, 0]);
+        for &v in &[16 + groups.len() as u32 * 12, 0, groups.len() as u32] {
+            data.extend_from_slice(&v.to_be_bytes());
+        }
+        for &(start, end, glyph) in groups {
+            for &v in &[start, end, glyph] {
+                data.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        data
+    }
+
+    /// Builds a cmap table from subtables of (platform, encoding, data).
+    pub(crate) fn cmap_table(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = words(&[0, subtables.len() as i32]);
+        let mut offset = 4 + subtables.len() * 8;
+        for (platform, encoding, subtable) in subtables {
+            data.extend(words(&[*platform as i32, *encoding as i32]));
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            offset += subtable.len();
+        }
+        for (_, _, subtable) in subtables {
+            data.extend_from_slice(subtable);
+        }
+        data
+    }
+
+    /// Returns the tables every font needs: a 1000 unit em, glyphs 500
+    /// units wide, and a character map from `chars` to glyphs 1, 2, ...
+    pub(crate) fn base_tables(num_glyphs: u16, chars: &str) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[4..10].copy_from_slice(&words(&[800, -200, 0]));
+        hhea[34..36].copy_from_slice(&num_glyphs.to_be_bytes());
+        let maxp = words(&[0, 0x5000, num_glyphs as i32]);
+        let hmtx = words(&(0..num_glyphs).flat_map(|_| vec![500, 0]).collect::<Vec<_>>());
+        let mut segments: Vec<(u16, u16, u16, &[u16])> = chars.chars().enumerate().map(|(i, c)| {
+            (c as u16, c as u16, (i as u16 + 1).wrapping_sub(c as u16), &[][..])
+        }).collect();
+        segments.sort_by_key(|segment| segment.0);
+        let cmap = cmap_table(&[(3, 1, cmap_format4(&segments))]);
+        vec![(b"head", head), (b"hhea", hhea), (b"maxp", maxp), (b"hmtx", hmtx), (b"cmap", cmap)]
+    }
+
+    /// Returns `glyf` and `loca` tables with short offsets holding glyphs.
+    pub(crate) fn glyf_tables(glyphs: &[Vec<u8>]) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let mut glyf = Vec::new();
+        let mut loca = vec![0];
+        for glyph in glyphs {
+            glyf.extend_from_slice(glyph);
+            glyf.resize((glyf.len() + 1) & !1, 0);
+            loca.push(glyf.len() as i32 / 2);
+        }
+        vec![(b"glyf", glyf), (b"loca", words(&loca))]
+    }
+
+    /// Returns a simple glyph with one contour of on curve points.
+    pub(crate) fn square_glyph(x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<u8> {
+        [
+            words(&[1, x0, y0, x1, y1, 3, 0]),
+            vec![1; 4],
+            words(&[x0, x1 - x0, 0, x0 - x1]),
+            words(&[y0, 0, y1 - y0, 0]),
+        ].concat()
+    }
+
+    /// Returns a font file mapping the characters of `chars` to glyphs 1,
+    /// 2, ... which are squares of half an em on the baseline.
+    pub(crate) fn square_font_data(chars: &str) -> Vec<u8> {
+        let mut glyphs = vec![vec![]];
+        glyphs.extend(chars.chars().map(|_| square_glyph(0, 0, 500, 500)));
+        let mut tables = base_tables(glyphs.len() as u16, chars);
+        tables.extend(glyf_tables(&glyphs));
+        sfnt(&tables)
+    }
+
+    /// Returns the font of `square_font_data`.
+    pub(crate) fn square_font(chars: &str) -> Font {
+        Font::new(square_font_data(chars), 0).unwrap()
+    }
+
+    /// Builds an INDEX of a CFF table with two byte offsets.
+    pub(crate) fn cff_index(objects: &[Vec<u8>]) -> Vec<u8> {
+        if objects.is_empty() {
+            return vec![0, 0];
+        }
+        let mut offsets = vec![1];
+        for object in objects {
+            offsets.push(offsets.last().unwrap() + object.len() as i32);
+        }
+        [words(&[objects.len() as i32]), vec![2], words(&offsets), objects.concat()].concat()
+    }
+
+    /// Builds a CFF table out of charstrings and subroutines.
+    pub(crate) fn cff_table(charstrings: &[Vec<u8>], global_subrs: &[Vec<u8>], local_subrs: &[Vec<u8>]) -> Vec<u8> {
+        // DICT operands are all 32 bit, so the top DICT has a fixed size.
+        let int = |v: usize| { let mut b = vec![29]; b.extend_from_slice(&(v as u32).to_be_bytes()); b };
+        let head = [vec![1, 0, 4, 2], cff_index(&[b"T".to_vec()])].concat();
+        let strings = cff_index(&[]);
+        let global_subrs = cff_index(global_subrs);
+        let charstrings = cff_index(charstrings);
+        let charstrings_at = head.len() + cff_index(&[vec![0; 17]]).len() + strings.len() + global_subrs.len();
+        let private_at = charstrings_at + charstrings.len();
+        let private = [int(6), vec![19]].concat();
+        let top = [int(charstrings_at), vec![17], int(private.len()), int(private_at), vec![18]].concat();
+        [head, cff_index(&[top]), strings, global_subrs, charstrings, private, cff_index(local_subrs)].concat()
+    }
+
+    /// Returns `fields` followed by offsets to `children` and the children,
+    /// with the offsets counted from the start of `fields`.
+    pub(crate) fn linked(fields: Vec<u8>, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = fields;
+        let mut offset = data.len() + children.len() * 2;
+        for child in children {
+            data.extend(words(&[offset as i32]));
+            offset += child.len();
+        }
+        [data, children.concat()].concat()
+    }
+
+    /// Appends a coverage table of sorted glyphs to a subtable and stores
+    /// its offset at `at`.
+    fn append_coverage(mut subtable: Vec<u8>, at: usize, glyphs: &[u16]) -> Vec<u8> {
+        let offset = subtable.len() as u16;
+        subtable[at..at + 2].copy_from_slice(&offset.to_be_bytes());
+        subtable.extend(coverage(glyphs));
+        subtable
+    }
+
+    /// Builds a coverage table of sorted glyphs.
+    pub(crate) fn coverage(glyphs: &[u16]) -> Vec<u8> {
+        [words(&[1, glyphs.len() as i32]), words(glyphs)].concat()
+    }
+
+    /// Builds a GSUB or GPOS table whose only script, the default one, has
+    /// features of (tag, lookup indices).
+    pub(crate) fn layout_table(features: &[(&[u8; 4], &[u16])], lookups: &[Vec<u8>]) -> Vec<u8> {
+        let n = features.len() as i32;
+        let scripts = [words(&[1]), b"DFLT".to_vec(), words(&[8, 4, 0, 0, 0xffff, n]),
+                       words(&(0..n).collect::<Vec<_>>())].concat();
+        let mut feature_list = words(&[n]);
+        let mut offset = 2 + features.len() * 6;
+        let tables: Vec<Vec<u8>> = features.iter().map(|&(tag, indices)| {
+            feature_list.extend_from_slice(tag);
+            feature_list.extend(words(&[offset as i32]));
+            let table = [words(&[0, indices.len() as i32]), words(indices)].concat();
+            offset += table.len();
+            table
+        }).collect();
+        feature_list.extend(tables.concat());
+        let lookup_list = linked(words(&[lookups.len() as i32]), lookups);
+        let header = words(&[1, 0, 10, 10 + scripts.len() as i32, 10 + (scripts.len() + feature_list.len()) as i32]);
+        [header, scripts, feature_list, lookup_list].concat()
+    }
+
+    /// Builds a lookup of a GSUB or GPOS table.
+    pub(crate) fn lookup(kind: u16, flags: u16, subtables: &[Vec<u8>]) -> Vec<u8> {
+        linked(words(&[kind, flags, subtables.len() as u16]), subtables)
+    }
+
+    /// Builds a single substitution from pairs of glyphs, sorted by the
+    /// glyph they replace.
+    pub(crate) fn single_subst(pairs: &[(u16, u16)]) -> Vec<u8> {
+        let (from, to): (Vec<u16>, Vec<u16>) = pairs.iter().cloned().unzip();
+        append_coverage([words(&[2, 0, to.len() as i32]), words(&to)].concat(), 2, &from)
+    }
+
+    /// Builds a ligature substitution from ligatures of (components,
+    /// glyph). Ligatures with the same first component are tried in order.
+    pub(crate) fn ligature_subst(ligatures: &[(&[u16], u16)]) -> Vec<u8> {
+        let mut firsts: Vec<u16> = ligatures.iter().map(|&(components, _)| components[0]).collect();
+        firsts.sort();
+        firsts.dedup();
+        let sets: Vec<Vec<u8>> = firsts.iter().map(|&first| {
+            let set: Vec<Vec<u8>> = ligatures.iter().filter(|&&(components, _)| components[0] == first)
+                .map(|&(components, glyph)| [words(&[glyph, components.len() as u16]), words(&components[1..])].concat())
+                .collect();
+            linked(words(&[set.len() as i32]), &set)
+        }).collect();
+        append_coverage(linked(words(&[1, 0, sets.len() as i32]), &sets), 2, &firsts)
+    }
+
+    /// Builds a chained context subtable from coverages of the backtrack
+    /// glyphs, nearest first, the input and the lookahead glyphs, which
+    /// applies lookups given as (input index, lookup index).
+    pub(crate) fn chain_context(backtrack: &[&[u16]], input: &[&[u16]], lookahead: &[&[u16]],
+                                records: &[(u16, u16)]) -> Vec<u8> {
+        let len = 10 + (backtrack.len() + input.len() + lookahead.len()) * 2 + records.len() * 4;
+        let mut head = words(&[3]);
+        let mut coverages = Vec::new();
+        for sequence in &[backtrack, input, lookahead] {
+            head.extend(words(&[sequence.len() as i32]));
+            for glyphs in sequence.iter() {
+                head.extend(words(&[(len + coverages.len()) as i32]));
+                coverages.extend(coverage(glyphs));
+            }
+        }
+        head.extend(words(&[records.len() as i32]));
+        for &(index, lookup) in records {
+            head.extend(words(&[index, lookup]));
+        }
+        [head, coverages].concat()
+    }
+
+    /// Builds a pair adjustment from (first, second, x advance of the
+    /// first), sorted by the glyphs.
+    pub(crate) fn pair_pos(pairs: &[(u16, u16, i16)]) -> Vec<u8> {
+        let mut firsts: Vec<u16> = pairs.iter().map(|p| p.0).collect();
+        firsts.dedup();
+        let sets: Vec<Vec<u8>> = firsts.iter().map(|&first| {
+            let records: Vec<(u16, i16)> = pairs.iter().filter(|p| p.0 == first).map(|p| (p.1, p.2)).collect();
+            let mut set = words(&[records.len() as i32]);
+            for &(second, advance) in &records {
+                set.extend(words(&[second as i32, advance as i32]));
+            }
+            set
+        }).collect();
+        append_coverage(linked(words(&[1, 0, 4, 0, sets.len() as i32]), &sets), 2, &firsts)
+    }
+
+    /// Builds a mark to base attachment with one mark class from the
+    /// anchors of marks and of bases, sorted by glyph.
+    pub(crate) fn mark_base_pos(marks: &[(u16, (i16, i16))], bases: &[(u16, (i16, i16))]) -> Vec<u8> {
+        let anchor = |&(_, (x, y)): &(u16, (i16, i16))| words(&[1, x as i32, y as i32]);
+        let mut mark_array = words(&[marks.len() as i32]);
+        for k in 0..marks.len() {
+            mark_array.extend(words(&[0, (2 + marks.len() * 4 + k * 6) as i32]));
+        }
+        mark_array.extend(marks.iter().flat_map(anchor));
+        let base_array = linked(words(&[bases.len() as i32]), &bases.iter().map(anchor).collect::<Vec<_>>());
+        let mark_glyphs: Vec<u16> = marks.iter().map(|m| m.0).collect();
+        let base_glyphs: Vec<u16> = bases.iter().map(|b| b.0).collect();
+        let at = 12 + mark_array.len() as i32;
+        let subtable = [words(&[1, 0, 0, 1, 12, at]), mark_array, base_array].concat();
+        let subtable = append_coverage(subtable, 2, &mark_glyphs);
+        append_coverage(subtable, 4, &base_glyphs)
+    }
+
+    /// Returns a font without outlines which maps the characters of `chars`
+    /// to glyphs 1, 2, ... of 64 glyphs, with more tables.
+    pub(crate) fn layout_font(chars: &str, tables: Vec<(&'static [u8; 4], Vec<u8>)>) -> Font {
+        let mut all = base_tables(64, chars);
+        all.extend(glyf_tables(&vec![vec![]; 64]));
+        all.extend(tables);
+        Font::new(sfnt(&all), 0).unwrap()
+    }
+
+    /// Shapes a run of text and returns the glyphs in logical order.
+    pub(crate) fn shaped(font: &Font, text: &str, script: Script, rtl: bool) -> Vec<ShapedGlyph> {
+        let mut out = Vec::new();
+        font.shape(text, script, rtl, &mut out);
+        if rtl {
+            out.reverse();
+        }
+        out
+    }
+
+    /// Returns the glyph indices of a shaped run in logical order.
+    pub(crate) fn shaped_glyphs(font: &Font, text: &str, script: Script, rtl: bool) -> Vec<u16> {
+        shaped(font, text, script, rtl).iter().map(|g| g.glyph).collect()
+    }
+
+    /// Returns a font with glyph outlines for "ab", glyph 3 being a
+    /// composite of the other two.
+    fn glyf_font() -> Vec<u8> {
+        let composite = [words(&[-1, 0, 0, 150, 200, 0x23, 1, 0, 0]), words(&[0x02, 2]), vec![100, 0]].concat();
+        let mut tables = base_tables(4, "ab");
+        tables.extend(glyf_tables(&[vec![], square_glyph(0, 0, 100, 100), square_glyph(0, 0, 50, 200), composite]));
+        sfnt(&tables)
+    }
+
+    /// Returns a font with CFF outlines for "ab".
+    fn cff_font() -> Vec<u8> {
+        let square = vec![139 + 10, 139 + 20, 21, 239, 6, 239, 7, 39, 6, 14];
+        let subrs = vec![139, 139, 21, 32, 10, 14];
+        let mut tables = base_tables(3, "ab");
+        tables.push((b"CFF ", cff_table(&[vec![14], square, subrs], &[], &[vec![239, 139, 5, 11]])));
+        sfnt(&tables)
+    }
+
+    /// Exercises every accessor of a font which may be corrupt. Nothing
+    /// may panic, whatever the data.
+    fn exercise(font: &Font) {
+        for c in "ab\u{0}\u{ffff}\u{10ffff}".chars() {
+            let _ = font.glyph_index(c);
+        }
+        let mut pixels = [0; 64];
+        for glyph in 0..font.num_glyphs().saturating_add(2) {
+            let _ = font.advance_width(glyph);
+            let _ = font.left_side_bearing(glyph);
+            let _ = font.kerning(glyph, 1);
+            let _ = font.outline(glyph, &mut Vec::new());
+            let _ = font.glyph_bitmap_box(glyph, 0.01);
+            font.render_glyph_bitmap(glyph, 0.01, &mut pixels, 8, 8, 8);
+        }
+        let mut out = Vec::new();
+        font.shape("ab", Script::Latin, false, &mut out);
+    }
+
+    /// Parses `data` cut at every length and with every byte replaced by
+    /// a few extreme values. Cut fonts must be rejected, and corrupt ones
+    /// must either be rejected or fail glyph by glyph without panicking.
+    fn check_malformed(data: &[u8]) {
+        for len in 0..data.len() {
+            assert!(Font::new(data[..len].to_vec(), 0).is_err(), "accepted {} of {} bytes", len, data.len());
+        }
+        let mut copy = data.to_vec();
+        for i in 0..data.len() {
+            for &byte in &[0x00, 0x01, 0x0f, 0x10, 0x7f, 0x80, 0xfe, 0xff] {
+                copy[i] = byte;
+                if let Ok(font) = Font::new(copy.clone(), 0) {
+                    exercise(&font);
+                }
+            }
+            copy[i] = data[i];
+        }
+    }
+
+    #[test]
+    fn parses_test_fonts() {
+        let font = Font::new(glyf_font(), 0).unwrap();
+        assert_eq!((font.num_glyphs(), font.units_per_em()), (4, 1000));
+        assert_eq!((font.ascender(), font.descender(), font.line_gap()), (800, -200, 0));
+        assert_eq!((font.glyph_index('a'), font.glyph_index('b'), font.glyph_index('c')), (Some(1), Some(2), None));
+        assert_eq!(font.advance_width(3), 500);
+        assert_eq!(font.glyph_bounds(3), Some([0.0, 0.0, 150.0, 200.0]));
+
+        let font = Font::new(cff_font(), 0).unwrap();
+        let mut outline = Vec::new();
+        assert!(font.outline(1, &mut outline));
+        assert_eq!(font.glyph_bounds(1), Some([10.0, 20.0, 110.0, 120.0]));
+        assert_eq!(font.glyph_bounds(2), Some([0.0, 0.0, 100.0, 0.0]));
+    }
+
+    #[test]
+    fn rejects_truncated_tables() {
+        let expect = |tables: &[(&[u8; 4], Vec<u8>)], what: &str| {
+            match Font::new(sfnt(tables), 0) {
+                Err(err) => assert_eq!(err.to_string(), what),
+                Ok(_) => panic!("accepted a font without {}", what),
+            }
+        };
+        let tables = {
+            let mut tables = base_tables(2, "a");
+            tables.extend(glyf_tables(&[vec![], square_glyph(0, 0, 1, 1)]));
+            tables
+        };
+        let with = |tag: &[u8; 4], data: Vec<u8>| -> Vec<(&[u8; 4], Vec<u8>)> {
+            tables.iter().map(|&(t, ref d)| (t, if t == tag { data.clone() } else { d.clone() })).collect()
+        };
+        expect(&with(b"head", vec![0; 50]), "corrupt font: truncated head table");
+        expect(&with(b"hhea", vec![0; 35]), "corrupt font: truncated table");
+        expect(&with(b"maxp", vec![0; 5]), "corrupt font: truncated maxp table");
+        expect(&with(b"hmtx", vec![0; 7]), "corrupt font: truncated hmtx table");
+        expect(&with(b"loca", vec![0; 4]), "corrupt font: truncated loca table");
+        expect(&tables[1..], "font has no head table");
+
+        let mut tables = base_tables(1, "");
+        tables.push((b"CFF ", cff_table(&[vec![14]], &[], &[])[..30].to_vec()));
+        expect(&tables, "corrupt font: invalid CFF table");
+
+        // Table records must stay inside the file.
+        let mut data = sfnt(&base_tables(1, ""));
+        data[12 + 12..12 + 16].copy_from_slice(&0x1000u32.to_be_bytes());
+        assert!(Font::new(data, 0).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_fonts() {
+        check_malformed(&glyf_font());
+        check_malformed(&cff_font());
+    }
+}
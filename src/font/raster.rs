@@ -0,0 +1,137 @@
+//! Rasterization of glyph outlines into coverage bitmaps.
+//!
+//! Every line adds the signed area it covers to the pixels it crosses,
+//! and a running sum along each row turns the areas into coverage. This
+//! gives exact anti-aliasing for the non-zero rule as long as contours do
+//! not overlap themselves, which holds for glyph outlines.
+
+/// Curves are flattened until they are this close to the true curve, in
+/// pixels.
+const TOLERANCE: f32 = 0.1;
+
+/// Accumulates lines into a bitmap of `width` by `height` pixels, with y
+/// pointing down.
+pub struct Rasterizer {
+    width:  usize,
+    height: usize,
+    /// Signed area per pixel, with two extra cells per row for lines on the
+    /// right edge.
+    acc:    Vec<f32>,
+}
+
+impl Rasterizer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Rasterizer{
+            width:  width,
+            height: height,
+            acc:    vec![0.0; (width + 2) * height],
+        }
+    }
+
+    pub fn line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) {
+        if y0 == y1 || !(x0.is_finite() && y0.is_finite() && x1.is_finite() && y1.is_finite()) {
+            return;
+        }
+        let (dir, x0, y0, x1, y1) = if y0 < y1 { (1.0, x0, y0, x1, y1) } else { (-1.0, x1, y1, x0, y0) };
+        let dxdy = (x1 - x0) / (y1 - y0);
+        let stride = self.width + 2;
+        let max_x = self.width as f32;
+        let mut x = x0;
+        let ystart = if y0 < 0.0 {
+            x -= y0 * dxdy;
+            0
+        } else {
+            y0 as usize
+        };
+        let yend = (y1.ceil().max(0.0) as usize).min(self.height);
+        for y in ystart..yend {
+            let row = y * stride;
+            let dy = ((y + 1) as f32).min(y1) - (y as f32).max(y0);
+            let xnext = x + dxdy * dy;
+            let d = dy * dir;
+            let (xa, xb) = if x < xnext { (x, xnext) } else { (xnext, x) };
+            let xa = xa.max(0.0).min(max_x);
+            let xb = xb.max(0.0).min(max_x);
+            let xa_floor = xa.floor();
+            let xai = xa_floor as usize;
+            let xb_ceil = xb.ceil();
+            let xbi = xb_ceil as usize;
+            if xbi <= xai + 1 {
+                // The line stays within one pixel column on this row.
+                let xmf = 0.5 * (xa + xb) - xa_floor;
+                self.acc[row + xai] += d - d * xmf;
+                self.acc[row + xai + 1] += d * xmf;
+            } else {
+                let s = 1.0 / (xb - xa);
+                let xaf = xa - xa_floor;
+                let a0 = 0.5 * s * (1.0 - xaf) * (1.0 - xaf);
+                let xbf = xb - xb_ceil + 1.0;
+                let am = 0.5 * s * xbf * xbf;
+                self.acc[row + xai] += d * a0;
+                if xbi == xai + 2 {
+                    self.acc[row + xai + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - xaf);
+                    self.acc[row + xai + 1] += d * (a1 - a0);
+                    for xi in xai + 2..xbi - 1 {
+                        self.acc[row + xi] += d * s;
+                    }
+                    let a2 = a1 + (xbi - xai - 3) as f32 * s;
+                    self.acc[row + xbi - 1] += d * (1.0 - a2 - am);
+                }
+                self.acc[row + xbi] += d * am;
+            }
+            x = xnext;
+        }
+    }
+
+    pub fn quad(&mut self, x0: f32, y0: f32, cx: f32, cy: f32, x1: f32, y1: f32) {
+        let ddx = x0 - 2.0 * cx + x1;
+        let ddy = y0 - 2.0 * cy + y1;
+        // The flattening error of n segments is |dd| / (8 n^2).
+        let dd = (ddx * ddx + ddy * ddy).sqrt();
+        let n = (1 + (dd / (8.0 * TOLERANCE)).sqrt() as usize).min(64);
+        let (mut px, mut py) = (x0, y0);
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let mt = 1.0 - t;
+            let x = mt * mt * x0 + 2.0 * mt * t * cx + t * t * x1;
+            let y = mt * mt * y0 + 2.0 * mt * t * cy + t * t * y1;
+            self.line(px, py, x, y);
+            px = x;
+            py = y;
+        }
+    }
+
+    pub fn cubic(&mut self, x0: f32, y0: f32, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x1: f32, y1: f32) {
+        let dd1 = (x0 - 2.0 * c1x + c2x).hypot(y0 - 2.0 * c1y + c2y);
+        let dd2 = (c1x - 2.0 * c2x + x1).hypot(c1y - 2.0 * c2y + y1);
+        // The second derivative is at most six times the larger difference.
+        let dd = 6.0 * dd1.max(dd2);
+        let n = (1 + (dd / (8.0 * TOLERANCE)).sqrt() as usize).min(64);
+        let (mut px, mut py) = (x0, y0);
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let mt = 1.0 - t;
+            let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            let x = a * x0 + b * c1x + c * c2x + d * x1;
+            let y = a * y0 + b * c1y + c * c2y + d * y1;
+            self.line(px, py, x, y);
+            px = x;
+            py = y;
+        }
+    }
+
+    /// Writes the coverage as 8-bit alpha to rows of `stride` bytes.
+    pub fn accumulate(&self, out: &mut [u8], stride: usize) {
+        let acc_stride = self.width + 2;
+        for y in 0..self.height {
+            let mut sum = 0.0f32;
+            let row = &mut out[y * stride..y * stride + self.width];
+            for (x, alpha) in row.iter_mut().enumerate() {
+                sum += self.acc[y * acc_stride + x];
+                *alpha = (sum.abs().min(1.0) * 255.0 + 0.5) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Scripts of characters, as far as shaping needs them.
+
+/// The writing system of a run of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    /// Punctuation, digits and symbols shared by all scripts.
+    Common,
+    /// Combining marks, which take the script of the character they follow.
+    Inherited,
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Syriac,
+    Thaana,
+    Nko,
+    Devanagari,
+    Bengali,
+    Gurmukhi,
+    Gujarati,
+    Oriya,
+    Tamil,
+    Telugu,
+    Kannada,
+    Malayalam,
+    Thai,
+    Lao,
+    Georgian,
+    Hangul,
+    Kana,
+    Han,
+    /// Scripts without special handling.
+    Other,
+}
+
+impl Script {
+    /// Returns the script of a character.
+    pub fn of(c: char) -> Script {
+        let c = c as u32;
+        match c {
+            0x41..=0x5a | 0x61..=0x7a | 0xaa | 0xba | 0xc0..=0xd6 | 0xd8..=0xf6 | 0xf8..=0x2af |
+            0x1d00..=0x1d7f | 0x1e00..=0x1eff | 0x2c60..=0x2c7f | 0xa720..=0xa7ff | 0xfb00..=0xfb06 |
+            0xff21..=0xff3a | 0xff41..=0xff5a => Script::Latin,
+            0x300..=0x36f | 0x485..=0x486 | 0x64b..=0x655 | 0x670 | 0x1ab0..=0x1aff | 0x1dc0..=0x1dff |
+            0x200c..=0x200d | 0x20d0..=0x20ff | 0xfe00..=0xfe0f | 0xfe20..=0xfe2f => Script::Inherited,
+            0x374 | 0x37e | 0x385 | 0x387 => Script::Common,
+            0x370..=0x3ff | 0x1f00..=0x1fff => Script::Greek,
+            0x400..=0x52f | 0x1c80..=0x1c8f | 0x2de0..=0x2dff | 0xa640..=0xa69f => Script::Cyrillic,
+            0x531..=0x58f | 0xfb13..=0xfb17 => Script::Armenian,
+            0x591..=0x5ff | 0xfb1d..=0xfb4f => Script::Hebrew,
+            0x60c | 0x61b | 0x61f | 0x640 => Script::Common,
+            0x600..=0x6ff | 0x750..=0x77f | 0x8a0..=0x8ff | 0xfb50..=0xfdff | 0xfe70..=0xfeff => Script::Arabic,
+            0x700..=0x74f | 0x860..=0x86f => Script::Syriac,
+            0x780..=0x7bf => Script::Thaana,
+            0x7c0..=0x7ff => Script::Nko,
+            0x964..=0x965 => Script::Common,
+            0x900..=0x97f | 0xa8e0..=0xa8ff => Script::Devanagari,
+            0x980..=0x9ff => Script::Bengali,
+            0xa00..=0xa7f => Script::Gurmukhi,
+            0xa80..=0xaff => Script::Gujarati,
+            0xb00..=0xb7f => Script::Oriya,
+            0xb80..=0xbff => Script::Tamil,
+            0xc00..=0xc7f => Script::Telugu,
+            0xc80..=0xcff => Script::Kannada,
+            0xd00..=0xd7f => Script::Malayalam,
+            0xe00..=0xe7f => Script::Thai,
+            0xe80..=0xeff => Script::Lao,
+            0x10a0..=0x10ff | 0x1c90..=0x1cbf | 0x2d00..=0x2d2f => Script::Georgian,
+            0x1100..=0x11ff | 0x3130..=0x318f | 0xa960..=0xa97f | 0xac00..=0xd7ff => Script::Hangul,
+            0x3041..=0x309f | 0x30a1..=0x30fa | 0x30fd..=0x30ff | 0x31f0..=0x31ff | 0xff66..=0xff9d => Script::Kana,
+            0x2e80..=0x2fdf | 0x3005 | 0x3007 | 0x3021..=0x3029 | 0x3038..=0x303b | 0x3400..=0x4dbf |
+            0x4e00..=0x9fff | 0xf900..=0xfaff | 0x20000..=0x3ffff => Script::Han,
+            0x0..=0x2ff | 0x2000..=0x2bff | 0x3000..=0x303f | 0xfe10..=0xfe6f | 0xff00..=0xffef |
+            0x1f000..=0x1faff => Script::Common,
+            _ => Script::Other,
+        }
+    }
+
+    /// Returns true if the script is written from right to left.
+    pub fn is_rtl(&self) -> bool {
+        matches!(*self, Script::Hebrew | Script::Arabic | Script::Syriac | Script::Thaana | Script::Nko)
+    }
+
+    /// Returns the OpenType script tags of the script, preferred first.
+    pub fn tags(&self) -> &'static [&'static [u8; 4]] {
+        match *self {
+            Script::Latin => &[b"latn"],
+            Script::Greek => &[b"grek"],
+            Script::Cyrillic => &[b"cyrl"],
+            Script::Armenian => &[b"armn"],
+            Script::Hebrew => &[b"hebr"],
+            Script::Arabic => &[b"arab"],
+            Script::Syriac => &[b"syrc"],
+            Script::Thaana => &[b"thaa"],
+            Script::Nko => &[b"nko "],
+            Script::Devanagari => &[b"dev2", b"deva"],
+            Script::Bengali => &[b"bng2", b"beng"],
+            Script::Gurmukhi => &[b"gur2", b"guru"],
+            Script::Gujarati => &[b"gjr2", b"gujr"],
+            Script::Oriya => &[b"ory2", b"orya"],
+            Script::Tamil => &[b"tml2", b"taml"],
+            Script::Telugu => &[b"tel2", b"telu"],
+            Script::Kannada => &[b"knd2", b"knda"],
+            Script::Malayalam => &[b"mlm2", b"mlym"],
+            Script::Thai => &[b"thai"],
+            Script::Lao => &[b"lao "],
+            Script::Georgian => &[b"geor"],
+            Script::Hangul => &[b"hang"],
+            Script::Kana => &[b"kana"],
+            Script::Han => &[b"hani"],
+            Script::Common | Script::Inherited | Script::Other => &[],
+        }
+    }
+
+    /// Returns true for the scripts of India, which reorder the
+    /// characters of each syllable.
+    pub fn is_indic(&self) -> bool {
+        matches!(*self, Script::Devanagari | Script::Bengali | Script::Gurmukhi | Script::Gujarati | Script::Oriya |
+                        Script::Tamil | Script::Telugu | Script::Kannada | Script::Malayalam)
+    }
+
+    /// Returns true for the scripts which join their letters like Arabic.
+    pub fn is_joining(&self) -> bool {
+        matches!(*self, Script::Arabic | Script::Syriac | Script::Nko)
+    }
+}
+
+/// Returns true for combining marks, which belong to the cluster of the
+/// character before them.
+pub fn is_mark(c: char) -> bool {
+    let c = c as u32;
+    match c {
+        0x300..=0x36f | 0x483..=0x489 | 0x591..=0x5bd | 0x5bf | 0x5c1..=0x5c2 | 0x5c4..=0x5c5 | 0x5c7 |
+        0x610..=0x61a | 0x64b..=0x65f | 0x670 | 0x6d6..=0x6dc | 0x6df..=0x6e4 | 0x6e7..=0x6e8 |
+        0x6ea..=0x6ed | 0x711 | 0x730..=0x74a | 0x7a6..=0x7b0 | 0x7eb..=0x7f3 | 0x8d3..=0x8e1 |
+        0x8e3..=0x8ff | 0xe31 | 0xe34..=0xe3a | 0xe47..=0xe4e | 0xeb1 | 0xeb4..=0xebc | 0xec8..=0xecd |
+        0x1ab0..=0x1aff | 0x1dc0..=0x1dff | 0x200c..=0x200d | 0x20d0..=0x20ff | 0x302a..=0x302f |
+        0x3099..=0x309a | 0xfe00..=0xfe0f | 0xfe20..=0xfe2f => true,
+        0x900..=0xd7f => {
+            // The Indic blocks share one layout.
+            matches!(c & 0x7f, 0x00..=0x03 | 0x3a..=0x3c | 0x3e..=0x4d | 0x4e..=0x4f | 0x51..=0x57 | 0x62..=0x63)
+        }
+        _ => false,
+    }
+}
+
+/// Returns true for format characters, which are not drawn.
+pub fn is_default_ignorable(c: char) -> bool {
+    let c = c as u32;
+    matches!(c, 0xad | 0x34f | 0x61c | 0x115f..=0x1160 | 0x17b4..=0x17b5 | 0x180b..=0x180e | 0x200b..=0x200f |
+                0x202a..=0x202e | 0x2060..=0x206f | 0x3164 | 0xfe00..=0xfe0f | 0xfeff | 0xffa0 |
+                0x1bca0..=0x1bca3 | 0xe0000..=0xe0fff)
+}
@@ -0,0 +1,471 @@
+//! Shaping of text with the GSUB and GPOS tables.
+//!
+//! Shaping maps the characters of a run of text to glyphs and positions
+//! them. The font's substitutions form ligatures and contextual forms, the
+//! script's shaper picks the joining forms of Arabic letters or reorders
+//! the characters of Indic syllables, and the font's positioning kerns the
+//! glyphs and attaches marks to their bases.
+//!
+//! Every glyph belongs to a cluster, the characters it was made from. A
+//! ligature covers the clusters of all its components, and combining marks
+//! and Indic syllables form one cluster with their base.
+
+use crate::bidi;
+
+use super::layout::{self, Glyphs, Lookup};
+use super::script::{self, Script};
+use super::{arabic, gpos, gsub, indic};
+use super::Font;
+
+/// Masks of the features applied to only some glyphs. All other features
+/// apply to every glyph.
+pub const GLOBAL: u32 = 1 << 0;
+pub const ISOL: u32 = 1 << 1;
+pub const FINA: u32 = 1 << 2;
+pub const MEDI: u32 = 1 << 3;
+pub const INIT: u32 = 1 << 4;
+pub const RPHF: u32 = 1 << 5;
+pub const HALF: u32 = 1 << 6;
+
+/// A glyph placed by `Font::shape`, in font units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph:     u16,
+    /// Byte offset of the first character of the glyph's cluster.
+    pub cluster:   usize,
+    /// How far the pen moves after the glyph.
+    pub x_advance: i32,
+    pub y_advance: i32,
+    /// Offset of the glyph from the pen position, with y pointing up.
+    pub x_offset:  i32,
+    pub y_offset:  i32,
+}
+
+/// A glyph while shaping.
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphInfo {
+    pub codepoint:   char,
+    pub glyph:       u16,
+    pub cluster:     usize,
+    /// Features which apply to the glyph.
+    pub mask:        u32,
+    /// Glyph class from GDEF, or guessed from the character.
+    pub class:       u16,
+    pub mark_class:  u16,
+    /// Ligatures and the marks between their components share an id. A mark
+    /// stores which component it follows, a ligature how many it has.
+    pub lig_id:      u8,
+    pub lig_comp:    u8,
+    pub lig_comps:   u8,
+    /// Whether a substitution replaced the glyph.
+    pub substituted: bool,
+    /// Category and position of the character for the script's shaper.
+    pub category:    u8,
+    pub position:    u8,
+    /// Number of the syllable, counting from 1.
+    pub syllable:    u16,
+}
+
+/// Position of a glyph while shaping.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlyphPos {
+    pub x_advance: i32,
+    pub y_advance: i32,
+    pub x_offset:  i32,
+    pub y_offset:  i32,
+    pub attach:    Option<Attach>,
+}
+
+/// How a glyph is attached to another glyph.
+#[derive(Debug, Clone, Copy)]
+pub enum Attach {
+    /// A mark moved by (dx, dy) from the pen position of its base.
+    Mark{ base: usize, dx: i32, dy: i32 },
+    /// A cursive glyph moved vertically to connect to its parent.
+    Cursive{ parent: usize, dy: i32 },
+}
+
+/// The glyphs of a run of text while it is shaped.
+pub struct Buffer<'a> {
+    pub font:    &'a Font,
+    pub info:    Vec<GlyphInfo>,
+    pub pos:     Vec<GlyphPos>,
+    pub rtl:     bool,
+    next_lig_id: u8,
+}
+
+impl<'a> Buffer<'a> {
+    fn new(font: &'a Font, rtl: bool) -> Self {
+        Buffer{
+            font:        font,
+            info:        Vec::new(),
+            pos:         Vec::new(),
+            rtl:         rtl,
+            next_lig_id: 1,
+        }
+    }
+
+    /// Returns a glyph for a character, which is not substituted yet.
+    pub fn glyph_info(&self, codepoint: char, cluster: usize) -> GlyphInfo {
+        let glyph = self.font.glyph_index(codepoint).unwrap_or(0);
+        let mut info = GlyphInfo{
+            codepoint:   codepoint,
+            glyph:       glyph,
+            cluster:     cluster,
+            mask:        GLOBAL,
+            class:       0,
+            mark_class:  0,
+            lig_id:      0,
+            lig_comp:    0,
+            lig_comps:   0,
+            substituted: false,
+            category:    0,
+            position:    0,
+            syllable:    0,
+        };
+        self.classify(&mut info);
+        info
+    }
+
+    /// Sets the glyph classes of a glyph. Fonts without glyph classes get
+    /// them from the characters.
+    fn classify(&self, info: &mut GlyphInfo) {
+        let data = self.font.data();
+        let gdef = &self.font.gdef;
+        if gdef.has_classes() {
+            info.class = gdef.glyph_class(data, info.glyph);
+        } else if info.lig_comps > 0 {
+            info.class = layout::LIGATURE_GLYPH;
+        } else if script::is_mark(info.codepoint) {
+            info.class = layout::MARK_GLYPH;
+        } else {
+            info.class = layout::BASE_GLYPH;
+        }
+        info.mark_class = gdef.mark_class(data, info.glyph);
+    }
+
+    /// Replaces glyph `i`.
+    pub fn set_glyph(&mut self, i: usize, glyph: u16) {
+        let mut info = self.info[i];
+        info.glyph = glyph;
+        info.substituted = true;
+        self.classify(&mut info);
+        self.info[i] = info;
+    }
+
+    pub fn insert(&mut self, i: usize, info: GlyphInfo) {
+        self.info.insert(i, info);
+        self.pos.insert(i, GlyphPos::default());
+    }
+
+    pub fn remove(&mut self, i: usize) {
+        self.info.remove(i);
+        self.pos.remove(i);
+    }
+
+    pub fn len(&self) -> usize {
+        self.info.len()
+    }
+
+    /// Returns the bytes of the font file.
+    pub fn data(&self) -> &'a [u8] {
+        self.font.data()
+    }
+
+    /// Returns a new id for a ligature.
+    pub fn new_lig_id(&mut self) -> u8 {
+        let id = self.next_lig_id;
+        self.next_lig_id = self.next_lig_id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    /// Gives the glyphs from `start` to `end` one cluster.
+    pub fn merge_clusters(&mut self, start: usize, end: usize) {
+        let end = end.min(self.info.len());
+        if let Some(cluster) = self.info[start..end].iter().map(|info| info.cluster).min() {
+            for info in &mut self.info[start..end] {
+                info.cluster = cluster;
+            }
+        }
+    }
+
+    /// Returns true if a lookup ignores glyph `i`.
+    pub fn skip(&self, i: usize, lookup: &Lookup) -> bool {
+        let info = &self.info[i];
+        let flags = lookup.flags;
+        match info.class {
+            layout::BASE_GLYPH => flags & layout::IGNORE_BASE_GLYPHS != 0,
+            layout::LIGATURE_GLYPH => flags & layout::IGNORE_LIGATURES != 0,
+            layout::MARK_GLYPH => {
+                if flags & layout::IGNORE_MARKS != 0 {
+                    true
+                } else if flags & layout::USE_MARK_FILTERING_SET != 0 {
+                    !self.font.gdef.in_mark_set(self.font.data(), lookup.mark_set, info.glyph)
+                } else {
+                    flags & 0xff00 != 0 && info.mark_class != flags >> 8
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the glyphs as seen by a lookup.
+    pub fn lookup_glyphs<'b>(&'b self, lookup: &'b Lookup) -> LookupGlyphs<'b, 'a> {
+        LookupGlyphs{ buffer: self, lookup: lookup }
+    }
+}
+
+/// The glyphs of a buffer with the glyphs a lookup ignores skipped.
+pub struct LookupGlyphs<'b, 'a: 'b> {
+    buffer: &'b Buffer<'a>,
+    lookup: &'b Lookup,
+}
+
+impl<'b, 'a> Glyphs for LookupGlyphs<'b, 'a> {
+    fn len(&self) -> usize {
+        self.buffer.info.len()
+    }
+
+    fn glyph(&self, i: usize) -> u16 {
+        self.buffer.info[i].glyph
+    }
+
+    fn skip(&self, i: usize) -> bool {
+        self.buffer.skip(i, self.lookup)
+    }
+}
+
+/// Returns the GSUB features of a script, in stages. The lookups of one
+/// stage are applied in lookup list order.
+fn gsub_stages(script: Script, rtl: bool) -> Vec<Vec<(&'static [u8; 4], u32)>> {
+    let direction: &[(&'static [u8; 4], u32)] = if rtl {
+        &[(b"rtla", GLOBAL), (b"rtlm", GLOBAL)]
+    } else {
+        &[(b"ltra", GLOBAL), (b"ltrm", GLOBAL)]
+    };
+    let mut stages = vec![vec![(b"rvrn", GLOBAL)]];
+    let mut common = direction.to_vec();
+    common.extend_from_slice(&[(b"ccmp", GLOBAL), (b"locl", GLOBAL)]);
+    let typographic = vec![(b"rlig", GLOBAL), (b"rclt", GLOBAL), (b"calt", GLOBAL), (b"liga", GLOBAL),
+                           (b"clig", GLOBAL)];
+    if script.is_joining() {
+        stages.push(common);
+        stages.push(vec![(b"isol", ISOL), (b"fina", FINA), (b"fin2", FINA), (b"fin3", FINA), (b"medi", MEDI),
+                         (b"med2", MEDI), (b"init", INIT)]);
+        let mut last = typographic;
+        last.push((b"mset", GLOBAL));
+        stages.push(last);
+    } else if script.is_indic() {
+        common.extend_from_slice(&[(b"nukt", GLOBAL), (b"akhn", GLOBAL), (b"rphf", RPHF), (b"rkrf", GLOBAL),
+                                   (b"pref", GLOBAL), (b"blwf", GLOBAL), (b"abvf", GLOBAL), (b"half", HALF),
+                                   (b"pstf", GLOBAL), (b"vatu", GLOBAL), (b"cjct", GLOBAL)]);
+        stages.push(common);
+        let mut last = vec![(b"init", INIT), (b"pres", GLOBAL), (b"abvs", GLOBAL), (b"blws", GLOBAL),
+                            (b"psts", GLOBAL), (b"haln", GLOBAL)];
+        last.extend(typographic);
+        stages.push(last);
+    } else {
+        stages.push(common);
+        stages.push(typographic);
+    }
+    stages
+}
+
+/// Returns the GPOS features of a script.
+fn gpos_features(script: Script) -> Vec<&'static [u8; 4]> {
+    let mut features: Vec<&'static [u8; 4]> = vec![b"kern", b"mark", b"mkmk"];
+    if script.is_joining() {
+        features.push(b"curs");
+    }
+    if script.is_indic() {
+        features.extend_from_slice(&[b"dist", b"abvm", b"blwm"]);
+    }
+    features
+}
+
+/// Returns the lookups of some features with their masks, in lookup list
+/// order.
+fn collect_lookups(font: &Font, table: usize, script: Script, features: &[(&[u8; 4], u32)]) -> Vec<(u16, u32)> {
+    let mut lookups: Vec<(u16, u32)> = Vec::new();
+    for &(tag, mask) in features {
+        for index in layout::script_lookups(font.data(), table, script.tags(), tag) {
+            lookups.push((index, mask));
+        }
+    }
+    lookups.sort_by_key(|&(index, _)| index);
+    let mut merged: Vec<(u16, u32)> = Vec::with_capacity(lookups.len());
+    for (index, mask) in lookups {
+        match merged.last_mut() {
+            Some(last) if last.0 == index => last.1 |= mask,
+            _ => merged.push((index, mask)),
+        }
+    }
+    merged
+}
+
+impl Font {
+    /// Shapes a run of text written in one script and direction. Appends the
+    /// glyphs to `out` in visual order, that is from right to left for right
+    /// to left runs. Characters the font does not have get glyph 0.
+    pub fn shape(&self, text: &str, script: Script, rtl: bool, out: &mut Vec<ShapedGlyph>) {
+        let mut buffer = Buffer::new(self, rtl);
+        for (offset, c) in text.char_indices() {
+            // Marks belong to the cluster of the character before them.
+            let cluster = match buffer.info.last() {
+                Some(prev) if script::is_mark(c) => prev.cluster,
+                _ => offset,
+            };
+            // Right to left text shows mirrored brackets.
+            let c = if rtl { bidi::mirror(c).unwrap_or(c) } else { c };
+            let info = buffer.glyph_info(c, cluster);
+            buffer.info.push(info);
+            buffer.pos.push(GlyphPos::default());
+        }
+
+        if script.is_joining() {
+            arabic::setup_masks(&mut buffer);
+        }
+        if script.is_indic() {
+            indic::setup(&mut buffer, script);
+        }
+
+        // Substitute
+        if let Some(gsub) = self.gsub {
+            let stages = gsub_stages(script, rtl);
+            for (stage, features) in stages.iter().enumerate() {
+                for (index, mask) in collect_lookups(self, gsub.offset, script, features) {
+                    gsub::apply(&mut buffer, gsub.offset, index, mask);
+                }
+                if script.is_indic() && stage + 2 == stages.len() {
+                    indic::final_reordering(&mut buffer, script);
+                }
+            }
+        }
+
+        // Position
+        for i in 0..buffer.len() {
+            buffer.pos[i].x_advance = self.advance_width(buffer.info[i].glyph) as i32;
+        }
+        match self.gpos {
+            Some(gpos) => {
+                let features: Vec<_> = gpos_features(script).into_iter().map(|tag| (tag, GLOBAL)).collect();
+                for (index, mask) in collect_lookups(self, gpos.offset, script, &features) {
+                    gpos::apply(&mut buffer, gpos.offset, index, mask);
+                }
+            }
+            None => self.apply_kern_table(&mut buffer),
+        }
+
+        // Marks take no space, and format characters are not drawn.
+        let space = self.glyph_index(' ');
+        for i in 0..buffer.len() {
+            let info = buffer.info[i];
+            if info.class == layout::MARK_GLYPH {
+                buffer.pos[i].x_advance = 0;
+                buffer.pos[i].y_advance = 0;
+            }
+            if script::is_default_ignorable(info.codepoint) && !info.substituted {
+                buffer.info[i].glyph = space.unwrap_or(info.glyph);
+                buffer.pos[i] = GlyphPos::default();
+            }
+        }
+
+        resolve_attachments(&mut buffer);
+        let first = out.len();
+        out.extend(buffer.info.iter().zip(&buffer.pos).map(|(info, pos)| ShapedGlyph{
+            glyph:     info.glyph,
+            cluster:   info.cluster,
+            x_advance: pos.x_advance,
+            y_advance: pos.y_advance,
+            x_offset:  pos.x_offset,
+            y_offset:  pos.y_offset,
+        }));
+        if rtl {
+            out[first..].reverse();
+        }
+    }
+
+    /// Kerns the glyphs with the `kern` table, for fonts without GPOS.
+    fn apply_kern_table(&self, buffer: &mut Buffer) {
+        let mut prev: Option<usize> = None;
+        for i in 0..buffer.len() {
+            if buffer.info[i].class == layout::MARK_GLYPH {
+                continue;
+            }
+            if let Some(p) = prev {
+                buffer.pos[p].x_advance += self.kerning(buffer.info[p].glyph, buffer.info[i].glyph) as i32;
+            }
+            prev = Some(i);
+        }
+    }
+}
+
+/// Turns attachments into offsets from the pen position, in visual order.
+fn resolve_attachments(buffer: &mut Buffer) {
+    let n = buffer.len();
+    // Pen position of each glyph when the run is drawn.
+    let mut pen = vec![0i32; n];
+    let mut x = 0;
+    for k in 0..n {
+        let i = if buffer.rtl { n - 1 - k } else { k };
+        pen[i] = x;
+        x += buffer.pos[i].x_advance;
+    }
+    let mut resolved = vec![false; n];
+    for i in 0..n {
+        resolve_attachment(buffer, &pen, &mut resolved, i, 0);
+    }
+}
+
+fn resolve_attachment(buffer: &mut Buffer, pen: &[i32], resolved: &mut [bool], i: usize, depth: usize) {
+    if resolved[i] || depth > 16 {
+        return;
+    }
+    resolved[i] = true;
+    match buffer.pos[i].attach {
+        Some(Attach::Mark{ base, dx, dy }) if base < buffer.len() => {
+            resolve_attachment(buffer, pen, resolved, base, depth + 1);
+            let b = buffer.pos[base];
+            buffer.pos[i].x_offset = b.x_offset + dx + pen[base] - pen[i];
+            buffer.pos[i].y_offset = b.y_offset + dy;
+        }
+        Some(Attach::Cursive{ parent, dy }) if parent < buffer.len() => {
+            resolve_attachment(buffer, pen, resolved, parent, depth + 1);
+            buffer.pos[i].y_offset = buffer.pos[parent].y_offset + dy;
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::{layout_font, shaped};
+
+    #[test]
+    fn orders_right_to_left_runs_visually() {
+        let font = layout_font("()a", vec![]);
+        let mut out = Vec::new();
+        font.shape("(a", Script::Hebrew, true, &mut out);
+        assert_eq!(out.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(3, 1), (2, 0)]);
+        out.clear();
+        font.shape("(a", Script::Latin, false, &mut out);
+        assert_eq!(out.iter().map(|g| (g.glyph, g.cluster)).collect::<Vec<_>>(), vec![(1, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn marks_join_the_cluster_of_their_base() {
+        let font = layout_font("ab\u{301}", vec![]);
+        let glyphs = shaped(&font, "a\u{301}b", Script::Latin, false);
+        let placed: Vec<_> = glyphs.iter().map(|g| (g.glyph, g.cluster, g.x_advance)).collect();
+        assert_eq!(placed, vec![(1, 0, 500), (3, 0, 0), (2, 3, 500)]);
+    }
+
+    #[test]
+    fn hides_format_characters() {
+        let font = layout_font(" ab\u{200d}", vec![]);
+        let glyphs = shaped(&font, "a\u{200d}b", Script::Latin, false);
+        let placed: Vec<_> = glyphs.iter().map(|g| (g.glyph, g.cluster, g.x_advance)).collect();
+        assert_eq!(placed, vec![(2, 0, 500), (1, 0, 0), (3, 4, 500)]);
+    }
+}
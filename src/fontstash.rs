@@ -0,0 +1,1224 @@
+//! Font storage, glyph cache and text layout, after NanoVG's fontstash.
+//!
+//! `Fontstash` owns the fonts of a context. Glyphs are rasterized when
+//! they are first drawn, at the size and blur they are drawn with, and
+//! packed into a single channel atlas by a skyline bin packer. The atlas
+//! only lives in memory here: the context uploads its dirty part to a
+//! texture before drawing, and grows or resets it when a glyph does not
+//! fit.
+//!
+//! `layout` puts a line of text into visual order with the bidirectional
+//! algorithm. Its runs are then laid out one character after the other
+//! or, with shaping enabled, from the glyphs the layout tables of the
+//! fonts produce.
+//!
+//! All positions and sizes are in pixels, with y pointing down.
+
+use std::collections::HashMap;
+
+use crate::bidi::{self, BidiInfo, Run};
+use crate::font::{self, Font, LineMetrics, Script, ShapedGlyph};
+use crate::{Align, FontId, TextDirection};
+
+/// Glyphs are blurred by at most this many pixels.
+const MAX_BLUR: i16 = 20;
+
+/// A font has at most this many fallback fonts.
+pub const MAX_FALLBACKS: usize = 20;
+
+/// Fixed point precision of the blur.
+const APREC: i32 = 16;
+const ZPREC: i32 = 7;
+
+/// Text settings to lay out and rasterize text with.
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub font:    FontId,
+    /// Font size, the distance from the descender to the ascender.
+    pub size:    f32,
+    /// Extra space between glyphs.
+    pub spacing: f32,
+    pub blur:    f32,
+    /// One of `Left`, `Center` or `Right`.
+    pub halign:  Align,
+    /// One of `Top`, `Middle`, `Bottom` or `Baseline`.
+    pub valign:  Align,
+    /// Whether text is shaped with the layout tables of the fonts.
+    pub shaping:   bool,
+    /// Base direction of paragraphs.
+    pub direction: TextDirection,
+}
+
+/// A shaped glyph and the characters it comes from. Offsets are in font
+/// units of its font, with y pointing up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    /// The font the glyph comes from, a fallback font if the requested
+    /// font does not have the characters.
+    pub font:      FontId,
+    pub glyph:     u16,
+    /// Byte range of the cluster of the glyph. Glyphs of a ligature or of
+    /// a character with marks share one cluster.
+    pub start:     usize,
+    pub end:       usize,
+    pub x_advance: i32,
+    pub x_offset:  i32,
+    pub y_offset:  i32,
+    /// Whether the glyph is in a right to left run.
+    pub rtl:       bool,
+}
+
+/// A line of text laid out for drawing or measuring, see
+/// `Fontstash::layout`.
+#[derive(Debug, Clone, Default)]
+pub struct TextLayout {
+    /// Runs of one direction in visual order.
+    pub runs:   Vec<Run>,
+    /// Shaped glyphs in visual order, empty if the style does not enable
+    /// shaping.
+    pub glyphs: Vec<PositionedGlyph>,
+}
+
+impl TextLayout {
+    /// Puts the runs and glyphs into the order of the string, with every
+    /// run left to right, for measuring text in that order.
+    pub fn logical_order(&mut self) {
+        self.runs.sort_by_key(|run| run.start);
+        for run in &mut self.runs {
+            run.level = 0;
+        }
+        self.glyphs.sort_by_key(|glyph| glyph.start);
+    }
+}
+
+/// Whether looking up a glyph has to put it into the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GlyphBitmap {
+    /// Only the metrics are needed, the bitmap is added when the glyph is
+    /// first drawn.
+    Optional,
+    /// The glyph is drawn and has to be in the atlas.
+    Required,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font:  FontId,
+    glyph: u16,
+    /// Font size in tenths of a pixel.
+    size:  i16,
+    blur:  i16,
+}
+
+/// A glyph cached at one size and blur.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// The font the glyph comes from, a fallback font if the requested
+    /// font does not have the character.
+    pub font:  FontId,
+    pub index: u16,
+    /// Rectangle in the atlas, including padding. Negative until the
+    /// bitmap is added to the atlas.
+    pub x0:    i32,
+    pub y0:    i32,
+    pub x1:    i32,
+    pub y1:    i32,
+    /// Horizontal advance.
+    pub xadv:  f32,
+    /// Offset of the padded bitmap from the pen position.
+    pub xoff:  i32,
+    pub yoff:  i32,
+}
+
+/// A glyph quad with its texture coordinates in the atlas.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quad {
+    pub x0: f32,
+    pub y0: f32,
+    pub s0: f32,
+    pub t0: f32,
+    pub x1: f32,
+    pub y1: f32,
+    pub s1: f32,
+    pub t1: f32,
+}
+
+/// Position while iterating over the glyphs of a string. The iterator is
+/// `Copy` so that a step can be retried after making room in the atlas.
+#[derive(Debug, Clone, Copy)]
+pub struct TextIter<'a> {
+    /// Pen position of the current glyph, after kerning and letter
+    /// spacing. This is where a caret before the glyph goes.
+    pub x:          f32,
+    pub y:          f32,
+    /// Pen position of the next glyph.
+    pub nextx:      f32,
+    pub nexty:      f32,
+    /// Character of the current glyph, the first character of its cluster
+    /// for shaped text.
+    pub codepoint:  char,
+    /// Byte offset of the current character or cluster.
+    pub start:      usize,
+    /// Byte offset of the next character, or the end of the cluster.
+    pub next:       usize,
+    /// Whether the current glyph is in a right to left run.
+    pub rtl:        bool,
+    /// The glyph of the last step, `None` before the first step and when
+    /// the glyph did not fit into the atlas.
+    pub prev_glyph: Option<Glyph>,
+    text:           &'a str,
+    layout:         &'a TextLayout,
+    shaped:         bool,
+    /// The current run and the offset of the next character in it, or the
+    /// index of the next shaped glyph.
+    run:            usize,
+    pos:            usize,
+    index:          usize,
+    font:           FontId,
+    size:           i16,
+    blur:           i16,
+    spacing:        f32,
+    bitmap:         GlyphBitmap,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AtlasNode {
+    x:     i32,
+    y:     i32,
+    width: i32,
+}
+
+/// Skyline bin packer. The nodes are the segments of the skyline from left
+/// to right, rectangles are placed on top of them.
+#[derive(Debug, Clone)]
+struct Atlas {
+    width:  i32,
+    height: i32,
+    nodes:  Vec<AtlasNode>,
+}
+
+impl Atlas {
+    fn new(width: i32, height: i32) -> Self {
+        Atlas{
+            width:  width,
+            height: height,
+            nodes:  vec![AtlasNode{ x: 0, y: 0, width: width }],
+        }
+    }
+
+    fn expand(&mut self, width: i32, height: i32) {
+        // Insert node for empty space
+        if width > self.width {
+            self.nodes.push(AtlasNode{ x: self.width, y: 0, width: width - self.width });
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    fn reset(&mut self, width: i32, height: i32) {
+        *self = Atlas::new(width, height);
+    }
+
+    fn add_skyline_level(&mut self, idx: usize, x: i32, y: i32, w: i32, h: i32) {
+        // Insert new node
+        self.nodes.insert(idx, AtlasNode{ x: x, y: y + h, width: w });
+
+        // Delete skyline segments that fall under the shadow of the new segment.
+        while idx + 1 < self.nodes.len() {
+            let prev = self.nodes[idx];
+            let node = &mut self.nodes[idx + 1];
+            if node.x >= prev.x + prev.width {
+                break;
+            }
+            let shrink = prev.x + prev.width - node.x;
+            node.x += shrink;
+            node.width -= shrink;
+            if node.width > 0 {
+                break;
+            }
+            self.nodes.remove(idx + 1);
+        }
+
+        // Merge same height skyline segments that are next to each other.
+        let mut i = 0;
+        while i + 1 < self.nodes.len() {
+            if self.nodes[i].y == self.nodes[i + 1].y {
+                self.nodes[i].width += self.nodes[i + 1].width;
+                self.nodes.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Checks if there is enough space at the location of skyline span `i`,
+    /// and returns the max height of all skyline spans under that location,
+    /// (think tetris block being dropped at that position), or `None` if
+    /// there is no space.
+    fn rect_fits(&self, mut i: usize, w: i32, h: i32) -> Option<i32> {
+        let x = self.nodes[i].x;
+        let mut y = self.nodes[i].y;
+        if x + w > self.width {
+            return None;
+        }
+        let mut space_left = w;
+        while space_left > 0 {
+            if i == self.nodes.len() {
+                return None;
+            }
+            y = y.max(self.nodes[i].y);
+            if y + h > self.height {
+                return None;
+            }
+            space_left -= self.nodes[i].width;
+            i += 1;
+        }
+        Some(y)
+    }
+
+    /// Finds a place for a rectangle, preferring the lowest and then the
+    /// narrowest position.
+    fn add_rect(&mut self, w: i32, h: i32) -> Option<(i32, i32)> {
+        let mut best_h = self.height;
+        let mut best_w = self.width;
+        let mut best = None;
+
+        // Bottom left fit heuristic.
+        for i in 0..self.nodes.len() {
+            if let Some(y) = self.rect_fits(i, w, h) {
+                if y + h < best_h || (y + h == best_h && self.nodes[i].width < best_w) {
+                    best_w = self.nodes[i].width;
+                    best_h = y + h;
+                    best = Some((i, self.nodes[i].x, y));
+                }
+            }
+        }
+
+        let (i, x, y) = best?;
+        self.add_skyline_level(i, x, y, w, h);
+        Some((x, y))
+    }
+}
+
+fn blur_cols(data: &mut [u8], w: usize, h: usize, stride: usize, alpha: i32) {
+    for y in 0..h {
+        let row = &mut data[y * stride..y * stride + w];
+        let mut z = 0; // force zero border
+        for px in &mut row[1..] {
+            z += (alpha * (((*px as i32) << ZPREC) - z)) >> APREC;
+            *px = (z >> ZPREC) as u8;
+        }
+        row[w - 1] = 0; // force zero border
+        z = 0;
+        for px in row[..w - 1].iter_mut().rev() {
+            z += (alpha * (((*px as i32) << ZPREC) - z)) >> APREC;
+            *px = (z >> ZPREC) as u8;
+        }
+        row[0] = 0; // force zero border
+    }
+}
+
+fn blur_rows(data: &mut [u8], w: usize, h: usize, stride: usize, alpha: i32) {
+    for x in 0..w {
+        let mut z = 0; // force zero border
+        for y in 1..h {
+            let p = &mut data[y * stride + x];
+            z += (alpha * (((*p as i32) << ZPREC) - z)) >> APREC;
+            *p = (z >> ZPREC) as u8;
+        }
+        data[(h - 1) * stride + x] = 0; // force zero border
+        z = 0;
+        for y in (0..h - 1).rev() {
+            let p = &mut data[y * stride + x];
+            z += (alpha * (((*p as i32) << ZPREC) - z)) >> APREC;
+            *p = (z >> ZPREC) as u8;
+        }
+        data[x] = 0; // force zero border
+    }
+}
+
+/// Blurs a `w` by `h` bitmap in place with two passes of a recursive
+/// exponential filter.
+fn blur(data: &mut [u8], w: usize, h: usize, stride: usize, blur: i16) {
+    if blur < 1 || w < 2 || h < 2 {
+        return;
+    }
+    // Calculate the alpha such that 90% of the kernel is within the radius. (Kernel extends to infinity)
+    let sigma = blur as f32 * 0.57735; // 1 / sqrt(3)
+    let alpha = ((1 << APREC) as f32 * (1.0 - (-2.3 / (sigma + 1.0)).exp())) as i32;
+    blur_rows(data, w, h, stride, alpha);
+    blur_cols(data, w, h, stride, alpha);
+    blur_rows(data, w, h, stride, alpha);
+    blur_cols(data, w, h, stride, alpha);
+}
+
+/// Returns the ascender, descender and line height of a font as fractions
+/// of the font size.
+fn font_metrics(font: &Font) -> (f32, f32, f32) {
+    let ascender = font.ascender() as f32;
+    let descender = font.descender() as f32;
+    let height = ascender - descender;
+    if height <= 0.0 {
+        return (1.0, 0.0, 1.0);
+    }
+    (ascender / height, descender / height, (height + font.line_gap() as f32) / height)
+}
+
+/// Returns the offset below the baseline and thickness of a line along
+/// text at `size`, or a line at `fallback` times the size below the
+/// baseline if the font has no metrics for it.
+fn decoration_line(font: &Font, line: Option<LineMetrics>, size: f32, fallback: f32) -> (f32, f32) {
+    let size = (size * 10.0) as i16 as f32 / 10.0;
+    match line {
+        Some(line) => {
+            let scale = font.scale_for_pixel_height(size);
+            let thickness = line.thickness as f32 * scale;
+            (thickness * 0.5 - line.position as f32 * scale, thickness)
+        }
+        None => (fallback * size, size / 16.0),
+    }
+}
+
+/// Returns the offset from the text position to the baseline.
+fn vert_align(font: &Font, valign: Align, size: f32) -> f32 {
+    let (ascender, descender, _) = font_metrics(font);
+    match valign {
+        Align::Top => ascender * size,
+        Align::Middle => (ascender + descender) / 2.0 * size,
+        Align::Bottom => descender * size,
+        _ => 0.0,
+    }
+}
+
+struct FontEntry {
+    name:      String,
+    font:      Font,
+    fallbacks: Vec<FontId>,
+}
+
+/// The fonts of a context and the atlas their glyphs are cached in.
+pub struct Fontstash {
+    fonts:  Vec<FontEntry>,
+    glyphs: HashMap<GlyphKey, Glyph>,
+    atlas:  Atlas,
+    width:  usize,
+    height: usize,
+    data:   Vec<u8>,
+    /// Part of the atlas changed since the last `validate_texture`, as
+    /// [x0, y0, x1, y1].
+    dirty:  [usize; 4],
+}
+
+impl Fontstash {
+    /// Creates an empty font store with an atlas of the given size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Fontstash{
+            fonts:  Vec::new(),
+            glyphs: HashMap::new(),
+            atlas:  Atlas::new(width as i32, height as i32),
+            width:  width,
+            height: height,
+            data:   vec![0; width * height],
+            dirty:  [width, height, 0, 0],
+        }
+    }
+
+    /// Adds a font under a name and returns its id. Names need not be
+    /// unique, lookups by name find the font added first.
+    pub fn add_font(&mut self, name: &str, font: Font) -> FontId {
+        self.fonts.push(FontEntry{ name: name.to_string(), font: font, fallbacks: Vec::new() });
+        FontId{ index: self.fonts.len() as u32 - 1 }
+    }
+
+    /// Returns the id of the first font added with the given name.
+    pub fn find_font(&self, name: &str) -> Option<FontId> {
+        self.fonts.iter()
+            .position(|entry| entry.name == name)
+            .map(|index| FontId{ index: index as u32 })
+    }
+
+    /// Returns a font, `None` if the id is not valid.
+    pub fn font(&self, id: FontId) -> Option<&Font> {
+        self.fonts.get(id.index as usize).map(|entry| &entry.font)
+    }
+
+    /// Returns the name a font was added with.
+    pub fn font_name(&self, id: FontId) -> Option<&str> {
+        self.fonts.get(id.index as usize).map(|entry| &entry.name[..])
+    }
+
+    /// Adds a fallback font, which is searched for characters missing from
+    /// the base font after the fallbacks added before it. Returns false if
+    /// either font is not valid or the base font has `MAX_FALLBACKS`
+    /// fallbacks already.
+    pub fn add_fallback_font(&mut self, base: FontId, fallback: FontId) -> bool {
+        if self.font(fallback).is_none() {
+            return false;
+        }
+        match self.fonts.get_mut(base.index as usize) {
+            Some(entry) if entry.fallbacks.len() < MAX_FALLBACKS => {
+                entry.fallbacks.push(fallback);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes all fallback fonts of a font.
+    pub fn reset_fallback_fonts(&mut self, base: FontId) {
+        if let Some(entry) = self.fonts.get_mut(base.index as usize) {
+            entry.fallbacks.clear();
+        }
+    }
+
+    /// Returns the fallback fonts of a font in search order.
+    pub fn fallback_fonts(&self, base: FontId) -> &[FontId] {
+        self.fonts.get(base.index as usize).map_or(&[], |entry| &entry.fallbacks[..])
+    }
+
+    /// Finds the glyph of a character in a font or its fallbacks. Returns
+    /// the .notdef glyph of the font itself if none of them has it.
+    fn find_glyph(&self, font: FontId, codepoint: char) -> Option<(FontId, u16)> {
+        let entry = self.fonts.get(font.index as usize)?;
+        if let Some(index) = entry.font.glyph_index(codepoint) {
+            return Some((font, index));
+        }
+        // Try to find the glyph in fallback fonts.
+        for &fallback in &entry.fallbacks {
+            if let Some(index) = self.font(fallback).and_then(|font| font.glyph_index(codepoint)) {
+                return Some((fallback, index));
+            }
+        }
+        Some((font, 0))
+    }
+
+    /// Returns the number of fonts.
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Returns whether no fonts were added.
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+
+    /// Returns the size of the atlas.
+    pub fn texture_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns the alpha pixels of the atlas, in rows of `width` bytes.
+    pub fn texture_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the part of the atlas which changed since the last call as
+    /// [x0, y0, x1, y1], `None` if nothing changed.
+    pub fn validate_texture(&mut self) -> Option<[usize; 4]> {
+        let dirty = self.dirty;
+        if dirty[0] < dirty[2] && dirty[1] < dirty[3] {
+            self.dirty = [self.width, self.height, 0, 0];
+            Some(dirty)
+        } else {
+            None
+        }
+    }
+
+    /// Grows the atlas, keeping the glyphs already in it. The whole used
+    /// part of the atlas is marked dirty, for uploading it to a new
+    /// texture.
+    pub fn expand_atlas(&mut self, width: usize, height: usize) {
+        let width = width.max(self.width);
+        let height = height.max(self.height);
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        // Copy old texture data over.
+        let mut data = vec![0; width * height];
+        for y in 0..self.height {
+            data[y * width..y * width + self.width].copy_from_slice(&self.data[y * self.width..(y + 1) * self.width]);
+        }
+        self.data = data;
+
+        // Increase atlas size
+        self.atlas.expand(width as i32, height as i32);
+
+        // Add existing data as dirty.
+        let maxy = self.atlas.nodes.iter().map(|node| node.y).max().unwrap_or(0);
+        self.dirty = [0, 0, self.width, maxy as usize];
+
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Empties the atlas and gives it a new size. Every cached glyph is
+    /// evicted and rasterized again when it is next drawn.
+    pub fn reset_atlas(&mut self, width: usize, height: usize) {
+        self.atlas.reset(width as i32, height as i32);
+        self.data.clear();
+        self.data.resize(width * height, 0);
+        self.dirty = [width, height, 0, 0];
+        self.glyphs.clear();
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Returns the glyph of a character at a size in tenths of a pixel,
+    /// rasterizing it into the atlas if the bitmap is required. Returns
+    /// `None` if the size is too small or the glyph does not fit into the
+    /// atlas.
+    fn get_glyph(&mut self, font_id: FontId, codepoint: char, size: i16, blur_size: i16,
+                 bitmap: GlyphBitmap) -> Option<Glyph> {
+        let (font_id, index) = self.find_glyph(font_id, codepoint)?;
+        self.get_glyph_index(font_id, index, size, blur_size, bitmap)
+    }
+
+    /// Returns a glyph by its index in a font, like `get_glyph`.
+    fn get_glyph_index(&mut self, font_id: FontId, index: u16, size: i16, blur_size: i16,
+                       bitmap: GlyphBitmap) -> Option<Glyph> {
+        if size < 2 {
+            return None;
+        }
+        let blur_size = blur_size.clamp(0, MAX_BLUR);
+        let pad = blur_size as i32 + 2;
+        let font = &self.fonts.get(font_id.index as usize)?.font;
+
+        let key = GlyphKey{ font: font_id, glyph: index, size: size, blur: blur_size };
+        let cached = self.glyphs.get(&key).cloned();
+        if let Some(glyph) = cached {
+            if bitmap == GlyphBitmap::Optional || (glyph.x0 >= 0 && glyph.y0 >= 0) {
+                return Some(glyph);
+            }
+        }
+
+        // Create a new glyph or rasterize bitmap data for a cached glyph.
+        let scale = font.scale_for_pixel_height(size as f32 / 10.0);
+        let advance = font.advance_width(index) as f32 * scale;
+        let bbox = font.glyph_bitmap_box(index, scale);
+        let gw = bbox[2] - bbox[0] + pad * 2;
+        let gh = bbox[3] - bbox[1] + pad * 2;
+
+        // Determines the spot to draw glyph in the atlas.
+        let (gx, gy) = match bitmap {
+            GlyphBitmap::Required => self.atlas.add_rect(gw, gh)?,
+            GlyphBitmap::Optional => (-1, -1),
+        };
+
+        let glyph = Glyph{
+            font:  font_id,
+            index: index,
+            x0:    gx,
+            y0:    gy,
+            x1:    gx + gw,
+            y1:    gy + gh,
+            xadv:  advance,
+            xoff:  bbox[0] - pad,
+            yoff:  bbox[1] - pad,
+        };
+        self.glyphs.insert(key, glyph);
+        if bitmap == GlyphBitmap::Optional {
+            return Some(glyph);
+        }
+
+        // Rasterize
+        let (stride, gx, gy, gw, gh, pad) = (self.width, gx as usize, gy as usize, gw as usize, gh as usize, pad as usize);
+        let origin = gy * stride + gx;
+        let font = &self.fonts[font_id.index as usize].font;
+        font.render_glyph_bitmap(index, scale, &mut self.data[origin + pad * stride + pad..],
+                                 gw - pad * 2, gh - pad * 2, stride);
+
+        // Make sure there is one pixel empty border.
+        let dst = &mut self.data[origin..];
+        for y in 0..gh {
+            dst[y * stride] = 0;
+            dst[y * stride + gw - 1] = 0;
+        }
+        for x in 0..gw {
+            dst[x] = 0;
+            dst[(gh - 1) * stride + x] = 0;
+        }
+
+        if blur_size > 0 {
+            blur(dst, gw, gh, stride, blur_size);
+        }
+
+        self.dirty[0] = self.dirty[0].min(gx);
+        self.dirty[1] = self.dirty[1].min(gy);
+        self.dirty[2] = self.dirty[2].max(gx + gw);
+        self.dirty[3] = self.dirty[3].max(gy + gh);
+
+        Some(glyph)
+    }
+
+    /// Returns how far the pen moves between two glyphs besides the advance
+    /// of the first one: the kerning of the pair plus the letter spacing.
+    /// Glyphs from different fonts are not kerned.
+    fn kern_advance(&self, prev_glyph: Option<&Glyph>, glyph: &Glyph, size: i16, spacing: f32) -> f32 {
+        let prev = match prev_glyph {
+            Some(prev) => prev,
+            None => return 0.0,
+        };
+        let mut adv = 0.0;
+        if prev.font == glyph.font {
+            if let Some(font) = self.font(glyph.font) {
+                let scale = font.scale_for_pixel_height(size as f32 / 10.0);
+                adv = font.kerning(prev.index, glyph.index) as f32 * scale;
+            }
+        }
+        (adv + spacing + 0.5).floor()
+    }
+
+    /// Returns the quad of a glyph at the pen position and advances the pen.
+    fn get_quad(&self, glyph: &Glyph, x: &mut f32, y: &mut f32) -> Quad {
+        // Each glyph has 2px border to allow good interpolation,
+        // one pixel to prevent leaking, and one to allow good interpolation for rendering.
+        // Inset the texture region by one pixel for correct interpolation.
+        let xoff = (glyph.xoff + 1) as f32;
+        let yoff = (glyph.yoff + 1) as f32;
+        let x0 = (glyph.x0 + 1) as f32;
+        let y0 = (glyph.y0 + 1) as f32;
+        let x1 = (glyph.x1 - 1) as f32;
+        let y1 = (glyph.y1 - 1) as f32;
+
+        let rx = (*x + xoff).floor();
+        let ry = (*y + yoff).floor();
+        let itw = 1.0 / self.width as f32;
+        let ith = 1.0 / self.height as f32;
+        let quad = Quad{
+            x0: rx,
+            y0: ry,
+            x1: rx + x1 - x0,
+            y1: ry + y1 - y0,
+            s0: x0 * itw,
+            t0: y0 * ith,
+            s1: x1 * itw,
+            t1: y1 * ith,
+        };
+
+        *x += (glyph.xadv + 0.5) as i32 as f32;
+        quad
+    }
+
+    /// Returns the ascender, descender and line height of the style's font
+    /// at its size.
+    pub fn vert_metrics(&self, style: &TextStyle) -> Option<(f32, f32, f32)> {
+        let font = self.font(style.font)?;
+        let size = (style.size * 10.0) as i16 as f32 / 10.0;
+        let (ascender, descender, lineh) = font_metrics(font);
+        Some((ascender * size, descender * size, lineh * size))
+    }
+
+    /// Returns the underline of the style's font at its size, as the offset
+    /// of the middle of the line below the baseline and its thickness.
+    /// Fonts without the metrics get a line a twelfth of the size below
+    /// the baseline.
+    pub fn underline(&self, style: &TextStyle) -> Option<(f32, f32)> {
+        let font = self.font(style.font)?;
+        Some(decoration_line(font, font.underline(), style.size, 0.08))
+    }
+
+    /// Returns the strikeout of the style's font at its size, like
+    /// `underline`. Fonts without the metrics get a line through the
+    /// middle of lowercase letters.
+    pub fn strikeout(&self, style: &TextStyle) -> Option<(f32, f32)> {
+        let font = self.font(style.font)?;
+        Some(decoration_line(font, font.strikeout(), style.size, -0.22))
+    }
+
+    /// Returns the top and bottom of a line of text at `y`.
+    pub fn line_bounds(&self, style: &TextStyle, y: f32) -> (f32, f32) {
+        let font = match self.font(style.font) {
+            Some(font) => font,
+            None => return (y, y),
+        };
+        let size = (style.size * 10.0) as i16 as f32 / 10.0;
+        let (ascender, _, lineh) = font_metrics(font);
+        let y = y + vert_align(font, style.valign, size);
+        let miny = y - ascender * size;
+        (miny, miny + lineh * size)
+    }
+
+    /// Lays out the line `start..end` of a string whose levels `bidi`
+    /// resolved. The line is split into runs of one direction, which are
+    /// shaped if the style enables shaping, and the runs are put into
+    /// visual order. Paragraphs within the line follow each other from
+    /// left to right.
+    pub fn layout(&self, style: &TextStyle, text: &str, bidi: &BidiInfo, start: usize, end: usize) -> TextLayout {
+        let mut runs = Vec::new();
+        for paragraph in &bidi.paragraphs {
+            let (start, end) = (start.max(paragraph.start), end.min(paragraph.end));
+            if start < end {
+                runs.extend(bidi.visual_runs(text, start, end));
+            }
+        }
+
+        let mut glyphs = Vec::new();
+        if style.shaping && self.font(style.font).is_some() {
+            for run in &runs {
+                let mut items = self.itemize(style, text, run.start, run.end);
+                if run.is_rtl() {
+                    items.reverse();
+                }
+                for (font, script, start, end) in items {
+                    self.shape_run(font, script, run.is_rtl(), text, start, end, &mut glyphs);
+                }
+            }
+        }
+        TextLayout{ runs: runs, glyphs: glyphs }
+    }
+
+    /// Lays out a whole string as one line, see `layout`.
+    pub fn layout_text(&self, style: &TextStyle, text: &str) -> TextLayout {
+        let bidi = BidiInfo::new(text, style.direction);
+        self.layout(style, text, &bidi, 0, text.len())
+    }
+
+    /// Splits `start..end` of a string into runs of one font and one
+    /// script, as (font, script, start, end).
+    fn itemize(&self, style: &TextStyle, text: &str, start: usize, end: usize) -> Vec<(FontId, Script, usize, usize)> {
+        let mut items = Vec::new();
+        let mut run_start = start;
+        let mut run_font = style.font;
+        let mut run_script = Script::Common;
+        for (i, c) in text[start..end].char_indices() {
+            let offset = start + i;
+            let script = Script::of(c);
+            // Marks and format characters stay in the font of the character
+            // before them.
+            let font = if i > 0 && (script == Script::Inherited || font::is_mark(c) || font::is_default_ignorable(c)) {
+                run_font
+            } else {
+                self.find_glyph(style.font, c).map_or(style.font, |(font, _)| font)
+            };
+            // Punctuation and spaces join the run they are in.
+            let strong = script != Script::Common && script != Script::Inherited;
+            let new_script = strong && run_script != Script::Common && script != run_script;
+            if offset > run_start && (font != run_font || new_script) {
+                items.push((run_font, run_script, run_start, offset));
+                run_start = offset;
+                run_script = Script::Common;
+            }
+            run_font = font;
+            if strong && run_script == Script::Common {
+                run_script = script;
+            }
+        }
+        if run_start < end {
+            items.push((run_font, run_script, run_start, end));
+        }
+        items
+    }
+
+    /// Shapes the run `start..end` of a string and appends its glyphs.
+    fn shape_run(&self, font_id: FontId, script: Script, rtl: bool, text: &str, start: usize, end: usize,
+                 out: &mut Vec<PositionedGlyph>) {
+        let font = match self.font(font_id) {
+            Some(font) => font,
+            None => return,
+        };
+        let mut shaped: Vec<ShapedGlyph> = Vec::new();
+        font.shape(&text[start..end], script, rtl, &mut shaped);
+
+        // A cluster ends where the next one in the string starts.
+        let mut clusters: Vec<usize> = shaped.iter().map(|glyph| glyph.cluster).collect();
+        clusters.sort();
+        clusters.dedup();
+        for glyph in &shaped {
+            let next = clusters.binary_search(&glyph.cluster).ok()
+                .and_then(|i| clusters.get(i + 1))
+                .map_or(end - start, |&next| next);
+            out.push(PositionedGlyph{
+                font:      font_id,
+                glyph:     glyph.glyph,
+                start:     start + glyph.cluster,
+                end:       start + next,
+                x_advance: glyph.x_advance,
+                x_offset:  glyph.x_offset,
+                y_offset:  glyph.y_offset,
+                rtl:       rtl,
+            });
+        }
+    }
+
+    /// Measures a string and returns its advance and its bounds as
+    /// [xmin, ymin, xmax, ymax]. Glyphs are not added to the atlas.
+    ///
+    /// The glyphs are laid out by the same steps as `text_iter_next`, so
+    /// the measurement matches the drawn text to the pixel.
+    pub fn text_bounds(&mut self, style: &TextStyle, x: f32, y: f32, text: &str) -> (f32, [f32; 4]) {
+        let layout = self.layout_text(style, text);
+        self.layout_bounds(style, x, y, text, &layout)
+    }
+
+    /// Measures a laid out string, like `text_bounds`.
+    pub fn layout_bounds(&mut self, style: &TextStyle, x: f32, y: f32, text: &str,
+                         layout: &TextLayout) -> (f32, [f32; 4]) {
+        let mut iter = self.start_iter(style, x, y, text, layout, GlyphBitmap::Optional);
+        let mut bounds = [x, iter.y, x, iter.y];
+        let mut q = Quad::default();
+        while self.text_iter_next(&mut iter, &mut q) {
+            if iter.prev_glyph.is_some() {
+                bounds[0] = bounds[0].min(q.x0);
+                bounds[1] = bounds[1].min(q.y0);
+                bounds[2] = bounds[2].max(q.x1);
+                bounds[3] = bounds[3].max(q.y1);
+            }
+        }
+
+        let advance = iter.nextx - x;
+
+        // Align horizontally
+        let shift = match style.halign {
+            Align::Right => advance,
+            Align::Center => advance * 0.5,
+            _ => 0.0,
+        };
+        bounds[0] -= shift;
+        bounds[2] -= shift;
+
+        (advance, bounds)
+    }
+
+    /// Starts iterating over the glyphs of a string drawn at (x,y), in the
+    /// order `layout` puts them in.
+    pub fn text_iter<'a>(&mut self, style: &TextStyle, x: f32, y: f32, text: &'a str,
+                         layout: &'a TextLayout, bitmap: GlyphBitmap) -> TextIter<'a> {
+        // Align horizontally
+        let shift = match style.halign {
+            Align::Right => self.layout_bounds(style, x, y, text, layout).0,
+            Align::Center => self.layout_bounds(style, x, y, text, layout).0 * 0.5,
+            _ => 0.0,
+        };
+        self.start_iter(style, x - shift, y, text, layout, bitmap)
+    }
+
+    /// Starts iterating at (x,y) aligned vertically, but not horizontally.
+    fn start_iter<'a>(&self, style: &TextStyle, x: f32, y: f32, text: &'a str, layout: &'a TextLayout,
+                      bitmap: GlyphBitmap) -> TextIter<'a> {
+        let size = (style.size * 10.0) as i16;
+        let mut y = y;
+        if let Some(font) = self.font(style.font) {
+            // Align vertically.
+            y += vert_align(font, style.valign, size as f32 / 10.0);
+        }
+
+        TextIter{
+            x:          x,
+            y:          y,
+            nextx:      x,
+            nexty:      y,
+            codepoint:  '\0',
+            start:      0,
+            next:       0,
+            rtl:        false,
+            prev_glyph: None,
+            text:       text,
+            layout:     layout,
+            shaped:     style.shaping,
+            run:        0,
+            pos:        layout.runs.first().map_or(0, run_start),
+            index:      0,
+            font:       style.font,
+            size:       size,
+            blur:       style.blur as i16,
+            spacing:    style.spacing,
+            bitmap:     bitmap,
+        }
+    }
+
+    /// Steps to the next glyph. Returns false at the end of the string.
+    /// If the glyph could not be added to the atlas, `prev_glyph` is `None`
+    /// and the quad is left as it was.
+    pub fn text_iter_next(&mut self, iter: &mut TextIter, quad: &mut Quad) -> bool {
+        if iter.shaped {
+            return self.shaped_iter_next(iter, quad);
+        }
+        // Step through the characters of right to left runs backwards.
+        let (codepoint, rtl) = loop {
+            let run = match iter.layout.runs.get(iter.run) {
+                Some(run) => *run,
+                None => return false,
+            };
+            let c = if run.is_rtl() {
+                iter.text[run.start..iter.pos].chars().next_back()
+            } else {
+                iter.text[iter.pos..run.end].chars().next()
+            };
+            match c {
+                Some(c) => break (c, run.is_rtl()),
+                None => {
+                    iter.run += 1;
+                    iter.pos = iter.layout.runs.get(iter.run).map_or(0, run_start);
+                }
+            }
+        };
+        if rtl {
+            iter.pos -= codepoint.len_utf8();
+            iter.start = iter.pos;
+        } else {
+            iter.start = iter.pos;
+            iter.pos += codepoint.len_utf8();
+        }
+        iter.next = iter.start + codepoint.len_utf8();
+        iter.codepoint = codepoint;
+        iter.rtl = rtl;
+
+        // Get glyph and quad, right to left text shows mirrored brackets.
+        let c = if rtl { bidi::mirror(codepoint).unwrap_or(codepoint) } else { codepoint };
+        let glyph = self.get_glyph(iter.font, c, iter.size, iter.blur, iter.bitmap);
+        if let Some(ref glyph) = glyph {
+            iter.nextx += self.kern_advance(iter.prev_glyph.as_ref(), glyph, iter.size, iter.spacing);
+        }
+        iter.x = iter.nextx;
+        iter.y = iter.nexty;
+        if let Some(ref glyph) = glyph {
+            *quad = self.get_quad(glyph, &mut iter.nextx, &mut iter.nexty);
+        }
+        iter.prev_glyph = glyph;
+        true
+    }
+
+    /// Steps to the next shaped glyph. The advances of shaped glyphs are
+    /// kerned already, letter spacing goes between clusters only.
+    fn shaped_iter_next(&mut self, iter: &mut TextIter, quad: &mut Quad) -> bool {
+        let shaped = match iter.layout.glyphs.get(iter.index) {
+            Some(shaped) => *shaped,
+            None => return false,
+        };
+        if iter.index > 0 && shaped.start != iter.start {
+            iter.nextx += (iter.spacing + 0.5).floor();
+        }
+        iter.index += 1;
+        iter.start = shaped.start;
+        iter.next = shaped.end;
+        iter.rtl = shaped.rtl;
+        iter.codepoint = iter.text[shaped.start..].chars().next().unwrap_or('\0');
+
+        let glyph = self.get_glyph_index(shaped.font, shaped.glyph, iter.size, iter.blur, iter.bitmap);
+        let scale = self.font(shaped.font).map_or(0.0, |font| font.scale_for_pixel_height(iter.size as f32 / 10.0));
+        iter.x = iter.nextx;
+        iter.y = iter.nexty;
+        if let Some(ref glyph) = glyph {
+            let mut x = iter.x + shaped.x_offset as f32 * scale;
+            let mut y = iter.y - shaped.y_offset as f32 * scale;
+            *quad = self.get_quad(glyph, &mut x, &mut y);
+        }
+        iter.nextx += (shaped.x_advance as f32 * scale + 0.5).floor();
+        iter.prev_glyph = glyph;
+        true
+    }
+}
+
+/// Returns where iterating over the characters of a run starts.
+fn run_start(run: &Run) -> usize {
+    if run.is_rtl() { run.end } else { run.start }
+}
+
+impl Default for Fontstash {
+    fn default() -> Self {
+        Fontstash::new(512, 512)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DejaVu Sans, for the tests which need the metrics of a real font.
+    /// They are ignored by default; run them with `--ignored` where the
+    /// font is installed.
+    const SANS: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+    fn style(font: FontId, size: f32) -> TextStyle {
+        TextStyle{
+            font:    font,
+            size:    size,
+            spacing: 0.0,
+            blur:    0.0,
+            halign:  Align::Left,
+            valign:  Align::Baseline,
+            shaping:   false,
+            direction: TextDirection::Auto,
+        }
+    }
+
+    /// Returns a font store with an atlas of the given size and a font
+    /// whose glyphs are 10 pixel squares at size 20.
+    fn squares(width: usize, height: usize) -> (Fontstash, FontId) {
+        let mut fonts = Fontstash::new(width, height);
+        let id = fonts.add_font("squares", font::tests::square_font("ab"));
+        (fonts, id)
+    }
+
+    /// Returns where a glyph is in the atlas as [x0, y0, x1, y1].
+    fn rect(glyph: Glyph) -> [i32; 4] {
+        [glyph.x0, glyph.y0, glyph.x1, glyph.y1]
+    }
+
+    #[test]
+    fn packs_rects_bottom_left() {
+        let mut atlas = Atlas::new(16, 16);
+        assert_eq!(atlas.add_rect(4, 4), Some((0, 0)));
+        assert_eq!(atlas.add_rect(4, 6), Some((4, 0)));
+        assert_eq!(atlas.add_rect(4, 2), Some((8, 0)));
+        // The lowest place for a wider rect spans the two lowest segments,
+        // and the skyline under it is merged into one segment.
+        assert_eq!(atlas.add_rect(8, 4), Some((8, 2)));
+        assert_eq!(atlas.nodes.len(), 2);
+        assert_eq!(atlas.add_rect(4, 12), Some((0, 4)));
+        assert_eq!(atlas.add_rect(1, 11), None);
+        assert_eq!(atlas.add_rect(12, 10), Some((4, 6)));
+        assert_eq!(atlas.add_rect(1, 1), None);
+        assert_eq!(Atlas::new(16, 16).add_rect(17, 1), None);
+    }
+
+    #[test]
+    fn caches_glyphs_by_size_and_blur() {
+        let (mut fonts, id) = squares(128, 128);
+        let glyph = fonts.get_glyph(id, 'a', 200, 0, GlyphBitmap::Required).unwrap();
+        // The bitmap has two pixels of padding on every side.
+        assert_eq!(rect(glyph), [0, 0, 14, 14]);
+        assert_eq!((glyph.xoff, glyph.yoff, glyph.xadv), (-2, -12, 10.0));
+        assert_eq!(fonts.validate_texture(), Some([0, 0, 14, 14]));
+        let row = &fonts.texture_data()[7 * 128..7 * 128 + 14];
+        assert_eq!(row, [0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 0]);
+
+        // Cache hits leave the atlas alone.
+        let again = fonts.get_glyph(id, 'a', 200, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(again), rect(glyph));
+        assert_eq!(fonts.validate_texture(), None);
+
+        // Other sizes and blurs are cached separately, with the blur
+        // clamped to MAX_BLUR.
+        let bigger = fonts.get_glyph(id, 'a', 201, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(bigger), [14, 0, 29, 15]);
+        let blurred = fonts.get_glyph(id, 'a', 200, 100, GlyphBitmap::Required).unwrap();
+        assert_eq!(blurred.x1 - blurred.x0, 10 + (MAX_BLUR as i32 + 2) * 2);
+        let clamped = fonts.get_glyph(id, 'a', 200, MAX_BLUR, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(clamped), rect(blurred));
+        assert_eq!(fonts.glyphs.len(), 3);
+
+        // Too small sizes and unknown fonts have no glyphs.
+        assert!(fonts.get_glyph(id, 'a', 1, 0, GlyphBitmap::Required).is_none());
+        assert!(fonts.get_glyph(FontId{ index: 1 }, 'a', 200, 0, GlyphBitmap::Required).is_none());
+    }
+
+    #[test]
+    fn adds_bitmaps_to_cached_metrics() {
+        let (mut fonts, id) = squares(64, 64);
+        let glyph = fonts.get_glyph(id, 'b', 200, 0, GlyphBitmap::Optional).unwrap();
+        assert_eq!((glyph.x0, glyph.y0, glyph.xadv), (-1, -1, 10.0));
+        assert_eq!(fonts.validate_texture(), None);
+        let glyph = fonts.get_glyph(id, 'b', 200, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(glyph), [0, 0, 14, 14]);
+        assert_eq!(fonts.validate_texture(), Some([0, 0, 14, 14]));
+        assert_eq!(fonts.glyphs.len(), 1);
+    }
+
+    #[test]
+    fn expands_full_atlases() {
+        let (mut fonts, id) = squares(16, 16);
+        let a = fonts.get_glyph(id, 'a', 200, 0, GlyphBitmap::Required).unwrap();
+        assert!(fonts.get_glyph(id, 'b', 200, 0, GlyphBitmap::Required).is_none());
+        fonts.validate_texture();
+
+        // Growing keeps the glyphs and marks the used part dirty.
+        fonts.expand_atlas(32, 16);
+        assert_eq!(fonts.texture_size(), (32, 16));
+        assert_eq!(fonts.validate_texture(), Some([0, 0, 16, 14]));
+        assert_eq!(fonts.texture_data()[7 * 32 + 7], 255);
+        assert_eq!(rect(fonts.get_glyph(id, 'a', 200, 0, GlyphBitmap::Required).unwrap()), rect(a));
+        let b = fonts.get_glyph(id, 'b', 200, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(b), [14, 0, 28, 14]);
+        assert_eq!(fonts.validate_texture(), Some([14, 0, 28, 14]));
+
+        // The atlas never shrinks.
+        fonts.expand_atlas(8, 8);
+        assert_eq!(fonts.texture_size(), (32, 16));
+        assert_eq!(fonts.validate_texture(), None);
+    }
+
+    #[test]
+    fn resets_atlases() {
+        let (mut fonts, id) = squares(16, 16);
+        fonts.get_glyph(id, 'a', 200, 0, GlyphBitmap::Required).unwrap();
+        fonts.reset_atlas(24, 20);
+        assert_eq!(fonts.texture_size(), (24, 20));
+        assert_eq!(fonts.texture_data(), &[0; 24 * 20][..]);
+        assert_eq!(fonts.validate_texture(), None);
+        assert!(fonts.glyphs.is_empty());
+        // Glyphs are packed from the start again.
+        let b = fonts.get_glyph(id, 'b', 200, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!(rect(b), [0, 0, 14, 14]);
+    }
+
+    #[test]
+    fn missing_glyphs_come_from_fallbacks_in_order() {
+        let mut fonts = Fontstash::new(64, 64);
+        let base = fonts.add_font("base", font::tests::square_font("a"));
+        let first = fonts.add_font("first", font::tests::square_font("b"));
+        let second = fonts.add_font("second", font::tests::square_font("bc"));
+        assert!(fonts.add_fallback_font(base, first));
+        assert!(fonts.add_fallback_font(base, second));
+        assert_eq!(fonts.fallback_fonts(base), [first, second]);
+
+        assert_eq!(fonts.find_glyph(base, 'a'), Some((base, 1)));
+        assert_eq!(fonts.find_glyph(base, 'b'), Some((first, 1)));
+        assert_eq!(fonts.find_glyph(base, 'c'), Some((second, 2)));
+        // Characters no font has get the .notdef glyph of the base font.
+        assert_eq!(fonts.find_glyph(base, 'd'), Some((base, 0)));
+        // Fallbacks are not transitive.
+        assert!(fonts.add_fallback_font(first, base));
+        assert_eq!(fonts.find_glyph(first, 'c'), Some((first, 0)));
+
+        let glyph = fonts.get_glyph(base, 'c', 200, 0, GlyphBitmap::Required).unwrap();
+        assert_eq!((glyph.font, glyph.index), (second, 2));
+    }
+
+    #[test]
+    fn limits_and_resets_fallbacks() {
+        let mut fonts = Fontstash::new(64, 64);
+        let base = fonts.add_font("base", font::tests::square_font("a"));
+        let fallback = fonts.add_font("fallback", font::tests::square_font("b"));
+        assert!(!fonts.add_fallback_font(base, FontId{ index: 2 }));
+        assert!(!fonts.add_fallback_font(FontId{ index: 2 }, fallback));
+        for _ in 0..MAX_FALLBACKS {
+            assert!(fonts.add_fallback_font(base, fallback));
+        }
+        assert!(!fonts.add_fallback_font(base, fallback));
+        assert_eq!(fonts.fallback_fonts(base).len(), MAX_FALLBACKS);
+
+        fonts.reset_fallback_fonts(base);
+        assert!(fonts.fallback_fonts(base).is_empty());
+        assert_eq!(fonts.find_glyph(base, 'b'), Some((base, 0)));
+        assert!(fonts.add_fallback_font(base, fallback));
+        assert_eq!(fonts.find_glyph(base, 'b'), Some((fallback, 1)));
+    }
+
+    #[test]
+    #[ignore = "needs DejaVu Sans"]
+    fn decoration_lines_come_from_the_font() {
+        let font = Font::load(SANS, 0).expect("DejaVu Sans is not installed");
+        let (underline, strikeout) = (font.underline().unwrap(), font.strikeout().unwrap());
+        let mut fonts = Fontstash::new(64, 64);
+        let id = fonts.add_font("sans", font);
+        let scale = fonts.font(id).unwrap().scale_for_pixel_height(20.0);
+
+        let (offset, thickness) = fonts.underline(&style(id, 20.0)).unwrap();
+        assert!((thickness - underline.thickness as f32 * scale).abs() < 1e-4);
+        assert!((offset - (thickness * 0.5 - underline.position as f32 * scale)).abs() < 1e-4);
+        assert!(offset > 0.0);
+        let (offset, thickness) = fonts.strikeout(&style(id, 20.0)).unwrap();
+        assert!((thickness - strikeout.thickness as f32 * scale).abs() < 1e-4);
+        assert!(offset < 0.0);
+
+        // Without metrics the lines are placed by fractions of the size.
+        let font = fonts.font(id).unwrap();
+        let (offset, thickness) = decoration_line(font, None, 20.0, 0.08);
+        assert!((offset - 1.6).abs() < 1e-4 && thickness == 1.25);
+        let (offset, thickness) = decoration_line(font, None, 20.0, -0.22);
+        assert!((offset + 4.4).abs() < 1e-4 && thickness == 1.25);
+        assert!(fonts.underline(&style(FontId{ index: 7 }, 20.0)).is_none());
+    }
+}
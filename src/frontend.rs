@@ -0,0 +1,1809 @@
+//! Backend independent half of a context.
+//!
+//! `Frontend` implements `Context` on top of any `RenderBackend`. It keeps
+//! the render state stack, records and flattens paths, expands them
+//! into fills and strokes and passes the resulting vertices on to the
+//! backend together with the paint and scissor to draw them with.
+
+use std::f32::consts::PI;
+
+use crate::backend::{RenderBackend, RenderPath, Scissor, TextureType};
+use crate::color::Color;
+use crate::font::{Font, FontData, FontError};
+use crate::bidi::BidiInfo;
+use crate::fontstash::{Fontstash, GlyphBitmap, Quad, TextLayout, TextStyle};
+use crate::hyphen::{Hyphenate, Patterns};
+use crate::image::{self, ImageError, RgbaImage};
+use crate::linebreak::{self, LineBreakClass};
+use crate::math::Transform;
+use crate::path::{dist_pt_seg, Command, PathCache, Vertex};
+use crate::state::{State, StateError, StateStack};
+use crate::{Align, BlendFactor, CompositeOperation, CompositeOperationState, Context, FontId,
+            GlyphPosition, ImageFlags, ImageId, LineBreaking, LineCap, LineJoin, Paint, RichTextRow, RichTextRun,
+            TextDecoration, TextDirection, TextRow, TextSpan, Winding};
+
+
+/// Kinds of characters for breaking text into lines.
+#[derive(Clone, Copy, PartialEq)]
+enum CodepointType {
+    Space,
+    Newline,
+    Char,
+    CjkChar,
+}
+
+/// A cluster of characters measured for breaking text into rows. Values
+/// are at the scale text is laid out at.
+#[derive(Clone, Copy)]
+struct Cluster {
+    start:        usize,
+    end:          usize,
+    codepoint:    char,
+    /// Pen position before and after the cluster.
+    x:            f32,
+    nextx:        f32,
+    /// Horizontal bounds of the glyphs.
+    minx:         f32,
+    maxx:         f32,
+    /// Advance and right bound of a hyphen after the cluster.
+    hyphen_width: f32,
+    hyphen_maxx:  f32,
+}
+
+/// Byte offsets of a text where rows may break.
+struct BreakPoints {
+    /// Rows may break before the characters at these offsets.
+    opportunities: Vec<bool>,
+    /// Words may be hyphenated before the characters at these offsets.
+    hyphens:       Vec<bool>,
+}
+
+/// A row of text found by `break_rows`, at the scale text is laid out at.
+#[derive(Clone, Copy)]
+struct RowBreak {
+    start:  usize,
+    end:    usize,
+    width:  f32,
+    minx:   f32,
+    maxx:   f32,
+    hyphen: bool,
+}
+
+/// Length proportional to radius of a cubic bezier handle for 90deg arcs.
+const KAPPA90: f32 = 0.5522848;
+
+/// Size of the first font atlas texture.
+const INIT_FONT_IMAGE_SIZE: usize = 512;
+/// Font atlas textures grow up to this size.
+const MAX_FONT_IMAGE_SIZE: usize = 2048;
+/// Number of font atlas textures a frame can fill.
+const MAX_FONT_IMAGES: usize = 4;
+
+fn quantize(a: f32, d: f32) -> f32 {
+    (a / d + 0.5).floor() * d
+}
+
+/// Intersects two rectangles given as [x, y, width, height].
+fn isect_rects(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let minx = a[0].max(b[0]);
+    let miny = a[1].max(b[1]);
+    let maxx = (a[0] + a[2]).min(b[0] + b[2]);
+    let maxy = (a[1] + a[3]).min(b[1] + b[3]);
+    [minx, miny, (maxx - minx).max(0.0), (maxy - miny).max(0.0)]
+}
+
+/// Borrows the vertices of every path in the cache.
+fn render_paths(cache: &PathCache) -> Vec<RenderPath<'_>> {
+    cache.paths.iter().map(|path| RenderPath{
+        fill:   cache.fill_verts(path),
+        stroke: cache.stroke_verts(path),
+        convex: path.convex,
+    }).collect()
+}
+
+/// A `Context` which draws through a `RenderBackend`.
+pub struct Frontend<B: RenderBackend> {
+    backend:      B,
+    states:       StateStack,
+    commands:     Vec<Command>,
+    command_x:    f32,
+    command_y:    f32,
+    cache:        PathCache,
+    device_ratio: f32,
+    tess_tol:     f32,
+    dist_tol:     f32,
+    fringe_width: f32,
+    fonts:        Fontstash,
+    /// Textures of the font atlas. Glyphs are added to the current one, the
+    /// others hold glyphs drawn earlier in the frame before the atlas was
+    /// full.
+    font_images:  [Option<ImageId>; MAX_FONT_IMAGES],
+    font_image:   usize,
+    /// Finds where words are hyphenated, the US English patterns unless
+    /// set. Loaded when first needed.
+    hyphenator:   Option<Box<dyn Hyphenate>>,
+}
+
+impl<B: RenderBackend> Frontend<B> {
+    /// Creates a context which draws with the given backend.
+    pub fn with_backend(mut backend: B) -> Self {
+        let font_image = backend.create_texture(TextureType::Alpha, INIT_FONT_IMAGE_SIZE, INIT_FONT_IMAGE_SIZE,
+                                                ImageFlags::empty(), None);
+        Frontend{
+            backend:      backend,
+            states:       StateStack::new(),
+            commands:     Vec::new(),
+            command_x:    0.0,
+            command_y:    0.0,
+            cache:        PathCache::new(),
+            device_ratio: 1.0,
+            tess_tol:     0.25,
+            dist_tol:     0.01,
+            fringe_width: 1.0,
+            fonts:        Fontstash::new(INIT_FONT_IMAGE_SIZE, INIT_FONT_IMAGE_SIZE),
+            font_images:  [font_image, None, None, None],
+            font_image:   0,
+            hyphenator:   None,
+        }
+    }
+
+    /// Returns the backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the backend for modification.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Returns the current render state.
+    pub fn state(&self) -> &State {
+        self.states.current()
+    }
+
+    fn state_mut(&mut self) -> &mut State {
+        self.states.current_mut()
+    }
+
+    fn set_device_pixel_ratio(&mut self, ratio: f32) {
+        self.tess_tol = 0.25 / ratio;
+        self.dist_tol = 0.01 / ratio;
+        self.fringe_width = 1.0 / ratio;
+        self.device_ratio = ratio;
+    }
+
+    fn premultiply_xform(&mut self, t: Transform) {
+        self.state_mut().xform.set_premultiply(&t);
+    }
+
+    /// Records commands, transforming them by the current transform.
+    fn append_commands(&mut self, cmds: &[Command]) {
+        let xform = self.state().xform;
+        for cmd in cmds {
+            match *cmd {
+                Command::MoveTo(x, y) | Command::LineTo(x, y) | Command::BezierTo(_, _, _, _, x, y) => {
+                    self.command_x = x;
+                    self.command_y = y;
+                }
+                _ => {}
+            }
+            self.commands.push(cmd.transformed(&xform));
+        }
+        // Flattened paths are stale once the commands change.
+        self.cache.clear();
+    }
+
+    /// Uploads a decoded image, premultiplying it first when the flags
+    /// say the texture holds premultiplied pixels.
+    fn create_image_decoded(&mut self, mut img: RgbaImage, image_flags: ImageFlags) -> Result<ImageId, ImageError> {
+        if image_flags.contains(ImageFlags::Premultiplied) {
+            img.premultiply();
+        }
+        self.backend.create_texture(TextureType::Rgba, img.width, img.height, image_flags, Some(&img.pixels))
+            .ok_or(ImageError::Backend)
+    }
+
+    /// Returns the fonts and the glyph atlas.
+    pub fn fonts(&self) -> &Fontstash {
+        &self.fonts
+    }
+
+    /// Returns the scale text is laid out and rasterized at: the scale of
+    /// the current transform, limited to 4, times the device pixel ratio.
+    fn font_scale(&self) -> f32 {
+        quantize(self.state().xform.average_scale(), 0.01).min(4.0) * self.device_ratio
+    }
+
+    /// Returns the current text style at `scale`, `None` if the current font
+    /// is not valid.
+    fn text_style(&self, scale: f32) -> Option<TextStyle> {
+        let state = self.state();
+        let font = state.font_id?;
+        self.fonts.font(font)?;
+        Some(TextStyle{
+            font:    font,
+            size:    state.font_size * scale,
+            spacing: state.letter_spacing * scale,
+            blur:    state.font_blur * scale,
+            halign:  state.text_halign,
+            valign:  state.text_valign,
+            shaping:   state.text_shaping,
+            direction: state.text_direction,
+        })
+    }
+
+    /// Returns the style of a span of rich text at `scale`, left aligned on
+    /// the baseline, `None` if the font of the span is not valid.
+    fn span_style(&self, span: &TextSpan, scale: f32) -> Option<TextStyle> {
+        let state = self.state();
+        self.fonts.font(span.font)?;
+        Some(TextStyle{
+            font:    span.font,
+            size:    span.size * scale,
+            spacing: span.letter_spacing * scale,
+            blur:    state.font_blur * scale,
+            halign:  Align::Left,
+            valign:  Align::Baseline,
+            shaping:   state.text_shaping,
+            direction: state.text_direction,
+        })
+    }
+
+    /// Uploads the glyphs added to the atlas since the last flush.
+    fn flush_text_texture(&mut self) {
+        if let Some(dirty) = self.fonts.validate_texture() {
+            // Update texture
+            if let Some(image) = self.font_images[self.font_image] {
+                let (width, _) = self.fonts.texture_size();
+                let data = &self.fonts.texture_data()[dirty[1] * width + dirty[0]..];
+                self.backend.update_texture(image, dirty[0], dirty[1], dirty[2] - dirty[0], dirty[3] - dirty[1],
+                                            data, width);
+            }
+        }
+    }
+
+    /// Makes room for more glyphs when the atlas is full. Textures already
+    /// drawn with this frame are left alone, so the atlas moves to the next
+    /// texture, which is twice as large as the current one until the
+    /// maximum size. A larger atlas keeps its glyphs, one of the same size
+    /// starts empty. Returns false when there are no more textures.
+    fn alloc_text_atlas(&mut self) -> bool {
+        self.flush_text_texture();
+        if self.font_image >= MAX_FONT_IMAGES - 1 {
+            return false;
+        }
+        let (iw, ih) = self.fonts.texture_size();
+        // If the next font image already has a texture, use it.
+        let next = self.font_images[self.font_image + 1]
+            .and_then(|image| self.backend.texture_size(image).map(|size| (image, size)));
+        let (image, (nw, nh)) = match next {
+            Some(next) => next,
+            None => {
+                // Calculate the new font image size and create it.
+                let (mut nw, mut nh) = (iw, ih);
+                if nw > nh {
+                    nh *= 2;
+                } else {
+                    nw *= 2;
+                }
+                if nw > MAX_FONT_IMAGE_SIZE || nh > MAX_FONT_IMAGE_SIZE {
+                    nw = MAX_FONT_IMAGE_SIZE;
+                    nh = MAX_FONT_IMAGE_SIZE;
+                }
+                match self.backend.create_texture(TextureType::Alpha, nw, nh, ImageFlags::empty(), None) {
+                    Some(image) => (image, (nw, nh)),
+                    None => return false,
+                }
+            }
+        };
+        self.font_image += 1;
+        self.font_images[self.font_image] = Some(image);
+        if nw >= iw && nh >= ih && (nw, nh) != (iw, ih) {
+            self.fonts.expand_atlas(nw, nh);
+        } else {
+            self.fonts.reset_atlas(nw, nh);
+        }
+        true
+    }
+
+    /// Draws a laid out string with a style scaled by `scale`. Returns the
+    /// end of the text.
+    fn draw_text(&mut self, style: &TextStyle, scale: f32, x: f32, y: f32, span: &str, layout: &TextLayout) -> f32 {
+        let invscale = 1.0 / scale;
+        let xform = self.state().xform;
+
+        let mut verts = Vec::with_capacity(span.len().max(2) * 6);
+        let mut iter = self.fonts.text_iter(style, x * scale, y * scale, span, layout, GlyphBitmap::Required);
+        let mut prev_iter = iter;
+        let mut q = Quad::default();
+        while self.fonts.text_iter_next(&mut iter, &mut q) {
+            if iter.prev_glyph.is_none() {
+                // The glyph does not fit, draw what we have and retry in a new atlas.
+                self.render_text(&verts);
+                verts.clear();
+                if !self.alloc_text_atlas() {
+                    break; // no memory :(
+                }
+                iter = prev_iter;
+                self.fonts.text_iter_next(&mut iter, &mut q); // try again
+                if iter.prev_glyph.is_none() {
+                    break;
+                }
+            }
+            prev_iter = iter;
+            // Transform corners.
+            let (c0x, c0y) = xform.point(q.x0 * invscale, q.y0 * invscale);
+            let (c1x, c1y) = xform.point(q.x1 * invscale, q.y0 * invscale);
+            let (c2x, c2y) = xform.point(q.x1 * invscale, q.y1 * invscale);
+            let (c3x, c3y) = xform.point(q.x0 * invscale, q.y1 * invscale);
+            // Create triangles
+            verts.push(Vertex::new(c0x, c0y, q.s0, q.t0));
+            verts.push(Vertex::new(c2x, c2y, q.s1, q.t1));
+            verts.push(Vertex::new(c1x, c1y, q.s1, q.t0));
+            verts.push(Vertex::new(c0x, c0y, q.s0, q.t0));
+            verts.push(Vertex::new(c3x, c3y, q.s0, q.t1));
+            verts.push(Vertex::new(c2x, c2y, q.s1, q.t1));
+        }
+
+        self.flush_text_texture();
+        self.render_text(&verts);
+
+        iter.nextx / scale
+    }
+
+    /// Returns where rows of `text` may break, as set by the current line
+    /// breaking rules and hyphenation.
+    fn break_points(&mut self, text: &str) -> BreakPoints {
+        // Offsets of the characters a row may break before.
+        let mut opportunities = vec![false; text.len() + 1];
+        if self.state().line_breaking == LineBreaking::Unicode {
+            for (offset, _) in linebreak::line_breaks(text) {
+                opportunities[offset] = true;
+            }
+        }
+
+        // Offsets of the characters a hyphen may go before: after soft
+        // hyphens, and where the hyphenator splits words without them.
+        let mut hyphens = vec![false; text.len() + 1];
+        if self.state().text_hyphenation {
+            let hyphenator = self.hyphenator.get_or_insert_with(|| Box::new(Patterns::english()));
+            let mut words = text.char_indices().peekable();
+            while let Some((start, c)) = words.next() {
+                if !c.is_alphabetic() && c != '\u{ad}' {
+                    continue;
+                }
+                let mut end = start + c.len_utf8();
+                while let Some(&(offset, c)) = words.peek() {
+                    if !c.is_alphabetic() && c != '\u{ad}' {
+                        break;
+                    }
+                    end = offset + c.len_utf8();
+                    words.next();
+                }
+                let word = &text[start..end];
+                if !word.contains('\u{ad}') {
+                    for offset in hyphenator.hyphenate(word) {
+                        hyphens[start + offset] = true;
+                    }
+                }
+            }
+        }
+        for (offset, c) in text.char_indices() {
+            if c == '\u{ad}' {
+                hyphens[offset + c.len_utf8()] = true;
+            }
+        }
+        BreakPoints{ opportunities: opportunities, hyphens: hyphens }
+    }
+
+    /// Measures the clusters of laid out text in logical order, starting at
+    /// `x`, and adds them to `clusters`. Returns the position after them.
+    fn measure_clusters(&mut self, style: &TextStyle, text: &str, layout: &TextLayout, x: f32,
+                        clusters: &mut Vec<Cluster>) -> f32 {
+        let (hyphen_width, hyphen_bounds) = self.fonts.text_bounds(style, 0.0, 0.0, "-");
+        let mut iter = self.fonts.text_iter(style, x, 0.0, text, layout, GlyphBitmap::Optional);
+        let mut prev_iter = iter;
+        let mut q = Quad::default();
+        let first = clusters.len();
+        while self.fonts.text_iter_next(&mut iter, &mut q) {
+            if iter.prev_glyph.is_none() && self.alloc_text_atlas() {
+                iter = prev_iter;
+                self.fonts.text_iter_next(&mut iter, &mut q); // try again
+            }
+            prev_iter = iter;
+            match clusters[first..].last_mut() {
+                Some(cluster) if cluster.start == iter.start => {
+                    // Further glyphs of a cluster extend it.
+                    cluster.end = iter.next;
+                    cluster.nextx = iter.nextx;
+                    cluster.minx = cluster.minx.min(q.x0);
+                    cluster.maxx = cluster.maxx.max(q.x1);
+                }
+                _ => clusters.push(Cluster{
+                    start:        iter.start,
+                    end:          iter.next,
+                    codepoint:    iter.codepoint,
+                    x:            iter.x,
+                    nextx:        iter.nextx,
+                    minx:         q.x0,
+                    maxx:         q.x1,
+                    hyphen_width: hyphen_width,
+                    hyphen_maxx:  hyphen_bounds[2],
+                }),
+            }
+        }
+        iter.nextx
+    }
+
+    /// Draws glyph quads with the fill paint, textured by the current atlas.
+    fn render_text(&mut self, verts: &[Vertex]) {
+        if verts.is_empty() {
+            return;
+        }
+        let state = *self.state();
+        let mut paint = state.fill;
+
+        // Render triangles.
+        paint.image = self.font_images[self.font_image];
+
+        // Apply global alpha
+        paint.inner_color.a *= state.alpha;
+        paint.outer_color.a *= state.alpha;
+
+        self.backend.triangles(&paint, state.composite, state.scissor.as_ref(), verts, self.fringe_width);
+    }
+}
+
+impl<B: RenderBackend> Context for Frontend<B> {
+    fn begin_frame(&mut self, window_width: f32, window_height: f32, device_pixel_ratio: f32) {
+        self.states.clear();
+        self.set_device_pixel_ratio(device_pixel_ratio);
+        self.backend.viewport(window_width, window_height, device_pixel_ratio);
+    }
+
+    fn cancel_frame(&mut self) {
+        self.backend.cancel();
+    }
+
+    fn end_frame(&mut self) {
+        self.backend.flush();
+        if self.font_image != 0 {
+            let font_image = match self.font_images[self.font_image].take() {
+                Some(image) => image,
+                None => return,
+            };
+            let (iw, ih) = self.backend.texture_size(font_image).unwrap_or((0, 0));
+            // Keep the font images at least as large as the current one for
+            // the next frames, and delete the smaller ones.
+            let mut j = 0;
+            for i in 0..self.font_image {
+                if let Some(image) = self.font_images[i].take() {
+                    let (nw, nh) = self.backend.texture_size(image).unwrap_or((0, 0));
+                    if nw < iw || nh < ih {
+                        self.backend.delete_texture(image);
+                    } else {
+                        self.font_images[j] = Some(image);
+                        j += 1;
+                    }
+                }
+            }
+            // Make the current font image the first.
+            self.font_images[j] = self.font_images[0];
+            self.font_images[0] = Some(font_image);
+            self.font_image = 0;
+        }
+    }
+
+    fn global_composite_operation(&mut self, op: CompositeOperation) {
+        self.state_mut().composite = op.into();
+    }
+
+    fn global_composite_blend_func(&mut self, sfactor: BlendFactor, dfactor: BlendFactor) {
+        self.state_mut().composite = CompositeOperationState::blend_func(sfactor, dfactor);
+    }
+
+    fn global_composite_blend_func_separate(&mut self, src_r_g_b: BlendFactor, dst_r_g_b: BlendFactor, src_alpha: BlendFactor, dst_alpha: BlendFactor) {
+        self.state_mut().composite = CompositeOperationState{
+            src_rgb:   src_r_g_b,
+            dst_rgb:   dst_r_g_b,
+            src_alpha: src_alpha,
+            dst_alpha: dst_alpha,
+        };
+    }
+
+    fn save(&mut self) -> Result<(), StateError> {
+        self.states.save()
+    }
+
+    fn restore(&mut self) -> Result<(), StateError> {
+        self.states.restore()
+    }
+
+    fn state_depth(&self) -> usize {
+        self.states.depth()
+    }
+
+    fn reset(&mut self) {
+        self.states.reset();
+    }
+
+    fn shape_anti_alias(&mut self, enabled: bool) {
+        self.state_mut().shape_anti_alias = enabled;
+    }
+
+    fn stroke_color(&mut self, color: Color) {
+        self.state_mut().stroke = Paint::color(color);
+    }
+
+    fn stroke_paint(&mut self, paint: Paint) {
+        let state = self.state_mut();
+        let mut xform = Transform{ m: paint.xform };
+        xform.set_multiply(&state.xform);
+        state.stroke = paint;
+        state.stroke.xform = xform.m;
+    }
+
+    fn fill_color(&mut self, color: Color) {
+        self.state_mut().fill = Paint::color(color);
+    }
+
+    fn fill_paint(&mut self, paint: Paint) {
+        let state = self.state_mut();
+        let mut xform = Transform{ m: paint.xform };
+        xform.set_multiply(&state.xform);
+        state.fill = paint;
+        state.fill.xform = xform.m;
+    }
+
+    fn miter_limit(&mut self, limit: f32) {
+        self.state_mut().miter_limit = limit;
+    }
+
+    fn stroke_width(&mut self, size: f32) {
+        self.state_mut().stroke_width = size;
+    }
+
+    fn line_cap(&mut self, cap: LineCap) {
+        self.state_mut().line_cap = cap;
+    }
+
+    fn line_join(&mut self, join: LineJoin) {
+        self.state_mut().line_join = join;
+    }
+
+    fn global_alpha(&mut self, alpha: f32) {
+        self.state_mut().alpha = alpha;
+    }
+
+    fn reset_transform(&mut self) {
+        self.state_mut().xform.set_identity();
+    }
+
+    fn transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        self.premultiply_xform(Transform{ m: [a, b, c, d, e, f] });
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        let mut t = Transform::identity();
+        t.set_translate(x, y);
+        self.premultiply_xform(t);
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        let mut t = Transform::identity();
+        t.set_rotate(angle);
+        self.premultiply_xform(t);
+    }
+
+    fn skew_x(&mut self, angle: f32) {
+        let mut t = Transform::identity();
+        t.set_skew_x(angle);
+        self.premultiply_xform(t);
+    }
+
+    fn skew_y(&mut self, angle: f32) {
+        let mut t = Transform::identity();
+        t.set_skew_y(angle);
+        self.premultiply_xform(t);
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        let mut t = Transform::identity();
+        t.set_scale(x, y);
+        self.premultiply_xform(t);
+    }
+
+    fn current_transform(&self) -> Transform {
+        self.state().xform
+    }
+
+    fn create_image(&mut self, filename: &str, image_flags: ImageFlags) -> Result<ImageId, ImageError> {
+        let img = image::load(filename)?;
+        self.create_image_decoded(img, image_flags)
+    }
+
+    fn create_image_mem(&mut self, image_flags: ImageFlags, data: &[u8]) -> Result<ImageId, ImageError> {
+        let img = image::decode(data)?;
+        self.create_image_decoded(img, image_flags)
+    }
+
+    fn create_image_r_g_b_a(&mut self, w: usize, h: usize, image_flags: ImageFlags, data: &[u8]) -> Result<ImageId, ImageError> {
+        self.create_image_format(w, h, TextureType::Rgba, image_flags, data)
+    }
+
+    fn create_image_format(&mut self, w: usize, h: usize, format: TextureType, image_flags: ImageFlags,
+                           data: &[u8]) -> Result<ImageId, ImageError> {
+        let len = w.checked_mul(h).and_then(|n| n.checked_mul(format.bytes_per_pixel()));
+        if w == 0 || h == 0 || len.is_none_or(|len| data.len() < len) {
+            return Err(ImageError::InvalidSize);
+        }
+        self.backend.create_texture(format, w, h, image_flags, Some(data)).ok_or(ImageError::Backend)
+    }
+
+    fn update_image(&mut self, image: ImageId, data: &[u8]) -> Result<(), ImageError> {
+        let (w, h) = self.image_size(image)?;
+        let bpp = self.backend.texture_type(image).map_or(4, TextureType::bytes_per_pixel);
+        self.update_image_region(image, 0, 0, w, h, data, w * bpp)
+    }
+
+    fn update_image_region(&mut self, image: ImageId, x: usize, y: usize, w: usize, h: usize,
+                           data: &[u8], stride: usize) -> Result<(), ImageError> {
+        let (iw, ih) = self.image_size(image)?;
+        let bpp = self.backend.texture_type(image).map_or(4, TextureType::bytes_per_pixel);
+        if x.checked_add(w).is_none_or(|x1| x1 > iw) || y.checked_add(h).is_none_or(|y1| y1 > ih) {
+            return Err(ImageError::InvalidRegion);
+        }
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        // The caller's stride may be huge, so the size of the data is
+        // computed without overflowing.
+        let row = w.checked_mul(bpp).ok_or(ImageError::InvalidSize)?;
+        let len = stride.checked_mul(h - 1).and_then(|len| len.checked_add(row)).ok_or(ImageError::InvalidSize)?;
+        if stride < row || data.len() < len {
+            return Err(ImageError::InvalidSize);
+        }
+        self.backend.update_texture(image, x, y, w, h, data, stride);
+        Ok(())
+    }
+
+    fn image_size(&self, image: ImageId) -> Result<(usize, usize), ImageError> {
+        self.backend.texture_size(image).ok_or(ImageError::InvalidImage)
+    }
+
+    fn delete_image(&mut self, image: ImageId) -> Result<(), ImageError> {
+        if self.backend.delete_texture(image) {
+            Ok(())
+        } else {
+            Err(ImageError::InvalidImage)
+        }
+    }
+
+    fn linear_gradient(&mut self, sx: f32, sy: f32, ex: f32, ey: f32, icol: Color, ocol: Color) -> Paint {
+        Paint::linear_gradient(sx, sy, ex, ey, icol, ocol)
+    }
+
+    fn box_gradient(&mut self, x: f32, y: f32, w: f32, h: f32, r: f32, f: f32, icol: Color, ocol: Color) -> Paint {
+        Paint::box_gradient(x, y, w, h, r, f, icol, ocol)
+    }
+
+    fn radial_gradient(&mut self, cx: f32, cy: f32, inr: f32, outr: f32, icol: Color, ocol: Color) -> Paint {
+        Paint::radial_gradient(cx, cy, inr, outr, icol, ocol)
+    }
+
+    fn image_pattern(&mut self, ox: f32, oy: f32, ex: f32, ey: f32, angle: f32, image: ImageId, alpha: f32) -> Result<Paint, ImageError> {
+        self.image_size(image)?;
+        Ok(Paint::image_pattern(ox, oy, ex, ey, angle, image, alpha))
+    }
+
+    fn scissor(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let state = self.state_mut();
+        state.scissor = Some(Scissor::new(x, y, w, h, &state.xform));
+    }
+
+    fn intersect_scissor(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let state = *self.state();
+
+        // If no previous scissor has been set, set the scissor as current scissor.
+        let previous = match state.scissor {
+            Some(scissor) => scissor,
+            None => {
+                self.scissor(x, y, w, h);
+                return;
+            }
+        };
+
+        // Transform the current scissor rect into current transform space.
+        // If there is difference in rotation, this will be approximation.
+        let mut pxform = previous.xform;
+        let invxform = state.xform.invert().unwrap_or_else(Transform::identity);
+        pxform.set_multiply(&invxform);
+        let ex = previous.extent[0];
+        let ey = previous.extent[1];
+        let tex = ex * pxform.m[0].abs() + ey * pxform.m[2].abs();
+        let tey = ex * pxform.m[1].abs() + ey * pxform.m[3].abs();
+
+        // Intersect rects.
+        let rect = isect_rects([pxform.m[4] - tex, pxform.m[5] - tey, tex * 2.0, tey * 2.0], [x, y, w, h]);
+
+        self.scissor(rect[0], rect[1], rect[2], rect[3]);
+    }
+
+    fn reset_scissor(&mut self) {
+        self.state_mut().scissor = None;
+    }
+
+    fn begin_path(&mut self) {
+        self.commands.clear();
+        self.cache.clear();
+    }
+
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.append_commands(&[Command::MoveTo(x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.append_commands(&[Command::LineTo(x, y)]);
+    }
+
+    fn bezier_to(&mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) {
+        self.append_commands(&[Command::BezierTo(c1x, c1y, c2x, c2y, x, y)]);
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        let x0 = self.command_x;
+        let y0 = self.command_y;
+        self.bezier_to(x0 + 2.0 / 3.0 * (cx - x0), y0 + 2.0 / 3.0 * (cy - y0),
+                       x + 2.0 / 3.0 * (cx - x), y + 2.0 / 3.0 * (cy - y),
+                       x, y);
+    }
+
+    fn arc_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) {
+        let x0 = self.command_x;
+        let y0 = self.command_y;
+        let dist_tol = self.dist_tol;
+
+        if self.commands.is_empty() {
+            return;
+        }
+
+        // Handle degenerate cases.
+        let pt_equals = |ax: f32, ay: f32, bx: f32, by: f32| {
+            let dx = bx - ax;
+            let dy = by - ay;
+            dx * dx + dy * dy < dist_tol * dist_tol
+        };
+        if pt_equals(x0, y0, x1, y1)
+            || pt_equals(x1, y1, x2, y2)
+            || dist_pt_seg(x1, y1, x0, y0, x2, y2) < dist_tol * dist_tol
+            || radius < dist_tol {
+            self.line_to(x1, y1);
+            return;
+        }
+
+        // Calculate tangential circle to lines (x0,y0)-(x1,y1) and (x1,y1)-(x2,y2).
+        let (mut dx0, mut dy0) = (x0 - x1, y0 - y1);
+        let (mut dx1, mut dy1) = (x2 - x1, y2 - y1);
+        let d0 = f32::sqrt(dx0 * dx0 + dy0 * dy0);
+        let d1 = f32::sqrt(dx1 * dx1 + dy1 * dy1);
+        dx0 /= d0;
+        dy0 /= d0;
+        dx1 /= d1;
+        dy1 /= d1;
+        let a = f32::acos(dx0 * dx1 + dy0 * dy1);
+        let d = radius / f32::tan(a / 2.0);
+
+        if d > 10000.0 {
+            self.line_to(x1, y1);
+            return;
+        }
+
+        if dx1 * dy0 - dx0 * dy1 > 0.0 {
+            let cx = x1 + dx0 * d + dy0 * radius;
+            let cy = y1 + dy0 * d + -dx0 * radius;
+            let a0 = f32::atan2(dx0, -dy0);
+            let a1 = f32::atan2(-dx1, dy1);
+            self.arc(cx, cy, radius, a0, a1, Winding::CW);
+        } else {
+            let cx = x1 + dx0 * d + -dy0 * radius;
+            let cy = y1 + dy0 * d + dx0 * radius;
+            let a0 = f32::atan2(-dx0, dy0);
+            let a1 = f32::atan2(dx1, -dy1);
+            self.arc(cx, cy, radius, a0, a1, Winding::CCW);
+        }
+    }
+
+    fn close_path(&mut self) {
+        self.append_commands(&[Command::Close]);
+    }
+
+    fn path_winding(&mut self, dir: Winding) {
+        self.append_commands(&[Command::Winding(dir)]);
+    }
+
+    fn arc(&mut self, cx: f32, cy: f32, r: f32, a0: f32, a1: f32, dir: Winding) {
+        let join = !self.commands.is_empty();
+
+        // Clamp angles
+        let mut da = a1 - a0;
+        if dir == Winding::CW {
+            if da.abs() >= PI * 2.0 {
+                da = PI * 2.0;
+            } else {
+                while da < 0.0 {
+                    da += PI * 2.0;
+                }
+            }
+        } else if da.abs() >= PI * 2.0 {
+            da = -PI * 2.0;
+        } else {
+            while da > 0.0 {
+                da -= PI * 2.0;
+            }
+        }
+
+        // Split arc into max 90 degree segments.
+        let ndivs = ((da.abs() / (PI * 0.5) + 0.5) as usize).clamp(1, 5);
+        let hda = (da / ndivs as f32) / 2.0;
+        let mut kappa = (4.0 / 3.0 * (1.0 - hda.cos()) / hda.sin()).abs();
+        if dir == Winding::CCW {
+            kappa = -kappa;
+        }
+
+        let (mut px, mut py, mut ptanx, mut ptany) = (0.0, 0.0, 0.0, 0.0);
+        for i in 0..=ndivs {
+            let a = a0 + da * (i as f32 / ndivs as f32);
+            let dx = a.cos();
+            let dy = a.sin();
+            let x = cx + dx * r;
+            let y = cy + dy * r;
+            let tanx = -dy * r * kappa;
+            let tany = dx * r * kappa;
+
+            if i == 0 {
+                if join {
+                    self.line_to(x, y);
+                } else {
+                    self.move_to(x, y);
+                }
+            } else {
+                self.bezier_to(px + ptanx, py + ptany, x - tanx, y - tany, x, y);
+            }
+            px = x;
+            py = y;
+            ptanx = tanx;
+            ptany = tany;
+        }
+    }
+
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.move_to(x, y);
+        self.line_to(x, y + h);
+        self.line_to(x + w, y + h);
+        self.line_to(x + w, y);
+        self.close_path();
+    }
+
+    fn rounded_rect(&mut self, x: f32, y: f32, w: f32, h: f32, r: f32) {
+        self.rounded_rect_varying(x, y, w, h, r, r, r, r);
+    }
+
+    fn rounded_rect_varying(&mut self, x: f32, y: f32, w: f32, h: f32, rad_top_left: f32, rad_top_right: f32, rad_bottom_right: f32, rad_bottom_left: f32) {
+        if rad_top_left < 0.1 && rad_top_right < 0.1 && rad_bottom_right < 0.1 && rad_bottom_left < 0.1 {
+            self.rect(x, y, w, h);
+            return;
+        }
+
+        let halfw = w.abs() * 0.5;
+        let halfh = h.abs() * 0.5;
+        let sw = if w < 0.0 { -1.0 } else { 1.0 };
+        let sh = if h < 0.0 { -1.0 } else { 1.0 };
+        let (rx_bl, ry_bl) = (rad_bottom_left.min(halfw) * sw, rad_bottom_left.min(halfh) * sh);
+        let (rx_br, ry_br) = (rad_bottom_right.min(halfw) * sw, rad_bottom_right.min(halfh) * sh);
+        let (rx_tr, ry_tr) = (rad_top_right.min(halfw) * sw, rad_top_right.min(halfh) * sh);
+        let (rx_tl, ry_tl) = (rad_top_left.min(halfw) * sw, rad_top_left.min(halfh) * sh);
+        let k = 1.0 - KAPPA90;
+
+        self.move_to(x, y + ry_tl);
+        self.line_to(x, y + h - ry_bl);
+        self.bezier_to(x, y + h - ry_bl * k, x + rx_bl * k, y + h, x + rx_bl, y + h);
+        self.line_to(x + w - rx_br, y + h);
+        self.bezier_to(x + w - rx_br * k, y + h, x + w, y + h - ry_br * k, x + w, y + h - ry_br);
+        self.line_to(x + w, y + ry_tr);
+        self.bezier_to(x + w, y + ry_tr * k, x + w - rx_tr * k, y, x + w - rx_tr, y);
+        self.line_to(x + rx_tl, y);
+        self.bezier_to(x + rx_tl * k, y, x, y + ry_tl * k, x, y + ry_tl);
+        self.close_path();
+    }
+
+    fn ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32) {
+        self.move_to(cx - rx, cy);
+        self.bezier_to(cx - rx, cy + ry * KAPPA90, cx - rx * KAPPA90, cy + ry, cx, cy + ry);
+        self.bezier_to(cx + rx * KAPPA90, cy + ry, cx + rx, cy + ry * KAPPA90, cx + rx, cy);
+        self.bezier_to(cx + rx, cy - ry * KAPPA90, cx + rx * KAPPA90, cy - ry, cx, cy - ry);
+        self.bezier_to(cx - rx * KAPPA90, cy - ry, cx - rx, cy - ry * KAPPA90, cx - rx, cy);
+        self.close_path();
+    }
+
+    fn circle(&mut self, cx: f32, cy: f32, r: f32) {
+        self.ellipse(cx, cy, r, r);
+    }
+
+    fn fill(&mut self) {
+        let state = *self.state();
+        let mut paint = state.fill;
+
+        self.cache.flatten(&self.commands, self.tess_tol, self.dist_tol);
+        if state.shape_anti_alias {
+            self.cache.expand_fill(self.fringe_width, self.fringe_width, LineJoin::Miter, 2.4);
+        } else {
+            self.cache.expand_fill(0.0, self.fringe_width, LineJoin::Miter, 2.4);
+        }
+
+        // Apply global alpha
+        paint.inner_color.a *= state.alpha;
+        paint.outer_color.a *= state.alpha;
+
+        self.backend.fill(&paint, state.composite, state.scissor.as_ref(), self.fringe_width,
+                          self.cache.bounds, &render_paths(&self.cache));
+    }
+
+    fn stroke(&mut self) {
+        let state = *self.state();
+        let scale = state.xform.average_scale();
+        let mut stroke_width = (state.stroke_width * scale).clamp(0.0, 200.0);
+        let mut paint = state.stroke;
+
+        if stroke_width < self.fringe_width {
+            // If the stroke width is less than pixel size, use alpha to emulate coverage.
+            // Since coverage is area, scale by alpha*alpha.
+            let alpha = (stroke_width / self.fringe_width).clamp(0.0, 1.0);
+            paint.inner_color.a *= alpha * alpha;
+            paint.outer_color.a *= alpha * alpha;
+            stroke_width = self.fringe_width;
+        }
+
+        // Apply global alpha
+        paint.inner_color.a *= state.alpha;
+        paint.outer_color.a *= state.alpha;
+
+        self.cache.flatten(&self.commands, self.tess_tol, self.dist_tol);
+
+        let fringe = if state.shape_anti_alias { self.fringe_width } else { 0.0 };
+        self.cache.expand_stroke(stroke_width * 0.5, fringe, state.line_cap, state.line_join,
+                                 state.miter_limit, self.tess_tol);
+
+        self.backend.stroke(&paint, state.composite, state.scissor.as_ref(), self.fringe_width,
+                            stroke_width, &render_paths(&self.cache));
+    }
+
+    fn create_font_at_index(&mut self, name: &str, filename: &str, font_index: usize) -> Result<FontId, FontError> {
+        let font = Font::load(filename, font_index)?;
+        Ok(self.fonts.add_font(name, font))
+    }
+
+    fn create_font_mem_at_index<D: Into<FontData>>(&mut self, name: &str, data: D,
+                                                   font_index: usize) -> Result<FontId, FontError> {
+        let font = Font::new(data, font_index)?;
+        Ok(self.fonts.add_font(name, font))
+    }
+
+    fn find_font(&self, name: &str) -> Option<FontId> {
+        self.fonts.find_font(name)
+    }
+
+    fn add_fallback_font_id(&mut self, base_font: FontId, fallback_font: FontId) -> bool {
+        self.fonts.add_fallback_font(base_font, fallback_font)
+    }
+
+    fn reset_fallback_fonts_id(&mut self, base_font: FontId) {
+        self.fonts.reset_fallback_fonts(base_font);
+    }
+
+    fn font_size(&mut self, size: f32) {
+        self.state_mut().font_size = size;
+    }
+
+    fn font_blur(&mut self, blur: f32) {
+        self.state_mut().font_blur = blur;
+    }
+
+    fn text_letter_spacing(&mut self, spacing: f32) {
+        self.state_mut().letter_spacing = spacing;
+    }
+
+    fn text_line_height(&mut self, line_height: f32) {
+        self.state_mut().line_height = line_height;
+    }
+
+    fn text_align(&mut self, align: Align) {
+        let state = self.state_mut();
+        match align {
+            Align::Left | Align::Center | Align::Right => state.text_halign = align,
+            _ => state.text_valign = align,
+        }
+    }
+
+    fn text_shaping(&mut self, shaping: bool) {
+        self.state_mut().text_shaping = shaping;
+    }
+
+    fn text_direction(&mut self, direction: TextDirection) {
+        self.state_mut().text_direction = direction;
+    }
+
+    fn text_line_breaking(&mut self, breaking: LineBreaking) {
+        self.state_mut().line_breaking = breaking;
+    }
+
+    fn text_hyphenation(&mut self, hyphenate: bool) {
+        self.state_mut().text_hyphenation = hyphenate;
+    }
+
+    fn set_hyphenator(&mut self, hyphenator: Box<dyn Hyphenate>) {
+        self.hyphenator = Some(hyphenator);
+    }
+
+    fn font_face_id(&mut self, font: FontId) {
+        self.state_mut().font_id = Some(font);
+    }
+
+    fn font_face(&mut self, font: &str) {
+        let id = self.find_font(font);
+        self.state_mut().font_id = id;
+    }
+
+    fn text(&mut self, x: f32, y: f32, span: &str) -> f32 {
+        let scale = self.font_scale();
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => return x,
+        };
+        let layout = self.fonts.layout_text(&style, span);
+        self.draw_text(&style, scale, x, y, span, &layout)
+    }
+
+    fn text_box(&mut self, x: f32, y: f32, break_row_width: f32, span: &str) {
+        if self.text_style(1.0).is_none() {
+            return;
+        }
+        let (mut ascender, mut descender, mut lineh) = (0.0, 0.0, 0.0);
+        self.text_metrics(&mut ascender, &mut descender, &mut lineh);
+
+        // Rows are broken and drawn left aligned and then moved into place.
+        let halign = self.state().text_halign;
+        self.state_mut().text_halign = Align::Left;
+
+        let mut rows = Vec::new();
+        self.text_break_lines(span, break_row_width, &mut rows);
+        let scale = self.font_scale();
+        let style = self.text_style(scale);
+        // Rows are put into visual order with the levels of their paragraph.
+        let bidi = style.map(|style| BidiInfo::new(span, style.direction)).unwrap_or_default();
+        let line_height = self.state().line_height;
+        let mut y = y;
+        for row in &rows {
+            let dx = match halign {
+                Align::Center => break_row_width * 0.5 - row.width * 0.5,
+                Align::Right => break_row_width - row.width,
+                _ => 0.0,
+            };
+            if let Some(ref style) = style {
+                let start = row.span.as_ptr() as usize - span.as_ptr() as usize;
+                let layout = self.fonts.layout(style, span, &bidi, start, start + row.span.len());
+                let end = self.draw_text(style, scale, x + dx, y, span, &layout);
+                if row.hyphen {
+                    let layout = self.fonts.layout_text(style, "-");
+                    self.draw_text(style, scale, end, y, "-", &layout);
+                }
+            }
+            y += lineh * line_height;
+        }
+
+        self.state_mut().text_halign = halign;
+    }
+
+    fn text_bounds(&mut self, x: f32, y: f32, span: &str, bounds: &mut [f32; 4]) -> f32 {
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => return 0.0,
+        };
+
+        let (width, mut b) = self.fonts.text_bounds(&style, x * scale, y * scale, span);
+        // Use line bounds for height.
+        let (miny, maxy) = self.fonts.line_bounds(&style, y * scale);
+        b[1] = miny;
+        b[3] = maxy;
+        for (bound, b) in bounds.iter_mut().zip(b.iter()) {
+            *bound = b * invscale;
+        }
+        width * invscale
+    }
+
+    fn text_box_bounds(&mut self, x: f32, y: f32, break_row_width: f32, span: &str, bounds: &mut [f32; 4]) {
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => {
+                *bounds = [0.0; 4];
+                return;
+            }
+        };
+        let (mut ascender, mut descender, mut lineh) = (0.0, 0.0, 0.0);
+        self.text_metrics(&mut ascender, &mut descender, &mut lineh);
+
+        let halign = self.state().text_halign;
+        self.state_mut().text_halign = Align::Left;
+
+        let (rminy, rmaxy) = self.fonts.line_bounds(&TextStyle{ halign: Align::Left, ..style }, 0.0);
+        let (rminy, rmaxy) = (rminy * invscale, rmaxy * invscale);
+
+        let mut rows = Vec::new();
+        self.text_break_lines(span, break_row_width, &mut rows);
+        let line_height = self.state().line_height;
+        let (mut minx, mut miny, mut maxx, mut maxy) = (x, y, x, y);
+        let mut y = y;
+        for row in &rows {
+            // Horizontal bounds
+            let dx = match halign {
+                Align::Center => break_row_width * 0.5 - row.width * 0.5,
+                Align::Right => break_row_width - row.width,
+                _ => 0.0,
+            };
+            minx = minx.min(x + row.minx + dx);
+            maxx = maxx.max(x + row.maxx + dx);
+            // Vertical bounds.
+            miny = miny.min(y + rminy);
+            maxy = maxy.max(y + rmaxy);
+
+            y += lineh * line_height;
+        }
+
+        self.state_mut().text_halign = halign;
+
+        *bounds = [minx, miny, maxx, maxy];
+    }
+
+    fn text_glyph_positions_into<'a>(&mut self, x: f32, y: f32, span: &'a str, vec: &mut Vec<GlyphPosition<'a>>) {
+        vec.clear();
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => return,
+        };
+
+        let layout = self.fonts.layout_text(&style, span);
+        let mut iter = self.fonts.text_iter(&style, x * scale, y * scale, span, &layout, GlyphBitmap::Optional);
+        let mut prev_iter = iter;
+        let mut q = Quad::default();
+        let mut prev_start = None;
+        while self.fonts.text_iter_next(&mut iter, &mut q) {
+            if iter.prev_glyph.is_none() && self.alloc_text_atlas() {
+                iter = prev_iter;
+                self.fonts.text_iter_next(&mut iter, &mut q); // try again
+            }
+            prev_iter = iter;
+            // The caret before a right to left glyph is on its right.
+            let caret = if iter.rtl { iter.nextx } else { iter.x } * invscale;
+            let minx = iter.x.min(q.x0) * invscale;
+            let maxx = iter.nextx.max(q.x1) * invscale;
+            match vec.last_mut() {
+                // The glyphs of a cluster make up one position.
+                Some(last) if prev_start == Some(iter.start) => {
+                    if iter.rtl {
+                        last.x = last.x.max(caret);
+                    }
+                    last.minx = last.minx.min(minx);
+                    last.maxx = last.maxx.max(maxx);
+                }
+                _ => vec.push(GlyphPosition{
+                    glyph: &span[iter.start..iter.next],
+                    x:     caret,
+                    minx:  minx,
+                    maxx:  maxx,
+                }),
+            }
+            prev_start = Some(iter.start);
+        }
+        vec.sort_by_key(|position| position.glyph.as_ptr() as usize);
+    }
+
+    fn text_metrics(&mut self, ascender: &mut f32, descender: &mut f32, lineh: &mut f32) {
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => return,
+        };
+
+        if let Some((a, d, h)) = self.fonts.vert_metrics(&style) {
+            *ascender = a * invscale;
+            *descender = d * invscale;
+            *lineh = h * invscale;
+        }
+    }
+
+    fn text_break_lines<'a>(&mut self, span: &'a str, break_row_width: f32, rows: &mut Vec<TextRow<'a>>) -> isize {
+        rows.clear();
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let style = match self.text_style(scale) {
+            Some(style) => style,
+            None => return 0,
+        };
+        let points = self.break_points(span);
+
+        // Lines are broken in the order of the string, and put into visual
+        // order when they are drawn.
+        let bidi = BidiInfo::new(span, style.direction);
+        let mut layout = self.fonts.layout(&style, span, &bidi, 0, span.len());
+        layout.logical_order();
+        let mut clusters = Vec::new();
+        self.measure_clusters(&style, span, &layout, 0.0, &mut clusters);
+        let breaking = self.state().line_breaking;
+        for row in break_rows(&clusters, breaking, &points, break_row_width * scale) {
+            rows.push(TextRow{
+                span:   &span[row.start..row.end],
+                width:  row.width * invscale,
+                minx:   row.minx * invscale,
+                maxx:   row.maxx * invscale,
+                hyphen: row.hyphen,
+            });
+        }
+        let (hyphen_width, hyphen_bounds) = self.fonts.text_bounds(&style, 0.0, 0.0, "-");
+
+        // Rows with right to left text are measured in visual order, the
+        // order they are drawn in.
+        if bidi.has_rtl() {
+            let style = TextStyle{ halign: Align::Left, ..style };
+            for row in rows.iter_mut() {
+                let start = row.span.as_ptr() as usize - span.as_ptr() as usize;
+                let layout = self.fonts.layout(&style, span, &bidi, start, start + row.span.len());
+                let (mut width, mut bounds) = self.fonts.layout_bounds(&style, 0.0, 0.0, span, &layout);
+                if row.hyphen {
+                    bounds[2] = bounds[2].max(width + hyphen_bounds[2]);
+                    width += hyphen_width;
+                }
+                row.width = width * invscale;
+                row.minx = bounds[0] * invscale;
+                row.maxx = bounds[2] * invscale;
+            }
+        }
+
+        rows.len() as isize
+    }
+
+    fn rich_text_break_lines(&mut self, spans: &[TextSpan], break_row_width: f32, rows: &mut Vec<RichTextRow>) {
+        rows.clear();
+        let scale = self.font_scale();
+        let invscale = 1.0 / scale;
+        let (text, ranges) = join_spans(spans);
+        let styles: Vec<Option<TextStyle>> = spans.iter().map(|span| self.span_style(span, scale)).collect();
+        let points = self.break_points(&text);
+
+        // The spans are measured one after the other and broken as one text.
+        let bidi = BidiInfo::new(&text, self.state().text_direction);
+        let mut clusters = Vec::new();
+        let mut x = 0.0;
+        for (style, range) in styles.iter().zip(&ranges) {
+            if let Some(ref style) = *style {
+                let mut layout = self.fonts.layout(style, &text, &bidi, range.0, range.1);
+                layout.logical_order();
+                x = self.measure_clusters(style, &text, &layout, x, &mut clusters);
+            }
+        }
+        let breaking = self.state().line_breaking;
+        let line_height = self.state().line_height;
+
+        for row in break_rows(&clusters, breaking, &points, break_row_width * scale) {
+            let (mut ascender, mut descender, mut lineh) = (0.0f32, 0.0f32, 0.0f32);
+            let mut metrics = |fonts: &Fontstash, style: &TextStyle| {
+                if let Some((a, d, h)) = fonts.vert_metrics(style) {
+                    ascender = ascender.max(a);
+                    descender = descender.min(d);
+                    lineh = lineh.max(h);
+                }
+            };
+
+            // The parts of the spans in each run are measured in visual order.
+            let mut runs = Vec::new();
+            let mut x = 0.0;
+            let mut bounds = [0.0f32, 0.0f32];
+            for run in bidi.visual_runs(&text, row.start, row.end) {
+                // Empty spans have no part in any run.
+                let mut parts: Vec<usize> = (0..spans.len())
+                    .filter(|&i| ranges[i].0 < ranges[i].1 && styles[i].is_some())
+                    .filter(|&i| ranges[i].0 < run.end && ranges[i].1 > run.start)
+                    .collect();
+                if run.is_rtl() {
+                    parts.reverse();
+                }
+                for i in parts {
+                    let style = match styles[i] {
+                        Some(ref style) => style,
+                        None => continue,
+                    };
+                    let (start, end) = (ranges[i].0.max(run.start), ranges[i].1.min(run.end));
+                    let layout = self.fonts.layout(style, &text, &bidi, start, end);
+                    let (width, b) = self.fonts.layout_bounds(style, x, 0.0, &text, &layout);
+                    if runs.is_empty() {
+                        bounds = [b[0], b[2]];
+                    }
+                    bounds = [bounds[0].min(b[0]), bounds[1].max(b[2])];
+                    runs.push(RichTextRun{
+                        span:  i,
+                        start: start - ranges[i].0,
+                        end:   end - ranges[i].0,
+                        x:     x * invscale,
+                        width: width * invscale,
+                    });
+                    metrics(&self.fonts, style);
+                    x += width;
+                }
+            }
+
+            // The hyphen takes the style of the last span in the row.
+            if row.hyphen {
+                let last = runs.iter().map(|run| run.span).max();
+                if let Some(style) = last.and_then(|i| styles[i].as_ref()) {
+                    let (width, b) = self.fonts.text_bounds(style, x, 0.0, "-");
+                    bounds[1] = bounds[1].max(b[2]);
+                    x += width;
+                }
+            }
+            // Empty rows are as high as the span they are in.
+            if runs.is_empty() {
+                let span = ranges.iter().rposition(|range| range.0 <= row.start).unwrap_or(0);
+                if let Some(style) = styles.get(span).and_then(|style| style.as_ref()) {
+                    metrics(&self.fonts, style);
+                }
+            }
+
+            rows.push(RichTextRow{
+                runs:      runs,
+                width:     x * invscale,
+                minx:      bounds[0] * invscale,
+                maxx:      bounds[1] * invscale,
+                ascender:  ascender * invscale,
+                descender: descender * invscale,
+                height:    lineh * line_height * invscale,
+                hyphen:    row.hyphen,
+            });
+        }
+    }
+
+    fn rich_text_rows(&mut self, x: f32, y: f32, break_row_width: f32, spans: &[TextSpan], rows: &[RichTextRow]) {
+        let scale = self.font_scale();
+        let (text, ranges) = join_spans(spans);
+        let bidi = BidiInfo::new(&text, self.state().text_direction);
+        let halign = self.state().text_halign;
+        let fill = self.state().fill;
+
+        // Decorations are drawn over the text, as (x, y, width, height, color).
+        let mut lines = Vec::new();
+        let mut top = y;
+        for row in rows {
+            let dx = match halign {
+                Align::Center => break_row_width * 0.5 - row.width * 0.5,
+                Align::Right => break_row_width - row.width,
+                _ => 0.0,
+            };
+            let baseline = top + row.ascender;
+            let mut end = x + dx;
+            for run in &row.runs {
+                let span = &spans[run.span];
+                let style = match self.span_style(span, scale) {
+                    Some(style) => style,
+                    None => continue,
+                };
+                let (start, stop) = (ranges[run.span].0 + run.start, ranges[run.span].0 + run.end);
+                let layout = self.fonts.layout(&style, &text, &bidi, start, stop);
+                self.state_mut().fill = Paint::color(span.color);
+                self.draw_text(&style, scale, x + dx + run.x, baseline, &text, &layout);
+                end = end.max(x + dx + run.x + run.width);
+
+                // Lines are at least a pixel thick.
+                let mut line = |metrics: Option<(f32, f32)>| {
+                    if let Some((offset, thickness)) = metrics {
+                        lines.push((x + dx + run.x, baseline + offset / scale, run.width, thickness.max(1.0) / scale,
+                                    span.color));
+                    }
+                };
+                if span.decoration.contains(TextDecoration::Underline) {
+                    line(self.fonts.underline(&style));
+                }
+                if span.decoration.contains(TextDecoration::Strikethrough) {
+                    line(self.fonts.strikeout(&style));
+                }
+            }
+            if row.hyphen {
+                let last = row.runs.iter().map(|run| run.span).max();
+                if let Some(span) = last.map(|i| &spans[i]) {
+                    if let Some(style) = self.span_style(span, scale) {
+                        let layout = self.fonts.layout_text(&style, "-");
+                        self.state_mut().fill = Paint::color(span.color);
+                        self.draw_text(&style, scale, end, baseline, "-", &layout);
+                    }
+                }
+            }
+            top += row.height;
+        }
+
+        // Decorations are filled as paths, the current path is kept.
+        if !lines.is_empty() {
+            let commands = std::mem::take(&mut self.commands);
+            let (command_x, command_y) = (self.command_x, self.command_y);
+            for (x, y, width, height, color) in lines {
+                self.begin_path();
+                self.rect(x, y - height * 0.5, width, height);
+                self.state_mut().fill = Paint::color(color);
+                self.fill();
+            }
+            self.begin_path();
+            self.commands = commands;
+            self.command_x = command_x;
+            self.command_y = command_y;
+        }
+        self.state_mut().fill = fill;
+    }
+}
+
+/// Joins the text of rich text spans, returns it with the byte range of
+/// each span in it.
+fn join_spans(spans: &[TextSpan]) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::new();
+    let mut ranges = Vec::with_capacity(spans.len());
+    for span in spans {
+        let start = text.len();
+        text.push_str(span.text);
+        ranges.push((start, text.len()));
+    }
+    (text, ranges)
+}
+
+/// Breaks measured clusters into rows of at most `break_row_width`.
+/// White space is skipped at the start of rows, rows end at new-line
+/// characters, at the last break point before a cluster beyond the width,
+/// or before that cluster if the row has no break point.
+fn break_rows(clusters: &[Cluster], breaking: LineBreaking, points: &BreakPoints, break_row_width: f32) -> Vec<RowBreak> {
+    let mut rows = Vec::new();
+    let mut row_start_x = 0.0;
+    let mut row_width = 0.0;
+    let mut row_min_x = 0.0;
+    let mut row_max_x: f32 = 0.0;
+    let mut row_start: Option<usize> = None;
+    let mut row_end: Option<usize> = None;
+    let mut word_start = 0;
+    let mut word_start_x = 0.0;
+    let mut word_min_x = 0.0;
+    let mut break_end: Option<usize> = None;
+    let mut break_width = 0.0;
+    let mut break_max_x = 0.0;
+    let mut break_hyphen = false;
+    let mut ptype = CodepointType::Space;
+    let mut pcodepoint = '\0';
+    let (mut hyphen_width, mut hyphen_maxx) = (0.0, 0.0);
+
+    let push_row = |rows: &mut Vec<RowBreak>, start: usize, end: usize, width: f32, minx: f32, maxx: f32,
+                    hyphen: bool| {
+        rows.push(RowBreak{
+            start:  start,
+            end:    end,
+            width:  width,
+            minx:   minx,
+            maxx:   maxx,
+            hyphen: hyphen,
+        });
+    };
+
+    for c in clusters {
+        let ty = match (breaking, c.codepoint) {
+            (LineBreaking::NanoVG, codepoint) => match codepoint {
+                '\t' | '\u{b}' | '\u{c}' | ' ' | '\u{a0}' => CodepointType::Space,
+                '\n' => if pcodepoint == '\r' { CodepointType::Space } else { CodepointType::Newline },
+                '\r' => if pcodepoint == '\n' { CodepointType::Space } else { CodepointType::Newline },
+                '\u{85}' => CodepointType::Newline,
+                codepoint => match codepoint as u32 {
+                    0x4e00..=0x9fff | 0x3000..=0x30ff | 0xff00..=0xffef |
+                    0x1100..=0x11ff | 0x3130..=0x318f | 0xac00..=0xd7af => CodepointType::CjkChar,
+                    _ => CodepointType::Char,
+                },
+            },
+            (LineBreaking::Unicode, '\n') if pcodepoint == '\r' => CodepointType::Space,
+            (LineBreaking::Unicode, codepoint) => match linebreak::line_break_class(codepoint) {
+                LineBreakClass::BK | LineBreakClass::CR | LineBreakClass::LF |
+                LineBreakClass::NL => CodepointType::Newline,
+                LineBreakClass::SP | LineBreakClass::ZW => CodepointType::Space,
+                LineBreakClass::BA if codepoint.is_whitespace() => CodepointType::Space,
+                _ => CodepointType::Char,
+            },
+        };
+
+        if ty == CodepointType::Newline {
+            // Always handle new lines.
+            push_row(&mut rows, row_start.unwrap_or(c.start), row_end.unwrap_or(c.start),
+                     row_width, row_min_x, row_max_x, false);
+            // Set null break point
+            break_end = row_start;
+            break_width = 0.0;
+            break_max_x = 0.0;
+            break_hyphen = false;
+            // Indicate to skip the white space at the beginning of the row.
+            row_start = None;
+            row_end = None;
+            row_width = 0.0;
+            row_min_x = 0.0;
+            row_max_x = 0.0;
+        } else if row_start.is_none() {
+            // Skip white space until the beginning of the line
+            if ty == CodepointType::Char || ty == CodepointType::CjkChar {
+                // The current char is the row so far
+                row_start_x = c.x;
+                row_start = Some(c.start);
+                row_end = Some(c.end);
+                row_width = c.nextx - row_start_x;
+                row_min_x = c.minx - row_start_x;
+                row_max_x = c.maxx - row_start_x;
+                word_start = c.start;
+                word_start_x = c.x;
+                word_min_x = c.minx - row_start_x;
+                // Set null break point
+                break_end = row_start;
+                break_width = 0.0;
+                break_max_x = 0.0;
+                break_hyphen = false;
+            }
+        } else {
+            let next_width = c.nextx - row_start_x;
+            let (prev_width, prev_max_x, prev_end) = (row_width, row_max_x, row_end);
+
+            // track last non-white space character, soft hyphens only show
+            // at the end of a row
+            if (ty == CodepointType::Char || ty == CodepointType::CjkChar) && c.codepoint != '\u{ad}' {
+                row_end = Some(c.end);
+                row_width = c.nextx - row_start_x;
+                row_max_x = c.maxx - row_start_x;
+            }
+            if breaking == LineBreaking::NanoVG {
+                // track last end of a word
+                if ((ptype == CodepointType::Char || ptype == CodepointType::CjkChar) && ty == CodepointType::Space) || ty == CodepointType::CjkChar {
+                    break_end = Some(c.start);
+                    break_width = row_width;
+                    break_max_x = row_max_x;
+                    break_hyphen = false;
+                }
+                // track last beginning of a word
+                if (ptype == CodepointType::Space && ty == CodepointType::Char) || ty == CodepointType::CjkChar {
+                    word_start = c.start;
+                    word_start_x = c.x;
+                    word_min_x = c.minx;
+                }
+            } else if ty == CodepointType::Char && points.opportunities[c.start] && !points.hyphens[c.start] {
+                // A break opportunity ends the row after the last non-white
+                // space character, and starts the next one here.
+                break_end = prev_end;
+                break_width = prev_width;
+                break_max_x = prev_max_x;
+                break_hyphen = false;
+                word_start = c.start;
+                word_start_x = c.x;
+                word_min_x = c.minx;
+            }
+            // A word can break where a hyphen goes if the hyphen still fits.
+            if ty != CodepointType::Space && points.hyphens[c.start] && prev_width + hyphen_width <= break_row_width {
+                break_end = prev_end;
+                break_width = prev_width + hyphen_width;
+                break_max_x = prev_max_x.max(prev_width + hyphen_maxx);
+                break_hyphen = true;
+                word_start = c.start;
+                word_start_x = c.x;
+                word_min_x = c.minx;
+            }
+
+            // Break to new line when a character is beyond break width.
+            if (ty == CodepointType::Char || ty == CodepointType::CjkChar) && next_width > break_row_width {
+                // The run length is too long, need to break to new line.
+                if break_end == row_start {
+                    // The current word is longer than the row length, just break it from here.
+                    push_row(&mut rows, row_start.unwrap_or(c.start), c.start, prev_width, row_min_x, prev_max_x, false);
+                    row_start_x = c.x;
+                    row_start = Some(c.start);
+                    row_end = Some(c.end);
+                    row_width = c.nextx - row_start_x;
+                    row_min_x = c.minx - row_start_x;
+                    row_max_x = c.maxx - row_start_x;
+                    word_start = c.start;
+                    word_start_x = c.x;
+                    word_min_x = c.minx - row_start_x;
+                } else {
+                    // Break the line from the end of the last word, and start new line from the beginning of the new.
+                    push_row(&mut rows, row_start.unwrap_or(c.start), break_end.unwrap_or(c.start),
+                             break_width, row_min_x, break_max_x, break_hyphen);
+                    // Update row
+                    row_start_x = word_start_x;
+                    row_start = Some(word_start);
+                    row_end = Some(c.end);
+                    row_width = c.nextx - row_start_x;
+                    row_min_x = word_min_x - row_start_x;
+                    row_max_x = c.maxx - row_start_x;
+                }
+                // Set null break point
+                break_end = row_start;
+                break_width = 0.0;
+                break_max_x = 0.0;
+                break_hyphen = false;
+            }
+        }
+
+        pcodepoint = c.codepoint;
+        ptype = ty;
+        hyphen_width = c.hyphen_width;
+        hyphen_maxx = c.hyphen_maxx;
+    }
+
+    // Break the line from the end of the last word, and start new line from the beginning of the new.
+    if let Some(start) = row_start {
+        push_row(&mut rows, start, row_end.unwrap_or(start), row_width, row_min_x, row_max_x, false);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::software::SoftwareContext;
+
+    /// DejaVu Sans, for the tests which need the kerning of a real font.
+    /// They are ignored by default; run them with `--ignored` where the
+    /// font is installed.
+    const SANS: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+    /// Returns a context with a font whose glyphs are squares of half an
+    /// em, for the letters used by the tests and space.
+    fn context_with_squares() -> (SoftwareContext, FontId) {
+        let mut ctx = SoftwareContext::new(64, 64);
+        let font = ctx.create_font_mem("squares", crate::font::tests::square_font_data(" BGIabcdlms")).unwrap();
+        (ctx, font)
+    }
+
+    #[test]
+    fn rich_text_skips_empty_spans() {
+        let (mut ctx, sans) = context_with_squares();
+        let black = Color::rgb(0, 0, 0);
+        let spans = [TextSpan::new("", sans, 16.0, black), TextSpan::new("ab", sans, 16.0, black),
+                     TextSpan::new("", sans, 40.0, black), TextSpan::new("cd", sans, 16.0, black),
+                     TextSpan::new("", sans, 16.0, black)];
+        let mut rows = Vec::new();
+        ctx.rich_text_break_lines(&spans, 200.0, &mut rows);
+        assert_eq!(rows.len(), 1);
+        let runs: Vec<(usize, usize, usize)> = rows[0].runs.iter().map(|run| (run.span, run.start, run.end)).collect();
+        assert_eq!(runs, [(1, 0, 2), (3, 0, 2)]);
+        assert!(rows[0].runs.iter().all(|run| run.width > 0.0));
+        // The empty span is not on the row, so it does not make it higher.
+        assert_eq!(rows[0].height, 16.0);
+    }
+
+    #[test]
+    fn rich_text_rows_share_a_baseline() {
+        let (mut ctx, sans) = context_with_squares();
+        let black = Color::rgb(0, 0, 0);
+        let spans = [TextSpan::new("small ", sans, 10.0, black), TextSpan::new("BIG", sans, 30.0, black),
+                     TextSpan::new(" small", sans, 10.0, black)];
+        let mut rows = Vec::new();
+        ctx.rich_text_break_lines(&spans, 500.0, &mut rows);
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.runs.len(), 3);
+        assert_eq!(row.height, 30.0);
+        assert!((row.ascender - row.descender - 30.0).abs() < 1e-3);
+        // Parts follow each other without gaps.
+        for pair in row.runs.windows(2) {
+            assert!((pair[0].x + pair[0].width - pair[1].x).abs() < 1e-3);
+        }
+        let last = &row.runs[2];
+        assert!((last.x + last.width - row.width).abs() < 1e-3);
+    }
+
+    /// Returns the center and half extents of the current scissor in window space.
+    fn scissor_rect(ctx: &SoftwareContext) -> [f32; 4] {
+        let scissor = ctx.state().scissor.expect("no scissor");
+        [scissor.xform.m[4], scissor.xform.m[5], scissor.extent[0], scissor.extent[1]]
+    }
+
+    fn assert_rect(actual: [f32; 4], expected: [f32; 4]) {
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!((a - e).abs() < 1e-3, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn intersect_scissor_without_scissor_sets_it() {
+        let mut ctx = SoftwareContext::new(64, 64);
+        ctx.intersect_scissor(10.0, 20.0, 30.0, 40.0);
+        assert_rect(scissor_rect(&ctx), [25.0, 40.0, 15.0, 20.0]);
+        ctx.reset_scissor();
+        assert!(ctx.state().scissor.is_none());
+    }
+
+    #[test]
+    fn intersect_scissor_intersects_rects() {
+        let mut ctx = SoftwareContext::new(64, 64);
+        ctx.scissor(0.0, 0.0, 100.0, 50.0);
+        ctx.intersect_scissor(50.0, 20.0, 100.0, 100.0);
+        assert_rect(scissor_rect(&ctx), [75.0, 35.0, 25.0, 15.0]);
+
+        // Disjoint rects leave an empty scissor.
+        ctx.scissor(0.0, 0.0, 10.0, 10.0);
+        ctx.intersect_scissor(20.0, 20.0, 5.0, 5.0);
+        assert_rect(scissor_rect(&ctx), [20.0, 20.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn intersect_scissor_uses_the_current_transform() {
+        let mut ctx = SoftwareContext::new(64, 64);
+        ctx.scissor(0.0, 0.0, 100.0, 100.0);
+        ctx.translate(30.0, 0.0);
+        ctx.intersect_scissor(0.0, 0.0, 100.0, 100.0);
+        assert_rect(scissor_rect(&ctx), [65.0, 50.0, 35.0, 50.0]);
+
+        // The previous scissor is rotated into the current space, where its
+        // bounding box contains the new rect.
+        ctx.reset_transform();
+        ctx.scissor(0.0, 0.0, 100.0, 100.0);
+        ctx.translate(50.0, 50.0);
+        ctx.rotate(std::f32::consts::FRAC_PI_4);
+        ctx.intersect_scissor(-10.0, -10.0, 20.0, 20.0);
+        assert_rect(scissor_rect(&ctx), [50.0, 50.0, 10.0, 10.0]);
+        let m = ctx.state().scissor.unwrap().xform.m;
+        assert!((m[0] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((m[1] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+        // Rotated by 45 degrees, the previous 20x20 scissor approximates to
+        // a bounding box of about 28.28x28.28.
+        ctx.reset_transform();
+        ctx.intersect_scissor(0.0, 0.0, 100.0, 100.0);
+        let half = 10.0 * std::f32::consts::SQRT_2;
+        assert_rect(scissor_rect(&ctx), [50.0, 50.0, half, half]);
+    }
+
+    #[test]
+    #[ignore = "needs DejaVu Sans"]
+    fn text_measures_match_drawing() {
+        let mut ctx = SoftwareContext::new(64, 64);
+        let sans = ctx.create_font("sans", SANS).expect("DejaVu Sans is not installed");
+        ctx.font_face_id(sans);
+        ctx.font_size(20.0);
+        let span = "AVATAR To";
+        let mut bounds = [0.0; 4];
+        // The font kerns this pair.
+        let pair = ctx.text_bounds(0.0, 0.0, "AV", &mut bounds);
+        let single = ctx.text_bounds(0.0, 0.0, "A", &mut bounds) + ctx.text_bounds(0.0, 0.0, "V", &mut bounds);
+        assert!(pair < single - 0.5, "{} {}", pair, single);
+
+        let unspaced = ctx.text_bounds(0.0, 0.0, span, &mut bounds);
+        for &spacing in &[0.0, 2.0] {
+            ctx.text_letter_spacing(spacing);
+            let x = 10.0;
+            let end = ctx.text(x, 30.0, span);
+            let advance = ctx.text_bounds(x, 30.0, span, &mut bounds);
+            assert!((end - x - advance).abs() < 1e-3, "spacing {}: {} {}", spacing, end - x, advance);
+            // Letter spacing goes between the glyphs.
+            assert!((advance - unspaced - spacing * 8.0).abs() < 1e-3, "spacing {}: {} {}", spacing, advance, unspaced);
+
+            // Every glyph starts where the rest of the text would end.
+            let mut positions = Vec::new();
+            ctx.text_glyph_positions_into(x, 30.0, span, &mut positions);
+            assert_eq!(positions.len(), span.chars().count());
+            for p in &positions {
+                let start = p.glyph.as_ptr() as usize - span.as_ptr() as usize;
+                let rest = ctx.text_bounds(0.0, 0.0, &span[start..], &mut bounds);
+                assert!((p.x - (end - rest)).abs() < 1e-3, "spacing {}: {:?} at {}, expected {}", spacing, p.glyph, p.x, end - rest);
+            }
+        }
+    }
+}
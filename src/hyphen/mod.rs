@@ -0,0 +1,291 @@
+//! Hyphenation of words with Frank Liang's patterns, as used by TeX.
+//!
+//! A pattern is a fragment of a word with digits between its letters,
+//! such as `1tio` or `.ach4`, where `.` stands for the start or end of the
+//! word. The highest digit any pattern puts between two letters decides:
+//! odd digits allow a hyphen there, even digits forbid it.
+//!
+//! `Patterns` reads TeX pattern files, with the patterns inside
+//! `\patterns{...}` and exceptions such as `ta-ble` inside
+//! `\hyphenation{...}`. `Patterns::english` has the US English patterns.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The US English patterns.
+const EN_US: &str = include_str!("en_us.pat");
+
+/// Finds the points where words may be hyphenated. `text_box` and
+/// `text_break_lines` ask it where to break words which do not fit in a
+/// row.
+pub trait Hyphenate {
+    /// Returns the byte offsets in `word` where a hyphen may go, in order.
+    /// `word` is a run of letters without spaces or punctuation.
+    fn hyphenate(&self, word: &str) -> Vec<usize>;
+}
+
+/// Errors from loading hyphenation patterns.
+#[derive(Debug)]
+pub enum PatternError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// A pattern or exception is not valid, such as `a12b` or `-ab`.
+    Invalid(String),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PatternError::Io(ref err) => write!(f, "{}", err),
+            PatternError::Invalid(ref pattern) => write!(f, "invalid hyphenation pattern {:?}", pattern),
+        }
+    }
+}
+
+impl Error for PatternError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            PatternError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatternError {
+    fn from(err: io::Error) -> Self {
+        PatternError::Io(err)
+    }
+}
+
+/// A set of Liang hyphenation patterns for one language.
+#[derive(Debug, Clone)]
+pub struct Patterns {
+    /// Digits of the patterns by their letters, one more than letters.
+    patterns:   HashMap<String, Vec<u8>>,
+    /// Hyphen positions of exceptions by word, in characters.
+    exceptions: HashMap<String, Vec<usize>>,
+    /// Letters of the longest pattern.
+    max_len:    usize,
+    /// Minimum number of characters before the first hyphen.
+    pub left_min:  usize,
+    /// Minimum number of characters after the last hyphen.
+    pub right_min: usize,
+}
+
+impl Patterns {
+    /// Parses patterns in the format of TeX pattern files. Text outside
+    /// `\hyphenation{...}` is read as patterns, and `%` starts a comment.
+    /// Words are hyphenated with at least two characters before and three
+    /// after a hyphen, as in TeX.
+    pub fn parse(text: &str) -> Result<Patterns, PatternError> {
+        let mut patterns = Patterns{
+            patterns:   HashMap::new(),
+            exceptions: HashMap::new(),
+            max_len:    0,
+            left_min:   2,
+            right_min:  3,
+        };
+        let mut in_exceptions = false;
+        for line in text.lines() {
+            let line = line.split('%').next().unwrap_or("");
+            for token in line.split_whitespace() {
+                match token {
+                    "\\patterns{" => in_exceptions = false,
+                    "\\hyphenation{" => in_exceptions = true,
+                    _ => {
+                        let word = token.trim_end_matches('}');
+                        if in_exceptions {
+                            patterns.add_exception(word)?;
+                        } else if !word.is_empty() {
+                            patterns.add_pattern(word)?;
+                        }
+                        if word.len() < token.len() {
+                            in_exceptions = false;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(patterns)
+    }
+
+    /// Loads patterns from a TeX pattern file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Patterns, PatternError> {
+        Patterns::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the US English patterns.
+    pub fn english() -> Patterns {
+        Patterns::parse(EN_US).expect("the US English patterns are valid")
+    }
+
+    /// Adds a pattern such as `.ach4` or `1tio`.
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<(), PatternError> {
+        let mut letters = String::new();
+        let mut levels = vec![0];
+        for c in pattern.chars() {
+            match c.to_digit(10) {
+                Some(level) => {
+                    let last = levels.len() - 1;
+                    if levels[last] != 0 {
+                        return Err(PatternError::Invalid(pattern.to_owned()));
+                    }
+                    levels[last] = level as u8;
+                }
+                None => {
+                    letters.push(lowercase(c));
+                    levels.push(0);
+                }
+            }
+        }
+        if letters.is_empty() {
+            return Err(PatternError::Invalid(pattern.to_owned()));
+        }
+        self.max_len = self.max_len.max(levels.len() - 1);
+        self.patterns.insert(letters, levels);
+        Ok(())
+    }
+
+    /// Adds an exception, a word with its hyphens such as `ta-ble`. The
+    /// word is only hyphenated where the exception says.
+    pub fn add_exception(&mut self, word: &str) -> Result<(), PatternError> {
+        if word.is_empty() {
+            return Ok(());
+        }
+        let mut letters = String::new();
+        let mut hyphens = Vec::new();
+        let mut len = 0;
+        for c in word.chars() {
+            if c == '-' {
+                if len == 0 || hyphens.last() == Some(&len) {
+                    return Err(PatternError::Invalid(word.to_owned()));
+                }
+                hyphens.push(len);
+            } else {
+                letters.push(lowercase(c));
+                len += 1;
+            }
+        }
+        if hyphens.last() == Some(&len) {
+            return Err(PatternError::Invalid(word.to_owned()));
+        }
+        self.exceptions.insert(letters, hyphens);
+        Ok(())
+    }
+}
+
+impl Hyphenate for Patterns {
+    fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let chars: Vec<char> = word.chars().map(lowercase).collect();
+        let len = chars.len();
+        if len < self.left_min + self.right_min {
+            return Vec::new();
+        }
+
+        let hyphens = match self.exceptions.get(&chars.iter().collect::<String>()) {
+            Some(hyphens) => hyphens.clone(),
+            None => {
+                // Levels between the characters of `.word.`.
+                let dotted: Vec<char> = Some('.').into_iter().chain(chars.iter().cloned()).chain(Some('.')).collect();
+                let mut levels = vec![0; dotted.len() + 1];
+                let mut key = String::new();
+                for start in 0..dotted.len() {
+                    key.clear();
+                    for &c in dotted[start..].iter().take(self.max_len) {
+                        key.push(c);
+                        if let Some(pattern) = self.patterns.get(&key) {
+                            for (k, &level) in pattern.iter().enumerate() {
+                                levels[start + k] = levels[start + k].max(level);
+                            }
+                        }
+                    }
+                }
+                // Level k of the dotted word is before character k - 1.
+                (1..len).filter(|&i| levels[i + 1] % 2 == 1).collect()
+            }
+        };
+
+        // Character positions to byte offsets.
+        let offsets: Vec<usize> = word.char_indices().map(|(offset, _)| offset).collect();
+        hyphens.into_iter()
+               .filter(|&i| i >= self.left_min && i + self.right_min <= len)
+               .map(|i| offsets[i])
+               .collect()
+    }
+}
+
+/// Patterns match lowercase letters, one per character of the word.
+fn lowercase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a word with a hyphen at every hyphenation point.
+    fn hyphenated(patterns: &Patterns, word: &str) -> String {
+        let mut out = String::new();
+        let mut last = 0;
+        for i in patterns.hyphenate(word) {
+            out.push_str(&word[last..i]);
+            out.push('-');
+            last = i;
+        }
+        out.push_str(&word[last..]);
+        out
+    }
+
+    #[test]
+    fn hyphenates_english() {
+        let english = Patterns::english();
+        for &word in &["ex-ten-sive", "hy-phen-ation", "Hy-phen-ation", "al-go-rithm", "com-puter", "word", "a"] {
+            assert_eq!(hyphenated(&english, &word.replace('-', "")), word);
+        }
+    }
+
+    #[test]
+    fn exceptions_override_patterns() {
+        let english = Patterns::english();
+        for &word in &["ta-ble", "Ta-ble", "as-so-ciates", "present", "project", "ret-ri-bu-tion"] {
+            assert_eq!(hyphenated(&english, &word.replace('-', "")), word);
+        }
+
+        let patterns = Patterns::parse("\\patterns{ 1b }\n\\hyphenation{ aab-bccdd } 1c").unwrap();
+        assert_eq!(hyphenated(&patterns, "aabbccdd"), "aab-bccdd");
+        assert_eq!(hyphenated(&patterns, "AABBCCDD"), "AAB-BCCDD");
+        // Patterns after the exceptions apply too.
+        assert_eq!(hyphenated(&patterns, "xxbxxcxxx"), "xx-bxx-cxxx");
+    }
+
+    #[test]
+    fn returns_byte_offsets() {
+        let patterns = Patterns::parse("1b").unwrap();
+        assert_eq!(patterns.hyphenate("\u{e4}\u{e4}b\u{e4}b\u{e4}\u{e4}"), [4, 7]);
+        // Hyphens stay two characters from the start and three from the end.
+        assert_eq!(hyphenated(&patterns, "abababab"), "aba-ba-bab");
+        let mut patterns = patterns;
+        patterns.left_min = 1;
+        patterns.right_min = 1;
+        assert_eq!(hyphenated(&patterns, "abababab"), "a-ba-ba-ba-b");
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for &text in &["a12b", "12", "\\hyphenation{ -ab }", "\\hyphenation{ ab- }", "\\hyphenation{ a--b }"] {
+            match Patterns::parse(text) {
+                Err(PatternError::Invalid(_)) => (),
+                other => panic!("{:?}: {:?}", text, other),
+            }
+        }
+        assert!(Patterns::parse("% just a comment\n").is_ok());
+    }
+}
@@ -0,0 +1,212 @@
+//! BMP decoder for uncompressed and bitfield images.
+
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+pub fn is_bmp(data: &[u8]) -> bool {
+    data.len() > 18 && data.starts_with(b"BM")
+}
+
+/// A channel mask of a bitfield image.
+#[derive(Clone, Copy)]
+struct Mask {
+    shift: u32,
+    bits:  u32,
+    mask:  u32,
+}
+
+impl Mask {
+    fn new(mask: u32) -> Self {
+        if mask == 0 {
+            return Mask{ shift: 0, bits: 0, mask: 0 };
+        }
+        let shift = mask.trailing_zeros();
+        Mask{
+            shift: shift,
+            bits:  (mask >> shift).count_ones(),
+            mask:  mask,
+        }
+    }
+
+    /// Extracts the channel and scales it to 8 bits.
+    fn get(&self, v: u32) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let c = (v & self.mask) >> self.shift;
+        let max = (1u64 << self.bits) - 1;
+        (c as u64 * 255 / max) as u8
+    }
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_bmp(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut r = Reader::new(data);
+    r.skip(10)?;
+    let offset = r.u32_le()? as usize;
+    let header_size = r.u32_le()? as usize;
+
+    let (width, height, bpp, compression, colors_used);
+    if header_size == 12 {
+        width = r.u16_le()? as i32;
+        height = r.u16_le()? as i16 as i32;
+        r.skip(2)?;
+        bpp = r.u16_le()?;
+        compression = 0;
+        colors_used = 0;
+    } else if header_size >= 40 {
+        width = r.u32_le()? as i32;
+        height = r.u32_le()? as i32;
+        r.skip(2)?;
+        bpp = r.u16_le()?;
+        compression = r.u32_le()?;
+        r.skip(12)?;
+        colors_used = r.u32_le()? as usize;
+        r.skip(4)?;
+    } else {
+        return Err(ImageError::Unsupported("BMP header"));
+    }
+    if width <= 0 || height == 0 {
+        return Err(ImageError::Corrupt("invalid BMP size"));
+    }
+    let top_down = height < 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+    check_size(width, height)?;
+
+    // Masks follow the 40 byte header for bitfield images, or are part of
+    // the larger headers.
+    let mut masks = None;
+    if compression == 3 || compression == 6 {
+        let n = if compression == 6 || header_size >= 56 { 4 } else { 3 };
+        let mut m = [0u32; 4];
+        for mask in m.iter_mut().take(n) {
+            *mask = r.u32_le()?;
+        }
+        masks = Some([Mask::new(m[0]), Mask::new(m[1]), Mask::new(m[2]), Mask::new(m[3])]);
+    } else if compression != 0 {
+        return Err(ImageError::Unsupported("compressed BMP"));
+    }
+
+    let mut palette = Vec::new();
+    if bpp <= 8 {
+        // The palette follows the header.
+        let mut p = Reader::new(data);
+        p.skip(14 + header_size)?;
+        let entry = if header_size == 12 { 3 } else { 4 };
+        let n = if colors_used > 0 { colors_used.min(256) } else { 1 << bpp };
+        for _ in 0..n {
+            let c = p.bytes(entry)?;
+            palette.push([c[2], c[1], c[0], 255]);
+        }
+    }
+
+    let masks = match (masks, bpp) {
+        (Some(m), _) => m,
+        (None, 16) => [Mask::new(0x7c00), Mask::new(0x03e0), Mask::new(0x001f), Mask::new(0)],
+        (None, 32) => [Mask::new(0xff0000), Mask::new(0xff00), Mask::new(0xff), Mask::new(0xff000000)],
+        _ => [Mask::new(0); 4],
+    };
+
+    let stride = (width * bpp as usize).div_ceil(32) * 4;
+    let mut r = Reader::new(data);
+    r.skip(offset)?;
+    let rows = r.bytes(stride * height)?;
+
+    let mut image = RgbaImage::new(width, height);
+    let mut any_alpha = false;
+    for y in 0..height {
+        let row = &rows[y * stride..(y + 1) * stride];
+        let dy = if top_down { y } else { height - 1 - y };
+        for x in 0..width {
+            let px = match bpp {
+                1 | 4 | 8 => {
+                    let per_byte = 8 / bpp as usize;
+                    let byte = row[x / per_byte];
+                    let shift = 8 - bpp as usize * (x % per_byte + 1);
+                    let index = (byte >> shift) as usize & ((1 << bpp) - 1);
+                    palette.get(index).cloned().unwrap_or([0, 0, 0, 255])
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                16 | 32 => {
+                    let v = if bpp == 16 {
+                        row[x * 2] as u32 | (row[x * 2 + 1] as u32) << 8
+                    } else {
+                        row[x * 4] as u32 | (row[x * 4 + 1] as u32) << 8 | (row[x * 4 + 2] as u32) << 16 | (row[x * 4 + 3] as u32) << 24
+                    };
+                    let a = if masks[3].bits > 0 { masks[3].get(v) } else { 255 };
+                    [masks[0].get(v), masks[1].get(v), masks[2].get(v), a]
+                }
+                _ => return Err(ImageError::Unsupported("BMP bit depth")),
+            };
+            any_alpha |= px[3] != 0;
+            let o = (dy * width + x) * 4;
+            image.pixels[o..o + 4].copy_from_slice(&px);
+        }
+    }
+
+    // Many writers leave the alpha channel of 32 bit images zeroed.
+    if !any_alpha {
+        for px in image.pixels.chunks_mut(4) {
+            px[3] = 255;
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a 2x1 BMP with an info header and the given pixel rows.
+    fn bmp(bpp: u16, compression: u32, extra: &[u8], rows: &[u8]) -> Vec<u8> {
+        let offset = 54 + extra.len() as u32;
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&(offset + rows.len() as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&offset.to_le_bytes());
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&2i32.to_le_bytes());
+        bmp.extend_from_slice(&1i32.to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&bpp.to_le_bytes());
+        bmp.extend_from_slice(&compression.to_le_bytes());
+        bmp.extend_from_slice(&[0; 20]);
+        bmp.extend_from_slice(extra);
+        bmp.extend_from_slice(rows);
+        bmp
+    }
+
+    #[test]
+    fn decodes_true_color() {
+        let data = bmp(24, 0, &[], &[30, 20, 10, 60, 50, 40, 0, 0]);
+        assert_eq!(decode(&data).unwrap().pixels, [10, 20, 30, 255, 40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn decodes_bitfields() {
+        // 16 bit 5-6-5.
+        let masks = [0x00, 0xf8, 0, 0, 0xe0, 0x07, 0, 0, 0x1f, 0, 0, 0];
+        let data = bmp(16, 3, &masks, &[0x00, 0xf8, 0x1f, 0x00]);
+        assert_eq!(decode(&data).unwrap().pixels, [255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        assert!(decode(&bmp(24, 0, &[], &[1, 2, 3])).is_err());
+        assert!(decode(&bmp(24, 1, &[], &[0; 8])).is_err());
+        assert!(decode(&bmp(7, 0, &[], &[0; 8])).is_err());
+        let mut data = bmp(24, 0, &[], &[0; 8]);
+        data[18..22].copy_from_slice(&(-2i32).to_le_bytes());
+        assert!(decode(&data).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        let palette = [0, 0, 255, 0, 255, 0, 0, 0];
+        let data = bmp(1, 0, &palette, &[0x40, 0, 0, 0]);
+        assert_eq!(decode(&data).unwrap().pixels, [255, 0, 0, 255, 0, 0, 255, 255]);
+        crate::image::tests::check_malformed(&data);
+        crate::image::tests::check_malformed(&bmp(32, 0, &[], &[1, 2, 3, 4, 5, 6, 7, 8]));
+    }
+}
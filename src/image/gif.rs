@@ -0,0 +1,228 @@
+//! GIF decoder. Only the first frame of an animation is decoded.
+
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+pub fn is_gif(data: &[u8]) -> bool {
+    data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")
+}
+
+fn read_palette(r: &mut Reader, size: usize) -> Result<Vec<[u8; 4]>, ImageError> {
+    let bytes = r.bytes(size * 3)?;
+    Ok(bytes.chunks(3).map(|c| [c[0], c[1], c[2], 255]).collect())
+}
+
+/// Concatenates the data sub-blocks following the current position.
+fn read_sub_blocks(r: &mut Reader) -> Result<Vec<u8>, ImageError> {
+    let mut data = Vec::new();
+    loop {
+        let len = r.u8()? as usize;
+        if len == 0 {
+            return Ok(data);
+        }
+        data.extend_from_slice(r.bytes(len)?);
+    }
+}
+
+/// Decodes LZW compressed color indices. Decoding stops once `count`
+/// indices were produced or at the end of the data.
+fn lzw_decode(data: &[u8], min_code_size: u32, count: usize) -> Result<Vec<u8>, ImageError> {
+    if !(1..=11).contains(&min_code_size) {
+        return Err(ImageError::Corrupt("invalid GIF code size"));
+    }
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+
+    // Every code is a prefix code plus one suffix byte.
+    let mut prefix = vec![0u16; 4096];
+    let mut suffix = vec![0u8; 4096];
+    let mut first = vec![0u8; 4096];
+    for i in 0..clear {
+        suffix[i] = i as u8;
+        first[i] = i as u8;
+    }
+
+    let mut out = Vec::with_capacity(count);
+    let mut stack = Vec::new();
+    let mut code_size = min_code_size + 1;
+    let mut next = end + 1;
+    let mut prev: Option<usize> = None;
+    let (mut bits, mut nbits, mut pos) = (0u32, 0u32, 0usize);
+
+    while out.len() < count {
+        while nbits < code_size {
+            if pos >= data.len() {
+                return Ok(out);
+            }
+            bits |= (data[pos] as u32) << nbits;
+            pos += 1;
+            nbits += 8;
+        }
+        let code = (bits & ((1 << code_size) - 1)) as usize;
+        bits >>= code_size;
+        nbits -= code_size;
+
+        if code == clear {
+            code_size = min_code_size + 1;
+            next = end + 1;
+            prev = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+
+        let p = match prev {
+            Some(p) => p,
+            None => {
+                if code >= clear {
+                    return Err(ImageError::Corrupt("invalid GIF code"));
+                }
+                out.push(code as u8);
+                prev = Some(code);
+                continue;
+            }
+        };
+
+        let known = code < next;
+        if !known && code != next {
+            return Err(ImageError::Corrupt("invalid GIF code"));
+        }
+        if next < 4096 {
+            prefix[next] = p as u16;
+            first[next] = first[p];
+            suffix[next] = if known { first[code] } else { first[p] };
+            next += 1;
+            if next == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+
+        let mut c = code;
+        stack.clear();
+        while c >= clear {
+            stack.push(suffix[c]);
+            c = prefix[c] as usize;
+        }
+        stack.push(c as u8);
+        out.extend(stack.iter().rev());
+        prev = Some(code);
+    }
+    out.truncate(count);
+    Ok(out)
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_gif(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut r = Reader::new(data);
+    r.skip(6)?;
+    let width = r.u16_le()? as usize;
+    let height = r.u16_le()? as usize;
+    let flags = r.u8()?;
+    r.skip(2)?; // background color and aspect ratio
+    check_size(width, height)?;
+    let global = if flags & 0x80 != 0 {
+        Some(read_palette(&mut r, 2 << (flags & 7))?)
+    } else {
+        None
+    };
+
+    let mut transparent = None;
+    loop {
+        match r.u8()? {
+            // Extension
+            0x21 => {
+                let label = r.u8()?;
+                let block = read_sub_blocks(&mut r)?;
+                if label == 0xf9 && block.len() >= 4 && block[0] & 1 != 0 {
+                    transparent = Some(block[3] as usize);
+                }
+            }
+            // Image descriptor
+            0x2c => {
+                let x0 = r.u16_le()? as usize;
+                let y0 = r.u16_le()? as usize;
+                let w = r.u16_le()? as usize;
+                let h = r.u16_le()? as usize;
+                let flags = r.u8()?;
+                let local = if flags & 0x80 != 0 {
+                    Some(read_palette(&mut r, 2 << (flags & 7))?)
+                } else {
+                    None
+                };
+                let interlaced = flags & 0x40 != 0;
+                let mut palette = local.or_else(|| global.clone())
+                    .ok_or(ImageError::Corrupt("missing GIF color table"))?;
+                if let Some(index) = transparent {
+                    if index < palette.len() {
+                        palette[index][3] = 0;
+                    }
+                }
+
+                let min_code_size = r.u8()? as u32;
+                let lzw = read_sub_blocks(&mut r)?;
+                let indices = lzw_decode(&lzw, min_code_size, w * h)?;
+
+                // Rows of interlaced images are stored in four passes.
+                let mut rows = Vec::with_capacity(h);
+                if interlaced {
+                    for &(start, step) in &[(0, 8), (4, 8), (2, 4), (1, 2)] {
+                        rows.extend((start..h).step_by(step));
+                    }
+                } else {
+                    rows.extend(0..h);
+                }
+
+                let mut image = RgbaImage::new(width, height);
+                for (i, &index) in indices.iter().enumerate() {
+                    let (x, y) = (x0 + i % w, y0 + rows[i / w]);
+                    if x < width && y < height {
+                        let px = palette.get(index as usize).cloned().unwrap_or([0, 0, 0, 0]);
+                        let o = (y * width + x) * 4;
+                        image.pixels[o..o + 4].copy_from_slice(&px);
+                    }
+                }
+                return Ok(image);
+            }
+            0x3b => return Err(ImageError::Corrupt("GIF without images")),
+            _ => return Err(ImageError::Corrupt("invalid GIF block")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1x1 GIF with a white pixel from a two color palette.
+    const WHITE: [u8; 35] = [
+        0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00,
+        0x00, 0x2c, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+    ];
+
+    #[test]
+    fn decodes_first_frame() {
+        let image = decode(&WHITE).unwrap();
+        assert_eq!((image.width, image.height), (1, 1));
+        assert_eq!(image.pixels, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        // LZW code size out of range.
+        let mut data = WHITE;
+        data[29] = 12;
+        assert!(decode(&data).is_err());
+        // Trailer before any image.
+        let mut data = WHITE[..19].to_vec();
+        data.push(0x3b);
+        assert!(decode(&data).is_err());
+        assert!(lzw_decode(&[0xff, 0xff], 2, 4).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        crate::image::tests::check_malformed(&WHITE);
+    }
+}
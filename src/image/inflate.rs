@@ -0,0 +1,323 @@
+//! Decompression of zlib streams, as used by PNG.
+//!
+//! A straightforward implementation of RFC 1950 and RFC 1951 after
+//! zlib's `puff`: Huffman codes are decoded a bit at a time from the
+//! count of codes of every length.
+
+use super::ImageError;
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits of the length codes 257..285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base offsets and extra bits of the distance codes 0..29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+/// Order in which code length code lengths are stored.
+const CLEN_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn corrupt() -> ImageError {
+    ImageError::Corrupt("invalid deflate stream")
+}
+
+struct BitReader<'a> {
+    data:  &'a [u8],
+    pos:   usize,
+    bits:  u32,
+    nbits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader{
+            data:  data,
+            pos:   0,
+            bits:  0,
+            nbits: 0,
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, ImageError> {
+        while self.nbits < n {
+            let byte = *self.data.get(self.pos).ok_or_else(corrupt)?;
+            self.pos += 1;
+            self.bits |= (byte as u32) << self.nbits;
+            self.nbits += 8;
+        }
+        let value = self.bits & ((1u32 << n) - 1);
+        self.bits >>= n;
+        self.nbits -= n;
+        Ok(value)
+    }
+
+    /// Discards the bits up to the next byte boundary.
+    fn align(&mut self) {
+        self.bits = 0;
+        self.nbits = 0;
+    }
+}
+
+/// Canonical Huffman code, stored as the number of codes of each length
+/// and the symbols ordered by code.
+struct Huffman {
+    count:  [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, ImageError> {
+        let mut count = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            count[len as usize] += 1;
+        }
+        // Check for an over-subscribed code. Incomplete codes are allowed.
+        let mut left: i32 = 1;
+        for &c in &count[1..] {
+            left <<= 1;
+            left -= c as i32;
+            if left < 0 {
+                return Err(corrupt());
+            }
+        }
+        let mut offs = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offs[len + 1] = offs[len] + count[len];
+        }
+        let mut symbol = vec![0u16; lengths.len()];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbol[offs[len as usize] as usize] = sym as u16;
+                offs[len as usize] += 1;
+            }
+        }
+        Ok(Huffman{
+            count:  count,
+            symbol: symbol,
+        })
+    }
+
+    fn decode(&self, br: &mut BitReader) -> Result<u16, ImageError> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= br.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(corrupt())
+    }
+}
+
+fn stored(br: &mut BitReader, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    br.align();
+    let data = br.data;
+    let pos = br.pos;
+    if pos + 4 > data.len() {
+        return Err(corrupt());
+    }
+    let len = data[pos] as usize | (data[pos + 1] as usize) << 8;
+    let nlen = data[pos + 2] as usize | (data[pos + 3] as usize) << 8;
+    if len != !nlen & 0xffff || pos + 4 + len > data.len() {
+        return Err(corrupt());
+    }
+    out.extend_from_slice(&data[pos + 4..pos + 4 + len]);
+    br.pos = pos + 4 + len;
+    Ok(())
+}
+
+fn codes(br: &mut BitReader, out: &mut Vec<u8>, lencode: &Huffman, distcode: &Huffman) -> Result<(), ImageError> {
+    loop {
+        let symbol = lencode.decode(br)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            let symbol = symbol - 257;
+            if symbol >= 29 {
+                return Err(corrupt());
+            }
+            let len = LENGTH_BASE[symbol] as usize + br.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+            let symbol = distcode.decode(br)? as usize;
+            if symbol >= 30 {
+                return Err(corrupt());
+            }
+            let dist = DIST_BASE[symbol] as usize + br.bits(DIST_EXTRA[symbol] as u32)? as usize;
+            if dist > out.len() {
+                return Err(corrupt());
+            }
+            let start = out.len() - dist;
+            for i in 0..len {
+                let byte = out[start + i];
+                out.push(byte);
+            }
+        }
+    }
+}
+
+fn fixed(br: &mut BitReader, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    let mut lengths = [0u8; 288];
+    for (sym, len) in lengths.iter_mut().enumerate() {
+        *len = match sym {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    let lencode = Huffman::new(&lengths)?;
+    let distcode = Huffman::new(&[5u8; 30])?;
+    codes(br, out, &lencode, &distcode)
+}
+
+fn dynamic(br: &mut BitReader, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    let nlen = br.bits(5)? as usize + 257;
+    let ndist = br.bits(5)? as usize + 1;
+    let ncode = br.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err(corrupt());
+    }
+
+    let mut lengths = [0u8; 320];
+    for &index in &CLEN_ORDER[..ncode] {
+        lengths[index] = br.bits(3)? as u8;
+    }
+    let lencode = Huffman::new(&lengths[..19])?;
+
+    let mut index = 0;
+    while index < nlen + ndist {
+        let symbol = lencode.decode(br)?;
+        if symbol < 16 {
+            lengths[index] = symbol as u8;
+            index += 1;
+            continue;
+        }
+        let (len, repeat) = match symbol {
+            16 => {
+                if index == 0 {
+                    return Err(corrupt());
+                }
+                (lengths[index - 1], 3 + br.bits(2)? as usize)
+            }
+            17 => (0, 3 + br.bits(3)? as usize),
+            _ => (0, 11 + br.bits(7)? as usize),
+        };
+        if index + repeat > nlen + ndist {
+            return Err(corrupt());
+        }
+        for _ in 0..repeat {
+            lengths[index] = len;
+            index += 1;
+        }
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt());
+    }
+
+    let lencode = Huffman::new(&lengths[..nlen])?;
+    let distcode = Huffman::new(&lengths[nlen..nlen + ndist])?;
+    codes(br, out, &lencode, &distcode)
+}
+
+/// Decompresses a raw deflate stream and appends it to `out`.
+pub fn inflate(data: &[u8], out: &mut Vec<u8>) -> Result<(), ImageError> {
+    let mut br = BitReader::new(data);
+    loop {
+        let last = br.bits(1)?;
+        match br.bits(2)? {
+            0 => stored(&mut br, out)?,
+            1 => fixed(&mut br, out)?,
+            2 => dynamic(&mut br, out)?,
+            _ => return Err(corrupt()),
+        }
+        if last == 1 {
+            return Ok(());
+        }
+    }
+}
+
+/// Decompresses a zlib stream. The Adler-32 checksum is not verified.
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    if data.len() < 2 {
+        return Err(corrupt());
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) || cmf & 15 != 8 {
+        return Err(ImageError::Corrupt("invalid zlib header"));
+    }
+    if flg & 32 != 0 {
+        return Err(ImageError::Unsupported("preset zlib dictionary"));
+    }
+    let mut out = Vec::new();
+    inflate(&data[2..], &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompresses_fixed_codes() {
+        let data = [0x78, 0xda, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x85, 0x8c, 0xd4, 0x9c, 0x9c, 0x7c, 0x08, 0x09, 0x00,
+                    0x70, 0x12, 0x09, 0x01];
+        assert_eq!(zlib_decompress(&data).unwrap(), b"abcabcabcabc hello hello");
+    }
+
+    #[test]
+    fn decompresses_dynamic_codes() {
+        let data = [0x78, 0xda, 0x2d, 0xc1, 0x01, 0x0a, 0x00, 0x10, 0x0c, 0x00, 0xc0, 0xaf, 0xf8, 0x1a, 0x63, 0xac, 0x36,
+                    0x8a, 0x51, 0xbc, 0xde, 0x94, 0x3b, 0xe7, 0x3e, 0x6f, 0x02, 0x40, 0x8c, 0xc9, 0x20, 0x62, 0x2e, 0x85,
+                    0x88, 0x98, 0x59, 0x44, 0xea, 0xd3, 0x4c, 0xef, 0xe3, 0x51, 0xd5, 0xb9, 0xf6, 0xb9, 0x59, 0x82, 0x16,
+                    0x97];
+        let out = zlib_decompress(&data).unwrap();
+        assert_eq!(&out[..], &b"         aaaabccddeeeefffghhiiilllmmmnnnnnoooorrssssstttuvyz"[..]);
+
+        // Every truncation and corruption fails or decodes, without panicking.
+        for len in 0..data.len() {
+            let _ = zlib_decompress(&data[..len]);
+        }
+        let mut copy = data;
+        for i in 0..data.len() {
+            for &byte in &[0x00, 0x55, 0xaa, 0xff] {
+                copy[i] = byte;
+                let _ = zlib_decompress(&copy);
+            }
+            copy[i] = data[i];
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        // Bad header, preset dictionary, reserved block type.
+        assert!(zlib_decompress(&[0x78, 0x00, 0x03, 0x00]).is_err());
+        assert!(zlib_decompress(&[0x78, 0xbb, 0, 0, 0, 0]).is_err());
+        assert!(zlib_decompress(&[0x78, 0x01, 0x07]).is_err());
+        // Stored block with a length which does not match its complement.
+        assert!(zlib_decompress(&[0x78, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 1, 2]).is_err());
+        // Fixed block copying from before the start of the output.
+        assert!(zlib_decompress(&[0x78, 0x01, 0x03, 0x02, 0x00]).is_err());
+    }
+}
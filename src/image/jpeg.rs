@@ -0,0 +1,780 @@
+//! JPEG decoder for baseline and progressive Huffman coded images.
+
+use std::f32::consts::PI;
+
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+/// Natural order index of the coefficients in zigzag order.
+const ZIGZAG: [usize; 64] = [
+     0,  1,  8, 16,  9,  2,  3, 10,
+    17, 24, 32, 25, 18, 11,  4,  5,
+    12, 19, 26, 33, 40, 48, 41, 34,
+    27, 20, 13,  6,  7, 14, 21, 28,
+    35, 42, 49, 56, 57, 50, 43, 36,
+    29, 22, 15, 23, 30, 37, 44, 51,
+    58, 59, 52, 45, 38, 31, 39, 46,
+    53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.len() > 3 && data[0] == 0xff && data[1] == 0xd8 && data[2] == 0xff
+}
+
+fn corrupt() -> ImageError {
+    ImageError::Corrupt("invalid JPEG data")
+}
+
+#[derive(Clone)]
+struct Huffman {
+    /// Largest code of every length, or -1 if there are none.
+    maxcode: [i32; 18],
+    /// Index into `values` of the first code of every length, minus that code.
+    valptr:  [i32; 17],
+    values:  Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], values: &[u8]) -> Self {
+        let mut maxcode = [-1i32; 18];
+        let mut valptr = [0i32; 17];
+        let mut code = 0i32;
+        let mut k = 0i32;
+        for len in 1..=16 {
+            let n = counts[len - 1] as i32;
+            valptr[len] = k - code;
+            if n > 0 {
+                code += n;
+                k += n;
+                maxcode[len] = code - 1;
+            }
+            code <<= 1;
+        }
+        maxcode[17] = i32::MAX;
+        Huffman{
+            maxcode: maxcode,
+            valptr:  valptr,
+            values:  values.to_vec(),
+        }
+    }
+}
+
+/// Reads bits of entropy coded data, skipping stuffed zero bytes and
+/// stopping at markers.
+struct BitReader<'a> {
+    data:   &'a [u8],
+    pos:    usize,
+    bits:   u32,
+    nbits:  u32,
+    marker: Option<u8>,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        BitReader{
+            data:   data,
+            pos:    pos,
+            bits:   0,
+            nbits:  0,
+            marker: None,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.nbits <= 24 {
+            let mut byte = 0;
+            if self.marker.is_none() && self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xff {
+                    let next = self.data.get(self.pos + 1).cloned().unwrap_or(0xd9);
+                    if next == 0 {
+                        self.pos += 2;
+                    } else {
+                        self.marker = Some(next);
+                        byte = 0;
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            // Past the end of the data the stream is padded with zeros.
+            self.bits |= (byte as u32) << (24 - self.nbits);
+            self.nbits += 8;
+        }
+    }
+
+    fn bit(&mut self) -> u32 {
+        self.bits(1)
+    }
+
+    fn bits(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        if self.nbits < n {
+            self.fill();
+        }
+        let value = self.bits >> (32 - n);
+        self.bits <<= n;
+        self.nbits -= n;
+        value
+    }
+
+    /// Reads `n` bits and sign extends them as a JPEG coefficient. `n` is
+    /// a decoded Huffman symbol, which is never more than 16 in valid data.
+    fn receive_extend(&mut self, n: u32) -> Result<i32, ImageError> {
+        if n == 0 {
+            return Ok(0);
+        }
+        if n > 16 {
+            return Err(corrupt());
+        }
+        let v = self.bits(n) as i32;
+        if v < 1 << (n - 1) {
+            Ok(v - (1 << n) + 1)
+        } else {
+            Ok(v)
+        }
+    }
+
+    fn decode(&mut self, table: &Huffman) -> Result<u8, ImageError> {
+        let mut code = 0i32;
+        for len in 1..=16 {
+            code = (code << 1) | self.bit() as i32;
+            if code <= table.maxcode[len] {
+                let index = (table.valptr[len] + code) as usize;
+                return table.values.get(index).cloned().ok_or_else(corrupt);
+            }
+        }
+        Err(corrupt())
+    }
+
+    /// Skips to the restart marker which must follow a restart interval.
+    fn restart(&mut self) -> Result<(), ImageError> {
+        self.bits = 0;
+        self.nbits = 0;
+        if self.marker.is_none() {
+            // Find the marker, there may be padding before it.
+            while self.pos + 1 < self.data.len() && !(self.data[self.pos] == 0xff && self.data[self.pos + 1] != 0 && self.data[self.pos + 1] != 0xff) {
+                self.pos += 1;
+            }
+            if self.pos + 1 >= self.data.len() {
+                return Err(corrupt());
+            }
+            self.marker = Some(self.data[self.pos + 1]);
+        }
+        match self.marker {
+            Some(m) if (0xd0..=0xd7).contains(&m) => {
+                self.pos += 2;
+                self.marker = None;
+                Ok(())
+            }
+            _ => Err(corrupt()),
+        }
+    }
+
+    /// Returns the position of the first byte after the entropy coded data.
+    fn end(&mut self) -> usize {
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xff && self.data[self.pos + 1] != 0 && !(0xd0..=0xd7).contains(&self.data[self.pos + 1]) {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.pos
+    }
+}
+
+struct Component {
+    id:       u8,
+    h:        usize,
+    v:        usize,
+    tq:       usize,
+    /// Blocks per line and per column, padded to whole MCUs.
+    bw:       usize,
+    bh:       usize,
+    coefs:    Vec<[i32; 64]>,
+    dc_pred:  i32,
+    dc_table: usize,
+    ac_table: usize,
+}
+
+struct Frame {
+    width:       usize,
+    height:      usize,
+    progressive: bool,
+    hmax:        usize,
+    vmax:        usize,
+    mcux:        usize,
+    mcuy:        usize,
+    components:  Vec<Component>,
+}
+
+struct Scan {
+    comps:  Vec<usize>,
+    ss:     usize,
+    se:     usize,
+    ah:     u32,
+    al:     u32,
+}
+
+struct Decoder {
+    qt:       [[u16; 64]; 4],
+    dc:       Vec<Option<Huffman>>,
+    ac:       Vec<Option<Huffman>>,
+    restart:  usize,
+    eobrun:   u32,
+    frame:    Option<Frame>,
+    adobe:    Option<u8>,
+}
+
+impl Decoder {
+    fn table(tables: &[Option<Huffman>], index: usize) -> Result<&Huffman, ImageError> {
+        tables.get(index).and_then(|t| t.as_ref()).ok_or(ImageError::Corrupt("missing JPEG Huffman table"))
+    }
+
+    fn decode_block(&mut self, br: &mut BitReader, c: usize, block: usize, scan: &Scan) -> Result<(), ImageError> {
+        let frame = self.frame.as_mut().unwrap();
+        let comp = &mut frame.components[c];
+        let coefs = &mut comp.coefs[block];
+
+        if !frame.progressive {
+            let t = br.decode(Self::table(&self.dc, comp.dc_table)?)? as u32;
+            comp.dc_pred = comp.dc_pred.wrapping_add(br.receive_extend(t)?);
+            coefs[0] = comp.dc_pred;
+            let ac = Self::table(&self.ac, comp.ac_table)?;
+            let mut k = 1;
+            while k < 64 {
+                let rs = br.decode(ac)?;
+                let (r, s) = ((rs >> 4) as usize, (rs & 15) as u32);
+                if s == 0 {
+                    if r != 15 {
+                        break;
+                    }
+                    k += 16;
+                    continue;
+                }
+                k += r;
+                if k > 63 {
+                    return Err(corrupt());
+                }
+                coefs[ZIGZAG[k]] = br.receive_extend(s)?;
+                k += 1;
+            }
+            return Ok(());
+        }
+
+        if scan.ss == 0 {
+            // DC scans.
+            if scan.ah == 0 {
+                let t = br.decode(Self::table(&self.dc, comp.dc_table)?)? as u32;
+                comp.dc_pred = comp.dc_pred.wrapping_add(br.receive_extend(t)?);
+                coefs[0] = comp.dc_pred.wrapping_mul(1 << scan.al);
+            } else if br.bit() == 1 {
+                coefs[0] |= 1 << scan.al;
+            }
+            return Ok(());
+        }
+
+        let ac = Self::table(&self.ac, comp.ac_table)?;
+        if scan.ah == 0 {
+            // First AC scan.
+            if self.eobrun > 0 {
+                self.eobrun -= 1;
+                return Ok(());
+            }
+            let mut k = scan.ss;
+            while k <= scan.se {
+                let rs = br.decode(ac)?;
+                let (r, s) = ((rs >> 4) as u32, (rs & 15) as u32);
+                if s == 0 {
+                    if r < 15 {
+                        self.eobrun = (1 << r) - 1 + br.bits(r);
+                        break;
+                    }
+                    k += 16;
+                    continue;
+                }
+                k += r as usize;
+                if k > 63 {
+                    return Err(corrupt());
+                }
+                coefs[ZIGZAG[k]] = br.receive_extend(s)? * (1 << scan.al);
+                k += 1;
+            }
+            return Ok(());
+        }
+
+        // AC refinement scan.
+        let p1 = 1 << scan.al;
+        let m1 = -1 << scan.al;
+        let refine = |br: &mut BitReader, coef: &mut i32| {
+            if br.bit() == 1 && *coef & p1 == 0 {
+                *coef += if *coef >= 0 { p1 } else { m1 };
+            }
+        };
+        let mut k = scan.ss;
+        if self.eobrun == 0 {
+            while k <= scan.se {
+                let rs = br.decode(ac)?;
+                let (mut r, s) = ((rs >> 4) as i32, rs & 15);
+                let mut value = 0;
+                if s != 0 {
+                    value = if br.bit() == 1 { p1 } else { m1 };
+                } else if r != 15 {
+                    self.eobrun = (1 << r) + br.bits(r as u32);
+                    break;
+                }
+                while k <= scan.se {
+                    let coef = &mut coefs[ZIGZAG[k]];
+                    if *coef != 0 {
+                        refine(br, coef);
+                    } else {
+                        if r == 0 {
+                            break;
+                        }
+                        r -= 1;
+                    }
+                    k += 1;
+                }
+                if value != 0 && k <= scan.se {
+                    coefs[ZIGZAG[k]] = value;
+                }
+                k += 1;
+            }
+        }
+        if self.eobrun > 0 {
+            while k <= scan.se {
+                let coef = &mut coefs[ZIGZAG[k]];
+                if *coef != 0 {
+                    refine(br, coef);
+                }
+                k += 1;
+            }
+            self.eobrun -= 1;
+        }
+        Ok(())
+    }
+
+    fn decode_scan(&mut self, data: &[u8], pos: usize, scan: &Scan) -> Result<usize, ImageError> {
+        let mut br = BitReader::new(data, pos);
+        self.eobrun = 0;
+        for comp in &mut self.frame.as_mut().unwrap().components {
+            comp.dc_pred = 0;
+        }
+
+        let (mcux, mcuy, hmax, vmax, width, height) = {
+            let f = self.frame.as_ref().unwrap();
+            (f.mcux, f.mcuy, f.hmax, f.vmax, f.width, f.height)
+        };
+        let mut todo = if self.restart > 0 { self.restart } else { usize::MAX };
+        let mut restart = |dec: &mut Decoder, br: &mut BitReader, last: bool| -> Result<(), ImageError> {
+            todo -= 1;
+            if todo == 0 && !last {
+                br.restart()?;
+                todo = dec.restart;
+                dec.eobrun = 0;
+                for comp in &mut dec.frame.as_mut().unwrap().components {
+                    comp.dc_pred = 0;
+                }
+            }
+            Ok(())
+        };
+
+        if scan.comps.len() == 1 {
+            // Non-interleaved scans cover only the blocks inside the image.
+            let c = scan.comps[0];
+            let (h, v, bw) = {
+                let comp = &self.frame.as_ref().unwrap().components[c];
+                (comp.h, comp.v, comp.bw)
+            };
+            let cw = (width * h).div_ceil(hmax).div_ceil(8);
+            let ch = (height * v).div_ceil(vmax).div_ceil(8);
+            for by in 0..ch {
+                for bx in 0..cw {
+                    self.decode_block(&mut br, c, by * bw + bx, scan)?;
+                    restart(self, &mut br, by + 1 == ch && bx + 1 == cw)?;
+                }
+            }
+        } else {
+            for my in 0..mcuy {
+                for mx in 0..mcux {
+                    for &c in &scan.comps {
+                        let (h, v, bw) = {
+                            let comp = &self.frame.as_ref().unwrap().components[c];
+                            (comp.h, comp.v, comp.bw)
+                        };
+                        for y in 0..v {
+                            for x in 0..h {
+                                let block = (my * v + y) * bw + mx * h + x;
+                                self.decode_block(&mut br, c, block, scan)?;
+                            }
+                        }
+                    }
+                    restart(self, &mut br, my + 1 == mcuy && mx + 1 == mcux)?;
+                }
+            }
+        }
+        Ok(br.end())
+    }
+
+    /// Dequantizes and transforms every block, returning one plane of
+    /// samples per component.
+    fn planes(&self) -> Vec<Vec<u8>> {
+        let frame = self.frame.as_ref().unwrap();
+        let mut cos = [[0.0f32; 8]; 8];
+        for (x, row) in cos.iter_mut().enumerate() {
+            for (u, c) in row.iter_mut().enumerate() {
+                let cu = if u == 0 { f32::sqrt(0.5) } else { 1.0 };
+                *c = cu * f32::cos((2 * x + 1) as f32 * u as f32 * PI / 16.0) * 0.5;
+            }
+        }
+
+        frame.components.iter().map(|comp| {
+            let stride = comp.bw * 8;
+            let mut plane = vec![0u8; stride * comp.bh * 8];
+            let q = &self.qt[comp.tq];
+            for by in 0..comp.bh {
+                for bx in 0..comp.bw {
+                    let coefs = &comp.coefs[by * comp.bw + bx];
+                    let mut f = [0.0f32; 64];
+                    for i in 0..64 {
+                        f[i] = coefs[i] as f32 * q[i] as f32;
+                    }
+                    // Rows, then columns.
+                    let mut tmp = [0.0f32; 64];
+                    for v in 0..8 {
+                        for x in 0..8 {
+                            let mut sum = 0.0;
+                            for u in 0..8 {
+                                sum += cos[x][u] * f[v * 8 + u];
+                            }
+                            tmp[v * 8 + x] = sum;
+                        }
+                    }
+                    for x in 0..8 {
+                        for y in 0..8 {
+                            let mut sum = 0.0;
+                            for v in 0..8 {
+                                sum += cos[y][v] * tmp[v * 8 + x];
+                            }
+                            let value = (sum + 128.0).round().clamp(0.0, 255.0) as u8;
+                            plane[(by * 8 + y) * stride + bx * 8 + x] = value;
+                        }
+                    }
+                }
+            }
+            plane
+        }).collect()
+    }
+
+    fn output(&self) -> RgbaImage {
+        let frame = self.frame.as_ref().unwrap();
+        let planes = self.planes();
+        let mut image = RgbaImage::new(frame.width, frame.height);
+        let comps = &frame.components;
+        let rgb = comps.len() == 3 && (self.adobe == Some(0) || (comps[0].id == b'R' && comps[1].id == b'G' && comps[2].id == b'B'));
+
+        let sample = |c: usize, x: usize, y: usize| {
+            let comp = &comps[c];
+            let sx = x * comp.h / frame.hmax;
+            let sy = y * comp.v / frame.vmax;
+            planes[c][sy * comp.bw * 8 + sx] as f32
+        };
+        let ycc = |y: f32, cb: f32, cr: f32| {
+            let cb = cb - 128.0;
+            let cr = cr - 128.0;
+            [y + 1.402 * cr, y - 0.344136 * cb - 0.714136 * cr, y + 1.772 * cb]
+        };
+        let clamp = |v: f32| (v + 0.5).clamp(0.0, 255.0) as u8;
+
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let px = match comps.len() {
+                    1 => {
+                        let g = sample(0, x, y) as u8;
+                        [g, g, g, 255]
+                    }
+                    3 if rgb => [sample(0, x, y) as u8, sample(1, x, y) as u8, sample(2, x, y) as u8, 255],
+                    3 => {
+                        let c = ycc(sample(0, x, y), sample(1, x, y), sample(2, x, y));
+                        [clamp(c[0]), clamp(c[1]), clamp(c[2]), 255]
+                    }
+                    _ => {
+                        let k = sample(3, x, y);
+                        match self.adobe {
+                            // CMYK, stored inverted.
+                            Some(0) => [
+                                clamp(sample(0, x, y) * k / 255.0),
+                                clamp(sample(1, x, y) * k / 255.0),
+                                clamp(sample(2, x, y) * k / 255.0),
+                                255,
+                            ],
+                            Some(2) => {
+                                let c = ycc(sample(0, x, y), sample(1, x, y), sample(2, x, y));
+                                [
+                                    clamp((255.0 - clamp(c[0]) as f32) * k / 255.0),
+                                    clamp((255.0 - clamp(c[1]) as f32) * k / 255.0),
+                                    clamp((255.0 - clamp(c[2]) as f32) * k / 255.0),
+                                    255,
+                                ]
+                            }
+                            // Unknown, ignore the fourth channel.
+                            _ => {
+                                let c = ycc(sample(0, x, y), sample(1, x, y), sample(2, x, y));
+                                [clamp(c[0]), clamp(c[1]), clamp(c[2]), 255]
+                            }
+                        }
+                    }
+                };
+                let o = (y * frame.width + x) * 4;
+                image.pixels[o..o + 4].copy_from_slice(&px);
+            }
+        }
+        image
+    }
+}
+
+fn read_frame(seg: &[u8], progressive: bool) -> Result<Frame, ImageError> {
+    let mut r = Reader::new(seg);
+    if r.u8()? != 8 {
+        return Err(ImageError::Unsupported("JPEG with 12 bit samples"));
+    }
+    let height = r.u16_be()? as usize;
+    let width = r.u16_be()? as usize;
+    if height == 0 {
+        return Err(ImageError::Unsupported("JPEG with DNL marker"));
+    }
+    check_size(width, height)?;
+    let n = r.u8()? as usize;
+    if n != 1 && n != 3 && n != 4 {
+        return Err(ImageError::Unsupported("JPEG component count"));
+    }
+    let mut components = Vec::with_capacity(n);
+    for _ in 0..n {
+        let id = r.u8()?;
+        let hv = r.u8()?;
+        let tq = r.u8()? as usize;
+        let (h, v) = ((hv >> 4) as usize, (hv & 15) as usize);
+        if h == 0 || h > 4 || v == 0 || v > 4 || tq > 3 {
+            return Err(corrupt());
+        }
+        components.push(Component{
+            id:       id,
+            h:        h,
+            v:        v,
+            tq:       tq,
+            bw:       0,
+            bh:       0,
+            coefs:    Vec::new(),
+            dc_pred:  0,
+            dc_table: 0,
+            ac_table: 0,
+        });
+    }
+    let hmax = components.iter().map(|c| c.h).max().unwrap();
+    let vmax = components.iter().map(|c| c.v).max().unwrap();
+    let mcux = width.div_ceil(8 * hmax);
+    let mcuy = height.div_ceil(8 * vmax);
+    for c in &mut components {
+        c.bw = mcux * c.h;
+        c.bh = mcuy * c.v;
+        c.coefs = vec![[0; 64]; c.bw * c.bh];
+    }
+    Ok(Frame{
+        width:       width,
+        height:      height,
+        progressive: progressive,
+        hmax:        hmax,
+        vmax:        vmax,
+        mcux:        mcux,
+        mcuy:        mcuy,
+        components:  components,
+    })
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_jpeg(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut dec = Decoder{
+        qt:      [[1; 64]; 4],
+        dc:      vec![None, None, None, None],
+        ac:      vec![None, None, None, None],
+        restart: 0,
+        eobrun:  0,
+        frame:   None,
+        adobe:   None,
+    };
+
+    let mut pos = 2;
+    loop {
+        // Skip fill bytes before the marker.
+        while pos < data.len() && data[pos] == 0xff && data.get(pos + 1) == Some(&0xff) {
+            pos += 1;
+        }
+        if pos + 1 >= data.len() {
+            break;
+        }
+        if data[pos] != 0xff {
+            return Err(corrupt());
+        }
+        let marker = data[pos + 1];
+        pos += 2;
+        if marker == 0xd9 {
+            break;
+        }
+        if (0xd0..=0xd7).contains(&marker) || marker == 0x01 {
+            continue;
+        }
+        if pos + 2 > data.len() {
+            return Err(corrupt());
+        }
+        let len = (data[pos] as usize) << 8 | data[pos + 1] as usize;
+        if len < 2 || pos + len > data.len() {
+            return Err(corrupt());
+        }
+        let seg = &data[pos + 2..pos + len];
+        pos += len;
+
+        match marker {
+            0xc0..=0xc2 => {
+                if dec.frame.is_some() {
+                    return Err(corrupt());
+                }
+                dec.frame = Some(read_frame(seg, marker == 0xc2)?);
+            }
+            0xc3 | 0xc5..=0xc7 | 0xc9..=0xcb | 0xcd..=0xcf => {
+                return Err(ImageError::Unsupported("lossless, hierarchical or arithmetic coded JPEG"));
+            }
+            0xc4 => {
+                let mut r = Reader::new(seg);
+                while r.remaining() > 0 {
+                    let tc_th = r.u8()?;
+                    let (class, index) = ((tc_th >> 4) as usize, (tc_th & 15) as usize);
+                    let counts = r.bytes(16)?;
+                    let total = counts.iter().map(|&c| c as usize).sum();
+                    let values = r.bytes(total)?;
+                    if class > 1 || index > 3 {
+                        return Err(corrupt());
+                    }
+                    let table = Some(Huffman::new(counts, values));
+                    if class == 0 {
+                        dec.dc[index] = table;
+                    } else {
+                        dec.ac[index] = table;
+                    }
+                }
+            }
+            0xdb => {
+                let mut r = Reader::new(seg);
+                while r.remaining() > 0 {
+                    let pq_tq = r.u8()?;
+                    let (precision, index) = (pq_tq >> 4, (pq_tq & 15) as usize);
+                    if index > 3 {
+                        return Err(corrupt());
+                    }
+                    for &k in &ZIGZAG {
+                        dec.qt[index][k] = if precision == 0 { r.u8()? as u16 } else { r.u16_be()? };
+                    }
+                }
+            }
+            0xdd => {
+                dec.restart = Reader::new(seg).u16_be()? as usize;
+            }
+            0xee if seg.len() >= 12 && seg.starts_with(b"Adobe") => {
+                dec.adobe = Some(seg[11]);
+            }
+            0xda => {
+                let frame = dec.frame.as_mut().ok_or_else(corrupt)?;
+                let mut r = Reader::new(seg);
+                let n = r.u8()? as usize;
+                let mut comps = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let id = r.u8()?;
+                    let tables = r.u8()?;
+                    let c = frame.components.iter().position(|c| c.id == id).ok_or_else(corrupt)?;
+                    frame.components[c].dc_table = (tables >> 4) as usize;
+                    frame.components[c].ac_table = (tables & 15) as usize;
+                    comps.push(c);
+                }
+                let ss = r.u8()? as usize;
+                let se = r.u8()? as usize;
+                let a = r.u8()?;
+                if comps.is_empty() || ss > 63 || se > 63 || ss > se {
+                    return Err(corrupt());
+                }
+                let scan = Scan{
+                    comps: comps,
+                    ss:    ss,
+                    se:    if frame.progressive { se } else { 63 },
+                    ah:    (a >> 4) as u32,
+                    al:    (a & 15) as u32,
+                };
+                pos = dec.decode_scan(data, pos, &scan)?;
+            }
+            _ => {}
+        }
+    }
+
+    if dec.frame.is_none() {
+        return Err(ImageError::Corrupt("missing JPEG frame"));
+    }
+    Ok(dec.output())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a baseline 8x8 grayscale JPEG with one DC and one AC code,
+    /// both the single bit 0, decoding to `dc_size` and end of block.
+    fn jpeg(dc_size: u8, scan: &[u8]) -> Vec<u8> {
+        let mut jpeg = vec![0xff, 0xd8];
+        jpeg.extend_from_slice(&[0xff, 0xdb, 0x00, 0x43, 0x00]);
+        jpeg.extend_from_slice(&[1; 64]);
+        jpeg.extend_from_slice(&[0xff, 0xc0, 0x00, 0x0b, 8, 0x00, 0x08, 0x00, 0x08, 1, 1, 0x11, 0]);
+        for &(class, value) in &[(0x00, dc_size), (0x10, 0x00)] {
+            jpeg.extend_from_slice(&[0xff, 0xc4, 0x00, 0x14, class, 1]);
+            jpeg.extend_from_slice(&[0; 15]);
+            jpeg.push(value);
+        }
+        jpeg.extend_from_slice(&[0xff, 0xda, 0x00, 0x08, 1, 1, 0x00, 0, 63, 0]);
+        jpeg.extend_from_slice(scan);
+        jpeg.extend_from_slice(&[0xff, 0xd9]);
+        jpeg
+    }
+
+    #[test]
+    fn decodes_baseline() {
+        // DC 0 and end of block, padded with ones.
+        let image = decode(&jpeg(0, &[0x3f])).unwrap();
+        assert_eq!((image.width, image.height), (8, 8));
+        assert!(image.pixels.chunks(4).all(|px| px == [128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn rejects_invalid_coefficient_sizes() {
+        for &size in &[17, 32, 40, 255] {
+            match decode(&jpeg(size, &[0x3f, 0xff, 0x00, 0xff, 0x00])) {
+                Err(ImageError::Corrupt(_)) => (),
+                other => panic!("size {}: {:?}", size, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_missing_tables_and_frames() {
+        let mut data = jpeg(0, &[0x3f]);
+        // Point the scan at the missing Huffman tables 1.
+        let sos = data.len() - 2 - 1 - 4;
+        data[sos] = 0x11;
+        assert!(decode(&data).is_err());
+        assert!(decode(&[0xff, 0xd8, 0xff, 0xd9]).is_err());
+        assert!(decode(&[0xff, 0xd8, 0xff, 0xc4, 0xff]).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        crate::image::tests::check_malformed(&jpeg(3, &[0x71, 0x3f]));
+    }
+}
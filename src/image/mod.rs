@@ -0,0 +1,256 @@
+//! Decoding of image files.
+//!
+//! Every supported format is decoded into rows of 8-bit RGBA pixels with
+//! straight alpha, top row first, which is what NanoVG gets from
+//! stb_image. The format is detected from the contents of the file:
+//!
+//! * PNG, all color types and bit depths, interlaced or not
+//! * JPEG, baseline and progressive, grayscale, YCbCr and CMYK
+//! * GIF, the first frame
+//! * TGA, true color, gray and color mapped, optionally RLE compressed
+//! * PSD, the composited image in RGB or grayscale mode
+//! * BMP, uncompressed or with bitfields
+//!
+//! Pixels are premultiplied when the image is created with
+//! `ImageFlags::Premultiplied`. `ImageFlags::Flipy` is applied by the
+//! backends when the image is sampled.
+//!
+//! Backends keep their textures in an `ImageRegistry`, which hands out
+//! the `ImageId`s identifying images in the `Context` API.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub mod inflate;
+pub mod png;
+pub mod jpeg;
+pub mod gif;
+pub mod tga;
+pub mod psd;
+pub mod bmp;
+
+mod registry;
+pub use self::registry::ImageRegistry;
+
+/// Errors from decoding an image.
+#[derive(Debug)]
+pub enum ImageError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The data is not in any of the supported formats.
+    UnknownFormat,
+    /// The format is recognized but uses a feature which is not supported.
+    Unsupported(&'static str),
+    /// The data is truncated or otherwise invalid.
+    Corrupt(&'static str),
+    /// The image handle is stale or the image was deleted.
+    InvalidImage,
+    /// The image is empty or the pixel data is smaller than the image.
+    InvalidSize,
+    /// The updated region does not lie within the image.
+    InvalidRegion,
+    /// The backend could not create the texture.
+    Backend,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImageError::Io(ref err) => write!(f, "{}", err),
+            ImageError::UnknownFormat => write!(f, "unknown image format"),
+            ImageError::Unsupported(what) => write!(f, "unsupported image: {}", what),
+            ImageError::Corrupt(what) => write!(f, "corrupt image: {}", what),
+            ImageError::InvalidImage => write!(f, "invalid image handle"),
+            ImageError::InvalidSize => write!(f, "invalid image size"),
+            ImageError::InvalidRegion => write!(f, "image region out of bounds"),
+            ImageError::Backend => write!(f, "failed to create texture"),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ImageError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(err: io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+/// A decoded image.
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width:  usize,
+    pub height: usize,
+    /// Rows of RGBA pixels, top row first.
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Creates an image of the given size, filled with transparent black.
+    pub fn new(width: usize, height: usize) -> Self {
+        RgbaImage{
+            width:  width,
+            height: height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    /// Multiplies the color channels by alpha.
+    pub fn premultiply(&mut self) {
+        for px in self.pixels.chunks_mut(4) {
+            let a = px[3] as u32;
+            for c in &mut px[..3] {
+                *c = ((*c as u32 * a + 127) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// Decodes an image file held in memory.
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if png::is_png(data) {
+        png::decode(data)
+    } else if jpeg::is_jpeg(data) {
+        jpeg::decode(data)
+    } else if gif::is_gif(data) {
+        gif::decode(data)
+    } else if psd::is_psd(data) {
+        psd::decode(data)
+    } else if bmp::is_bmp(data) {
+        bmp::decode(data)
+    } else if tga::is_tga(data) {
+        // TGA has no signature, so it is only tried after everything else.
+        tga::decode(data)
+    } else {
+        Err(ImageError::UnknownFormat)
+    }
+}
+
+/// Reads and decodes an image file.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<RgbaImage, ImageError> {
+    decode(&fs::read(path)?)
+}
+
+/// Checks that an image of the given size can be allocated.
+fn check_size(width: usize, height: usize) -> Result<(), ImageError> {
+    if width == 0 || height == 0 {
+        return Err(ImageError::Corrupt("zero image size"));
+    }
+    if width > 1 << 24 || height > 1 << 24 || width.checked_mul(height).is_none_or(|n| n > 1 << 28) {
+        return Err(ImageError::Unsupported("image too large"));
+    }
+    Ok(())
+}
+
+/// Reads little and big endian integers from a byte slice, failing on
+/// truncated data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos:  usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader{
+            data: data,
+            pos:  0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], ImageError> {
+        if self.remaining() < n {
+            return Err(ImageError::Corrupt("unexpected end of data"));
+        }
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), ImageError> {
+        self.bytes(n).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, ImageError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16_le(&mut self) -> Result<u16, ImageError> {
+        let b = self.bytes(2)?;
+        Ok(b[0] as u16 | (b[1] as u16) << 8)
+    }
+
+    fn u32_le(&mut self) -> Result<u32, ImageError> {
+        let b = self.bytes(4)?;
+        Ok(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24)
+    }
+
+    fn u16_be(&mut self) -> Result<u16, ImageError> {
+        let b = self.bytes(2)?;
+        Ok((b[0] as u16) << 8 | b[1] as u16)
+    }
+
+    fn u32_be(&mut self) -> Result<u32, ImageError> {
+        let b = self.bytes(4)?;
+        Ok((b[0] as u32) << 24 | (b[1] as u32) << 16 | (b[2] as u32) << 8 | b[3] as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes `data` cut at every length and with every byte replaced by
+    /// a few extreme values. Malformed images must fail without panicking.
+    pub(crate) fn check_malformed(data: &[u8]) {
+        for len in 0..data.len() {
+            let _ = decode(&data[..len]);
+        }
+        let mut copy = data.to_vec();
+        for i in 0..data.len() {
+            for &byte in &[0x00, 0x01, 0x0f, 0x10, 0x7f, 0x80, 0xfe, 0xff] {
+                copy[i] = byte;
+                let _ = decode(&copy);
+            }
+            copy[i] = data[i];
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        match decode(b"not an image at all") {
+            Err(ImageError::UnknownFormat) => (),
+            other => panic!("{:?}", other.map(|_| ())),
+        }
+        assert!(decode(&[]).is_err());
+    }
+
+    #[test]
+    fn premultiplies() {
+        let mut image = RgbaImage::new(1, 1);
+        image.pixels.copy_from_slice(&[255, 128, 0, 128]);
+        image.premultiply();
+        assert_eq!(image.pixels, [128, 64, 0, 128]);
+    }
+
+    #[test]
+    fn checks_sizes() {
+        assert!(check_size(1, 1).is_ok());
+        assert!(check_size(0, 1).is_err());
+        assert!(check_size(1 << 25, 1).is_err());
+        assert!(check_size(1 << 15, 1 << 15).is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! PNG decoder.
+
+use super::inflate::zlib_decompress;
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// Origin and spacing of the pixels of the seven Adam7 passes.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(&SIGNATURE)
+}
+
+struct Header {
+    width:      usize,
+    height:     usize,
+    depth:      u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            2 => 3,
+            4 => 2,
+            _ => 4,
+        }
+    }
+
+    /// Bytes per complete pixel, at least one, as used by the filters.
+    fn filter_bpp(&self) -> usize {
+        (self.channels() * self.depth as usize).div_ceil(8).max(1)
+    }
+
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.channels() * self.depth as usize).div_ceil(8)
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverses the filter of every row of one (sub)image in place. `data`
+/// holds the rows, each preceded by its filter type byte.
+fn unfilter(data: &mut [u8], rows: usize, row_bytes: usize, bpp: usize) -> Result<(), ImageError> {
+    let stride = row_bytes + 1;
+    for y in 0..rows {
+        let (prev, cur) = data.split_at_mut(y * stride);
+        let prev = if y > 0 { &prev[(y - 1) * stride + 1..] } else { &[][..] };
+        let filter = cur[0];
+        let cur = &mut cur[1..stride];
+        for x in 0..row_bytes {
+            let a = if x >= bpp { cur[x - bpp] } else { 0 };
+            let b = if y > 0 { prev[x] } else { 0 };
+            let c = if y > 0 && x >= bpp { prev[x - bpp] } else { 0 };
+            cur[x] = cur[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(ImageError::Corrupt("invalid PNG filter")),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns sample `i` of an unfiltered row, not scaled.
+fn sample(row: &[u8], i: usize, depth: u8) -> u16 {
+    match depth {
+        16 => (row[i * 2] as u16) << 8 | row[i * 2 + 1] as u16,
+        8 => row[i] as u16,
+        _ => {
+            let per_byte = 8 / depth as usize;
+            let byte = row[i / per_byte];
+            let shift = 8 - depth as usize * (i % per_byte + 1);
+            (byte >> shift) as u16 & ((1 << depth) - 1)
+        }
+    }
+}
+
+struct Decoder<'a> {
+    header:  Header,
+    palette: Vec<[u8; 4]>,
+    /// Transparent color of gray and true color images, unscaled.
+    key:     Option<[u16; 3]>,
+    image:   &'a mut RgbaImage,
+}
+
+impl<'a> Decoder<'a> {
+    /// Scales a sample to 8 bits.
+    fn scale(&self, v: u16) -> u8 {
+        match self.header.depth {
+            16 => (v >> 8) as u8,
+            8 => v as u8,
+            d => (v as u32 * 255 / ((1u32 << d) - 1)) as u8,
+        }
+    }
+
+    /// Converts one unfiltered row to RGBA and stores its pixels at
+    /// (x0 + i * dx, y).
+    fn store_row(&mut self, row: &[u8], width: usize, x0: usize, dx: usize, y: usize) {
+        let depth = self.header.depth;
+        let channels = self.header.channels();
+        for i in 0..width {
+            let px = match self.header.color_type {
+                3 => {
+                    let index = sample(row, i, depth) as usize;
+                    self.palette.get(index).cloned().unwrap_or([0, 0, 0, 255])
+                }
+                0 | 4 => {
+                    let g = sample(row, i * channels, depth);
+                    let a = if channels == 2 {
+                        self.scale(sample(row, i * 2 + 1, depth))
+                    } else if self.key.is_some_and(|k| k[0] == g) {
+                        0
+                    } else {
+                        255
+                    };
+                    let g = self.scale(g);
+                    [g, g, g, a]
+                }
+                _ => {
+                    let r = sample(row, i * channels, depth);
+                    let g = sample(row, i * channels + 1, depth);
+                    let b = sample(row, i * channels + 2, depth);
+                    let a = if channels == 4 {
+                        self.scale(sample(row, i * channels + 3, depth))
+                    } else if self.key.is_some_and(|k| k == [r, g, b]) {
+                        0
+                    } else {
+                        255
+                    };
+                    [self.scale(r), self.scale(g), self.scale(b), a]
+                }
+            };
+            let o = (y * self.image.width + x0 + i * dx) * 4;
+            self.image.pixels[o..o + 4].copy_from_slice(&px);
+        }
+    }
+
+    fn decode_pass(&mut self, data: &mut [u8], x0: usize, y0: usize, dx: usize, dy: usize) -> Result<usize, ImageError> {
+        let (w, h) = (self.header.width, self.header.height);
+        if x0 >= w || y0 >= h {
+            return Ok(0);
+        }
+        let pw = (w - x0).div_ceil(dx);
+        let ph = (h - y0).div_ceil(dy);
+        let row_bytes = self.header.row_bytes(pw);
+        let size = (row_bytes + 1) * ph;
+        if data.len() < size {
+            return Err(ImageError::Corrupt("not enough PNG image data"));
+        }
+        unfilter(&mut data[..size], ph, row_bytes, self.header.filter_bpp())?;
+        for j in 0..ph {
+            let start = j * (row_bytes + 1) + 1;
+            let row = &data[start..start + row_bytes];
+            self.store_row(row, pw, x0, dx, y0 + j * dy);
+        }
+        Ok(size)
+    }
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_png(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut r = Reader::new(data);
+    r.skip(8)?;
+
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut trns = None;
+    let mut idat = Vec::new();
+    loop {
+        let len = r.u32_be()? as usize;
+        let kind = r.bytes(4)?;
+        let chunk = r.bytes(len)?;
+        r.skip(4)?; // CRC
+        match kind {
+            b"CgBI" => return Err(ImageError::Unsupported("iPhone PNG")),
+            b"IHDR" => {
+                let mut c = Reader::new(chunk);
+                let width = c.u32_be()? as usize;
+                let height = c.u32_be()? as usize;
+                let depth = c.u8()?;
+                let color_type = c.u8()?;
+                let compression = c.u8()?;
+                let filter = c.u8()?;
+                let interlace = c.u8()?;
+                check_size(width, height)?;
+                let valid_depth = match color_type {
+                    0 => [1, 2, 4, 8, 16].contains(&depth),
+                    3 => [1, 2, 4, 8].contains(&depth),
+                    2 | 4 | 6 => depth == 8 || depth == 16,
+                    _ => false,
+                };
+                if !valid_depth || compression != 0 || filter != 0 || interlace > 1 {
+                    return Err(ImageError::Corrupt("invalid PNG header"));
+                }
+                header = Some(Header{
+                    width:      width,
+                    height:     height,
+                    depth:      depth,
+                    color_type: color_type,
+                    interlaced: interlace == 1,
+                });
+            }
+            b"PLTE" => {
+                palette = chunk.chunks(3).filter(|c| c.len() == 3).map(|c| [c[0], c[1], c[2], 255]).collect();
+            }
+            b"tRNS" => trns = Some(chunk),
+            b"IDAT" => idat.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(ImageError::Corrupt("missing PNG header"))?;
+    if header.color_type == 3 && palette.is_empty() {
+        return Err(ImageError::Corrupt("missing PNG palette"));
+    }
+    let mut key = None;
+    if let Some(trns) = trns {
+        let mut c = Reader::new(trns);
+        match header.color_type {
+            3 => {
+                for (entry, &a) in palette.iter_mut().zip(trns) {
+                    entry[3] = a;
+                }
+            }
+            0 => key = Some([c.u16_be()?, 0, 0]),
+            2 => key = Some([c.u16_be()?, c.u16_be()?, c.u16_be()?]),
+            _ => {}
+        }
+    }
+
+    let mut raw = zlib_decompress(&idat)?;
+    let mut image = RgbaImage::new(header.width, header.height);
+    let interlaced = header.interlaced;
+    let mut decoder = Decoder{
+        header:  header,
+        palette: palette,
+        key:     key,
+        image:   &mut image,
+    };
+    if interlaced {
+        let mut offset = 0;
+        for &(x0, y0, dx, dy) in &ADAM7 {
+            offset += decoder.decode_pass(&mut raw[offset..], x0, y0, dx, dy)?;
+        }
+    } else {
+        decoder.decode_pass(&mut raw, 0, 0, 1, 1)?;
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a PNG of the given chunks. CRCs are not checked, so they
+    /// are left zero.
+    fn png(chunks: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        for &(kind, data) in chunks {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]);
+        }
+        png
+    }
+
+    /// Returns the header of a 2x1 image.
+    fn ihdr(depth: u8, color_type: u8) -> Vec<u8> {
+        vec![0, 0, 0, 2, 0, 0, 0, 1, depth, color_type, 0, 0, 0]
+    }
+
+    /// Returns a zlib stream with `data` in one stored block.
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let len = data.len() as u16;
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(data);
+        zlib.extend_from_slice(&[0; 4]);
+        zlib
+    }
+
+    #[test]
+    fn decodes_rgb() {
+        let idat = zlib(&[0, 10, 20, 30, 40, 50, 60]);
+        let image = decode(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IDAT", &idat), (b"IEND", &[])])).unwrap();
+        assert_eq!(image.pixels, [10, 20, 30, 255, 40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn decodes_palette_with_transparency() {
+        let idat = zlib(&[0, 0x10]);
+        let chunks: [(&[u8], &[u8]); 5] = [(b"IHDR", &ihdr(4, 3)), (b"PLTE", &[1, 2, 3, 4, 5, 6]), (b"tRNS", &[0]),
+                                           (b"IDAT", &idat), (b"IEND", &[])];
+        let image = decode(&png(&chunks)).unwrap();
+        assert_eq!(image.pixels, [4, 5, 6, 255, 1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        let idat = zlib(&[0, 10, 20, 30, 40, 50, 60]);
+        // Missing header, palette and image data.
+        assert!(decode(&png(&[(b"IDAT", &idat), (b"IEND", &[])])).is_err());
+        assert!(decode(&png(&[(b"IHDR", &ihdr(8, 3)), (b"IDAT", &idat), (b"IEND", &[])])).is_err());
+        assert!(decode(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IEND", &[])])).is_err());
+        // Invalid depth, filter and truncated rows.
+        assert!(decode(&png(&[(b"IHDR", &ihdr(3, 2)), (b"IDAT", &idat), (b"IEND", &[])])).is_err());
+        let bad_filter = zlib(&[5, 10, 20, 30, 40, 50, 60]);
+        assert!(decode(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IDAT", &bad_filter), (b"IEND", &[])])).is_err());
+        let short = zlib(&[0, 10, 20]);
+        assert!(decode(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IDAT", &short), (b"IEND", &[])])).is_err());
+        // No IEND.
+        assert!(decode(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IDAT", &idat)])).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        let idat = zlib(&[1, 10, 20, 30, 40, 50, 60]);
+        crate::image::tests::check_malformed(&png(&[(b"IHDR", &ihdr(8, 2)), (b"IDAT", &idat), (b"IEND", &[])]));
+        let idat = zlib(&[0, 0x10, 0, 0x01]);
+        let mut interlaced = ihdr(4, 3);
+        interlaced[12] = 1;
+        crate::image::tests::check_malformed(&png(&[(b"IHDR", &interlaced), (b"PLTE", &[1, 2, 3, 4, 5, 6]), (b"IDAT", &idat),
+                                                    (b"IEND", &[])]));
+    }
+}
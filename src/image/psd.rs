@@ -0,0 +1,156 @@
+//! PSD decoder. Only the composited image is decoded, layers are ignored.
+
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+pub fn is_psd(data: &[u8]) -> bool {
+    data.starts_with(b"8BPS")
+}
+
+/// Decodes PackBits compressed data until `out` holds `len` bytes.
+fn unpack_bits(r: &mut Reader, len: usize, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    let end = out.len() + len;
+    while out.len() < end {
+        let n = r.u8()? as i8;
+        if n >= 0 {
+            out.extend_from_slice(r.bytes(n as usize + 1)?);
+        } else if n != -128 {
+            let byte = r.u8()?;
+            for _ in 0..(1 - n as isize) {
+                out.push(byte);
+            }
+        }
+    }
+    out.truncate(end);
+    Ok(())
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_psd(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut r = Reader::new(data);
+    r.skip(4)?;
+    if r.u16_be()? != 1 {
+        return Err(ImageError::Unsupported("PSD version"));
+    }
+    r.skip(6)?;
+    let channels = r.u16_be()? as usize;
+    let height = r.u32_be()? as usize;
+    let width = r.u32_be()? as usize;
+    let depth = r.u16_be()?;
+    let mode = r.u16_be()?;
+    check_size(width, height)?;
+    if channels == 0 || channels > 56 {
+        return Err(ImageError::Corrupt("invalid PSD channel count"));
+    }
+    if depth != 8 && depth != 16 {
+        return Err(ImageError::Unsupported("PSD bit depth"));
+    }
+    // Grayscale and RGB.
+    let color_channels = match mode {
+        1 => 1,
+        3 => 3,
+        _ => return Err(ImageError::Unsupported("PSD color mode")),
+    };
+    if channels < color_channels {
+        return Err(ImageError::Corrupt("too few PSD channels for the color mode"));
+    }
+
+    // Skip the color mode data, image resources and layer and mask info.
+    for _ in 0..3 {
+        let len = r.u32_be()? as usize;
+        r.skip(len)?;
+    }
+
+    let compression = r.u16_be()?;
+    let bytes = depth as usize / 8;
+    let plane = width * height * bytes;
+    let used = channels.min(color_channels + 1);
+    let mut planes = Vec::with_capacity(plane * used);
+    match compression {
+        0 => planes.extend_from_slice(r.bytes(plane * used)?),
+        1 => {
+            // Byte counts of every row of every channel, then the rows.
+            r.skip(height * channels * 2)?;
+            for _ in 0..used {
+                unpack_bits(&mut r, plane, &mut planes)?;
+            }
+        }
+        _ => return Err(ImageError::Unsupported("PSD compression")),
+    }
+
+    let mut image = RgbaImage::new(width, height);
+    for i in 0..width * height {
+        let channel = |c: usize| planes[c * plane + i * bytes];
+        let mut px = [0u8, 0, 0, 255];
+        if color_channels == 1 {
+            px[0] = channel(0);
+            px[1] = px[0];
+            px[2] = px[0];
+        } else {
+            px[0] = channel(0);
+            px[1] = channel(1);
+            px[2] = channel(2);
+        }
+        if used > color_channels {
+            px[3] = channel(color_channels);
+            // Remove the white matte Photoshop blends transparent pixels with.
+            if px[3] != 0 && px[3] != 255 {
+                let a = px[3] as f32 / 255.0;
+                let ra = 1.0 / a;
+                let inv_a = 255.0 * (1.0 - ra);
+                for c in &mut px[..3] {
+                    *c = (*c as f32 * ra + inv_a).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+        image.pixels[i * 4..i * 4 + 4].copy_from_slice(&px);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a raw 8-bit PSD of 2x1 pixels with `data` after the header.
+    fn psd(channels: u16, mode: u16, data: &[u8]) -> Vec<u8> {
+        let mut psd = b"8BPS\x00\x01\x00\x00\x00\x00\x00\x00".to_vec();
+        psd.extend_from_slice(&channels.to_be_bytes());
+        psd.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 8]);
+        psd.extend_from_slice(&mode.to_be_bytes());
+        psd.extend_from_slice(&[0; 12]);
+        psd.extend_from_slice(&[0, 0]);
+        psd.extend_from_slice(data);
+        psd
+    }
+
+    #[test]
+    fn decodes_rgb() {
+        let image = decode(&psd(3, 3, &[10, 20, 30, 40, 50, 60])).unwrap();
+        assert_eq!(image.pixels, [10, 30, 50, 255, 20, 40, 60, 255]);
+    }
+
+    #[test]
+    fn rejects_too_few_channels() {
+        for channels in 1..3 {
+            match decode(&psd(channels, 3, &[0; 12])) {
+                Err(ImageError::Corrupt(_)) => (),
+                other => panic!("{} channels: {:?}", channels, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        assert!(decode(&psd(3, 3, &[1, 2, 3])).is_err());
+        assert!(decode(&psd(1, 1, &[])).is_err());
+        assert!(decode(&psd(1, 1, &[])[..20]).is_err());
+        assert!(decode(&psd(0, 1, &[0; 2])).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        crate::image::tests::check_malformed(&psd(4, 3, &[1, 2, 3, 4, 5, 6, 7, 8]));
+    }
+}
@@ -0,0 +1,140 @@
+//! Storage handing out `ImageId`s.
+
+use crate::ImageId;
+
+struct Slot<T> {
+    generation: u32,
+    value:      Option<T>,
+}
+
+/// Owns the images of a backend and hands out an `ImageId` for each.
+///
+/// Slots of deleted images are reused, but every reuse bumps the
+/// generation of the slot, so ids of deleted images never refer to a
+/// later image.
+pub struct ImageRegistry<T> {
+    slots: Vec<Slot<T>>,
+    free:  Vec<u32>,
+}
+
+impl<T> ImageRegistry<T> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        ImageRegistry{
+            slots: Vec::new(),
+            free:  Vec::new(),
+        }
+    }
+
+    /// Stores an image and returns its id.
+    pub fn insert(&mut self, value: T) -> ImageId {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.generation = slot.generation.wrapping_add(1);
+                slot.value = Some(value);
+                ImageId{ index: index, generation: slot.generation }
+            }
+            None => {
+                self.slots.push(Slot{ generation: 0, value: Some(value) });
+                ImageId{ index: self.slots.len() as u32 - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Removes an image, returning it if the id was live.
+    pub fn remove(&mut self, id: ImageId) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let value = slot.value.take();
+        if value.is_some() {
+            self.free.push(id.index);
+        }
+        value
+    }
+
+    /// Returns the image with the given id, `None` for stale or deleted ids.
+    pub fn get(&self, id: ImageId) -> Option<&T> {
+        match self.slots.get(id.index as usize) {
+            Some(slot) if slot.generation == id.generation => slot.value.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the image with the given id for modification.
+    pub fn get_mut(&mut self, id: ImageId) -> Option<&mut T> {
+        match self.slots.get_mut(id.index as usize) {
+            Some(slot) if slot.generation == id.generation => slot.value.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the id refers to a live image.
+    pub fn contains(&self, id: ImageId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the number of live images.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Returns whether there are no live images.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for ImageRegistry<T> {
+    fn default() -> Self {
+        ImageRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleted_ids_stay_invalid() {
+        let mut images = ImageRegistry::new();
+        let a = images.insert("a");
+        let b = images.insert("b");
+        assert_eq!(images.len(), 2);
+        assert_eq!(images.remove(a), Some("a"));
+        assert_eq!(images.remove(a), None);
+        assert_eq!(images.get(a), None);
+        assert!(!images.contains(a));
+        assert_eq!(images.get(b), Some(&"b"));
+        assert_eq!(images.len(), 1);
+
+        // The slot of a is reused, but a does not refer to the new image.
+        let c = images.insert("c");
+        assert_ne!(a, c);
+        assert_eq!(c.index, a.index);
+        assert_eq!(images.get(a), None);
+        assert_eq!(images.get_mut(a), None);
+        assert_eq!(images.remove(a), None);
+        assert_eq!(images.get(c), Some(&"c"));
+        assert_eq!(images.len(), 2);
+    }
+
+    #[test]
+    fn unknown_ids_are_rejected() {
+        let mut images = ImageRegistry::new();
+        let a = images.insert(1);
+        let unknown = ImageId{ index: 5, generation: 0 };
+        let future = ImageId{ index: a.index, generation: 1 };
+        for &id in &[unknown, future] {
+            assert_eq!(images.get(id), None);
+            assert_eq!(images.get_mut(id), None);
+            assert_eq!(images.remove(id), None);
+        }
+        assert_eq!(images.len(), 1);
+        *images.get_mut(a).unwrap() = 2;
+        assert_eq!(images.remove(a), Some(2));
+        assert!(images.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! TGA decoder.
+
+use super::{check_size, ImageError, Reader, RgbaImage};
+
+/// Checks whether the data starts with a plausible TGA header, since the
+/// format has no signature.
+pub fn is_tga(data: &[u8]) -> bool {
+    if data.len() < 18 {
+        return false;
+    }
+    let cmap_type = data[1];
+    let image_type = data[2];
+    let bits = data[16];
+    let width = data[12] as usize | (data[13] as usize) << 8;
+    let height = data[14] as usize | (data[15] as usize) << 8;
+    if cmap_type > 1 || width == 0 || height == 0 {
+        return false;
+    }
+    match image_type {
+        1 | 9 => cmap_type == 1 && (bits == 8 || bits == 16),
+        2 | 10 => [15, 16, 24, 32].contains(&bits),
+        3 | 11 => bits == 8 || bits == 16,
+        _ => false,
+    }
+}
+
+/// Converts one stored pixel of `bytes` bytes to RGBA. Gray pixels have
+/// one byte, or two with alpha.
+fn to_rgba(p: &[u8], gray: bool) -> [u8; 4] {
+    match (p.len(), gray) {
+        (1, _) => [p[0], p[0], p[0], 255],
+        (2, true) => [p[0], p[0], p[0], p[1]],
+        (2, false) => {
+            // 5 bits per channel, the attribute bit is ignored like stb_image does.
+            let v = p[0] as u16 | (p[1] as u16) << 8;
+            let scale = |c: u16| ((c & 31) * 255 / 31) as u8;
+            [scale(v >> 10), scale(v >> 5), scale(v), 255]
+        }
+        (3, _) => [p[2], p[1], p[0], 255],
+        _ => [p[2], p[1], p[0], p[3]],
+    }
+}
+
+pub fn decode(data: &[u8]) -> Result<RgbaImage, ImageError> {
+    if !is_tga(data) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let mut r = Reader::new(data);
+    let id_len = r.u8()? as usize;
+    let cmap_type = r.u8()?;
+    let image_type = r.u8()?;
+    let cmap_first = r.u16_le()? as usize;
+    let cmap_len = r.u16_le()? as usize;
+    let cmap_bits = r.u8()?;
+    r.skip(4)?; // origin
+    let width = r.u16_le()? as usize;
+    let height = r.u16_le()? as usize;
+    let bits = r.u8()? as usize;
+    let descriptor = r.u8()?;
+    check_size(width, height)?;
+    r.skip(id_len)?;
+
+    let rle = image_type >= 8;
+    let mapped = image_type & 7 == 1;
+    let gray = image_type & 7 == 3;
+
+    let mut palette = Vec::new();
+    if cmap_type == 1 {
+        let entry = (cmap_bits as usize).div_ceil(8);
+        if !(2..=4).contains(&entry) {
+            return Err(ImageError::Unsupported("TGA color map format"));
+        }
+        let bytes = r.bytes(cmap_len * entry)?;
+        palette = bytes.chunks(entry).map(|p| to_rgba(p, false)).collect();
+    }
+
+    let bpp = bits.div_ceil(8);
+    let pixel = |p: &[u8]| -> [u8; 4] {
+        if mapped {
+            let index = if bpp == 1 { p[0] as usize } else { p[0] as usize | (p[1] as usize) << 8 };
+            palette.get(index.wrapping_sub(cmap_first)).cloned().unwrap_or([0, 0, 0, 255])
+        } else {
+            to_rgba(p, gray)
+        }
+    };
+
+    let count = width * height;
+    let mut pixels = Vec::with_capacity(count);
+    if rle {
+        while pixels.len() < count {
+            let header = r.u8()?;
+            let n = (header & 0x7f) as usize + 1;
+            if header & 0x80 != 0 {
+                let px = pixel(r.bytes(bpp)?);
+                for _ in 0..n {
+                    pixels.push(px);
+                }
+            } else {
+                for _ in 0..n {
+                    pixels.push(pixel(r.bytes(bpp)?));
+                }
+            }
+        }
+        pixels.truncate(count);
+    } else {
+        for p in r.bytes(count * bpp)?.chunks(bpp) {
+            pixels.push(pixel(p));
+        }
+    }
+
+    // Rows are stored bottom up unless the descriptor says otherwise.
+    let top_down = descriptor & 0x20 != 0;
+    let right_to_left = descriptor & 0x10 != 0;
+    let mut image = RgbaImage::new(width, height);
+    for y in 0..height {
+        let sy = if top_down { y } else { height - 1 - y };
+        for x in 0..width {
+            let sx = if right_to_left { width - 1 - x } else { x };
+            let o = (y * width + x) * 4;
+            image.pixels[o..o + 4].copy_from_slice(&pixels[sy * width + sx]);
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the header of a 2x1 image stored top down.
+    fn header(image_type: u8, bits: u8) -> Vec<u8> {
+        vec![0, 0, image_type, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0, bits, 0x20]
+    }
+
+    #[test]
+    fn decodes_true_color() {
+        let mut data = header(2, 24);
+        data.extend_from_slice(&[30, 20, 10, 60, 50, 40]);
+        assert_eq!(decode(&data).unwrap().pixels, [10, 20, 30, 255, 40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn decodes_run_length_gray() {
+        let mut data = header(11, 8);
+        data.extend_from_slice(&[0x81, 77]);
+        assert_eq!(decode(&data).unwrap().pixels, [77, 77, 77, 255, 77, 77, 77, 255]);
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        assert!(!is_tga(&header(2, 12)));
+        assert!(decode(&header(2, 24)).is_err());
+        let mut data = header(10, 24);
+        data.extend_from_slice(&[0x00, 1, 2, 3]);
+        assert!(decode(&data).is_err());
+        // A color map with entries of one byte.
+        let mut data = header(1, 8);
+        data[1] = 1;
+        data[5] = 1;
+        data[7] = 8;
+        data.extend_from_slice(&[0, 0, 0]);
+        assert!(decode(&data).is_err());
+    }
+
+    #[test]
+    fn survives_malformed_data() {
+        let mut data = header(9, 8);
+        data[1] = 1;
+        data[5] = 2;
+        data[7] = 24;
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 0x81, 1]);
+        assert_eq!(decode(&data).unwrap().pixels, [6, 5, 4, 255, 6, 5, 4, 255]);
+        crate::image::tests::check_malformed(&data);
+    }
+}
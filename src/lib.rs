@@ -54,6 +54,13 @@
 //! rendered.  The state contains transform, fill and stroke
 //! styles, text and font styles, and scissor clipping.
 //!
+//! # Backends
+//!
+//! A `Frontend` implements `Context` for any `RenderBackend`. The
+//! frontend keeps the state and tessellates paths, the backend owns the
+//! textures and draws the resulting triangles. `SoftwareContext` is a
+//! frontend drawing with the CPU rasterizer in `software`.
+//!
 //! # Render styles
 //!
 //! Fill and stroke render style can be either a solid color or a paint which is a gradient or a pattern.
@@ -64,9 +71,9 @@
 //!
 //! # Images
 //!
-//! NanoVG allows you to load jpg, png, psd, tga, pic and gif files to be used for rendering.
-//! In addition you can upload your own image. The image loading is provided by stb_image.
-//! The parameter imageFlags is combination of flags defined in NVGimageFlags.
+//! NanoVG allows you to load png, jpg, gif, tga, psd and bmp files to be used for rendering.
+//! In addition you can upload your own image. The image loading is provided by the `image` module.
+//! Images are referred to by an `ImageId`, and the image flags are a combination of `ImageFlags`.
 //!
 //! # Scissoring
 //!
@@ -91,13 +98,51 @@
 //!
 //! # Text
 //!
-//! NanoVG allows you to load .ttf files and use the font to render text.
+//! NanoVG allows you to load .ttf and .otf files, including faces of .ttc
+//! collections, and use the font to render text. Fonts are parsed by the
+//! `font` module and referred to by a `FontId`. Glyphs are rasterized when
+//! first drawn and cached in a texture atlas by the `fontstash` module.
 //!
 //! The appearance of the text can be defined by setting the current text style
 //! and by specifying the fill color. Common text and font settings such as
 //! font size, letter spacing and text align are supported. Font blur allows you
 //! to create simple text effects such as drop shadows.
 //!
+//! Glyph pairs are kerned with the font's GPOS or `kern` table. Drawing and
+//! measuring text lay out the glyphs the same way, so the values from
+//! `text_bounds`, `text_glyph_positions_into` and `text_break_lines` match
+//! the drawn text exactly.
+//!
+//! Scripts like Arabic and Devanagari, and fonts with ligatures, need more
+//! than one glyph per character. With `text_shaping` enabled, text is split
+//! into runs of one font and script which are shaped with the font's GSUB
+//! and GPOS tables. Glyph positions and line breaks then refer to clusters,
+//! the characters which make up one or more glyphs and cannot be split.
+//!
+//! Text mixing left to right and right to left scripts, such as Hebrew
+//! with embedded numbers and English, is reordered for display by the
+//! Unicode Bidirectional Algorithm in the `bidi` module. Lines are broken
+//! in the order of the string and every line is then put into visual
+//! order, so `TextRow::span` is always a contiguous part of the string.
+//!
+//! `text_box` and `text_break_lines` break rows at the break opportunities
+//! of Unicode line breaking in the `linebreak` module: after spaces and
+//! hyphens, between ideographs, after soft hyphens and zero width spaces,
+//! but never before closing punctuation. `text_line_breaking` switches back
+//! to NanoVG's rules, which only know white space and CJK characters.
+//!
+//! With `text_hyphenation` enabled, words which do not fit in a row are
+//! hyphenated where the `Hyphenate` implementation of `set_hyphenator`
+//! allows, by default Liang's patterns for US English from the `hyphen`
+//! module. Soft hyphens are hyphenation points in any case. A row ending
+//! in a hyphenated word has `TextRow::hyphen` set, `text_box` draws a
+//! hyphen after it.
+//!
+//! Rich text mixes fonts, sizes and colors: `rich_text_break_lines` breaks
+//! a list of `TextSpan`s into rows as one text, putting the spans on each
+//! row on a shared baseline, and `rich_text_box` draws them with their
+//! underlines and strikethroughs in one call.
+//!
 //! At render time the font face can be set based on the font handles or name.
 //!
 //! Font measure functions return values in local space, the calculations are
@@ -121,6 +166,8 @@
 //!
 //! Note: currently only solid color fill is supported for text.
 
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
 pub mod math;
 
 use math::*;
@@ -129,6 +176,47 @@ pub mod color;
 
 use color::*;
 
+pub mod path;
+
+pub mod stroke;
+
+pub mod fill;
+
+pub mod composite;
+
+pub mod paint;
+
+pub mod image;
+
+pub mod font;
+
+pub mod bidi;
+
+pub mod linebreak;
+
+pub mod hyphen;
+
+use hyphen::Hyphenate;
+
+pub mod fontstash;
+
+pub mod backend;
+
+use backend::TextureType;
+
+pub mod state;
+
+use state::{SaveGuard, StateError};
+
+use image::ImageError;
+
+use font::{FontData, FontError};
+
+pub mod frontend;
+
+pub mod software;
+
+#[derive(Debug, Clone, Copy)]
 pub struct Paint {
     pub xform:       [f32; 6],
     pub extent:      [f32; 2],
@@ -136,9 +224,10 @@ pub struct Paint {
     pub feather:     f32,
     pub inner_color: Color,
     pub outer_color: Color,
-    pub image:       isize,
+    pub image:       Option<ImageId>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Winding {
     /// Winding for solid shapes.
     CCW,
@@ -146,6 +235,7 @@ pub enum Winding {
     CW,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Solidity {
     /// Counter clockwise.
     Solid,
@@ -153,18 +243,21 @@ pub enum Solidity {
     Hole,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LineCap {
     Butt,
     Round,
     Square,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LineJoin {
     Round,
     Bevel,
     Miter,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Align {
     // Horizontal align
     /// Default, align text horizontally to left.
@@ -185,6 +278,27 @@ pub enum Align {
     Baseline = 1 << 6,
 }
 
+/// Base direction of paragraphs of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    /// Default, the direction of the first letter of each paragraph, left
+    /// to right if it has none.
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Rules deciding where `text_box` and `text_break_lines` may break rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBreaking {
+    /// Default, the break opportunities of Unicode line breaking, UAX #14.
+    Unicode,
+    /// NanoVG's rules: rows break at white space, before and after CJK
+    /// characters and at new-line characters.
+    NanoVG,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlendFactor {
     Zero                     = 1 << 0,
     One                      = 1 << 1,
@@ -199,6 +313,7 @@ pub enum BlendFactor {
     SourceAlphaSaturate      = 1 << 10,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CompositeOperation {
     SourceOver,
     SourceIn,
@@ -213,17 +328,23 @@ pub enum CompositeOperation {
     Xor,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct CompositeOperationState {
-    pub src_rgb:   isize,
-    pub dst_rgb:   isize,
-    pub src_alpha: isize,
-    pub dst_alpha: isize,
+    pub src_rgb:   BlendFactor,
+    pub dst_rgb:   BlendFactor,
+    pub src_alpha: BlendFactor,
+    pub dst_alpha: BlendFactor,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct GlyphPosition<'a> {
-    /// Position of the glyph in the input string.
+    /// Position of the glyph in the input string. With shaping this is a
+    /// whole cluster, such as the characters of a ligature or a character
+    /// with its marks.
     pub glyph: &'a str,
-    /// The x-coordinate of the logical glyph position.
+    /// The x-coordinate of the logical glyph position, after kerning and
+    /// letter spacing. A caret before the glyph goes here, which is on its
+    /// right for right to left text.
     pub x:     f32,
     /// The bounds of the glyph shape.
     pub minx:  f32,
@@ -231,6 +352,7 @@ pub struct GlyphPosition<'a> {
     pub maxx:  f32,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct TextRow<'a> {
     /// Text from the input string.
     pub span: &'a str,
@@ -239,21 +361,210 @@ pub struct TextRow<'a> {
     /// Actual bounds of the row. Logical width and bounds can differ because of kerning and some parts over extending.
     pub minx:     f32,
     pub maxx:     f32,
+    /// The row ends inside a hyphenated word. A hyphen goes after the span,
+    /// it is included in the width and bounds.
+    pub hyphen:   bool,
+}
+
+/// Lines drawn along a span of rich text, combined with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextDecoration(u32);
+
+#[allow(non_upper_case_globals)]
+impl TextDecoration {
+    /// A line below the baseline, where the `post` table of the font
+    /// puts it.
+    pub const Underline:     TextDecoration = TextDecoration(1 << 0);
+    /// A line through lowercase letters, where the `OS/2` table of the
+    /// font puts it.
+    pub const Strikethrough: TextDecoration = TextDecoration(1 << 1);
+
+    /// Returns decorations with nothing set.
+    pub fn empty() -> Self {
+        TextDecoration(0)
+    }
+
+    /// Returns whether all decorations of `other` are set.
+    pub fn contains(self, other: TextDecoration) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for TextDecoration {
+    type Output = TextDecoration;
+
+    fn bitor(self, other: TextDecoration) -> TextDecoration {
+        TextDecoration(self.0 | other.0)
+    }
+}
+
+/// A part of rich text with its own font, size, color and decorations.
+/// The other settings, such as blur, line height and alignment, come from
+/// the current text style.
+#[derive(Debug, Clone, Copy)]
+pub struct TextSpan<'a> {
+    pub text:           &'a str,
+    pub font:           FontId,
+    /// Font size, like `font_size`.
+    pub size:           f32,
+    pub color:          Color,
+    /// Letter spacing, like `text_letter_spacing`.
+    pub letter_spacing: f32,
+    pub decoration:     TextDecoration,
+}
+
+impl<'a> TextSpan<'a> {
+    /// Returns a span of text in a font, size and color, without letter
+    /// spacing or decorations.
+    pub fn new(text: &'a str, font: FontId, size: f32, color: Color) -> Self {
+        TextSpan{
+            text:           text,
+            font:           font,
+            size:           size,
+            color:          color,
+            letter_spacing: 0.0,
+            decoration:     TextDecoration::empty(),
+        }
+    }
 }
 
-pub enum ImageFlags {
+/// The part of one span on a row of rich text.
+#[derive(Debug, Clone, Copy)]
+pub struct RichTextRun {
+    /// Index of the span.
+    pub span:  usize,
+    /// Byte range of the part in the text of the span.
+    pub start: usize,
+    pub end:   usize,
+    /// Position of the part from the left of the row.
+    pub x:     f32,
+    /// Logical width of the part.
+    pub width: f32,
+}
+
+/// A row of rich text. The spans on a row share one baseline.
+#[derive(Debug, Clone)]
+pub struct RichTextRow {
+    /// The parts of the spans on the row, from left to right.
+    pub runs:      Vec<RichTextRun>,
+    /// Logical width of the row.
+    pub width:     f32,
+    /// Actual bounds of the row.
+    pub minx:      f32,
+    pub maxx:      f32,
+    /// Distance from the top of the row to the baseline, the largest
+    /// ascender of the spans on the row.
+    pub ascender:  f32,
+    /// The lowest descender of the spans on the row, negative below the
+    /// baseline.
+    pub descender: f32,
+    /// Distance to the top of the next row, the largest line height of the
+    /// spans on the row times `text_line_height`.
+    pub height:    f32,
+    /// The row ends inside a hyphenated word. A hyphen in the style of its
+    /// span goes after the runs, it is included in the width and bounds.
+    pub hyphen:    bool,
+}
+
+/// Flags of an image, combined with `|`.
+///
+/// Images clamp to their edge texels unless one of the wrap flags is set
+/// for a direction. If several are set, mirrored repeat wins over repeat,
+/// which wins over clamp to border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ImageFlags(u32);
+
+#[allow(non_upper_case_globals)]
+impl ImageFlags {
     /// Generate mipmaps during creation of the image.
-    GenerateMipmaps = 1 << 0,
+    pub const GenerateMipmaps: ImageFlags = ImageFlags(1 << 0);
     /// Repeat image in X direction.
-    Repeatx         = 1 << 1,
+    pub const Repeatx:         ImageFlags = ImageFlags(1 << 1);
     /// Repeat image in Y direction.
-    Repeaty         = 1 << 2,
+    pub const Repeaty:         ImageFlags = ImageFlags(1 << 2);
     /// Flips (inverses) image in Y direction when rendered.
-    Flipy           = 1 << 3,
+    pub const Flipy:           ImageFlags = ImageFlags(1 << 3);
     /// Image data has premultiplied alpha.
-    Premultiplied   = 1 << 4,
+    pub const Premultiplied:   ImageFlags = ImageFlags(1 << 4);
     /// Image interpolation is Nearest instead Linear
-    Nearest         = 1 << 5,
+    pub const Nearest:         ImageFlags = ImageFlags(1 << 5);
+    /// Repeat image in X direction, mirroring every other copy.
+    pub const MirroredRepeatx: ImageFlags = ImageFlags(1 << 6);
+    /// Repeat image in Y direction, mirroring every other copy.
+    pub const MirroredRepeaty: ImageFlags = ImageFlags(1 << 7);
+    /// Image is transparent outside its X range instead of repeating the edge.
+    pub const ClampToBorderx:  ImageFlags = ImageFlags(1 << 8);
+    /// Image is transparent outside its Y range instead of repeating the edge.
+    pub const ClampToBordery:  ImageFlags = ImageFlags(1 << 9);
+
+    /// Returns flags with nothing set.
+    pub fn empty() -> Self {
+        ImageFlags(0)
+    }
+
+    /// Returns the raw bits of the flags.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Creates flags from raw bits, dropping unknown bits.
+    pub fn from_bits_truncate(bits: u32) -> Self {
+        ImageFlags(bits & 0x3ff)
+    }
+
+    /// Returns whether all flags of `other` are set.
+    pub fn contains(self, other: ImageFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets the flags of `other`.
+    pub fn insert(&mut self, other: ImageFlags) {
+        self.0 |= other.0;
+    }
+
+    /// Clears the flags of `other`.
+    pub fn remove(&mut self, other: ImageFlags) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for ImageFlags {
+    type Output = ImageFlags;
+
+    fn bitor(self, other: ImageFlags) -> ImageFlags {
+        ImageFlags(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for ImageFlags {
+    fn bitor_assign(&mut self, other: ImageFlags) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for ImageFlags {
+    type Output = ImageFlags;
+
+    fn bitand(self, other: ImageFlags) -> ImageFlags {
+        ImageFlags(self.0 & other.0)
+    }
+}
+
+/// Handle to an image created by a context.
+///
+/// Handles of deleted images stay invalid even after the image storage
+/// is reused, so using one is reported as `ImageError::InvalidImage`
+/// instead of silently drawing another image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImageId {
+    index:      u32,
+    generation: u32,
+}
+
+/// Handle to a font created by a context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontId {
+    index: u32,
 }
 
 pub trait Context {
@@ -283,15 +594,30 @@ pub trait Context {
     /// Sets the composite operation with custom pixel arithmetic.
     fn global_composite_blend_func(&mut self, sfactor: BlendFactor, dfactor: BlendFactor);
 
-    /// Sets the composite operation with custom pixel arithmetic for RGB and alpha components separately. The parameters should be one of NVGblendFactor.
-    fn global_composite_blend_func_separate(&mut self, src_r_g_b: isize, dst_r_g_b: isize, src_alpha: isize, dst_alpha: isize);
+    /// Sets the composite operation with custom pixel arithmetic for RGB and alpha components separately.
+    fn global_composite_blend_func_separate(&mut self, src_r_g_b: BlendFactor, dst_r_g_b: BlendFactor, src_alpha: BlendFactor, dst_alpha: BlendFactor);
 
     /// Pushes and saves the current render state into a state stack.
     /// A matching nvgRestore() must be used to restore the state.
-    fn save(&mut self);
+    ///
+    /// The stack holds at most `MAX_STATES` states; saving more fails
+    /// with `StateError::Overflow` and leaves the stack unchanged.
+    fn save(&mut self) -> Result<(), StateError>;
 
     /// Pops and restores current render state.
-    fn restore(&mut self);
+    ///
+    /// Fails with `StateError::Underflow` when there is no saved state.
+    fn restore(&mut self) -> Result<(), StateError>;
+
+    /// Returns the number of states on the render state stack, including
+    /// the current one.
+    fn state_depth(&self) -> usize;
+
+    /// Saves the render state and returns a guard which restores it when
+    /// dropped, so early returns cannot unbalance the stack.
+    fn save_guard(&mut self) -> Result<SaveGuard<'_, Self>, StateError> where Self: Sized {
+        SaveGuard::new(self)
+    }
 
     /// Resets current render state to default values. Does not affect the render state stack.
     fn reset(&mut self);
@@ -363,24 +689,35 @@ pub trait Context {
 
     /// Creates image by loading it from the disk from specified file name.
     /// Returns handle to the image.
-    fn create_image(&mut self, filename: &str, image_flags: isize) -> isize;
+    fn create_image(&mut self, filename: &str, image_flags: ImageFlags) -> Result<ImageId, ImageError>;
 
     /// Creates image by loading it from the specified chunk of memory.
     /// Returns handle to the image.
-    fn create_image_mem(&mut self, image_flags: isize, data: &[u8]) -> isize;
+    fn create_image_mem(&mut self, image_flags: ImageFlags, data: &[u8]) -> Result<ImageId, ImageError>;
 
     /// Creates image from specified image data.
     /// Returns handle to the image.
-    fn create_image_r_g_b_a(&mut self, w: isize, h: isize, image_flags: isize, data: &[u8]) -> isize;
+    fn create_image_r_g_b_a(&mut self, w: usize, h: usize, image_flags: ImageFlags, data: &[u8]) -> Result<ImageId, ImageError>;
+
+    /// Creates image from specified image data in the given pixel format.
+    /// Alpha images are tinted by the inner color of image patterns using them.
+    /// Returns handle to the image.
+    fn create_image_format(&mut self, w: usize, h: usize, format: TextureType, image_flags: ImageFlags,
+                           data: &[u8]) -> Result<ImageId, ImageError>;
 
     /// Updates image data specified by image handle.
-    fn update_image(&mut self, image: isize, data: &[u8]);
+    fn update_image(&mut self, image: ImageId, data: &[u8]) -> Result<(), ImageError>;
+
+    /// Updates the w x h region of the image with the top-left corner at (x,y).
+    /// data holds the rows of the region, stride bytes apart.
+    fn update_image_region(&mut self, image: ImageId, x: usize, y: usize, w: usize, h: usize,
+                           data: &[u8], stride: usize) -> Result<(), ImageError>;
 
     /// Returns the width and height of a created image.
-    fn image_size(&mut self, image: isize) -> (usize, usize);
+    fn image_size(&self, image: ImageId) -> Result<(usize, usize), ImageError>;
 
     /// Deletes created image.
-    fn delete_image(&mut self, image: isize);
+    fn delete_image(&mut self, image: ImageId) -> Result<(), ImageError>;
 
     /// Creates and returns a linear gradient. Parameters (sx,sy)-(ex,ey) specify the start and end coordinates
     /// of the linear gradient, icol specifies the start color and ocol the end color.
@@ -402,7 +739,8 @@ pub trait Context {
     /// Creates and returns an image pattern. Parameters (ox,oy) specify the left-top location of the image pattern,
     /// (ex,ey) the size of one image, angle rotation around the top-left corner, image is handle to the image to render.
     /// The gradient is transformed by the current transform when it is passed to nvgFillPaint() or nvgStrokePaint().
-    fn image_pattern(&mut self, ox: f32, oy: f32, ex: f32, ey: f32, angle: f32, image: isize, alpha: f32) -> Paint;
+    /// Fails if the image handle is stale or the image was deleted.
+    fn image_pattern(&mut self, ox: f32, oy: f32, ex: f32, ey: f32, angle: f32, image: ImageId, alpha: f32) -> Result<Paint, ImageError>;
 
     /// Sets the current scissor rectangle.
     /// The scissor rectangle is transformed by the current transform.
@@ -471,20 +809,50 @@ pub trait Context {
 
     /// Creates font by loading it from the disk from specified file name.
     /// Returns handle to the font.
-    fn create_font(&mut self, name: &str, filename: &str) -> isize;
-
-    /// Creates font by loading it from the specified memory chunk.
-    /// Returns handle to the font.
-    fn create_font_mem(&mut self, name: &str, data: &[u8], ndata: isize, free_data: isize) -> isize;
-
-    /// Finds a loaded font of specified name, and returns handle to it, or -1 if the font is not found.
-    fn find_font(&mut self, name: &str) -> isize;
+    fn create_font(&mut self, name: &str, filename: &str) -> Result<FontId, FontError> {
+        self.create_font_at_index(name, filename, 0)
+    }
 
-    /// Adds a fallback font by handle.
-    fn add_fallback_font_id(&mut self, base_font: isize, fallback_font: isize) -> isize;
+    /// Creates font by loading the face at `font_index` of a font collection
+    /// (.ttc) from the disk. Plain font files only have face 0.
+    fn create_font_at_index(&mut self, name: &str, filename: &str, font_index: usize) -> Result<FontId, FontError>;
 
-    /// Adds a fallback font by name.
-    fn add_fallback_font(&mut self, base_font: &str, fallback_font: &str) -> isize;
+    /// Creates font from font file data, either an owned `Vec<u8>` or an
+    /// `Arc<[u8]>` which can be shared between contexts without copying.
+    /// Returns handle to the font.
+    fn create_font_mem<D: Into<FontData>>(&mut self, name: &str, data: D) -> Result<FontId, FontError> where Self: Sized {
+        self.create_font_mem_at_index(name, data, 0)
+    }
+
+    /// Creates font from the face at `font_index` of font collection data.
+    fn create_font_mem_at_index<D: Into<FontData>>(&mut self, name: &str, data: D,
+                                                   font_index: usize) -> Result<FontId, FontError> where Self: Sized;
+
+    /// Finds a loaded font of specified name, and returns handle to it, or `None` if the font is not found.
+    fn find_font(&self, name: &str) -> Option<FontId>;
+
+    /// Adds a fallback font by handle. Characters missing from the base font are
+    /// drawn and measured with the first of its fallback fonts which has them.
+    /// Returns false if the base font already has the maximum of 20 fallbacks.
+    fn add_fallback_font_id(&mut self, base_font: FontId, fallback_font: FontId) -> bool;
+
+    /// Adds a fallback font by name. Returns false if either font is not found.
+    fn add_fallback_font(&mut self, base_font: &str, fallback_font: &str) -> bool {
+        match (self.find_font(base_font), self.find_font(fallback_font)) {
+            (Some(base), Some(fallback)) => self.add_fallback_font_id(base, fallback),
+            _ => false,
+        }
+    }
+
+    /// Removes all fallback fonts of a font by handle.
+    fn reset_fallback_fonts_id(&mut self, base_font: FontId);
+
+    /// Removes all fallback fonts of a font by name.
+    fn reset_fallback_fonts(&mut self, base_font: &str) {
+        if let Some(base) = self.find_font(base_font) {
+            self.reset_fallback_fonts_id(base);
+        }
+    }
 
     /// Sets the font size of current text style.
     fn font_size(&mut self, size: f32);
@@ -492,7 +860,8 @@ pub trait Context {
     /// Sets the blur of current text style.
     fn font_blur(&mut self, blur: f32);
 
-    /// Sets the letter spacing of current text style.
+    /// Sets the letter spacing of current text style. The spacing is added
+    /// between glyphs, on top of the font's kerning.
     fn text_letter_spacing(&mut self, spacing: f32);
 
     /// Sets the proportional line height of current text style. The line height is specified as multiple of font size.
@@ -501,8 +870,33 @@ pub trait Context {
     /// Sets the text align of current text style, see NVGalign for options.
     fn text_align(&mut self, align: Align);
 
+    /// Enables or disables shaping of text. Shaped text is laid out with the
+    /// GSUB and GPOS tables of the font, so that ligatures form, Arabic
+    /// letters join, Indic syllables are reordered and marks sit on their
+    /// base. Off by default, which lays out one glyph per character.
+    fn text_shaping(&mut self, shaping: bool);
+
+    /// Sets the base direction of paragraphs of current text style. Text
+    /// mixing left to right and right to left scripts is put into visual
+    /// order by the Unicode Bidirectional Algorithm, the base direction
+    /// decides the order of the runs and where neutral characters go.
+    fn text_direction(&mut self, direction: TextDirection);
+
+    /// Sets the rules for breaking text into rows of current text style,
+    /// see `LineBreaking`.
+    fn text_line_breaking(&mut self, breaking: LineBreaking);
+
+    /// Sets whether words which do not fit in a row are hyphenated by
+    /// `text_box` and `text_break_lines` of current text style. Off by
+    /// default.
+    fn text_hyphenation(&mut self, hyphenate: bool);
+
+    /// Sets what finds the hyphenation points of words, such as `Patterns`
+    /// loaded from a TeX pattern file. The default is US English.
+    fn set_hyphenator(&mut self, hyphenator: Box<dyn Hyphenate>);
+
     /// Sets the font face based on specified id of current text style.
-    fn font_face_id(&mut self, font: isize);
+    fn font_face_id(&mut self, font: FontId);
 
     /// Sets the font face based on specified name of current text style.
     fn font_face(&mut self, font: &str);
@@ -511,31 +905,54 @@ pub trait Context {
     fn text(&mut self, x: f32, y: f32, span: &str) -> f32;
 
     /// Draws multi-line text string at specified location wrapped at the specified width. If end is specified only the sub-string up to the end is drawn.
-    /// White space is stripped at the beginning of the rows, the text is split at word boundaries or when new-line characters are encountered.
-    /// Words longer than the max width are slit at nearest character (i.e. no hyphenation).
+    /// White space is stripped at the beginning of the rows, the text is split at the break opportunities of current
+    /// line breaking rules or when new-line characters are encountered.
+    /// Words which do not fit are hyphenated if `text_hyphenation` is on, words longer than the max width are
+    /// otherwise slit at nearest character.
     fn text_box(&mut self, x: f32, y: f32, break_row_width: f32, span: &str);
 
-    /// Measures the specified text string. Parameter bounds should be a pointer to float[4] -> f32;
-    /// if the bounding box of the text should be returned. The bounds value are [xmin,ymin, xmax,ymax]
+    /// Measures the specified text string. The bounding box of the text is returned in bounds
+    /// as [xmin,ymin, xmax,ymax].
     /// Returns the horizontal advance of the measured text (i.e. where the next character should drawn).
     /// Measured values are returned in local coordinate space.
-    fn text_bounds(&mut self, x: f32, y: f32, span: &str, bounds: &mut f32) -> f32;
+    fn text_bounds(&mut self, x: f32, y: f32, span: &str, bounds: &mut [f32; 4]) -> f32;
 
-    /// Measures the specified multi-text string. Parameter bounds should be a pointer to float[4],
-    /// if the bounding box of the text should be returned. The bounds value are [xmin,ymin, xmax,ymax]
+    /// Measures the specified multi-text string. The bounding box of the text is returned in bounds
+    /// as [xmin,ymin, xmax,ymax].
     /// Measured values are returned in local coordinate space.
-    fn text_box_bounds(&mut self, x: f32, y: f32, break_row_width: f32, span: &str, bounds: &mut f32);
+    fn text_box_bounds(&mut self, x: f32, y: f32, break_row_width: f32, span: &str, bounds: &mut [f32; 4]);
 
     /// Calculates the glyph x positions of the specified text. If end is specified only the sub-string will be used.
+    /// The positions are in the order of the string, which is not left to right for bidirectional text.
     /// Measured values are returned in local coordinate space.
-    fn text_glyph_positions_into(&mut self, x: f32, y: f32, span: &str, vec: &mut Vec<GlyphPosition>);
+    fn text_glyph_positions_into<'a>(&mut self, x: f32, y: f32, span: &'a str, vec: &mut Vec<GlyphPosition<'a>>);
 
     /// Returns the vertical metrics based on the current text style.
     /// Measured values are returned in local coordinate space.
     fn text_metrics(&mut self, ascender: &mut f32, descender: &mut f32, lineh: &mut f32);
 
     /// Breaks the specified text into lines. If end is specified only the sub-string will be used.
-    /// White space is stripped at the beginning of the rows, the text is split at word boundaries or when new-line characters are encountered.
-    /// Words longer than the max width are slit at nearest character (i.e. no hyphenation).
-    fn text_break_lines(&mut self, span: &str, break_row_width: f32, rows: &mut Vec<TextRow>) -> isize;
+    /// White space is stripped at the beginning of the rows, the text is split at the break opportunities of current
+    /// line breaking rules or when new-line characters are encountered.
+    /// Words which do not fit are hyphenated if `text_hyphenation` is on, words longer than the max width are
+    /// otherwise slit at nearest character.
+    fn text_break_lines<'a>(&mut self, span: &'a str, break_row_width: f32, rows: &mut Vec<TextRow<'a>>) -> isize;
+
+    /// Breaks rich text into rows of at most `break_row_width`, like `text_break_lines`. The spans are broken as one
+    /// text, so a row can hold parts of several spans and a word can continue across spans.
+    /// Measured values are returned in local coordinate space.
+    fn rich_text_break_lines(&mut self, spans: &[TextSpan], break_row_width: f32, rows: &mut Vec<RichTextRow>);
+
+    /// Draws rows of rich text broken by `rich_text_break_lines` from the same spans. The top of the first row is at
+    /// `y`, the rows are aligned horizontally in `break_row_width` by the current text alignment.
+    fn rich_text_rows(&mut self, x: f32, y: f32, break_row_width: f32, spans: &[TextSpan], rows: &[RichTextRow]);
+
+    /// Draws rich text wrapped at the specified width, with the top of the first row at `y`, see
+    /// `rich_text_break_lines` and `rich_text_rows`. Returns the height of the rows.
+    fn rich_text_box(&mut self, x: f32, y: f32, break_row_width: f32, spans: &[TextSpan]) -> f32 {
+        let mut rows = Vec::new();
+        self.rich_text_break_lines(spans, break_row_width, &mut rows);
+        self.rich_text_rows(x, y, break_row_width, spans, &rows);
+        rows.iter().map(|row| row.height).sum()
+    }
 }
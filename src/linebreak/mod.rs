@@ -0,0 +1,331 @@
+//! Unicode line breaking, UAX #14.
+//!
+//! `line_breaks` finds the positions in a string where a line may or must
+//! end: between ideographs, after spaces and hyphens, after soft hyphens
+//! and zero width spaces, but not before closing punctuation or inside
+//! numbers. Which opportunities are taken is left to the caller.
+
+mod tables;
+
+/// Line break classes of characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBreakClass {
+    /// Mandatory breaks, such as form feed and the line separator.
+    BK,
+    /// Carriage return.
+    CR,
+    /// Line feed.
+    LF,
+    /// Next line.
+    NL,
+    /// Space.
+    SP,
+    /// Zero width space.
+    ZW,
+    /// Zero width joiner.
+    ZWJ,
+    /// Word joiners.
+    WJ,
+    /// Non-breaking glue, such as the no-break space.
+    GL,
+    /// Combining marks and control characters.
+    CM,
+    /// Break opportunity after, such as tabs and soft hyphens.
+    BA,
+    /// Break opportunity before.
+    BB,
+    /// Break opportunity before and after, the em dash.
+    B2,
+    /// Hyphen-minus.
+    HY,
+    /// Contingent break opportunity, objects.
+    CB,
+    /// Closing punctuation.
+    CL,
+    /// Closing parentheses.
+    CP,
+    /// Opening punctuation.
+    OP,
+    /// Quotation marks.
+    QU,
+    /// Exclamation and interrogation marks.
+    EX,
+    /// Infix numeric separators, such as comma and full stop.
+    IS,
+    /// Symbols allowing a break after, the slash.
+    SY,
+    /// Prefix numeric, such as currency signs.
+    PR,
+    /// Postfix numeric, such as percent signs.
+    PO,
+    /// Digits.
+    NU,
+    /// Nonstarters, such as ideographic iteration marks.
+    NS,
+    /// Conditional Japanese starters, small kana.
+    CJ,
+    /// Inseparable characters, leaders and ellipses.
+    IN,
+    /// Ideographs.
+    ID,
+    /// Hangul leading jamo.
+    JL,
+    /// Hangul vowel jamo.
+    JV,
+    /// Hangul trailing jamo.
+    JT,
+    /// Hangul syllables without a trailing consonant.
+    H2,
+    /// Hangul syllables with a trailing consonant.
+    H3,
+    /// Hebrew letters.
+    HL,
+    /// Regional indicators, which pair up into flags.
+    RI,
+    /// Emoji bases.
+    EB,
+    /// Emoji modifiers.
+    EM,
+    /// Alphabetic characters and everything else.
+    AL,
+}
+
+use self::LineBreakClass::*;
+
+/// Kinds of break opportunities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Break {
+    /// The line must end here, after a new-line character.
+    Mandatory,
+    /// The line may end here.
+    Allowed,
+}
+
+/// Returns the line break class of a character, with the classes UAX #14
+/// resolves before breaking (`CJ` to `NS`) already resolved.
+pub fn line_break_class(c: char) -> LineBreakClass {
+    let c = c as u32;
+    match tables::LINE_BREAK_CLASSES.binary_search_by(|&(start, end, _)| {
+        if end < c {
+            std::cmp::Ordering::Less
+        } else if start > c {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }) {
+        Ok(i) => match tables::LINE_BREAK_CLASSES[i].2 {
+            CJ => NS,
+            class => class,
+        },
+        Err(_) => AL,
+    }
+}
+
+/// Returns true for characters which are wide in East Asian typography.
+fn is_wide(c: char) -> bool {
+    matches!(c as u32, 0x1100..=0x115f | 0x2e80..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff | 0xfe30..=0xfe4f |
+                       0xff00..=0xff60 | 0xffe0..=0xffe6 | 0x1f300..=0x1f64f | 0x1f900..=0x1f9ff | 0x20000..=0x3fffd)
+}
+
+/// What the rules of UAX #14 look at before a position.
+struct Before {
+    /// Class of the character before the position.
+    class:      LineBreakClass,
+    c:          char,
+    /// Class of the last character which is not a space, for the rules
+    /// which look across spaces.
+    spaces:     LineBreakClass,
+    /// The character before is followed by a zero width joiner.
+    zwj:        bool,
+    /// The character before is a hyphen after a Hebrew letter.
+    hl_hyphen:  bool,
+    /// Number of regional indicators before the position.
+    regional:   usize,
+}
+
+/// Returns the break opportunities of a string, sorted by byte offset. A
+/// break at an offset ends the line before the character there. The end
+/// of the string, which always ends a line, is not included.
+pub fn line_breaks(text: &str) -> Vec<(usize, Break)> {
+    let mut breaks = Vec::new();
+    let mut chars = text.char_indices();
+    let mut before = match chars.next() {
+        Some((_, c)) => {
+            // Marks at the start of the text are letters (LB10).
+            let class = match line_break_class(c) { CM | ZWJ => AL, class => class };
+            Before{ class:     class,
+                    c:         c,
+                    spaces:    class,
+                    zwj:       false,
+                    hl_hyphen: false,
+                    regional:  if class == RI { 1 } else { 0 } }
+        }
+        None => return breaks,
+    };
+
+    for (offset, c) in chars {
+        let mut class = line_break_class(c);
+        // Combining marks take the class of the character before them (LB9).
+        if class == CM || class == ZWJ {
+            match before.class {
+                BK | CR | LF | NL | SP | ZW => class = AL, // LB10
+                _ => {
+                    before.zwj = class == ZWJ;
+                    continue;
+                }
+            }
+        }
+        if let Some(kind) = break_before(&before, class, c) {
+            breaks.push((offset, kind));
+        }
+
+        before.hl_hyphen = (class == HY || class == BA) && before.class == HL;
+        before.regional = if class == RI { before.regional + 1 } else { 0 };
+        before.zwj = class == ZWJ;
+        if class != SP {
+            before.spaces = class;
+        }
+        before.class = class;
+        before.c = c;
+    }
+    breaks
+}
+
+/// Applies the rules of UAX #14 to a character `c` of class `b`. Returns
+/// the kind of break before it, or `None` if a line must not break there.
+fn break_before(before: &Before, b: LineBreakClass, c: char) -> Option<Break> {
+    let a = before.class;
+    // New lines (LB4, LB5, LB6).
+    match (a, b) {
+        (BK, _) => return Some(Break::Mandatory),
+        (CR, LF) => return None,
+        (CR, _) | (LF, _) | (NL, _) => return Some(Break::Mandatory),
+        (_, BK) | (_, CR) | (_, LF) | (_, NL) => return None,
+        _ => (),
+    }
+    // Spaces (LB7, LB8, LB8a).
+    if b == SP || b == ZW {
+        return None;
+    }
+    if before.spaces == ZW && (a == ZW || a == SP) {
+        return Some(Break::Allowed);
+    }
+    if before.zwj {
+        return None;
+    }
+    // Joiners and glue (LB11, LB12, LB12a).
+    if a == WJ || b == WJ || a == GL {
+        return None;
+    }
+    if b == GL && a != SP && a != BA && a != HY {
+        return None;
+    }
+    // Punctuation, also after spaces (LB13 to LB17).
+    match b {
+        CL | CP | EX | IS | SY => return None,
+        _ => (),
+    }
+    match (before.spaces, b) {
+        (OP, _) | (QU, OP) | (CL, NS) | (CP, NS) | (B2, B2) => return None,
+        _ => (),
+    }
+    // Otherwise lines break after spaces (LB18).
+    if a == SP {
+        return Some(Break::Allowed);
+    }
+    let allowed = match (a, b) {
+        // Quotation marks and contingent breaks (LB19, LB20).
+        (_, QU) | (QU, _) => false,
+        (_, CB) | (CB, _) => true,
+        // Breaks after hyphens and before nonstarters (LB21 to LB22).
+        (_, BA) | (_, HY) | (_, NS) | (BB, _) => false,
+        _ if before.hl_hyphen => false,
+        (SY, HL) | (_, IN) => false,
+        // Numbers and the letters, ideographs and signs around them
+        // (LB23 to LB25).
+        (AL, NU) | (HL, NU) | (NU, AL) | (NU, HL) => false,
+        (PR, ID) | (PR, EB) | (PR, EM) | (ID, PO) | (EB, PO) | (EM, PO) => false,
+        (PR, AL) | (PR, HL) | (PO, AL) | (PO, HL) | (AL, PR) | (AL, PO) | (HL, PR) | (HL, PO) => false,
+        (CL, PO) | (CP, PO) | (CL, PR) | (CP, PR) | (NU, PO) | (NU, PR) => false,
+        (PO, OP) | (PO, NU) | (PR, OP) | (PR, NU) | (HY, NU) | (IS, NU) | (NU, NU) | (SY, NU) => false,
+        // Korean syllables (LB26, LB27).
+        (JL, JL) | (JL, JV) | (JL, H2) | (JL, H3) | (JV, JV) | (JV, JT) | (H2, JV) | (H2, JT) |
+        (JT, JT) | (H3, JT) => false,
+        (JL, PO) | (JV, PO) | (JT, PO) | (H2, PO) | (H3, PO) => false,
+        (PR, JL) | (PR, JV) | (PR, JT) | (PR, H2) | (PR, H3) => false,
+        // Words (LB28, LB29).
+        (AL, AL) | (AL, HL) | (HL, AL) | (HL, HL) | (IS, AL) | (IS, HL) => false,
+        // Parentheses around words (LB30).
+        (AL, OP) | (HL, OP) | (NU, OP) if !is_wide(c) => false,
+        (CP, AL) | (CP, HL) | (CP, NU) if !is_wide(before.c) => false,
+        // Flags and emoji (LB30a, LB30b).
+        (RI, RI) if before.regional % 2 == 1 => false,
+        (EB, EM) => false,
+        // Everywhere else (LB31).
+        _ => true,
+    };
+    if allowed {
+        Some(Break::Allowed)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the offsets where a line may end.
+    fn allowed(text: &str) -> Vec<usize> {
+        line_breaks(text).into_iter()
+            .filter(|&(_, kind)| kind == Break::Allowed)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn breaks_after_spaces_and_hyphens() {
+        assert_eq!(allowed("hello world"), [6]);
+        assert_eq!(allowed("well-known  fact"), [5, 12]);
+        assert_eq!(allowed("no\u{a0}break"), []);
+        assert_eq!(allowed("1,000.50 $100"), [9]);
+        assert_eq!(allowed(""), []);
+    }
+
+    #[test]
+    fn breaks_at_new_lines() {
+        assert_eq!(line_breaks("a\nb"), [(2, Break::Mandatory)]);
+        assert_eq!(line_breaks("a\r\nb c"), [(3, Break::Mandatory), (5, Break::Allowed)]);
+        assert_eq!(line_breaks("a\u{2028}b"), [(4, Break::Mandatory)]);
+    }
+
+    #[test]
+    fn breaks_between_ideographs() {
+        assert_eq!(allowed("\u{65e5}\u{672c}\u{8a9e}"), [3, 6]);
+        assert_eq!(allowed("\u{d55c}\u{ad6d}"), [3]);
+        // Small kana do not start a line.
+        assert_eq!(allowed("\u{3061}\u{3087}\u{3063}\u{3068}"), [9]);
+        assert_eq!(line_break_class('\u{3087}'), NS);
+    }
+
+    #[test]
+    fn keeps_closing_punctuation_on_the_line() {
+        // Ideographic full stop and corner brackets.
+        assert_eq!(allowed("\u{65e5}\u{672c}\u{3002}\u{8a9e}"), [3, 9]);
+        assert_eq!(allowed("\u{65e5}\u{300c}\u{672c}\u{300d}\u{8a9e}"), [3, 12]);
+        assert_eq!(allowed("(word) next"), [7]);
+        assert_eq!(allowed("x )"), []);
+        assert_eq!(allowed("end! next"), [5]);
+    }
+
+    #[test]
+    fn breaks_after_soft_hyphens_and_zero_width_spaces() {
+        assert_eq!(allowed("hy\u{ad}phen"), [4]);
+        assert_eq!(allowed("a\u{200b}b"), [4]);
+        // The break goes after the spaces following a zero width space.
+        assert_eq!(allowed("a\u{200b} b"), [5]);
+        // But not after a word joiner.
+        assert_eq!(allowed("a\u{2060}b"), []);
+    }
+}
@@ -0,0 +1,442 @@
+//! Line break classes of characters, derived from the general categories
+//! and East Asian widths of the Unicode Character Database 14.0.0, with
+//! the classes of spaces, punctuation and symbols assigned as in
+//! LineBreak.txt. Complex context scripts such as Thai are `AL`.
+
+use super::LineBreakClass;
+use super::LineBreakClass::*;
+
+/// Ranges of characters with their line break class, sorted. Characters
+/// not in the table are `AL`.
+pub const LINE_BREAK_CLASSES: &[(u32, u32, LineBreakClass)] = &[
+    (0x0000, 0x0008, CM), (0x0009, 0x0009, BA), (0x000a, 0x000a, LF), (0x000b, 0x000c, BK),
+    (0x000d, 0x000d, CR), (0x000e, 0x001f, CM), (0x0020, 0x0020, SP), (0x0021, 0x0021, EX),
+    (0x0022, 0x0022, QU), (0x0024, 0x0024, PR), (0x0025, 0x0025, PO), (0x0027, 0x0027, QU),
+    (0x0028, 0x0028, OP), (0x0029, 0x0029, CP), (0x002b, 0x002b, PR), (0x002c, 0x002c, IS),
+    (0x002d, 0x002d, HY), (0x002e, 0x002e, IS), (0x002f, 0x002f, SY), (0x0030, 0x0039, NU),
+    (0x003a, 0x003b, IS), (0x003f, 0x003f, EX), (0x005b, 0x005b, OP), (0x005c, 0x005c, PR),
+    (0x005d, 0x005d, CP), (0x007b, 0x007b, OP), (0x007c, 0x007c, BA), (0x007d, 0x007d, CL),
+    (0x007f, 0x0084, CM), (0x0085, 0x0085, NL), (0x0086, 0x009f, CM), (0x00a0, 0x00a0, GL),
+    (0x00a2, 0x00a2, PO), (0x00a3, 0x00a5, PR), (0x00ab, 0x00ab, QU), (0x00ad, 0x00ad, BA),
+    (0x00b0, 0x00b0, PO), (0x00b1, 0x00b1, PR), (0x00b4, 0x00b4, BB), (0x00bb, 0x00bb, QU),
+    (0x02c8, 0x02c8, BB), (0x02cc, 0x02cc, BB), (0x02df, 0x02df, BB), (0x0300, 0x034e, CM),
+    (0x034f, 0x034f, GL), (0x0350, 0x035b, CM), (0x035c, 0x0362, GL), (0x0363, 0x036f, CM),
+    (0x037e, 0x037e, IS), (0x0483, 0x0489, CM), (0x0589, 0x0589, IS), (0x058a, 0x058a, BA),
+    (0x058f, 0x058f, PR), (0x0591, 0x05bd, CM), (0x05be, 0x05be, BA), (0x05bf, 0x05bf, CM),
+    (0x05c1, 0x05c2, CM), (0x05c4, 0x05c5, CM), (0x05c6, 0x05c6, EX), (0x05c7, 0x05c7, CM),
+    (0x05d0, 0x05ea, HL), (0x05ef, 0x05f2, HL), (0x060b, 0x060b, PO), (0x060c, 0x060d, IS),
+    (0x0610, 0x061a, CM), (0x061b, 0x061b, EX), (0x061d, 0x061f, EX), (0x064b, 0x065f, CM),
+    (0x0660, 0x0669, NU), (0x066a, 0x066a, PO), (0x0670, 0x0670, CM), (0x06d4, 0x06d4, EX),
+    (0x06d6, 0x06dc, CM), (0x06df, 0x06e4, CM), (0x06e7, 0x06e8, CM), (0x06ea, 0x06ed, CM),
+    (0x06f0, 0x06f9, NU), (0x0711, 0x0711, CM), (0x0730, 0x074a, CM), (0x07a6, 0x07b0, CM),
+    (0x07c0, 0x07c9, NU), (0x07eb, 0x07f3, CM), (0x07f8, 0x07f8, IS), (0x07f9, 0x07f9, EX),
+    (0x07fd, 0x07fd, CM), (0x07fe, 0x07ff, PR), (0x0816, 0x0819, CM), (0x081b, 0x0823, CM),
+    (0x0825, 0x0827, CM), (0x0829, 0x082d, CM), (0x0859, 0x085b, CM), (0x0898, 0x089f, CM),
+    (0x08ca, 0x08e1, CM), (0x08e3, 0x0903, CM), (0x093a, 0x093c, CM), (0x093e, 0x094f, CM),
+    (0x0951, 0x0957, CM), (0x0962, 0x0963, CM), (0x0964, 0x0965, BA), (0x0966, 0x096f, NU),
+    (0x0981, 0x0983, CM), (0x09bc, 0x09bc, CM), (0x09be, 0x09c4, CM), (0x09c7, 0x09c8, CM),
+    (0x09cb, 0x09cd, CM), (0x09d7, 0x09d7, CM), (0x09e2, 0x09e3, CM), (0x09e6, 0x09ef, NU),
+    (0x09f2, 0x09f3, PO), (0x09f9, 0x09f9, PO), (0x09fb, 0x09fb, PR), (0x09fe, 0x09fe, CM),
+    (0x0a01, 0x0a03, CM), (0x0a3c, 0x0a3c, CM), (0x0a3e, 0x0a42, CM), (0x0a47, 0x0a48, CM),
+    (0x0a4b, 0x0a4d, CM), (0x0a51, 0x0a51, CM), (0x0a66, 0x0a6f, NU), (0x0a70, 0x0a71, CM),
+    (0x0a75, 0x0a75, CM), (0x0a81, 0x0a83, CM), (0x0abc, 0x0abc, CM), (0x0abe, 0x0ac5, CM),
+    (0x0ac7, 0x0ac9, CM), (0x0acb, 0x0acd, CM), (0x0ae2, 0x0ae3, CM), (0x0ae6, 0x0aef, NU),
+    (0x0af1, 0x0af1, PR), (0x0afa, 0x0aff, CM), (0x0b01, 0x0b03, CM), (0x0b3c, 0x0b3c, CM),
+    (0x0b3e, 0x0b44, CM), (0x0b47, 0x0b48, CM), (0x0b4b, 0x0b4d, CM), (0x0b55, 0x0b57, CM),
+    (0x0b62, 0x0b63, CM), (0x0b66, 0x0b6f, NU), (0x0b82, 0x0b82, CM), (0x0bbe, 0x0bc2, CM),
+    (0x0bc6, 0x0bc8, CM), (0x0bca, 0x0bcd, CM), (0x0bd7, 0x0bd7, CM), (0x0be6, 0x0bef, NU),
+    (0x0bf9, 0x0bf9, PR), (0x0c00, 0x0c04, CM), (0x0c3c, 0x0c3c, CM), (0x0c3e, 0x0c44, CM),
+    (0x0c46, 0x0c48, CM), (0x0c4a, 0x0c4d, CM), (0x0c55, 0x0c56, CM), (0x0c62, 0x0c63, CM),
+    (0x0c66, 0x0c6f, NU), (0x0c81, 0x0c83, CM), (0x0cbc, 0x0cbc, CM), (0x0cbe, 0x0cc4, CM),
+    (0x0cc6, 0x0cc8, CM), (0x0cca, 0x0ccd, CM), (0x0cd5, 0x0cd6, CM), (0x0ce2, 0x0ce3, CM),
+    (0x0ce6, 0x0cef, NU), (0x0d00, 0x0d03, CM), (0x0d3b, 0x0d3c, CM), (0x0d3e, 0x0d44, CM),
+    (0x0d46, 0x0d48, CM), (0x0d4a, 0x0d4d, CM), (0x0d57, 0x0d57, CM), (0x0d62, 0x0d63, CM),
+    (0x0d66, 0x0d6f, NU), (0x0d79, 0x0d79, PO), (0x0d81, 0x0d83, CM), (0x0dca, 0x0dca, CM),
+    (0x0dcf, 0x0dd4, CM), (0x0dd6, 0x0dd6, CM), (0x0dd8, 0x0ddf, CM), (0x0de6, 0x0def, NU),
+    (0x0df2, 0x0df3, CM), (0x0e31, 0x0e31, CM), (0x0e34, 0x0e3a, CM), (0x0e47, 0x0e4e, CM),
+    (0x0e5a, 0x0e5b, BA), (0x0eb1, 0x0eb1, CM), (0x0eb4, 0x0ebc, CM), (0x0ec8, 0x0ecd, CM),
+    (0x0f01, 0x0f04, BB), (0x0f06, 0x0f07, BB), (0x0f08, 0x0f08, GL), (0x0f09, 0x0f0a, BB),
+    (0x0f0b, 0x0f0b, BA), (0x0f0c, 0x0f0c, GL), (0x0f0d, 0x0f11, EX), (0x0f12, 0x0f12, GL),
+    (0x0f14, 0x0f14, EX), (0x0f18, 0x0f19, CM), (0x0f20, 0x0f29, NU), (0x0f35, 0x0f35, CM),
+    (0x0f37, 0x0f37, CM), (0x0f39, 0x0f39, CM), (0x0f3a, 0x0f3a, OP), (0x0f3b, 0x0f3b, CL),
+    (0x0f3c, 0x0f3c, OP), (0x0f3d, 0x0f3d, CL), (0x0f3e, 0x0f3f, CM), (0x0f71, 0x0f84, CM),
+    (0x0f86, 0x0f87, CM), (0x0f8d, 0x0f97, CM), (0x0f99, 0x0fbc, CM), (0x0fc6, 0x0fc6, CM),
+    (0x0fd0, 0x0fd1, BB), (0x0fd3, 0x0fd3, BB), (0x102b, 0x103e, CM), (0x104a, 0x104b, BA),
+    (0x1056, 0x1059, CM), (0x105e, 0x1060, CM), (0x1062, 0x1064, CM), (0x1067, 0x106d, CM),
+    (0x1071, 0x1074, CM), (0x1082, 0x108d, CM), (0x108f, 0x108f, CM), (0x109a, 0x109d, CM),
+    (0x1100, 0x115f, JL), (0x1160, 0x11a7, JV), (0x11a8, 0x11ff, JT), (0x135d, 0x135f, CM),
+    (0x1361, 0x1361, BA), (0x1680, 0x1680, BA), (0x169b, 0x169b, OP), (0x169c, 0x169c, CL),
+    (0x1712, 0x1715, CM), (0x1732, 0x1734, CM), (0x1752, 0x1753, CM), (0x1772, 0x1773, CM),
+    (0x17b4, 0x17d3, CM), (0x17d6, 0x17d6, NS), (0x17d8, 0x17d8, BA), (0x17da, 0x17da, BA),
+    (0x17dd, 0x17dd, CM), (0x1802, 0x1803, EX), (0x1804, 0x1805, BA), (0x1806, 0x1806, BB),
+    (0x1808, 0x1809, EX), (0x180b, 0x180d, CM), (0x180e, 0x180e, GL), (0x180f, 0x180f, CM),
+    (0x1810, 0x1819, NU), (0x1885, 0x1886, CM), (0x18a9, 0x18a9, CM), (0x1920, 0x192b, CM),
+    (0x1930, 0x193b, CM), (0x1944, 0x1945, EX), (0x1946, 0x194f, NU), (0x1a17, 0x1a1b, CM),
+    (0x1a55, 0x1a5e, CM), (0x1a60, 0x1a7c, CM), (0x1a7f, 0x1a7f, CM), (0x1ab0, 0x1ace, CM),
+    (0x1b00, 0x1b04, CM), (0x1b34, 0x1b44, CM), (0x1b50, 0x1b59, NU), (0x1b6b, 0x1b73, CM),
+    (0x1b80, 0x1b82, CM), (0x1ba1, 0x1bad, CM), (0x1bb0, 0x1bb9, NU), (0x1be6, 0x1bf3, CM),
+    (0x1c24, 0x1c37, CM), (0x1c40, 0x1c49, NU), (0x1c50, 0x1c59, NU), (0x1cd0, 0x1cd2, CM),
+    (0x1cd4, 0x1ce8, CM), (0x1ced, 0x1ced, CM), (0x1cf4, 0x1cf4, CM), (0x1cf7, 0x1cf9, CM),
+    (0x1dc0, 0x1dff, CM), (0x1ffd, 0x1ffd, BB), (0x2000, 0x2006, BA), (0x2007, 0x2007, GL),
+    (0x2008, 0x200a, BA), (0x200b, 0x200b, ZW), (0x200c, 0x200c, CM), (0x200d, 0x200d, ZWJ),
+    (0x2010, 0x2010, BA), (0x2011, 0x2011, GL), (0x2012, 0x2013, BA), (0x2014, 0x2014, B2),
+    (0x2018, 0x2019, QU), (0x201a, 0x201a, OP), (0x201b, 0x201d, QU), (0x201e, 0x201e, OP),
+    (0x201f, 0x201f, QU), (0x2024, 0x2026, IN), (0x2027, 0x2027, BA), (0x2028, 0x2029, BK),
+    (0x202f, 0x202f, GL), (0x2030, 0x2037, PO), (0x2039, 0x203a, QU), (0x203c, 0x203d, NS),
+    (0x2044, 0x2044, IS), (0x2045, 0x2045, OP), (0x2046, 0x2046, CL), (0x2047, 0x2049, NS),
+    (0x2056, 0x2056, BA), (0x2058, 0x205b, BA), (0x205d, 0x205f, BA), (0x2060, 0x2060, WJ),
+    (0x207d, 0x207d, OP), (0x207e, 0x207e, CL), (0x208d, 0x208d, OP), (0x208e, 0x208e, CL),
+    (0x20a0, 0x20a6, PR), (0x20a7, 0x20a7, PO), (0x20a8, 0x20b5, PR), (0x20b6, 0x20b6, PO),
+    (0x20b7, 0x20ba, PR), (0x20bb, 0x20bb, PO), (0x20bc, 0x20bd, PR), (0x20be, 0x20be, PO),
+    (0x20bf, 0x20c0, PR), (0x20d0, 0x20f0, CM), (0x2103, 0x2103, PO), (0x2109, 0x2109, PO),
+    (0x2116, 0x2116, PR), (0x2212, 0x2213, PR), (0x22ef, 0x22ef, IN), (0x2308, 0x2308, OP),
+    (0x2309, 0x2309, CL), (0x230a, 0x230a, OP), (0x230b, 0x230b, CL), (0x231a, 0x231b, ID),
+    (0x2329, 0x2329, OP), (0x232a, 0x232a, CL), (0x23e9, 0x23ec, ID), (0x23f0, 0x23f0, ID),
+    (0x23f3, 0x23f3, ID), (0x25fd, 0x25fe, ID), (0x2614, 0x2615, ID), (0x261d, 0x261d, EB),
+    (0x2648, 0x2653, ID), (0x267f, 0x267f, ID), (0x2693, 0x2693, ID), (0x26a1, 0x26a1, ID),
+    (0x26aa, 0x26ab, ID), (0x26bd, 0x26be, ID), (0x26c4, 0x26c5, ID), (0x26ce, 0x26ce, ID),
+    (0x26d4, 0x26d4, ID), (0x26ea, 0x26ea, ID), (0x26f2, 0x26f3, ID), (0x26f5, 0x26f5, ID),
+    (0x26f9, 0x26f9, EB), (0x26fa, 0x26fa, ID), (0x26fd, 0x26fd, ID), (0x2705, 0x2705, ID),
+    (0x270a, 0x270d, EB), (0x2728, 0x2728, ID), (0x274c, 0x274c, ID), (0x274e, 0x274e, ID),
+    (0x2753, 0x2755, ID), (0x2757, 0x2757, ID), (0x275b, 0x2760, QU), (0x2762, 0x2763, EX),
+    (0x2768, 0x2768, OP), (0x2769, 0x2769, CL), (0x276a, 0x276a, OP), (0x276b, 0x276b, CL),
+    (0x276c, 0x276c, OP), (0x276d, 0x276d, CL), (0x276e, 0x276e, OP), (0x276f, 0x276f, CL),
+    (0x2770, 0x2770, OP), (0x2771, 0x2771, CL), (0x2772, 0x2772, OP), (0x2773, 0x2773, CL),
+    (0x2774, 0x2774, OP), (0x2775, 0x2775, CL), (0x2795, 0x2797, ID), (0x27b0, 0x27b0, ID),
+    (0x27bf, 0x27bf, ID), (0x27c5, 0x27c5, OP), (0x27c6, 0x27c6, CL), (0x27e6, 0x27e6, OP),
+    (0x27e7, 0x27e7, CL), (0x27e8, 0x27e8, OP), (0x27e9, 0x27e9, CL), (0x27ea, 0x27ea, OP),
+    (0x27eb, 0x27eb, CL), (0x27ec, 0x27ec, OP), (0x27ed, 0x27ed, CL), (0x27ee, 0x27ee, OP),
+    (0x27ef, 0x27ef, CL), (0x2983, 0x2983, OP), (0x2984, 0x2984, CL), (0x2985, 0x2985, OP),
+    (0x2986, 0x2986, CL), (0x2987, 0x2987, OP), (0x2988, 0x2988, CL), (0x2989, 0x2989, OP),
+    (0x298a, 0x298a, CL), (0x298b, 0x298b, OP), (0x298c, 0x298c, CL), (0x298d, 0x298d, OP),
+    (0x298e, 0x298e, CL), (0x298f, 0x298f, OP), (0x2990, 0x2990, CL), (0x2991, 0x2991, OP),
+    (0x2992, 0x2992, CL), (0x2993, 0x2993, OP), (0x2994, 0x2994, CL), (0x2995, 0x2995, OP),
+    (0x2996, 0x2996, CL), (0x2997, 0x2997, OP), (0x2998, 0x2998, CL), (0x29d8, 0x29d8, OP),
+    (0x29d9, 0x29d9, CL), (0x29da, 0x29da, OP), (0x29db, 0x29db, CL), (0x29fc, 0x29fc, OP),
+    (0x29fd, 0x29fd, CL), (0x2b1b, 0x2b1c, ID), (0x2b50, 0x2b50, ID), (0x2b55, 0x2b55, ID),
+    (0x2cef, 0x2cf1, CM), (0x2cf9, 0x2cf9, EX), (0x2cfa, 0x2cfd, BA), (0x2cfe, 0x2cfe, EX),
+    (0x2cff, 0x2cff, BA), (0x2d70, 0x2d70, BA), (0x2d7f, 0x2d7f, CM), (0x2de0, 0x2dff, CM),
+    (0x2e00, 0x2e0d, QU), (0x2e0e, 0x2e15, BA), (0x2e17, 0x2e17, BA), (0x2e19, 0x2e19, BA),
+    (0x2e1c, 0x2e1d, QU), (0x2e20, 0x2e21, QU), (0x2e22, 0x2e22, OP), (0x2e23, 0x2e23, CL),
+    (0x2e24, 0x2e24, OP), (0x2e25, 0x2e25, CL), (0x2e26, 0x2e26, OP), (0x2e27, 0x2e27, CL),
+    (0x2e28, 0x2e28, OP), (0x2e29, 0x2e29, CL), (0x2e2a, 0x2e2d, BA), (0x2e2e, 0x2e2e, EX),
+    (0x2e30, 0x2e31, BA), (0x2e33, 0x2e34, BA), (0x2e3a, 0x2e3b, B2), (0x2e3c, 0x2e3e, BA),
+    (0x2e40, 0x2e41, BA), (0x2e42, 0x2e42, OP), (0x2e43, 0x2e4a, BA), (0x2e4c, 0x2e4c, BA),
+    (0x2e4e, 0x2e4e, BA), (0x2e55, 0x2e55, OP), (0x2e56, 0x2e56, CL), (0x2e57, 0x2e57, OP),
+    (0x2e58, 0x2e58, CL), (0x2e59, 0x2e59, OP), (0x2e5a, 0x2e5a, CL), (0x2e5b, 0x2e5b, OP),
+    (0x2e5c, 0x2e5c, CL), (0x2e80, 0x2e99, ID), (0x2e9b, 0x2ef3, ID), (0x2f00, 0x2fd5, ID),
+    (0x2ff0, 0x2ffb, ID), (0x3000, 0x3000, BA), (0x3001, 0x3002, CL), (0x3003, 0x3004, ID),
+    (0x3005, 0x3005, NS), (0x3006, 0x3007, ID), (0x3008, 0x3008, OP), (0x3009, 0x3009, CL),
+    (0x300a, 0x300a, OP), (0x300b, 0x300b, CL), (0x300c, 0x300c, OP), (0x300d, 0x300d, CL),
+    (0x300e, 0x300e, OP), (0x300f, 0x300f, CL), (0x3010, 0x3010, OP), (0x3011, 0x3011, CL),
+    (0x3012, 0x3013, ID), (0x3014, 0x3014, OP), (0x3015, 0x3015, CL), (0x3016, 0x3016, OP),
+    (0x3017, 0x3017, CL), (0x3018, 0x3018, OP), (0x3019, 0x3019, CL), (0x301a, 0x301a, OP),
+    (0x301b, 0x301b, CL), (0x301c, 0x301c, NS), (0x301d, 0x301d, OP), (0x301e, 0x301f, CL),
+    (0x3020, 0x3029, ID), (0x302a, 0x302f, CM), (0x3030, 0x303a, ID), (0x303b, 0x303c, NS),
+    (0x303d, 0x303e, ID), (0x3041, 0x3041, CJ), (0x3042, 0x3042, ID), (0x3043, 0x3043, CJ),
+    (0x3044, 0x3044, ID), (0x3045, 0x3045, CJ), (0x3046, 0x3046, ID), (0x3047, 0x3047, CJ),
+    (0x3048, 0x3048, ID), (0x3049, 0x3049, CJ), (0x304a, 0x3062, ID), (0x3063, 0x3063, CJ),
+    (0x3064, 0x3082, ID), (0x3083, 0x3083, CJ), (0x3084, 0x3084, ID), (0x3085, 0x3085, CJ),
+    (0x3086, 0x3086, ID), (0x3087, 0x3087, CJ), (0x3088, 0x308d, ID), (0x308e, 0x308e, CJ),
+    (0x308f, 0x3094, ID), (0x3095, 0x3096, CJ), (0x3099, 0x309a, CM), (0x309b, 0x309e, NS),
+    (0x309f, 0x309f, ID), (0x30a0, 0x30a0, NS), (0x30a1, 0x30a1, CJ), (0x30a2, 0x30a2, ID),
+    (0x30a3, 0x30a3, CJ), (0x30a4, 0x30a4, ID), (0x30a5, 0x30a5, CJ), (0x30a6, 0x30a6, ID),
+    (0x30a7, 0x30a7, CJ), (0x30a8, 0x30a8, ID), (0x30a9, 0x30a9, CJ), (0x30aa, 0x30c2, ID),
+    (0x30c3, 0x30c3, CJ), (0x30c4, 0x30e2, ID), (0x30e3, 0x30e3, CJ), (0x30e4, 0x30e4, ID),
+    (0x30e5, 0x30e5, CJ), (0x30e6, 0x30e6, ID), (0x30e7, 0x30e7, CJ), (0x30e8, 0x30ed, ID),
+    (0x30ee, 0x30ee, CJ), (0x30ef, 0x30f4, ID), (0x30f5, 0x30f6, CJ), (0x30f7, 0x30fa, ID),
+    (0x30fb, 0x30fb, NS), (0x30fc, 0x30fc, CJ), (0x30fd, 0x30fe, NS), (0x30ff, 0x30ff, ID),
+    (0x3105, 0x312f, ID), (0x3131, 0x318e, ID), (0x3190, 0x31e3, ID), (0x31f0, 0x31ff, CJ),
+    (0x3200, 0x321e, ID), (0x3220, 0x3247, ID), (0x3250, 0x4dbf, ID), (0x4e00, 0xa014, ID),
+    (0xa015, 0xa015, NS), (0xa016, 0xa48c, ID), (0xa490, 0xa4c6, ID), (0xa60e, 0xa60e, EX),
+    (0xa620, 0xa629, NU), (0xa66f, 0xa672, CM), (0xa674, 0xa67d, CM), (0xa69e, 0xa69f, CM),
+    (0xa6f0, 0xa6f1, CM), (0xa802, 0xa802, CM), (0xa806, 0xa806, CM), (0xa80b, 0xa80b, CM),
+    (0xa823, 0xa827, CM), (0xa82c, 0xa82c, CM), (0xa838, 0xa838, PO), (0xa874, 0xa875, BB),
+    (0xa876, 0xa877, EX), (0xa880, 0xa881, CM), (0xa8b4, 0xa8c5, CM), (0xa8d0, 0xa8d9, NU),
+    (0xa8e0, 0xa8f1, CM), (0xa8ff, 0xa8ff, CM), (0xa900, 0xa909, NU), (0xa926, 0xa92d, CM),
+    (0xa947, 0xa953, CM), (0xa960, 0xa97c, JL), (0xa980, 0xa983, CM), (0xa9b3, 0xa9c0, CM),
+    (0xa9d0, 0xa9d9, NU), (0xa9e5, 0xa9e5, CM), (0xaa29, 0xaa36, CM), (0xaa43, 0xaa43, CM),
+    (0xaa4c, 0xaa4d, CM), (0xaa50, 0xaa59, NU), (0xaa7b, 0xaa7d, CM), (0xaab0, 0xaab0, CM),
+    (0xaab2, 0xaab4, CM), (0xaab7, 0xaab8, CM), (0xaabe, 0xaabf, CM), (0xaac1, 0xaac1, CM),
+    (0xaaeb, 0xaaef, CM), (0xaaf5, 0xaaf6, CM), (0xabe3, 0xabea, CM), (0xabec, 0xabed, CM),
+    (0xabf0, 0xabf9, NU), (0xac00, 0xac00, H2), (0xac01, 0xac1b, H3), (0xac1c, 0xac1c, H2),
+    (0xac1d, 0xac37, H3), (0xac38, 0xac38, H2), (0xac39, 0xac53, H3), (0xac54, 0xac54, H2),
+    (0xac55, 0xac6f, H3), (0xac70, 0xac70, H2), (0xac71, 0xac8b, H3), (0xac8c, 0xac8c, H2),
+    (0xac8d, 0xaca7, H3), (0xaca8, 0xaca8, H2), (0xaca9, 0xacc3, H3), (0xacc4, 0xacc4, H2),
+    (0xacc5, 0xacdf, H3), (0xace0, 0xace0, H2), (0xace1, 0xacfb, H3), (0xacfc, 0xacfc, H2),
+    (0xacfd, 0xad17, H3), (0xad18, 0xad18, H2), (0xad19, 0xad33, H3), (0xad34, 0xad34, H2),
+    (0xad35, 0xad4f, H3), (0xad50, 0xad50, H2), (0xad51, 0xad6b, H3), (0xad6c, 0xad6c, H2),
+    (0xad6d, 0xad87, H3), (0xad88, 0xad88, H2), (0xad89, 0xada3, H3), (0xada4, 0xada4, H2),
+    (0xada5, 0xadbf, H3), (0xadc0, 0xadc0, H2), (0xadc1, 0xaddb, H3), (0xaddc, 0xaddc, H2),
+    (0xaddd, 0xadf7, H3), (0xadf8, 0xadf8, H2), (0xadf9, 0xae13, H3), (0xae14, 0xae14, H2),
+    (0xae15, 0xae2f, H3), (0xae30, 0xae30, H2), (0xae31, 0xae4b, H3), (0xae4c, 0xae4c, H2),
+    (0xae4d, 0xae67, H3), (0xae68, 0xae68, H2), (0xae69, 0xae83, H3), (0xae84, 0xae84, H2),
+    (0xae85, 0xae9f, H3), (0xaea0, 0xaea0, H2), (0xaea1, 0xaebb, H3), (0xaebc, 0xaebc, H2),
+    (0xaebd, 0xaed7, H3), (0xaed8, 0xaed8, H2), (0xaed9, 0xaef3, H3), (0xaef4, 0xaef4, H2),
+    (0xaef5, 0xaf0f, H3), (0xaf10, 0xaf10, H2), (0xaf11, 0xaf2b, H3), (0xaf2c, 0xaf2c, H2),
+    (0xaf2d, 0xaf47, H3), (0xaf48, 0xaf48, H2), (0xaf49, 0xaf63, H3), (0xaf64, 0xaf64, H2),
+    (0xaf65, 0xaf7f, H3), (0xaf80, 0xaf80, H2), (0xaf81, 0xaf9b, H3), (0xaf9c, 0xaf9c, H2),
+    (0xaf9d, 0xafb7, H3), (0xafb8, 0xafb8, H2), (0xafb9, 0xafd3, H3), (0xafd4, 0xafd4, H2),
+    (0xafd5, 0xafef, H3), (0xaff0, 0xaff0, H2), (0xaff1, 0xb00b, H3), (0xb00c, 0xb00c, H2),
+    (0xb00d, 0xb027, H3), (0xb028, 0xb028, H2), (0xb029, 0xb043, H3), (0xb044, 0xb044, H2),
+    (0xb045, 0xb05f, H3), (0xb060, 0xb060, H2), (0xb061, 0xb07b, H3), (0xb07c, 0xb07c, H2),
+    (0xb07d, 0xb097, H3), (0xb098, 0xb098, H2), (0xb099, 0xb0b3, H3), (0xb0b4, 0xb0b4, H2),
+    (0xb0b5, 0xb0cf, H3), (0xb0d0, 0xb0d0, H2), (0xb0d1, 0xb0eb, H3), (0xb0ec, 0xb0ec, H2),
+    (0xb0ed, 0xb107, H3), (0xb108, 0xb108, H2), (0xb109, 0xb123, H3), (0xb124, 0xb124, H2),
+    (0xb125, 0xb13f, H3), (0xb140, 0xb140, H2), (0xb141, 0xb15b, H3), (0xb15c, 0xb15c, H2),
+    (0xb15d, 0xb177, H3), (0xb178, 0xb178, H2), (0xb179, 0xb193, H3), (0xb194, 0xb194, H2),
+    (0xb195, 0xb1af, H3), (0xb1b0, 0xb1b0, H2), (0xb1b1, 0xb1cb, H3), (0xb1cc, 0xb1cc, H2),
+    (0xb1cd, 0xb1e7, H3), (0xb1e8, 0xb1e8, H2), (0xb1e9, 0xb203, H3), (0xb204, 0xb204, H2),
+    (0xb205, 0xb21f, H3), (0xb220, 0xb220, H2), (0xb221, 0xb23b, H3), (0xb23c, 0xb23c, H2),
+    (0xb23d, 0xb257, H3), (0xb258, 0xb258, H2), (0xb259, 0xb273, H3), (0xb274, 0xb274, H2),
+    (0xb275, 0xb28f, H3), (0xb290, 0xb290, H2), (0xb291, 0xb2ab, H3), (0xb2ac, 0xb2ac, H2),
+    (0xb2ad, 0xb2c7, H3), (0xb2c8, 0xb2c8, H2), (0xb2c9, 0xb2e3, H3), (0xb2e4, 0xb2e4, H2),
+    (0xb2e5, 0xb2ff, H3), (0xb300, 0xb300, H2), (0xb301, 0xb31b, H3), (0xb31c, 0xb31c, H2),
+    (0xb31d, 0xb337, H3), (0xb338, 0xb338, H2), (0xb339, 0xb353, H3), (0xb354, 0xb354, H2),
+    (0xb355, 0xb36f, H3), (0xb370, 0xb370, H2), (0xb371, 0xb38b, H3), (0xb38c, 0xb38c, H2),
+    (0xb38d, 0xb3a7, H3), (0xb3a8, 0xb3a8, H2), (0xb3a9, 0xb3c3, H3), (0xb3c4, 0xb3c4, H2),
+    (0xb3c5, 0xb3df, H3), (0xb3e0, 0xb3e0, H2), (0xb3e1, 0xb3fb, H3), (0xb3fc, 0xb3fc, H2),
+    (0xb3fd, 0xb417, H3), (0xb418, 0xb418, H2), (0xb419, 0xb433, H3), (0xb434, 0xb434, H2),
+    (0xb435, 0xb44f, H3), (0xb450, 0xb450, H2), (0xb451, 0xb46b, H3), (0xb46c, 0xb46c, H2),
+    (0xb46d, 0xb487, H3), (0xb488, 0xb488, H2), (0xb489, 0xb4a3, H3), (0xb4a4, 0xb4a4, H2),
+    (0xb4a5, 0xb4bf, H3), (0xb4c0, 0xb4c0, H2), (0xb4c1, 0xb4db, H3), (0xb4dc, 0xb4dc, H2),
+    (0xb4dd, 0xb4f7, H3), (0xb4f8, 0xb4f8, H2), (0xb4f9, 0xb513, H3), (0xb514, 0xb514, H2),
+    (0xb515, 0xb52f, H3), (0xb530, 0xb530, H2), (0xb531, 0xb54b, H3), (0xb54c, 0xb54c, H2),
+    (0xb54d, 0xb567, H3), (0xb568, 0xb568, H2), (0xb569, 0xb583, H3), (0xb584, 0xb584, H2),
+    (0xb585, 0xb59f, H3), (0xb5a0, 0xb5a0, H2), (0xb5a1, 0xb5bb, H3), (0xb5bc, 0xb5bc, H2),
+    (0xb5bd, 0xb5d7, H3), (0xb5d8, 0xb5d8, H2), (0xb5d9, 0xb5f3, H3), (0xb5f4, 0xb5f4, H2),
+    (0xb5f5, 0xb60f, H3), (0xb610, 0xb610, H2), (0xb611, 0xb62b, H3), (0xb62c, 0xb62c, H2),
+    (0xb62d, 0xb647, H3), (0xb648, 0xb648, H2), (0xb649, 0xb663, H3), (0xb664, 0xb664, H2),
+    (0xb665, 0xb67f, H3), (0xb680, 0xb680, H2), (0xb681, 0xb69b, H3), (0xb69c, 0xb69c, H2),
+    (0xb69d, 0xb6b7, H3), (0xb6b8, 0xb6b8, H2), (0xb6b9, 0xb6d3, H3), (0xb6d4, 0xb6d4, H2),
+    (0xb6d5, 0xb6ef, H3), (0xb6f0, 0xb6f0, H2), (0xb6f1, 0xb70b, H3), (0xb70c, 0xb70c, H2),
+    (0xb70d, 0xb727, H3), (0xb728, 0xb728, H2), (0xb729, 0xb743, H3), (0xb744, 0xb744, H2),
+    (0xb745, 0xb75f, H3), (0xb760, 0xb760, H2), (0xb761, 0xb77b, H3), (0xb77c, 0xb77c, H2),
+    (0xb77d, 0xb797, H3), (0xb798, 0xb798, H2), (0xb799, 0xb7b3, H3), (0xb7b4, 0xb7b4, H2),
+    (0xb7b5, 0xb7cf, H3), (0xb7d0, 0xb7d0, H2), (0xb7d1, 0xb7eb, H3), (0xb7ec, 0xb7ec, H2),
+    (0xb7ed, 0xb807, H3), (0xb808, 0xb808, H2), (0xb809, 0xb823, H3), (0xb824, 0xb824, H2),
+    (0xb825, 0xb83f, H3), (0xb840, 0xb840, H2), (0xb841, 0xb85b, H3), (0xb85c, 0xb85c, H2),
+    (0xb85d, 0xb877, H3), (0xb878, 0xb878, H2), (0xb879, 0xb893, H3), (0xb894, 0xb894, H2),
+    (0xb895, 0xb8af, H3), (0xb8b0, 0xb8b0, H2), (0xb8b1, 0xb8cb, H3), (0xb8cc, 0xb8cc, H2),
+    (0xb8cd, 0xb8e7, H3), (0xb8e8, 0xb8e8, H2), (0xb8e9, 0xb903, H3), (0xb904, 0xb904, H2),
+    (0xb905, 0xb91f, H3), (0xb920, 0xb920, H2), (0xb921, 0xb93b, H3), (0xb93c, 0xb93c, H2),
+    (0xb93d, 0xb957, H3), (0xb958, 0xb958, H2), (0xb959, 0xb973, H3), (0xb974, 0xb974, H2),
+    (0xb975, 0xb98f, H3), (0xb990, 0xb990, H2), (0xb991, 0xb9ab, H3), (0xb9ac, 0xb9ac, H2),
+    (0xb9ad, 0xb9c7, H3), (0xb9c8, 0xb9c8, H2), (0xb9c9, 0xb9e3, H3), (0xb9e4, 0xb9e4, H2),
+    (0xb9e5, 0xb9ff, H3), (0xba00, 0xba00, H2), (0xba01, 0xba1b, H3), (0xba1c, 0xba1c, H2),
+    (0xba1d, 0xba37, H3), (0xba38, 0xba38, H2), (0xba39, 0xba53, H3), (0xba54, 0xba54, H2),
+    (0xba55, 0xba6f, H3), (0xba70, 0xba70, H2), (0xba71, 0xba8b, H3), (0xba8c, 0xba8c, H2),
+    (0xba8d, 0xbaa7, H3), (0xbaa8, 0xbaa8, H2), (0xbaa9, 0xbac3, H3), (0xbac4, 0xbac4, H2),
+    (0xbac5, 0xbadf, H3), (0xbae0, 0xbae0, H2), (0xbae1, 0xbafb, H3), (0xbafc, 0xbafc, H2),
+    (0xbafd, 0xbb17, H3), (0xbb18, 0xbb18, H2), (0xbb19, 0xbb33, H3), (0xbb34, 0xbb34, H2),
+    (0xbb35, 0xbb4f, H3), (0xbb50, 0xbb50, H2), (0xbb51, 0xbb6b, H3), (0xbb6c, 0xbb6c, H2),
+    (0xbb6d, 0xbb87, H3), (0xbb88, 0xbb88, H2), (0xbb89, 0xbba3, H3), (0xbba4, 0xbba4, H2),
+    (0xbba5, 0xbbbf, H3), (0xbbc0, 0xbbc0, H2), (0xbbc1, 0xbbdb, H3), (0xbbdc, 0xbbdc, H2),
+    (0xbbdd, 0xbbf7, H3), (0xbbf8, 0xbbf8, H2), (0xbbf9, 0xbc13, H3), (0xbc14, 0xbc14, H2),
+    (0xbc15, 0xbc2f, H3), (0xbc30, 0xbc30, H2), (0xbc31, 0xbc4b, H3), (0xbc4c, 0xbc4c, H2),
+    (0xbc4d, 0xbc67, H3), (0xbc68, 0xbc68, H2), (0xbc69, 0xbc83, H3), (0xbc84, 0xbc84, H2),
+    (0xbc85, 0xbc9f, H3), (0xbca0, 0xbca0, H2), (0xbca1, 0xbcbb, H3), (0xbcbc, 0xbcbc, H2),
+    (0xbcbd, 0xbcd7, H3), (0xbcd8, 0xbcd8, H2), (0xbcd9, 0xbcf3, H3), (0xbcf4, 0xbcf4, H2),
+    (0xbcf5, 0xbd0f, H3), (0xbd10, 0xbd10, H2), (0xbd11, 0xbd2b, H3), (0xbd2c, 0xbd2c, H2),
+    (0xbd2d, 0xbd47, H3), (0xbd48, 0xbd48, H2), (0xbd49, 0xbd63, H3), (0xbd64, 0xbd64, H2),
+    (0xbd65, 0xbd7f, H3), (0xbd80, 0xbd80, H2), (0xbd81, 0xbd9b, H3), (0xbd9c, 0xbd9c, H2),
+    (0xbd9d, 0xbdb7, H3), (0xbdb8, 0xbdb8, H2), (0xbdb9, 0xbdd3, H3), (0xbdd4, 0xbdd4, H2),
+    (0xbdd5, 0xbdef, H3), (0xbdf0, 0xbdf0, H2), (0xbdf1, 0xbe0b, H3), (0xbe0c, 0xbe0c, H2),
+    (0xbe0d, 0xbe27, H3), (0xbe28, 0xbe28, H2), (0xbe29, 0xbe43, H3), (0xbe44, 0xbe44, H2),
+    (0xbe45, 0xbe5f, H3), (0xbe60, 0xbe60, H2), (0xbe61, 0xbe7b, H3), (0xbe7c, 0xbe7c, H2),
+    (0xbe7d, 0xbe97, H3), (0xbe98, 0xbe98, H2), (0xbe99, 0xbeb3, H3), (0xbeb4, 0xbeb4, H2),
+    (0xbeb5, 0xbecf, H3), (0xbed0, 0xbed0, H2), (0xbed1, 0xbeeb, H3), (0xbeec, 0xbeec, H2),
+    (0xbeed, 0xbf07, H3), (0xbf08, 0xbf08, H2), (0xbf09, 0xbf23, H3), (0xbf24, 0xbf24, H2),
+    (0xbf25, 0xbf3f, H3), (0xbf40, 0xbf40, H2), (0xbf41, 0xbf5b, H3), (0xbf5c, 0xbf5c, H2),
+    (0xbf5d, 0xbf77, H3), (0xbf78, 0xbf78, H2), (0xbf79, 0xbf93, H3), (0xbf94, 0xbf94, H2),
+    (0xbf95, 0xbfaf, H3), (0xbfb0, 0xbfb0, H2), (0xbfb1, 0xbfcb, H3), (0xbfcc, 0xbfcc, H2),
+    (0xbfcd, 0xbfe7, H3), (0xbfe8, 0xbfe8, H2), (0xbfe9, 0xc003, H3), (0xc004, 0xc004, H2),
+    (0xc005, 0xc01f, H3), (0xc020, 0xc020, H2), (0xc021, 0xc03b, H3), (0xc03c, 0xc03c, H2),
+    (0xc03d, 0xc057, H3), (0xc058, 0xc058, H2), (0xc059, 0xc073, H3), (0xc074, 0xc074, H2),
+    (0xc075, 0xc08f, H3), (0xc090, 0xc090, H2), (0xc091, 0xc0ab, H3), (0xc0ac, 0xc0ac, H2),
+    (0xc0ad, 0xc0c7, H3), (0xc0c8, 0xc0c8, H2), (0xc0c9, 0xc0e3, H3), (0xc0e4, 0xc0e4, H2),
+    (0xc0e5, 0xc0ff, H3), (0xc100, 0xc100, H2), (0xc101, 0xc11b, H3), (0xc11c, 0xc11c, H2),
+    (0xc11d, 0xc137, H3), (0xc138, 0xc138, H2), (0xc139, 0xc153, H3), (0xc154, 0xc154, H2),
+    (0xc155, 0xc16f, H3), (0xc170, 0xc170, H2), (0xc171, 0xc18b, H3), (0xc18c, 0xc18c, H2),
+    (0xc18d, 0xc1a7, H3), (0xc1a8, 0xc1a8, H2), (0xc1a9, 0xc1c3, H3), (0xc1c4, 0xc1c4, H2),
+    (0xc1c5, 0xc1df, H3), (0xc1e0, 0xc1e0, H2), (0xc1e1, 0xc1fb, H3), (0xc1fc, 0xc1fc, H2),
+    (0xc1fd, 0xc217, H3), (0xc218, 0xc218, H2), (0xc219, 0xc233, H3), (0xc234, 0xc234, H2),
+    (0xc235, 0xc24f, H3), (0xc250, 0xc250, H2), (0xc251, 0xc26b, H3), (0xc26c, 0xc26c, H2),
+    (0xc26d, 0xc287, H3), (0xc288, 0xc288, H2), (0xc289, 0xc2a3, H3), (0xc2a4, 0xc2a4, H2),
+    (0xc2a5, 0xc2bf, H3), (0xc2c0, 0xc2c0, H2), (0xc2c1, 0xc2db, H3), (0xc2dc, 0xc2dc, H2),
+    (0xc2dd, 0xc2f7, H3), (0xc2f8, 0xc2f8, H2), (0xc2f9, 0xc313, H3), (0xc314, 0xc314, H2),
+    (0xc315, 0xc32f, H3), (0xc330, 0xc330, H2), (0xc331, 0xc34b, H3), (0xc34c, 0xc34c, H2),
+    (0xc34d, 0xc367, H3), (0xc368, 0xc368, H2), (0xc369, 0xc383, H3), (0xc384, 0xc384, H2),
+    (0xc385, 0xc39f, H3), (0xc3a0, 0xc3a0, H2), (0xc3a1, 0xc3bb, H3), (0xc3bc, 0xc3bc, H2),
+    (0xc3bd, 0xc3d7, H3), (0xc3d8, 0xc3d8, H2), (0xc3d9, 0xc3f3, H3), (0xc3f4, 0xc3f4, H2),
+    (0xc3f5, 0xc40f, H3), (0xc410, 0xc410, H2), (0xc411, 0xc42b, H3), (0xc42c, 0xc42c, H2),
+    (0xc42d, 0xc447, H3), (0xc448, 0xc448, H2), (0xc449, 0xc463, H3), (0xc464, 0xc464, H2),
+    (0xc465, 0xc47f, H3), (0xc480, 0xc480, H2), (0xc481, 0xc49b, H3), (0xc49c, 0xc49c, H2),
+    (0xc49d, 0xc4b7, H3), (0xc4b8, 0xc4b8, H2), (0xc4b9, 0xc4d3, H3), (0xc4d4, 0xc4d4, H2),
+    (0xc4d5, 0xc4ef, H3), (0xc4f0, 0xc4f0, H2), (0xc4f1, 0xc50b, H3), (0xc50c, 0xc50c, H2),
+    (0xc50d, 0xc527, H3), (0xc528, 0xc528, H2), (0xc529, 0xc543, H3), (0xc544, 0xc544, H2),
+    (0xc545, 0xc55f, H3), (0xc560, 0xc560, H2), (0xc561, 0xc57b, H3), (0xc57c, 0xc57c, H2),
+    (0xc57d, 0xc597, H3), (0xc598, 0xc598, H2), (0xc599, 0xc5b3, H3), (0xc5b4, 0xc5b4, H2),
+    (0xc5b5, 0xc5cf, H3), (0xc5d0, 0xc5d0, H2), (0xc5d1, 0xc5eb, H3), (0xc5ec, 0xc5ec, H2),
+    (0xc5ed, 0xc607, H3), (0xc608, 0xc608, H2), (0xc609, 0xc623, H3), (0xc624, 0xc624, H2),
+    (0xc625, 0xc63f, H3), (0xc640, 0xc640, H2), (0xc641, 0xc65b, H3), (0xc65c, 0xc65c, H2),
+    (0xc65d, 0xc677, H3), (0xc678, 0xc678, H2), (0xc679, 0xc693, H3), (0xc694, 0xc694, H2),
+    (0xc695, 0xc6af, H3), (0xc6b0, 0xc6b0, H2), (0xc6b1, 0xc6cb, H3), (0xc6cc, 0xc6cc, H2),
+    (0xc6cd, 0xc6e7, H3), (0xc6e8, 0xc6e8, H2), (0xc6e9, 0xc703, H3), (0xc704, 0xc704, H2),
+    (0xc705, 0xc71f, H3), (0xc720, 0xc720, H2), (0xc721, 0xc73b, H3), (0xc73c, 0xc73c, H2),
+    (0xc73d, 0xc757, H3), (0xc758, 0xc758, H2), (0xc759, 0xc773, H3), (0xc774, 0xc774, H2),
+    (0xc775, 0xc78f, H3), (0xc790, 0xc790, H2), (0xc791, 0xc7ab, H3), (0xc7ac, 0xc7ac, H2),
+    (0xc7ad, 0xc7c7, H3), (0xc7c8, 0xc7c8, H2), (0xc7c9, 0xc7e3, H3), (0xc7e4, 0xc7e4, H2),
+    (0xc7e5, 0xc7ff, H3), (0xc800, 0xc800, H2), (0xc801, 0xc81b, H3), (0xc81c, 0xc81c, H2),
+    (0xc81d, 0xc837, H3), (0xc838, 0xc838, H2), (0xc839, 0xc853, H3), (0xc854, 0xc854, H2),
+    (0xc855, 0xc86f, H3), (0xc870, 0xc870, H2), (0xc871, 0xc88b, H3), (0xc88c, 0xc88c, H2),
+    (0xc88d, 0xc8a7, H3), (0xc8a8, 0xc8a8, H2), (0xc8a9, 0xc8c3, H3), (0xc8c4, 0xc8c4, H2),
+    (0xc8c5, 0xc8df, H3), (0xc8e0, 0xc8e0, H2), (0xc8e1, 0xc8fb, H3), (0xc8fc, 0xc8fc, H2),
+    (0xc8fd, 0xc917, H3), (0xc918, 0xc918, H2), (0xc919, 0xc933, H3), (0xc934, 0xc934, H2),
+    (0xc935, 0xc94f, H3), (0xc950, 0xc950, H2), (0xc951, 0xc96b, H3), (0xc96c, 0xc96c, H2),
+    (0xc96d, 0xc987, H3), (0xc988, 0xc988, H2), (0xc989, 0xc9a3, H3), (0xc9a4, 0xc9a4, H2),
+    (0xc9a5, 0xc9bf, H3), (0xc9c0, 0xc9c0, H2), (0xc9c1, 0xc9db, H3), (0xc9dc, 0xc9dc, H2),
+    (0xc9dd, 0xc9f7, H3), (0xc9f8, 0xc9f8, H2), (0xc9f9, 0xca13, H3), (0xca14, 0xca14, H2),
+    (0xca15, 0xca2f, H3), (0xca30, 0xca30, H2), (0xca31, 0xca4b, H3), (0xca4c, 0xca4c, H2),
+    (0xca4d, 0xca67, H3), (0xca68, 0xca68, H2), (0xca69, 0xca83, H3), (0xca84, 0xca84, H2),
+    (0xca85, 0xca9f, H3), (0xcaa0, 0xcaa0, H2), (0xcaa1, 0xcabb, H3), (0xcabc, 0xcabc, H2),
+    (0xcabd, 0xcad7, H3), (0xcad8, 0xcad8, H2), (0xcad9, 0xcaf3, H3), (0xcaf4, 0xcaf4, H2),
+    (0xcaf5, 0xcb0f, H3), (0xcb10, 0xcb10, H2), (0xcb11, 0xcb2b, H3), (0xcb2c, 0xcb2c, H2),
+    (0xcb2d, 0xcb47, H3), (0xcb48, 0xcb48, H2), (0xcb49, 0xcb63, H3), (0xcb64, 0xcb64, H2),
+    (0xcb65, 0xcb7f, H3), (0xcb80, 0xcb80, H2), (0xcb81, 0xcb9b, H3), (0xcb9c, 0xcb9c, H2),
+    (0xcb9d, 0xcbb7, H3), (0xcbb8, 0xcbb8, H2), (0xcbb9, 0xcbd3, H3), (0xcbd4, 0xcbd4, H2),
+    (0xcbd5, 0xcbef, H3), (0xcbf0, 0xcbf0, H2), (0xcbf1, 0xcc0b, H3), (0xcc0c, 0xcc0c, H2),
+    (0xcc0d, 0xcc27, H3), (0xcc28, 0xcc28, H2), (0xcc29, 0xcc43, H3), (0xcc44, 0xcc44, H2),
+    (0xcc45, 0xcc5f, H3), (0xcc60, 0xcc60, H2), (0xcc61, 0xcc7b, H3), (0xcc7c, 0xcc7c, H2),
+    (0xcc7d, 0xcc97, H3), (0xcc98, 0xcc98, H2), (0xcc99, 0xccb3, H3), (0xccb4, 0xccb4, H2),
+    (0xccb5, 0xcccf, H3), (0xccd0, 0xccd0, H2), (0xccd1, 0xcceb, H3), (0xccec, 0xccec, H2),
+    (0xcced, 0xcd07, H3), (0xcd08, 0xcd08, H2), (0xcd09, 0xcd23, H3), (0xcd24, 0xcd24, H2),
+    (0xcd25, 0xcd3f, H3), (0xcd40, 0xcd40, H2), (0xcd41, 0xcd5b, H3), (0xcd5c, 0xcd5c, H2),
+    (0xcd5d, 0xcd77, H3), (0xcd78, 0xcd78, H2), (0xcd79, 0xcd93, H3), (0xcd94, 0xcd94, H2),
+    (0xcd95, 0xcdaf, H3), (0xcdb0, 0xcdb0, H2), (0xcdb1, 0xcdcb, H3), (0xcdcc, 0xcdcc, H2),
+    (0xcdcd, 0xcde7, H3), (0xcde8, 0xcde8, H2), (0xcde9, 0xce03, H3), (0xce04, 0xce04, H2),
+    (0xce05, 0xce1f, H3), (0xce20, 0xce20, H2), (0xce21, 0xce3b, H3), (0xce3c, 0xce3c, H2),
+    (0xce3d, 0xce57, H3), (0xce58, 0xce58, H2), (0xce59, 0xce73, H3), (0xce74, 0xce74, H2),
+    (0xce75, 0xce8f, H3), (0xce90, 0xce90, H2), (0xce91, 0xceab, H3), (0xceac, 0xceac, H2),
+    (0xcead, 0xcec7, H3), (0xcec8, 0xcec8, H2), (0xcec9, 0xcee3, H3), (0xcee4, 0xcee4, H2),
+    (0xcee5, 0xceff, H3), (0xcf00, 0xcf00, H2), (0xcf01, 0xcf1b, H3), (0xcf1c, 0xcf1c, H2),
+    (0xcf1d, 0xcf37, H3), (0xcf38, 0xcf38, H2), (0xcf39, 0xcf53, H3), (0xcf54, 0xcf54, H2),
+    (0xcf55, 0xcf6f, H3), (0xcf70, 0xcf70, H2), (0xcf71, 0xcf8b, H3), (0xcf8c, 0xcf8c, H2),
+    (0xcf8d, 0xcfa7, H3), (0xcfa8, 0xcfa8, H2), (0xcfa9, 0xcfc3, H3), (0xcfc4, 0xcfc4, H2),
+    (0xcfc5, 0xcfdf, H3), (0xcfe0, 0xcfe0, H2), (0xcfe1, 0xcffb, H3), (0xcffc, 0xcffc, H2),
+    (0xcffd, 0xd017, H3), (0xd018, 0xd018, H2), (0xd019, 0xd033, H3), (0xd034, 0xd034, H2),
+    (0xd035, 0xd04f, H3), (0xd050, 0xd050, H2), (0xd051, 0xd06b, H3), (0xd06c, 0xd06c, H2),
+    (0xd06d, 0xd087, H3), (0xd088, 0xd088, H2), (0xd089, 0xd0a3, H3), (0xd0a4, 0xd0a4, H2),
+    (0xd0a5, 0xd0bf, H3), (0xd0c0, 0xd0c0, H2), (0xd0c1, 0xd0db, H3), (0xd0dc, 0xd0dc, H2),
+    (0xd0dd, 0xd0f7, H3), (0xd0f8, 0xd0f8, H2), (0xd0f9, 0xd113, H3), (0xd114, 0xd114, H2),
+    (0xd115, 0xd12f, H3), (0xd130, 0xd130, H2), (0xd131, 0xd14b, H3), (0xd14c, 0xd14c, H2),
+    (0xd14d, 0xd167, H3), (0xd168, 0xd168, H2), (0xd169, 0xd183, H3), (0xd184, 0xd184, H2),
+    (0xd185, 0xd19f, H3), (0xd1a0, 0xd1a0, H2), (0xd1a1, 0xd1bb, H3), (0xd1bc, 0xd1bc, H2),
+    (0xd1bd, 0xd1d7, H3), (0xd1d8, 0xd1d8, H2), (0xd1d9, 0xd1f3, H3), (0xd1f4, 0xd1f4, H2),
+    (0xd1f5, 0xd20f, H3), (0xd210, 0xd210, H2), (0xd211, 0xd22b, H3), (0xd22c, 0xd22c, H2),
+    (0xd22d, 0xd247, H3), (0xd248, 0xd248, H2), (0xd249, 0xd263, H3), (0xd264, 0xd264, H2),
+    (0xd265, 0xd27f, H3), (0xd280, 0xd280, H2), (0xd281, 0xd29b, H3), (0xd29c, 0xd29c, H2),
+    (0xd29d, 0xd2b7, H3), (0xd2b8, 0xd2b8, H2), (0xd2b9, 0xd2d3, H3), (0xd2d4, 0xd2d4, H2),
+    (0xd2d5, 0xd2ef, H3), (0xd2f0, 0xd2f0, H2), (0xd2f1, 0xd30b, H3), (0xd30c, 0xd30c, H2),
+    (0xd30d, 0xd327, H3), (0xd328, 0xd328, H2), (0xd329, 0xd343, H3), (0xd344, 0xd344, H2),
+    (0xd345, 0xd35f, H3), (0xd360, 0xd360, H2), (0xd361, 0xd37b, H3), (0xd37c, 0xd37c, H2),
+    (0xd37d, 0xd397, H3), (0xd398, 0xd398, H2), (0xd399, 0xd3b3, H3), (0xd3b4, 0xd3b4, H2),
+    (0xd3b5, 0xd3cf, H3), (0xd3d0, 0xd3d0, H2), (0xd3d1, 0xd3eb, H3), (0xd3ec, 0xd3ec, H2),
+    (0xd3ed, 0xd407, H3), (0xd408, 0xd408, H2), (0xd409, 0xd423, H3), (0xd424, 0xd424, H2),
+    (0xd425, 0xd43f, H3), (0xd440, 0xd440, H2), (0xd441, 0xd45b, H3), (0xd45c, 0xd45c, H2),
+    (0xd45d, 0xd477, H3), (0xd478, 0xd478, H2), (0xd479, 0xd493, H3), (0xd494, 0xd494, H2),
+    (0xd495, 0xd4af, H3), (0xd4b0, 0xd4b0, H2), (0xd4b1, 0xd4cb, H3), (0xd4cc, 0xd4cc, H2),
+    (0xd4cd, 0xd4e7, H3), (0xd4e8, 0xd4e8, H2), (0xd4e9, 0xd503, H3), (0xd504, 0xd504, H2),
+    (0xd505, 0xd51f, H3), (0xd520, 0xd520, H2), (0xd521, 0xd53b, H3), (0xd53c, 0xd53c, H2),
+    (0xd53d, 0xd557, H3), (0xd558, 0xd558, H2), (0xd559, 0xd573, H3), (0xd574, 0xd574, H2),
+    (0xd575, 0xd58f, H3), (0xd590, 0xd590, H2), (0xd591, 0xd5ab, H3), (0xd5ac, 0xd5ac, H2),
+    (0xd5ad, 0xd5c7, H3), (0xd5c8, 0xd5c8, H2), (0xd5c9, 0xd5e3, H3), (0xd5e4, 0xd5e4, H2),
+    (0xd5e5, 0xd5ff, H3), (0xd600, 0xd600, H2), (0xd601, 0xd61b, H3), (0xd61c, 0xd61c, H2),
+    (0xd61d, 0xd637, H3), (0xd638, 0xd638, H2), (0xd639, 0xd653, H3), (0xd654, 0xd654, H2),
+    (0xd655, 0xd66f, H3), (0xd670, 0xd670, H2), (0xd671, 0xd68b, H3), (0xd68c, 0xd68c, H2),
+    (0xd68d, 0xd6a7, H3), (0xd6a8, 0xd6a8, H2), (0xd6a9, 0xd6c3, H3), (0xd6c4, 0xd6c4, H2),
+    (0xd6c5, 0xd6df, H3), (0xd6e0, 0xd6e0, H2), (0xd6e1, 0xd6fb, H3), (0xd6fc, 0xd6fc, H2),
+    (0xd6fd, 0xd717, H3), (0xd718, 0xd718, H2), (0xd719, 0xd733, H3), (0xd734, 0xd734, H2),
+    (0xd735, 0xd74f, H3), (0xd750, 0xd750, H2), (0xd751, 0xd76b, H3), (0xd76c, 0xd76c, H2),
+    (0xd76d, 0xd787, H3), (0xd788, 0xd788, H2), (0xd789, 0xd7a3, H3), (0xd7b0, 0xd7c6, JV),
+    (0xd7cb, 0xd7fb, JT), (0xf900, 0xfaff, ID), (0xfb1d, 0xfb1d, HL), (0xfb1e, 0xfb1e, CM),
+    (0xfb1f, 0xfb28, HL), (0xfb2a, 0xfb4f, HL), (0xfd3e, 0xfd3e, CL), (0xfd3f, 0xfd3f, OP),
+    (0xfdfc, 0xfdfc, PO), (0xfe00, 0xfe0f, CM), (0xfe10, 0xfe10, IS), (0xfe11, 0xfe12, CL),
+    (0xfe13, 0xfe14, IS), (0xfe15, 0xfe16, EX), (0xfe17, 0xfe17, OP), (0xfe18, 0xfe18, CL),
+    (0xfe19, 0xfe19, IN), (0xfe20, 0xfe2f, CM), (0xfe30, 0xfe34, ID), (0xfe35, 0xfe35, OP),
+    (0xfe36, 0xfe36, CL), (0xfe37, 0xfe37, OP), (0xfe38, 0xfe38, CL), (0xfe39, 0xfe39, OP),
+    (0xfe3a, 0xfe3a, CL), (0xfe3b, 0xfe3b, OP), (0xfe3c, 0xfe3c, CL), (0xfe3d, 0xfe3d, OP),
+    (0xfe3e, 0xfe3e, CL), (0xfe3f, 0xfe3f, OP), (0xfe40, 0xfe40, CL), (0xfe41, 0xfe41, OP),
+    (0xfe42, 0xfe42, CL), (0xfe43, 0xfe43, OP), (0xfe44, 0xfe44, CL), (0xfe45, 0xfe46, ID),
+    (0xfe47, 0xfe47, OP), (0xfe48, 0xfe48, CL), (0xfe49, 0xfe4f, ID), (0xfe50, 0xfe50, CL),
+    (0xfe51, 0xfe51, ID), (0xfe52, 0xfe52, CL), (0xfe54, 0xfe55, NS), (0xfe56, 0xfe57, EX),
+    (0xfe58, 0xfe58, ID), (0xfe59, 0xfe59, OP), (0xfe5a, 0xfe5a, CL), (0xfe5b, 0xfe5b, OP),
+    (0xfe5c, 0xfe5c, CL), (0xfe5d, 0xfe5d, OP), (0xfe5e, 0xfe5e, CL), (0xfe5f, 0xfe66, ID),
+    (0xfe68, 0xfe68, ID), (0xfe69, 0xfe69, PR), (0xfe6a, 0xfe6a, PO), (0xfe6b, 0xfe6b, ID),
+    (0xfeff, 0xfeff, WJ), (0xff01, 0xff01, EX), (0xff02, 0xff03, ID), (0xff04, 0xff04, PR),
+    (0xff05, 0xff05, PO), (0xff06, 0xff07, ID), (0xff08, 0xff08, OP), (0xff09, 0xff09, CL),
+    (0xff0a, 0xff0b, ID), (0xff0c, 0xff0c, CL), (0xff0d, 0xff0d, ID), (0xff0e, 0xff0e, CL),
+    (0xff0f, 0xff19, ID), (0xff1a, 0xff1b, NS), (0xff1c, 0xff1e, ID), (0xff1f, 0xff1f, EX),
+    (0xff20, 0xff3a, ID), (0xff3b, 0xff3b, OP), (0xff3c, 0xff3c, ID), (0xff3d, 0xff3d, CL),
+    (0xff3e, 0xff5a, ID), (0xff5b, 0xff5b, OP), (0xff5c, 0xff5c, ID), (0xff5d, 0xff5d, CL),
+    (0xff5e, 0xff5e, ID), (0xff5f, 0xff5f, OP), (0xff60, 0xff61, CL), (0xff62, 0xff62, OP),
+    (0xff63, 0xff64, CL), (0xff65, 0xff65, NS), (0xff67, 0xff70, CJ), (0xff9e, 0xff9f, NS),
+    (0xffe0, 0xffe0, PO), (0xffe1, 0xffe1, PR), (0xffe2, 0xffe4, ID), (0xffe5, 0xffe6, PR),
+    (0xfffc, 0xfffc, CB), (0x101fd, 0x101fd, CM), (0x102e0, 0x102e0, CM), (0x10376, 0x1037a, CM),
+    (0x104a0, 0x104a9, NU), (0x10a01, 0x10a03, CM), (0x10a05, 0x10a06, CM), (0x10a0c, 0x10a0f, CM),
+    (0x10a38, 0x10a3a, CM), (0x10a3f, 0x10a3f, CM), (0x10ae5, 0x10ae6, CM), (0x10af6, 0x10af6, IN),
+    (0x10d24, 0x10d27, CM), (0x10d30, 0x10d39, NU), (0x10eab, 0x10eac, CM), (0x10f46, 0x10f50, CM),
+    (0x10f82, 0x10f85, CM), (0x11000, 0x11002, CM), (0x11038, 0x11046, CM), (0x11066, 0x1106f, NU),
+    (0x11070, 0x11070, CM), (0x11073, 0x11074, CM), (0x1107f, 0x11082, CM), (0x110b0, 0x110ba, CM),
+    (0x110c2, 0x110c2, CM), (0x110f0, 0x110f9, NU), (0x11100, 0x11102, CM), (0x11127, 0x11134, CM),
+    (0x11136, 0x1113f, NU), (0x11145, 0x11146, CM), (0x11173, 0x11173, CM), (0x11180, 0x11182, CM),
+    (0x111b3, 0x111c0, CM), (0x111c9, 0x111cc, CM), (0x111ce, 0x111cf, CM), (0x111d0, 0x111d9, NU),
+    (0x1122c, 0x11237, CM), (0x1123e, 0x1123e, CM), (0x112df, 0x112ea, CM), (0x112f0, 0x112f9, NU),
+    (0x11300, 0x11303, CM), (0x1133b, 0x1133c, CM), (0x1133e, 0x11344, CM), (0x11347, 0x11348, CM),
+    (0x1134b, 0x1134d, CM), (0x11357, 0x11357, CM), (0x11362, 0x11363, CM), (0x11366, 0x1136c, CM),
+    (0x11370, 0x11374, CM), (0x11435, 0x11446, CM), (0x11450, 0x11459, NU), (0x1145e, 0x1145e, CM),
+    (0x114b0, 0x114c3, CM), (0x114d0, 0x114d9, NU), (0x115af, 0x115b5, CM), (0x115b8, 0x115c0, CM),
+    (0x115dc, 0x115dd, CM), (0x11630, 0x11640, CM), (0x11650, 0x11659, NU), (0x116ab, 0x116b7, CM),
+    (0x116c0, 0x116c9, NU), (0x1171d, 0x1172b, CM), (0x11730, 0x11739, NU), (0x1182c, 0x1183a, CM),
+    (0x118e0, 0x118e9, NU), (0x11930, 0x11935, CM), (0x11937, 0x11938, CM), (0x1193b, 0x1193e, CM),
+    (0x11940, 0x11940, CM), (0x11942, 0x11943, CM), (0x11950, 0x11959, NU), (0x119d1, 0x119d7, CM),
+    (0x119da, 0x119e0, CM), (0x119e4, 0x119e4, CM), (0x11a01, 0x11a0a, CM), (0x11a33, 0x11a39, CM),
+    (0x11a3b, 0x11a3e, CM), (0x11a47, 0x11a47, CM), (0x11a51, 0x11a5b, CM), (0x11a8a, 0x11a99, CM),
+    (0x11c2f, 0x11c36, CM), (0x11c38, 0x11c3f, CM), (0x11c50, 0x11c59, NU), (0x11c92, 0x11ca7, CM),
+    (0x11ca9, 0x11cb6, CM), (0x11d31, 0x11d36, CM), (0x11d3a, 0x11d3a, CM), (0x11d3c, 0x11d3d, CM),
+    (0x11d3f, 0x11d45, CM), (0x11d47, 0x11d47, CM), (0x11d50, 0x11d59, NU), (0x11d8a, 0x11d8e, CM),
+    (0x11d90, 0x11d91, CM), (0x11d93, 0x11d97, CM), (0x11da0, 0x11da9, NU), (0x11ef3, 0x11ef6, CM),
+    (0x11fdd, 0x11fe0, PR), (0x16a60, 0x16a69, NU), (0x16ac0, 0x16ac9, NU), (0x16af0, 0x16af4, CM),
+    (0x16b30, 0x16b36, CM), (0x16b50, 0x16b59, NU), (0x16f4f, 0x16f4f, CM), (0x16f51, 0x16f87, CM),
+    (0x16f8f, 0x16f92, CM), (0x16fe0, 0x16fe3, ID), (0x16fe4, 0x16fe4, CM), (0x16ff0, 0x16ff1, CM),
+    (0x17000, 0x187f7, ID), (0x18800, 0x18cd5, ID), (0x18d00, 0x18d08, ID), (0x1aff0, 0x1aff3, ID),
+    (0x1aff5, 0x1affb, ID), (0x1affd, 0x1affe, ID), (0x1b000, 0x1b122, ID), (0x1b150, 0x1b152, ID),
+    (0x1b164, 0x1b167, ID), (0x1b170, 0x1b2fb, ID), (0x1bc9d, 0x1bc9e, CM), (0x1cf00, 0x1cf2d, CM),
+    (0x1cf30, 0x1cf46, CM), (0x1d165, 0x1d169, CM), (0x1d16d, 0x1d172, CM), (0x1d17b, 0x1d182, CM),
+    (0x1d185, 0x1d18b, CM), (0x1d1aa, 0x1d1ad, CM), (0x1d242, 0x1d244, CM), (0x1d7ce, 0x1d7ff, NU),
+    (0x1da00, 0x1da36, CM), (0x1da3b, 0x1da6c, CM), (0x1da75, 0x1da75, CM), (0x1da84, 0x1da84, CM),
+    (0x1da9b, 0x1da9f, CM), (0x1daa1, 0x1daaf, CM), (0x1e000, 0x1e006, CM), (0x1e008, 0x1e018, CM),
+    (0x1e01b, 0x1e021, CM), (0x1e023, 0x1e024, CM), (0x1e026, 0x1e02a, CM), (0x1e130, 0x1e136, CM),
+    (0x1e140, 0x1e149, NU), (0x1e2ae, 0x1e2ae, CM), (0x1e2ec, 0x1e2ef, CM), (0x1e2f0, 0x1e2f9, NU),
+    (0x1e2ff, 0x1e2ff, PR), (0x1e8d0, 0x1e8d6, CM), (0x1e944, 0x1e94a, CM), (0x1e950, 0x1e959, NU),
+    (0x1ecb0, 0x1ecb0, PR), (0x1f000, 0x1f1e5, ID), (0x1f1e6, 0x1f1ff, RI), (0x1f200, 0x1f384, ID),
+    (0x1f385, 0x1f385, EB), (0x1f386, 0x1f3c1, ID), (0x1f3c2, 0x1f3c4, EB), (0x1f3c5, 0x1f3c6, ID),
+    (0x1f3c7, 0x1f3c7, EB), (0x1f3c8, 0x1f3c9, ID), (0x1f3ca, 0x1f3cc, EB), (0x1f3cd, 0x1f3fa, ID),
+    (0x1f3fb, 0x1f3ff, EM), (0x1f400, 0x1f441, ID), (0x1f442, 0x1f443, EB), (0x1f444, 0x1f445, ID),
+    (0x1f446, 0x1f450, EB), (0x1f451, 0x1f465, ID), (0x1f466, 0x1f478, EB), (0x1f479, 0x1f47b, ID),
+    (0x1f47c, 0x1f47c, EB), (0x1f47d, 0x1f480, ID), (0x1f481, 0x1f483, EB), (0x1f484, 0x1f484, ID),
+    (0x1f485, 0x1f487, EB), (0x1f488, 0x1f4a9, ID), (0x1f4aa, 0x1f4aa, EB), (0x1f4ab, 0x1f573, ID),
+    (0x1f574, 0x1f575, EB), (0x1f576, 0x1f579, ID), (0x1f57a, 0x1f57a, EB), (0x1f57b, 0x1f58f, ID),
+    (0x1f590, 0x1f590, EB), (0x1f591, 0x1f594, ID), (0x1f595, 0x1f596, EB), (0x1f597, 0x1f644, ID),
+    (0x1f645, 0x1f647, EB), (0x1f648, 0x1f64a, ID), (0x1f64b, 0x1f64f, EB), (0x1f650, 0x1f675, ID),
+    (0x1f676, 0x1f678, QU), (0x1f679, 0x1f6a2, ID), (0x1f6a3, 0x1f6a3, EB), (0x1f6a4, 0x1f6b3, ID),
+    (0x1f6b4, 0x1f6b6, EB), (0x1f6b7, 0x1f6bf, ID), (0x1f6c0, 0x1f6c0, EB), (0x1f6c1, 0x1f6cb, ID),
+    (0x1f6cc, 0x1f6cc, EB), (0x1f6cd, 0x1f90b, ID), (0x1f90c, 0x1f90c, EB), (0x1f90d, 0x1f90e, ID),
+    (0x1f90f, 0x1f90f, EB), (0x1f910, 0x1f917, ID), (0x1f918, 0x1f91f, EB), (0x1f920, 0x1f925, ID),
+    (0x1f926, 0x1f926, EB), (0x1f927, 0x1f92f, ID), (0x1f930, 0x1f939, EB), (0x1f93a, 0x1f93b, ID),
+    (0x1f93c, 0x1f93e, EB), (0x1f93f, 0x1f976, ID), (0x1f977, 0x1f977, EB), (0x1f978, 0x1f9b4, ID),
+    (0x1f9b5, 0x1f9b6, EB), (0x1f9b7, 0x1f9b7, ID), (0x1f9b8, 0x1f9b9, EB), (0x1f9ba, 0x1f9ba, ID),
+    (0x1f9bb, 0x1f9bb, EB), (0x1f9bc, 0x1f9cc, ID), (0x1f9cd, 0x1f9cf, EB), (0x1f9d0, 0x1f9d0, ID),
+    (0x1f9d1, 0x1f9dd, EB), (0x1f9de, 0x1faff, ID), (0x1fb93, 0x1fb93, ID), (0x1fbcb, 0x1fbef, ID),
+    (0x1fbf0, 0x1fbf9, NU), (0x1fbfa, 0x1fffd, ID), (0x20000, 0x3fffd, ID), (0xe0100, 0xe01ef, CM),
+];
@@ -7,6 +7,11 @@ pub struct Transform {
 }
 
 impl Transform {
+    /// Returns an identity transform.
+    pub fn identity() -> Self {
+        Transform{ m: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0] }
+    }
+
     /// Sets the transform to identity matrix.
     pub fn set_identity(&mut self) {
         self.m[0] = 1.0;
@@ -52,10 +57,10 @@ impl Transform {
 
     /// Sets the transform to skew-x matrix. Angle is specified
     /// in radians.
-    pub fn set_skew_y(&mut self, y: f32) {
+    pub fn set_skew_x(&mut self, x: f32) {
         self.m[0] = 1.0;
         self.m[1] = 0.0;
-    	self.m[2] = f32::tan(y);
+    	self.m[2] = f32::tan(x);
         self.m[3] = 1.0;
     	self.m[4] = 0.0;
         self.m[5] = 0.0;
@@ -63,9 +68,9 @@ impl Transform {
 
     /// Sets the transform to skew-y matrix. Angle is specified
     /// in radians.
-    pub fn set_skew_x(&mut self, x: f32) {
+    pub fn set_skew_y(&mut self, y: f32) {
         self.m[0] = 1.0;
-        self.m[1] = f32::tan(x);
+        self.m[1] = f32::tan(y);
     	self.m[2] = 0.0;
         self.m[3] = 1.0;
     	self.m[4] = 0.0;
@@ -100,8 +105,7 @@ impl Transform {
     	if det > -1e-6 && det < 1e-6 {
     		None
     	} else {
-            // NB we are about to set every value, and 2x3 matrices don't change size.
-            let mut result: Transform = unsafe { ::std::mem::uninitialized() };
+            let mut result = Transform{ m: [0.0; 6] };
         	let invdet: f64 = 1.0 / det;
         	result.m[0] = ((self.m[3] as f64) * invdet) as f32;
         	result.m[2] = (-(self.m[2] as f64) * invdet) as f32;
@@ -113,6 +117,13 @@ impl Transform {
         }
     }
 
+    /// Returns the average scale factor of the transform.
+    pub fn average_scale(&self) -> f32 {
+        let sx = f32::sqrt(self.m[0] * self.m[0] + self.m[2] * self.m[2]);
+        let sy = f32::sqrt(self.m[1] * self.m[1] + self.m[3] * self.m[3]);
+        (sx + sy) * 0.5
+    }
+
     /// Transform a point by given transform.
     pub fn point(&self, sx: f32, sy: f32) -> (f32, f32) {
         let dx = sx * self.m[0] + sy * self.m[2] + self.m[4];
@@ -0,0 +1,301 @@
+//! Paint constructors and a CPU evaluator for paints.
+//!
+//! Every paint is a rounded rectangle with a feathered edge in its own
+//! space: solid colors and gradients differ only in how that rectangle
+//! is placed, and image patterns map the rectangle onto the image.
+//! `PaintEvaluator` computes the same colors as NanoVG's GL shader, so
+//! the software backend and snapshot tests agree with GPU rendering.
+
+use crate::color::Color;
+use crate::math::Transform;
+use crate::{ImageId, Paint};
+
+impl Paint {
+    /// Returns a paint which fills with a single solid color.
+    pub fn color(color: Color) -> Self {
+        Paint{
+            xform:       Transform::identity().m,
+            extent:      [0.0, 0.0],
+            radius:      0.0,
+            feather:     1.0,
+            inner_color: color,
+            outer_color: color,
+            image:       None,
+        }
+    }
+
+    /// Creates a linear gradient from (sx,sy) to (ex,ey). See
+    /// `Context::linear_gradient`.
+    pub fn linear_gradient(sx: f32, sy: f32, ex: f32, ey: f32, icol: Color, ocol: Color) -> Self {
+        let large = 1e5;
+
+        // Calculate transform aligned to the line
+        let mut dx = ex - sx;
+        let mut dy = ey - sy;
+        let d = f32::sqrt(dx * dx + dy * dy);
+        if d > 0.0001 {
+            dx /= d;
+            dy /= d;
+        } else {
+            dx = 0.0;
+            dy = 1.0;
+        }
+
+        Paint{
+            xform:       [dy, -dx, dx, dy, sx - dx * large, sy - dy * large],
+            extent:      [large, large + d * 0.5],
+            radius:      0.0,
+            feather:     f32::max(1.0, d),
+            inner_color: icol,
+            outer_color: ocol,
+            image:       None,
+        }
+    }
+
+    /// Creates a box gradient. See `Context::box_gradient`.
+    pub fn box_gradient(x: f32, y: f32, w: f32, h: f32, r: f32, f: f32, icol: Color, ocol: Color) -> Self {
+        let mut xform = Transform::identity();
+        xform.set_translate(x + w * 0.5, y + h * 0.5);
+        Paint{
+            xform:       xform.m,
+            extent:      [w * 0.5, h * 0.5],
+            radius:      r,
+            feather:     f32::max(1.0, f),
+            inner_color: icol,
+            outer_color: ocol,
+            image:       None,
+        }
+    }
+
+    /// Creates a radial gradient. See `Context::radial_gradient`.
+    pub fn radial_gradient(cx: f32, cy: f32, inr: f32, outr: f32, icol: Color, ocol: Color) -> Self {
+        let r = (inr + outr) * 0.5;
+        let f = outr - inr;
+        let mut xform = Transform::identity();
+        xform.set_translate(cx, cy);
+        Paint{
+            xform:       xform.m,
+            extent:      [r, r],
+            radius:      r,
+            feather:     f32::max(1.0, f),
+            inner_color: icol,
+            outer_color: ocol,
+            image:       None,
+        }
+    }
+
+    /// Creates an image pattern. See `Context::image_pattern`.
+    pub fn image_pattern(ox: f32, oy: f32, ex: f32, ey: f32, angle: f32, image: ImageId, alpha: f32) -> Self {
+        let mut xform = Transform::identity();
+        xform.set_rotate(angle);
+        xform.m[4] = ox;
+        xform.m[5] = oy;
+        let color = Color::rgbaf(1.0, 1.0, 1.0, alpha);
+        Paint{
+            xform:       xform.m,
+            extent:      [ex, ey],
+            radius:      0.0,
+            feather:     0.0,
+            inner_color: color,
+            outer_color: color,
+            image:       Some(image),
+        }
+    }
+}
+
+/// Returns a color with premultiplied alpha as [r, g, b, a].
+pub fn premul(c: Color) -> [f32; 4] {
+    [c.r * c.a, c.g * c.a, c.b * c.a, c.a]
+}
+
+/// Signed distance from a point to a rounded rectangle centered at origin.
+fn sdroundrect(px: f32, py: f32, ex: f32, ey: f32, rad: f32) -> f32 {
+    let dx = px.abs() - (ex - rad);
+    let dy = py.abs() - (ey - rad);
+    let outside = f32::sqrt(dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0));
+    dx.max(dy).min(0.0) + outside - rad
+}
+
+/// Samples a `Paint` at points in window space.
+///
+/// All colors are returned premultiplied as [r, g, b, a], which is what
+/// the shader outputs before blending.
+#[derive(Debug, Clone, Copy)]
+pub struct PaintEvaluator {
+    inv_xform: Transform,
+    extent:    [f32; 2],
+    radius:    f32,
+    feather:   f32,
+    inner:     [f32; 4],
+    outer:     [f32; 4],
+    image:     Option<ImageId>,
+}
+
+impl PaintEvaluator {
+    pub fn new(paint: &Paint) -> Self {
+        PaintEvaluator{
+            inv_xform: Transform{ m: paint.xform }.invert().unwrap_or_else(Transform::identity),
+            extent:    paint.extent,
+            radius:    paint.radius,
+            feather:   paint.feather,
+            inner:     premul(paint.inner_color),
+            outer:     premul(paint.outer_color),
+            image:     paint.image,
+        }
+    }
+
+    /// Returns the image of the paint, `None` for gradients.
+    pub fn image(&self) -> Option<ImageId> {
+        self.image
+    }
+
+    /// Returns the normalized texture coordinates of the image pattern at
+    /// (x,y). The image covers 0..1 in both directions.
+    pub fn image_coords(&self, x: f32, y: f32) -> (f32, f32) {
+        let (px, py) = self.inv_xform.point(x, y);
+        (px / self.extent[0], py / self.extent[1])
+    }
+
+    /// Returns how much the normalized texture coordinates change per
+    /// window unit as [du/dx, dv/dx, du/dy, dv/dy]. Image patterns are
+    /// affine, so this is the same at every point.
+    pub fn image_coords_gradient(&self) -> [f32; 4] {
+        let m = &self.inv_xform.m;
+        [m[0] / self.extent[0], m[1] / self.extent[1], m[2] / self.extent[0], m[3] / self.extent[1]]
+    }
+
+    /// Tints a premultiplied texel of the image by the inner color.
+    pub fn tint(&self, texel: [f32; 4]) -> [f32; 4] {
+        [
+            texel[0] * self.inner[0],
+            texel[1] * self.inner[1],
+            texel[2] * self.inner[2],
+            texel[3] * self.inner[3],
+        ]
+    }
+
+    /// Returns the gradient color at (x,y), ignoring the image.
+    pub fn gradient(&self, x: f32, y: f32) -> [f32; 4] {
+        let (px, py) = self.inv_xform.point(x, y);
+        let d = (sdroundrect(px, py, self.extent[0], self.extent[1], self.radius) + self.feather * 0.5) / self.feather;
+        let d = d.clamp(0.0, 1.0);
+        let mut color = [0.0; 4];
+        for ((c, inner), outer) in color.iter_mut().zip(&self.inner).zip(&self.outer) {
+            *c = inner + (outer - inner) * d;
+        }
+        color
+    }
+
+    /// Returns the color of the paint at (x,y). `texture(u, v)` returns the
+    /// premultiplied texel of the image at normalized texture coordinates
+    /// and is only called for image patterns.
+    pub fn eval<F>(&self, x: f32, y: f32, texture: F) -> [f32; 4]
+        where F: FnOnce(f32, f32) -> [f32; 4]
+    {
+        if self.image.is_some() {
+            let (u, v) = self.image_coords(x, y);
+            self.tint(texture(u, v))
+        } else {
+            self.gradient(x, y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns how far along the gradient (x,y) is, going from black at
+    /// the inner color to white at the outer color.
+    fn ramp(paint: Paint, x: f32, y: f32) -> f32 {
+        let color = PaintEvaluator::new(&paint).eval(x, y, |_, _| unreachable!());
+        assert!(color[..3].iter().all(|&c| (c - color[0]).abs() < 1e-6));
+        assert_eq!(color[3], 1.0);
+        color[0]
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    const BLACK: Color = Color{ r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
+    const WHITE: Color = Color{ r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+
+    #[test]
+    fn linear_gradients_ramp_between_the_end_points() {
+        let paint = Paint::linear_gradient(10.0, 0.0, 20.0, 0.0, BLACK, WHITE);
+        for &(x, t) in &[(0.0, 0.0), (10.0, 0.0), (12.5, 0.25), (15.0, 0.5), (20.0, 1.0), (30.0, 1.0)] {
+            assert_close(ramp(paint, x, 0.0), t);
+            assert_close(ramp(paint, x, 50.0), t);
+        }
+
+        // Along y, the gradient is the same line turned a quarter.
+        let paint = Paint::linear_gradient(0.0, 10.0, 0.0, 20.0, BLACK, WHITE);
+        assert_close(ramp(paint, -50.0, 15.0), 0.5);
+    }
+
+    #[test]
+    fn radial_gradients_ramp_between_the_radii() {
+        // The feather spans from the inner to the outer radius.
+        let paint = Paint::radial_gradient(50.0, 50.0, 10.0, 20.0, BLACK, WHITE);
+        for &(r, t) in &[(0.0, 0.0), (10.0, 0.0), (15.0, 0.5), (20.0, 1.0), (40.0, 1.0)] {
+            assert_close(ramp(paint, 50.0 + r, 50.0), t);
+            assert_close(ramp(paint, 50.0, 50.0 - r), t);
+            let d = r * std::f32::consts::FRAC_1_SQRT_2;
+            assert_close(ramp(paint, 50.0 + d, 50.0 + d), t);
+        }
+    }
+
+    #[test]
+    fn box_gradients_feather_across_the_edge() {
+        // A 20x10 box at (0,0) with corner radius 2 and feather 4: the
+        // edge is the middle of the feather.
+        let paint = Paint::box_gradient(0.0, 0.0, 20.0, 10.0, 2.0, 4.0, BLACK, WHITE);
+        assert_close(ramp(paint, 10.0, 5.0), 0.0);
+        assert_close(ramp(paint, 18.0, 5.0), 0.0);
+        assert_close(ramp(paint, 19.0, 5.0), 0.25);
+        assert_close(ramp(paint, 20.0, 5.0), 0.5);
+        assert_close(ramp(paint, 22.0, 5.0), 1.0);
+        assert_close(ramp(paint, 10.0, 0.0), 0.5);
+        assert_close(ramp(paint, 10.0, -1.0), 0.75);
+
+        // The corner is rounded: its edge is the arc around (18,8), and
+        // the box corner lies 2*sqrt(2) - 2 outside of it.
+        let d = std::f32::consts::SQRT_2;
+        assert_close(ramp(paint, 18.0 + d, 8.0 + d), 0.5);
+        assert_close(ramp(paint, 20.0, 10.0), (2.0 * d - 2.0 + 2.0) / 4.0);
+    }
+
+    #[test]
+    fn gradients_are_premultiplied() {
+        let paint = Paint::box_gradient(0.0, 0.0, 10.0, 10.0, 0.0, 1.0,
+                                        Color::rgbaf(1.0, 0.5, 0.0, 0.5), Color::rgbaf(0.0, 0.0, 1.0, 0.0));
+        let eval = PaintEvaluator::new(&paint);
+        assert_eq!(eval.gradient(5.0, 5.0), [0.5, 0.25, 0.0, 0.5]);
+        assert_eq!(eval.gradient(20.0, 5.0), [0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn image_patterns_map_the_extent_to_the_image() {
+        let image = ImageId{ index: 0, generation: 0 };
+        let paint = Paint::image_pattern(10.0, 20.0, 40.0, 80.0, 0.0, image, 0.5);
+        let eval = PaintEvaluator::new(&paint);
+        assert_eq!(eval.image(), Some(image));
+        assert_eq!(eval.image_coords(10.0, 20.0), (0.0, 0.0));
+        assert_eq!(eval.image_coords(30.0, 60.0), (0.5, 0.5));
+        assert_eq!(eval.image_coords_gradient(), [1.0 / 40.0, 0.0, 0.0, 1.0 / 80.0]);
+
+        // The texel is tinted by the alpha of the pattern.
+        let color = eval.eval(30.0, 60.0, |u, v| {
+            assert_eq!((u, v), (0.5, 0.5));
+            [0.8, 0.4, 0.2, 0.8]
+        });
+        assert_eq!(color, [0.4, 0.2, 0.1, 0.4]);
+
+        // Rotated a quarter turn, the u axis of the image points down.
+        let paint = Paint::image_pattern(0.0, 0.0, 10.0, 10.0, std::f32::consts::FRAC_PI_2, image, 1.0);
+        let (u, v) = PaintEvaluator::new(&paint).image_coords(0.0, 10.0);
+        assert_close(u, 1.0);
+        assert_close(v, 0.0);
+    }
+}
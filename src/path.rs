@@ -0,0 +1,525 @@
+//! Path command buffer and the cache which flattens it into polylines.
+//!
+//! Commands are recorded already transformed by the current transform,
+//! so the cache only has to deal with window space coordinates.
+
+use std::ops::Range;
+
+use crate::math::Transform;
+use crate::{LineJoin, Winding};
+
+/// A path command, with coordinates in window space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    BezierTo(f32, f32, f32, f32, f32, f32),
+    Close,
+    Winding(Winding),
+}
+
+impl Command {
+    /// Returns the command with its points transformed.
+    pub fn transformed(&self, xform: &Transform) -> Command {
+        match *self {
+            Command::MoveTo(x, y) => {
+                let (x, y) = xform.point(x, y);
+                Command::MoveTo(x, y)
+            }
+            Command::LineTo(x, y) => {
+                let (x, y) = xform.point(x, y);
+                Command::LineTo(x, y)
+            }
+            Command::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                let (c1x, c1y) = xform.point(c1x, c1y);
+                let (c2x, c2y) = xform.point(c2x, c2y);
+                let (x, y) = xform.point(x, y);
+                Command::BezierTo(c1x, c1y, c2x, c2y, x, y)
+            }
+            cmd => cmd,
+        }
+    }
+}
+
+/// The point is a corner of the path, rather than part of a curve.
+pub const PT_CORNER: u8 = 0x01;
+/// The path turns left at the point.
+pub const PT_LEFT: u8 = 0x02;
+/// The corner at the point is beveled.
+pub const PT_BEVEL: u8 = 0x04;
+/// The inner side of the corner needs a bevel.
+pub const PT_INNERBEVEL: u8 = 0x08;
+
+/// A point of a flattened path.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Point {
+    pub x:     f32,
+    pub y:     f32,
+    /// Normalized direction to the next point.
+    pub dx:    f32,
+    pub dy:    f32,
+    /// Distance to the next point.
+    pub len:   f32,
+    /// Extrusion direction of the join, scaled by the miter length.
+    pub dmx:   f32,
+    pub dmy:   f32,
+    pub flags: u8,
+}
+
+/// A vertex of the geometry handed to the renderer.
+///
+/// For strokes and fringes `u` runs across the stroke and `v` along it,
+/// fading towards 0 at antialiased edges.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vertex {
+    pub x: f32,
+    pub y: f32,
+    pub u: f32,
+    pub v: f32,
+}
+
+impl Vertex {
+    pub fn new(x: f32, y: f32, u: f32, v: f32) -> Self {
+        Vertex{ x: x, y: y, u: u, v: v }
+    }
+}
+
+/// A flattened sub-path, referring to a range of the cache's points.
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub first:   usize,
+    pub count:   usize,
+    pub closed:  bool,
+    pub nbevel:  usize,
+    pub winding: Winding,
+    pub convex:  bool,
+    /// Triangle fan of the fill, as a range of the cache's vertices.
+    pub fill:    Range<usize>,
+    /// Triangle strip of the stroke or fill fringe, as a range of the
+    /// cache's vertices.
+    pub stroke:  Range<usize>,
+}
+
+/// Flattened points and sub-paths of the current path.
+pub struct PathCache {
+    pub points: Vec<Point>,
+    pub paths:  Vec<Path>,
+    pub verts:  Vec<Vertex>,
+    /// Bounds of all points as [minx, miny, maxx, maxy].
+    pub bounds: [f32; 4],
+}
+
+fn pt_equals(x1: f32, y1: f32, x2: f32, y2: f32, tol: f32) -> bool {
+    let dx = x2 - x1;
+    let dy = y2 - y1;
+    dx * dx + dy * dy < tol * tol
+}
+
+/// Squared distance from point (x,y) to segment (px,py)-(qx,qy).
+pub fn dist_pt_seg(x: f32, y: f32, px: f32, py: f32, qx: f32, qy: f32) -> f32 {
+    let pqx = qx - px;
+    let pqy = qy - py;
+    let d = pqx * pqx + pqy * pqy;
+    let mut t = pqx * (x - px) + pqy * (y - py);
+    if d > 0.0 {
+        t /= d;
+    }
+    let t = t.clamp(0.0, 1.0);
+    let dx = px + t * pqx - x;
+    let dy = py + t * pqy - y;
+    dx * dx + dy * dy
+}
+
+fn poly_area(points: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for i in 2..points.len() {
+        let a = &points[0];
+        let b = &points[i - 1];
+        let c = &points[i];
+        area += (c.x - a.x) * (b.y - a.y) - (b.x - a.x) * (c.y - a.y);
+    }
+    area * 0.5
+}
+
+/// Normalizes a vector in place and returns its original length.
+pub fn normalize(x: &mut f32, y: &mut f32) -> f32 {
+    let d = f32::sqrt(*x * *x + *y * *y);
+    if d > 1e-6 {
+        let id = 1.0 / d;
+        *x *= id;
+        *y *= id;
+    }
+    d
+}
+
+impl Default for PathCache {
+    fn default() -> Self {
+        PathCache::new()
+    }
+}
+
+impl PathCache {
+    pub fn new() -> Self {
+        PathCache{
+            points: Vec::with_capacity(128),
+            paths:  Vec::with_capacity(16),
+            verts:  Vec::with_capacity(256),
+            bounds: [0.0; 4],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.paths.clear();
+        self.verts.clear();
+    }
+
+    /// Returns true when the cache holds no flattened paths.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    fn add_path(&mut self) {
+        self.paths.push(Path{
+            first:   self.points.len(),
+            count:   0,
+            closed:  false,
+            nbevel:  0,
+            winding: Winding::CCW,
+            convex:  false,
+            fill:    0..0,
+            stroke:  0..0,
+        });
+    }
+
+    fn add_point(&mut self, x: f32, y: f32, flags: u8, dist_tol: f32) {
+        let path = match self.paths.last_mut() {
+            Some(path) => path,
+            None => return,
+        };
+
+        if path.count > 0 {
+            if let Some(pt) = self.points.last_mut() {
+                if pt_equals(pt.x, pt.y, x, y, dist_tol) {
+                    pt.flags |= flags;
+                    return;
+                }
+            }
+        }
+
+        self.points.push(Point{
+            x:     x,
+            y:     y,
+            flags: flags,
+            ..Point::default()
+        });
+        path.count += 1;
+    }
+
+    fn last_point(&self) -> Option<&Point> {
+        match self.paths.last() {
+            Some(path) if path.count > 0 => self.points.last(),
+            _ => None,
+        }
+    }
+
+    fn tesselate_bezier(&mut self,
+                        x1: f32, y1: f32, x2: f32, y2: f32,
+                        x3: f32, y3: f32, x4: f32, y4: f32,
+                        level: usize, flags: u8,
+                        tess_tol: f32, dist_tol: f32) {
+        if level > 10 {
+            return;
+        }
+
+        let x12 = (x1 + x2) * 0.5;
+        let y12 = (y1 + y2) * 0.5;
+        let x23 = (x2 + x3) * 0.5;
+        let y23 = (y2 + y3) * 0.5;
+        let x34 = (x3 + x4) * 0.5;
+        let y34 = (y3 + y4) * 0.5;
+        let x123 = (x12 + x23) * 0.5;
+        let y123 = (y12 + y23) * 0.5;
+
+        let dx = x4 - x1;
+        let dy = y4 - y1;
+        let d2 = ((x2 - x4) * dy - (y2 - y4) * dx).abs();
+        let d3 = ((x3 - x4) * dy - (y3 - y4) * dx).abs();
+
+        if (d2 + d3) * (d2 + d3) < tess_tol * (dx * dx + dy * dy) {
+            self.add_point(x4, y4, flags, dist_tol);
+            return;
+        }
+
+        let x234 = (x23 + x34) * 0.5;
+        let y234 = (y23 + y34) * 0.5;
+        let x1234 = (x123 + x234) * 0.5;
+        let y1234 = (y123 + y234) * 0.5;
+
+        self.tesselate_bezier(x1, y1, x12, y12, x123, y123, x1234, y1234, level + 1, 0, tess_tol, dist_tol);
+        self.tesselate_bezier(x1234, y1234, x234, y234, x34, y34, x4, y4, level + 1, flags, tess_tol, dist_tol);
+    }
+
+    /// Flattens the commands into polylines. Curves are subdivided until
+    /// they are within `tess_tol`, points closer than `dist_tol` are merged.
+    /// Does nothing if the cache already holds the flattened paths.
+    pub fn flatten(&mut self, commands: &[Command], tess_tol: f32, dist_tol: f32) {
+        if !self.paths.is_empty() {
+            return;
+        }
+
+        for cmd in commands {
+            match *cmd {
+                Command::MoveTo(x, y) => {
+                    self.add_path();
+                    self.add_point(x, y, PT_CORNER, dist_tol);
+                }
+                Command::LineTo(x, y) => {
+                    self.add_point(x, y, PT_CORNER, dist_tol);
+                }
+                Command::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                    if let Some(&Point{ x: lx, y: ly, .. }) = self.last_point() {
+                        self.tesselate_bezier(lx, ly, c1x, c1y, c2x, c2y, x, y, 0, PT_CORNER, tess_tol, dist_tol);
+                    }
+                }
+                Command::Close => {
+                    if let Some(path) = self.paths.last_mut() {
+                        path.closed = true;
+                    }
+                }
+                Command::Winding(dir) => {
+                    if let Some(path) = self.paths.last_mut() {
+                        path.winding = dir;
+                    }
+                }
+            }
+        }
+
+        self.bounds = [1e6, 1e6, -1e6, -1e6];
+
+        // Calculate the direction and length of line segments.
+        for path in &mut self.paths {
+            if path.count == 0 {
+                continue;
+            }
+            let pts = &mut self.points[path.first..path.first + path.count];
+
+            // If the first and last points are the same, remove the last, mark as closed path.
+            let p0 = pts[pts.len() - 1];
+            if pts.len() > 1 && pt_equals(p0.x, p0.y, pts[0].x, pts[0].y, dist_tol) {
+                path.count -= 1;
+                path.closed = true;
+            }
+            let pts = &mut pts[..path.count];
+
+            // Enforce winding.
+            if pts.len() > 2 {
+                let area = poly_area(pts);
+                if (path.winding == Winding::CCW && area < 0.0) || (path.winding == Winding::CW && area > 0.0) {
+                    pts.reverse();
+                }
+            }
+
+            let n = pts.len();
+            for i in 0..n {
+                let p1 = pts[(i + 1) % n];
+                let p0 = &mut pts[i];
+                p0.dx = p1.x - p0.x;
+                p0.dy = p1.y - p0.y;
+                p0.len = normalize(&mut p0.dx, &mut p0.dy);
+                self.bounds[0] = self.bounds[0].min(p0.x);
+                self.bounds[1] = self.bounds[1].min(p0.y);
+                self.bounds[2] = self.bounds[2].max(p0.x);
+                self.bounds[3] = self.bounds[3].max(p0.y);
+            }
+        }
+    }
+
+    /// Returns the points of a flattened path.
+    pub fn path_points(&self, path: &Path) -> &[Point] {
+        &self.points[path.first..path.first + path.count]
+    }
+
+    /// Returns the stroke triangle strip of a path.
+    pub fn stroke_verts(&self, path: &Path) -> &[Vertex] {
+        &self.verts[path.stroke.clone()]
+    }
+
+    /// Calculates the extrusion of every point and decides which joins
+    /// need to be beveled, for a stroke or fringe of half width `w`.
+    pub fn calculate_joins(&mut self, w: f32, line_join: LineJoin, miter_limit: f32) {
+        let iw = if w > 0.0 { 1.0 / w } else { 0.0 };
+
+        // Calculate which joins needs extra vertices to append, and gather vertex count.
+        for path in &mut self.paths {
+            let pts = &mut self.points[path.first..path.first + path.count];
+            let n = pts.len();
+            let mut nleft = 0;
+
+            path.nbevel = 0;
+
+            for i in 0..n {
+                let p0 = pts[(i + n - 1) % n];
+                let p1 = &mut pts[i];
+                let dlx0 = p0.dy;
+                let dly0 = -p0.dx;
+                let dlx1 = p1.dy;
+                let dly1 = -p1.dx;
+
+                // Calculate extrusions
+                p1.dmx = (dlx0 + dlx1) * 0.5;
+                p1.dmy = (dly0 + dly1) * 0.5;
+                let dmr2 = p1.dmx * p1.dmx + p1.dmy * p1.dmy;
+                if dmr2 > 0.000001 {
+                    let scale = f32::min(1.0 / dmr2, 600.0);
+                    p1.dmx *= scale;
+                    p1.dmy *= scale;
+                }
+
+                // Clear flags, but keep the corner.
+                p1.flags &= PT_CORNER;
+
+                // Keep track of left turns.
+                let cross = p1.dx * p0.dy - p0.dx * p1.dy;
+                if cross > 0.0 {
+                    nleft += 1;
+                    p1.flags |= PT_LEFT;
+                }
+
+                // Calculate if we should use bevel or miter for inner join.
+                let limit = f32::max(1.01, f32::min(p0.len, p1.len) * iw);
+                if dmr2 * limit * limit < 1.0 {
+                    p1.flags |= PT_INNERBEVEL;
+                }
+
+                // Check to see if the corner needs to be beveled.
+                if p1.flags & PT_CORNER != 0
+                    && (dmr2 * miter_limit * miter_limit < 1.0 || line_join == LineJoin::Bevel || line_join == LineJoin::Round) {
+                    p1.flags |= PT_BEVEL;
+                }
+
+                if p1.flags & (PT_BEVEL | PT_INNERBEVEL) != 0 {
+                    path.nbevel += 1;
+                }
+            }
+
+            path.convex = nleft == n;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flatten(commands: &[Command], tess_tol: f32, dist_tol: f32) -> PathCache {
+        let mut cache = PathCache::new();
+        cache.flatten(commands, tess_tol, dist_tol);
+        cache
+    }
+
+    fn coords(cache: &PathCache, path: &Path) -> Vec<(f32, f32)> {
+        cache.path_points(path).iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn transforms_commands() {
+        let mut xform = Transform::identity();
+        xform.set_translate(10.0, 20.0);
+        assert_eq!(Command::MoveTo(1.0, 2.0).transformed(&xform), Command::MoveTo(11.0, 22.0));
+        assert_eq!(Command::BezierTo(0.0, 0.0, 1.0, 1.0, 2.0, 2.0).transformed(&xform),
+                   Command::BezierTo(10.0, 20.0, 11.0, 21.0, 12.0, 22.0));
+        assert_eq!(Command::Winding(Winding::CW).transformed(&xform), Command::Winding(Winding::CW));
+    }
+
+    #[test]
+    fn flattens_lines_into_sub_paths() {
+        let cache = flatten(&[
+            Command::MoveTo(0.0, 0.0), Command::LineTo(0.0, 5.0), Command::LineTo(10.0, 5.0),
+            Command::MoveTo(20.0, 0.0), Command::LineTo(30.0, 0.0), Command::LineTo(30.0, 10.0), Command::Close,
+        ], 0.25, 0.01);
+        assert_eq!(cache.paths.len(), 2);
+        assert!(!cache.paths[0].closed);
+        assert!(cache.paths[1].closed);
+        assert_eq!(coords(&cache, &cache.paths[0]), [(0.0, 0.0), (0.0, 5.0), (10.0, 5.0)]);
+        assert_eq!(cache.bounds, [0.0, 0.0, 30.0, 10.0]);
+        let p = &cache.path_points(&cache.paths[0])[0];
+        assert_eq!((p.dx, p.dy, p.len), (0.0, 1.0, 5.0));
+        assert!(cache.path_points(&cache.paths[0]).iter().all(|p| p.flags & PT_CORNER != 0));
+    }
+
+    #[test]
+    fn merges_close_points() {
+        let cache = flatten(&[
+            Command::MoveTo(0.0, 0.0), Command::LineTo(10.0, 0.0), Command::LineTo(10.005, 0.0),
+            Command::LineTo(10.0, 10.0), Command::LineTo(0.0, 10.0), Command::LineTo(0.0, 0.005),
+        ], 0.25, 0.01);
+        // The last point is the first one again, which closes the path.
+        let path = &cache.paths[0];
+        assert!(path.closed);
+        assert_eq!(path.count, 4);
+        // Points further apart than the tolerance are kept.
+        let cache = flatten(&[Command::MoveTo(0.0, 0.0), Command::LineTo(0.02, 0.0)], 0.25, 0.01);
+        assert_eq!(cache.paths[0].count, 2);
+        assert!(!cache.paths[0].closed);
+    }
+
+    #[test]
+    fn tessellates_beziers_within_tolerance() {
+        let curve = |t: f32| {
+            let mt = 1.0 - t;
+            // Control points (0,0), (0,100), (100,100) and (100,0).
+            let (b1, b2, b3) = (3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            ((b2 + b3) * 100.0, (b1 + b2) * 100.0)
+        };
+        let commands = [Command::MoveTo(0.0, 0.0), Command::BezierTo(0.0, 100.0, 100.0, 100.0, 100.0, 0.0)];
+        let mut counts = Vec::new();
+        for &tess_tol in &[1.0, 0.25, 0.01] {
+            let cache = flatten(&commands, tess_tol, 0.01);
+            let pts = coords(&cache, &cache.paths[0]);
+            assert_eq!(pts[0], (0.0, 0.0));
+            assert_eq!(pts[pts.len() - 1], (100.0, 0.0));
+            // Only the end of the curve is a corner.
+            let flags: Vec<u8> = cache.path_points(&cache.paths[0]).iter().map(|p| p.flags).collect();
+            assert!(flags[1..flags.len() - 1].iter().all(|&f| f == 0));
+            // Every point of the curve is close to the polyline.
+            let max_dist = (0..=1000).map(|i| {
+                let (x, y) = curve(i as f32 / 1000.0);
+                pts.windows(2).map(|s| dist_pt_seg(x, y, s[0].0, s[0].1, s[1].0, s[1].1)).fold(f32::MAX, f32::min).sqrt()
+            }).fold(0.0, f32::max);
+            assert!(max_dist < tess_tol.sqrt(), "tess_tol {}: {}", tess_tol, max_dist);
+            counts.push(pts.len());
+        }
+        assert!(counts[0] < counts[1] && counts[1] < counts[2], "{:?}", counts);
+    }
+
+    #[test]
+    fn forces_winding() {
+        // Solid shapes wind counter clockwise, holes clockwise.
+        let square = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let reversed = [(10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)];
+        for &(points, winding, expected) in &[
+            (square, Winding::CCW, square), (reversed, Winding::CCW, square),
+            (square, Winding::CW, reversed), (reversed, Winding::CW, reversed),
+        ] {
+            let mut commands = vec![Command::MoveTo(points[0].0, points[0].1)];
+            commands.extend(points[1..].iter().map(|&(x, y)| Command::LineTo(x, y)));
+            commands.push(Command::Winding(winding));
+            let cache = flatten(&commands, 0.25, 0.01);
+            let path = &cache.paths[0];
+            assert_eq!(path.winding, winding);
+            assert_eq!(coords(&cache, path), expected, "{:?} {:?}", points, winding);
+            assert!((poly_area(cache.path_points(path)) > 0.0) == (winding == Winding::CCW));
+        }
+    }
+
+    #[test]
+    fn flattens_only_once() {
+        let mut cache = flatten(&[Command::MoveTo(0.0, 0.0), Command::LineTo(1.0, 0.0)], 0.25, 0.01);
+        cache.flatten(&[Command::MoveTo(5.0, 5.0), Command::LineTo(6.0, 0.0)], 0.25, 0.01);
+        assert_eq!(cache.paths.len(), 1);
+        assert_eq!(coords(&cache, &cache.paths[0]), [(0.0, 0.0), (1.0, 0.0)]);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,1232 @@
+//! Pure-Rust CPU rasterizer.
+//!
+//! `SoftwareBackend` implements `RenderBackend` and renders into an
+//! in-memory RGBA8 framebuffer, so it runs on machines without a GPU and
+//! serves as the reference for the other backends. `SoftwareContext` is
+//! a `Frontend` drawing with it.
+//!
+//! The framebuffer stores premultiplied alpha, which is what a GL
+//! framebuffer holds after blending with the default composite operation,
+//! and every composite operation is applied to premultiplied colors.
+
+use std::ops::Range;
+
+use crate::backend::{RenderBackend, RenderPath, Scissor, TextureType};
+use crate::color::Color;
+use crate::frontend::Frontend;
+use crate::math::Transform;
+use crate::paint::{premul, PaintEvaluator};
+use crate::path::Vertex;
+use crate::image::ImageRegistry;
+use crate::{CompositeOperationState, ImageFlags, ImageId, Paint};
+
+struct Image {
+    width:        usize,
+    height:       usize,
+    texture_type: TextureType,
+    flags:        ImageFlags,
+    data:         Vec<u8>,
+    /// Levels below the base image when mipmaps are generated.
+    mips:         Vec<Mip>,
+}
+
+/// A mipmap level, holding premultiplied texels.
+struct Mip {
+    width:  usize,
+    height: usize,
+    texels: Vec<[f32; 4]>,
+}
+
+impl Image {
+    /// Rebuilds the mip chain from the base image by averaging 2x2 texels,
+    /// if the image was created with `ImageFlags::GenerateMipmaps`.
+    fn build_mips(&mut self) {
+        self.mips.clear();
+        if !self.flags.contains(ImageFlags::GenerateMipmaps) {
+            return;
+        }
+        let (mut width, mut height) = (self.width, self.height);
+        while width > 1 || height > 1 {
+            let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+            let mut texels = Vec::with_capacity(w * h);
+            for y in 0..h {
+                for x in 0..w {
+                    let (x0, y0) = (x * 2, y * 2);
+                    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+                    let mut c = [0.0; 4];
+                    for &(sx, sy) in &[(x0, y0), (x1, y0), (x0, y1), (x1, y1)] {
+                        let t = level_texel(self, self.mips.len(), sx, sy);
+                        for i in 0..4 {
+                            c[i] += t[i] * 0.25;
+                        }
+                    }
+                    texels.push(c);
+                }
+            }
+            self.mips.push(Mip{ width: w, height: h, texels: texels });
+            width = w;
+            height = h;
+        }
+    }
+
+    /// Returns the width and height of a mip level, 0 being the base image.
+    fn level_size(&self, level: usize) -> (usize, usize) {
+        match level {
+            0 => (self.width, self.height),
+            _ => (self.mips[level - 1].width, self.mips[level - 1].height),
+        }
+    }
+}
+
+/// Converts an IEEE 754 half precision float to f32.
+fn f16_to_f32(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = (h >> 10) & 0x1f;
+    let mant = (h & 0x3ff) as f32;
+    match exp {
+        0 => sign * mant * (2.0f32).powi(-24),
+        31 => if mant == 0.0 { sign * f32::INFINITY } else { f32::NAN },
+        _ => sign * (1.0 + mant / 1024.0) * (2.0f32).powi(exp as i32 - 15),
+    }
+}
+
+/// Calls `f(x, y, la, lb, lc)` with the barycentric coordinates of every
+/// pixel center inside the triangle, whose vertices are in pixels. A
+/// center lying on an edge belongs to only one of the triangles sharing
+/// that edge. Returns the pixel bounds which were visited.
+fn raster_triangle<F>(a: (f32, f32), b: (f32, f32), c: (f32, f32), width: usize, height: usize,
+                      mut f: F) -> Option<(Range<usize>, Range<usize>)>
+    where F: FnMut(usize, usize, f32, f32, f32)
+{
+    let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+    if area.abs() < 1e-8 {
+        return None;
+    }
+    // Orient the triangle so the edge functions are positive inside.
+    let flipped = area < 0.0;
+    let (b, c) = if flipped { (c, b) } else { (b, c) };
+    let inv_area = 1.0 / area.abs();
+
+    let pixels = |min: f32, max: f32, size: usize| {
+        let start = (min - 0.5).ceil().max(0.0) as usize;
+        let end = ((max - 0.5).floor() + 1.0).max(0.0).min(size as f32) as usize;
+        start..end
+    };
+    let xr = pixels(a.0.min(b.0).min(c.0), a.0.max(b.0).max(c.0), width);
+    let yr = pixels(a.1.min(b.1).min(c.1), a.1.max(b.1).max(c.1), height);
+    if xr.start >= xr.end || yr.start >= yr.end {
+        return None;
+    }
+
+    let edge = |p: (f32, f32), q: (f32, f32), x: f32, y: f32| (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0);
+    let owns = |p: (f32, f32), q: (f32, f32)| q.1 > p.1 || (q.1 == p.1 && q.0 < p.0);
+    let inside = |w: f32, owned: bool| w > 0.0 || (w == 0.0 && owned);
+    let (own_bc, own_ca, own_ab) = (owns(b, c), owns(c, a), owns(a, b));
+
+    for y in yr.clone() {
+        let py = y as f32 + 0.5;
+        for x in xr.clone() {
+            let px = x as f32 + 0.5;
+            let wa = edge(b, c, px, py);
+            let wb = edge(c, a, px, py);
+            let wc = edge(a, b, px, py);
+            if inside(wa, own_bc) && inside(wb, own_ca) && inside(wc, own_ab) {
+                let (la, lb, lc) = (wa * inv_area, wb * inv_area, wc * inv_area);
+                if flipped {
+                    f(x, y, la, lc, lb);
+                } else {
+                    f(x, y, la, lb, lc);
+                }
+            }
+        }
+    }
+    Some((xr, yr))
+}
+
+/// Pixel bounds touched since the last sweep of a buffer.
+#[derive(Debug, Clone, Copy)]
+struct Dirty {
+    minx: usize,
+    maxx: usize,
+    miny: usize,
+    maxy: usize,
+}
+
+impl Dirty {
+    fn new() -> Self {
+        Dirty{
+            minx: usize::MAX,
+            maxx: 0,
+            miny: usize::MAX,
+            maxy: 0,
+        }
+    }
+
+    fn add(&mut self, bounds: Option<(Range<usize>, Range<usize>)>) {
+        if let Some((xr, yr)) = bounds {
+            self.minx = self.minx.min(xr.start);
+            self.maxx = self.maxx.max(xr.end);
+            self.miny = self.miny.min(yr.start);
+            self.maxy = self.maxy.max(yr.end);
+        }
+    }
+}
+
+/// Winding numbers of triangle fans sampled at pixel centers, the
+/// software counterpart of the GL stencil buffer.
+struct Stencil {
+    width:   usize,
+    height:  usize,
+    winding: Vec<i32>,
+    dirty:   Dirty,
+}
+
+impl Stencil {
+    fn new(width: usize, height: usize) -> Self {
+        Stencil{
+            width:   width,
+            height:  height,
+            winding: vec![0; width * height],
+            dirty:   Dirty::new(),
+        }
+    }
+
+    /// Adds a triangle fan. Front facing triangles increment the winding
+    /// and back facing ones decrement it.
+    fn add_fan(&mut self, verts: &[Vertex], ratio: f32) {
+        if verts.len() < 3 {
+            return;
+        }
+        let a = (verts[0].x * ratio, verts[0].y * ratio);
+        for pair in verts[1..].windows(2) {
+            let b = (pair[0].x * ratio, pair[0].y * ratio);
+            let c = (pair[1].x * ratio, pair[1].y * ratio);
+            let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+            let delta = if area > 0.0 { 1 } else { -1 };
+            let (width, winding) = (self.width, &mut self.winding);
+            let bounds = raster_triangle(a, b, c, width, self.height, |x, y, _, _, _| {
+                winding[y * width + x] += delta;
+            });
+            self.dirty.add(bounds);
+        }
+    }
+
+    fn is_set(&self, x: usize, y: usize) -> bool {
+        self.winding[y * self.width + x] != 0
+    }
+
+    fn clear(&mut self) {
+        for y in self.dirty.miny..self.dirty.maxy {
+            let row = y * self.width;
+            for w in &mut self.winding[row + self.dirty.minx..row + self.dirty.maxx] {
+                *w = 0;
+            }
+        }
+        self.dirty = Dirty::new();
+    }
+}
+
+/// Per-pixel coverage of triangles sampled at pixel centers.
+///
+/// Overlapping triangles keep the largest coverage, so a stroke which
+/// crosses itself is blended only once.
+struct CoverageMask {
+    width:  usize,
+    height: usize,
+    cov:    Vec<f32>,
+    dirty:  Dirty,
+}
+
+impl CoverageMask {
+    fn new(width: usize, height: usize) -> Self {
+        CoverageMask{
+            width:  width,
+            height: height,
+            cov:    vec![0.0; width * height],
+            dirty:  Dirty::new(),
+        }
+    }
+
+    /// Adds a triangle strip. Vertices are scaled by `ratio` into pixels
+    /// and `coverage(x, y, u, v)` gives the coverage of a pixel from the
+    /// interpolated texture coordinates.
+    fn add_strip<F: Fn(usize, usize, f32, f32) -> f32>(&mut self, verts: &[Vertex], ratio: f32, coverage: F) {
+        for tri in verts.windows(3) {
+            self.add_triangle(&tri[0], &tri[1], &tri[2], ratio, &coverage);
+        }
+    }
+
+    /// Adds a triangle fan, see `add_strip`.
+    fn add_fan<F: Fn(usize, usize, f32, f32) -> f32>(&mut self, verts: &[Vertex], ratio: f32, coverage: F) {
+        if verts.len() < 3 {
+            return;
+        }
+        for pair in verts[1..].windows(2) {
+            self.add_triangle(&verts[0], &pair[0], &pair[1], ratio, &coverage);
+        }
+    }
+
+    fn add_triangle<F: Fn(usize, usize, f32, f32) -> f32>(&mut self, a: &Vertex, b: &Vertex, c: &Vertex,
+                                                          ratio: f32, coverage: &F) {
+        let (width, cov) = (self.width, &mut self.cov);
+        let bounds = raster_triangle((a.x * ratio, a.y * ratio), (b.x * ratio, b.y * ratio), (c.x * ratio, c.y * ratio),
+                                     width, self.height, |x, y, la, lb, lc| {
+            let u = la * a.u + lb * b.u + lc * c.u;
+            let v = la * a.v + lb * b.v + lc * c.v;
+            let i = y * width + x;
+            cov[i] = cov[i].max(coverage(x, y, u, v));
+        });
+        self.dirty.add(bounds);
+    }
+
+    /// Fully covers every pixel set in the stencil.
+    fn cover(&mut self, stencil: &Stencil) {
+        let d = stencil.dirty;
+        for y in d.miny..d.maxy {
+            for x in d.minx..d.maxx {
+                if stencil.is_set(x, y) {
+                    self.cov[y * self.width + x] = 1.0;
+                }
+            }
+        }
+        if d.miny < d.maxy {
+            self.dirty.add(Some((d.minx..d.maxx, d.miny..d.maxy)));
+        }
+    }
+
+    /// Calls `f(x, y, coverage)` for every covered pixel, then clears the mask.
+    fn sweep<F: FnMut(usize, usize, f32)>(&mut self, mut f: F) {
+        for y in self.dirty.miny..self.dirty.maxy {
+            for x in self.dirty.minx..self.dirty.maxx {
+                let i = y * self.width + x;
+                let coverage = self.cov[i];
+                if coverage > 0.0 {
+                    self.cov[i] = 0.0;
+                    f(x, y, coverage.min(1.0));
+                }
+            }
+        }
+        self.dirty = Dirty::new();
+    }
+}
+
+/// Per-pixel shading, following the math of NanoVG's GL shader.
+struct Shader<'a> {
+    paint:         PaintEvaluator,
+    composite:     CompositeOperationState,
+    image:         Option<&'a Image>,
+    /// Level of detail of image patterns.
+    lod:           f32,
+    scissor_inv:   Transform,
+    scissor_ext:   [f32; 2],
+    scissor_scale: [f32; 2],
+}
+
+impl<'a> Shader<'a> {
+    fn new(call: &Call, images: &'a ImageRegistry<Image>, device_ratio: f32) -> Self {
+        let paint = &call.paint;
+        let image = paint.image.and_then(|id| images.get(id));
+        let evaluator = PaintEvaluator::new(paint);
+        let lod = match image {
+            Some(image) => {
+                // Texture coordinate change per pixel rather than per window unit.
+                let g = evaluator.image_coords_gradient();
+                let r = 1.0 / device_ratio;
+                lod(image, [g[0] * r, g[1] * r, g[2] * r, g[3] * r])
+            }
+            None => 0.0,
+        };
+        let (scissor_inv, scissor_ext, scissor_scale) = match call.scissor {
+            Some(ref s) => (s.inverse_xform(), s.extent, s.scale(call.fringe)),
+            None => (Transform{ m: [0.0; 6] }, [1.0, 1.0], [1.0, 1.0]),
+        };
+        Shader{
+            paint:         evaluator,
+            composite:     call.composite,
+            image:         image,
+            lod:           lod,
+            scissor_inv:   scissor_inv,
+            scissor_ext:   scissor_ext,
+            scissor_scale: scissor_scale,
+        }
+    }
+
+    /// Returns the premultiplied color of the image at normalized texture
+    /// coordinates, tinted by the inner color. `lod` is the mip level of
+    /// detail, see `lod`.
+    fn texture(&self, u: f32, v: f32, lod: f32) -> [f32; 4] {
+        match self.image {
+            Some(image) => self.paint.tint(sample(image, u, v, lod)),
+            None => self.paint.tint([1.0; 4]),
+        }
+    }
+
+    fn scissor_mask(&self, x: f32, y: f32) -> f32 {
+        let (sx, sy) = self.scissor_inv.point(x, y);
+        let sx = 0.5 - (sx.abs() - self.scissor_ext[0]) * self.scissor_scale[0];
+        let sy = 0.5 - (sy.abs() - self.scissor_ext[1]) * self.scissor_scale[1];
+        sx.clamp(0.0, 1.0) * sy.clamp(0.0, 1.0)
+    }
+
+    /// Returns the premultiplied color at point (x,y) in window space.
+    fn shade(&self, x: f32, y: f32) -> [f32; 4] {
+        match self.image {
+            Some(image) => self.paint.eval(x, y, |u, v| sample(image, u, v, self.lod)),
+            None => self.paint.gradient(x, y),
+        }
+    }
+}
+
+/// Returns the premultiplied texel of the base image at (x,y).
+fn texel(image: &Image, x: usize, y: usize) -> [f32; 4] {
+    let bpp = image.texture_type.bytes_per_pixel();
+    let i = (y * image.width + x) * bpp;
+    let d = &image.data[i..i + bpp];
+    let unorm = |b: u8| b as f32 / 255.0;
+    let mut c = match image.texture_type {
+        TextureType::Alpha => return [unorm(d[0]); 4],
+        TextureType::Luminance => return [unorm(d[0]), unorm(d[0]), unorm(d[0]), 1.0],
+        TextureType::Rgb => return [unorm(d[0]), unorm(d[1]), unorm(d[2]), 1.0],
+        TextureType::Rgba => [unorm(d[0]), unorm(d[1]), unorm(d[2]), unorm(d[3])],
+        TextureType::RgbaF16 => {
+            let mut c = [0.0; 4];
+            for (j, c) in c.iter_mut().enumerate() {
+                let v = f16_to_f32(d[j * 2] as u16 | (d[j * 2 + 1] as u16) << 8);
+                // NaN fails both comparisons and ends up as 0.
+                *c = if v > 0.0 { v.min(1.0) } else { 0.0 };
+            }
+            c
+        }
+    };
+    if !image.flags.contains(ImageFlags::Premultiplied) {
+        c[0] *= c[3];
+        c[1] *= c[3];
+        c[2] *= c[3];
+    }
+    c
+}
+
+/// Returns the premultiplied texel at (x,y) of a mip level.
+fn level_texel(image: &Image, level: usize, x: usize, y: usize) -> [f32; 4] {
+    match level {
+        0 => texel(image, x, y),
+        _ => {
+            let mip = &image.mips[level - 1];
+            mip.texels[y * mip.width + x]
+        }
+    }
+}
+
+/// Returns the mip level of detail for texture coordinates changing by
+/// [du/dx, dv/dx, du/dy, dv/dy] per pixel, like GL computes it. Zero or
+/// less means the image is magnified.
+fn lod(image: &Image, gradient: [f32; 4]) -> f32 {
+    let (w, h) = (image.width as f32, image.height as f32);
+    let dx = (gradient[0] * w).hypot(gradient[1] * h);
+    let dy = (gradient[2] * w).hypot(gradient[3] * h);
+    dx.max(dy).log2()
+}
+
+/// Returns [du/dx, dv/dx, du/dy, dv/dy] per pixel of the texture
+/// coordinates interpolated across a triangle.
+fn triangle_uv_gradient(a: &Vertex, b: &Vertex, c: &Vertex, ratio: f32) -> [f32; 4] {
+    let (x1, y1) = ((b.x - a.x) * ratio, (b.y - a.y) * ratio);
+    let (x2, y2) = ((c.x - a.x) * ratio, (c.y - a.y) * ratio);
+    let area = x1 * y2 - x2 * y1;
+    if area.abs() < 1e-8 {
+        return [0.0; 4];
+    }
+    let (u1, v1) = (b.u - a.u, b.v - a.v);
+    let (u2, v2) = (c.u - a.u, c.v - a.v);
+    [
+        (u1 * y2 - u2 * y1) / area,
+        (v1 * y2 - v2 * y1) / area,
+        (u2 * x1 - u1 * x2) / area,
+        (v2 * x1 - v1 * x2) / area,
+    ]
+}
+
+/// How texture coordinates outside the image are mapped in one direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Wrap {
+    Clamp,
+    Repeat,
+    MirroredRepeat,
+    Border,
+}
+
+impl Wrap {
+    fn new(flags: ImageFlags, mirrored: ImageFlags, repeat: ImageFlags, border: ImageFlags) -> Self {
+        if flags.contains(mirrored) {
+            Wrap::MirroredRepeat
+        } else if flags.contains(repeat) {
+            Wrap::Repeat
+        } else if flags.contains(border) {
+            Wrap::Border
+        } else {
+            Wrap::Clamp
+        }
+    }
+
+    /// Maps texel index `i` into 0..size, `None` for the border.
+    fn apply(self, i: f32, size: usize) -> Option<usize> {
+        // Saturates for huge coordinates, which keeps the math in range.
+        let i = i as i64;
+        let n = size as i64;
+        match self {
+            Wrap::Clamp => Some(i.max(0).min(n - 1) as usize),
+            Wrap::Repeat => Some(i.rem_euclid(n) as usize),
+            Wrap::MirroredRepeat => {
+                let m = i.rem_euclid(2 * n);
+                Some(if m < n { m } else { 2 * n - 1 - m } as usize)
+            }
+            Wrap::Border => if i >= 0 && i < n { Some(i as usize) } else { None },
+        }
+    }
+}
+
+/// Returns the wrap modes of an image in X and Y.
+fn wrap_modes(image: &Image) -> (Wrap, Wrap) {
+    let f = image.flags;
+    (Wrap::new(f, ImageFlags::MirroredRepeatx, ImageFlags::Repeatx, ImageFlags::ClampToBorderx),
+     Wrap::new(f, ImageFlags::MirroredRepeaty, ImageFlags::Repeaty, ImageFlags::ClampToBordery))
+}
+
+/// Returns the texel at (x,y) of a mip level after wrapping, transparent
+/// black for the border.
+fn wrapped_texel(image: &Image, level: usize, x: f32, y: f32) -> [f32; 4] {
+    let (w, h) = image.level_size(level);
+    let (wrap_x, wrap_y) = wrap_modes(image);
+    match (wrap_x.apply(x, w), wrap_y.apply(y, h)) {
+        (Some(x), Some(y)) => level_texel(image, level, x, y),
+        _ => [0.0; 4],
+    }
+}
+
+/// Samples a mip level at normalized coordinates with the nearest texel.
+fn sample_nearest(image: &Image, level: usize, u: f32, v: f32) -> [f32; 4] {
+    let (w, h) = image.level_size(level);
+    wrapped_texel(image, level, (u * w as f32).floor(), (v * h as f32).floor())
+}
+
+/// Samples a mip level at normalized coordinates, interpolating the four
+/// nearest texels.
+fn sample_bilinear(image: &Image, level: usize, u: f32, v: f32) -> [f32; 4] {
+    let (w, h) = image.level_size(level);
+    let fx = u * w as f32 - 0.5;
+    let fy = v * h as f32 - 0.5;
+    let x0 = fx.floor();
+    let y0 = fy.floor();
+    let tx = fx - x0;
+    let ty = fy - y0;
+    let c00 = wrapped_texel(image, level, x0, y0);
+    let c10 = wrapped_texel(image, level, x0 + 1.0, y0);
+    let c01 = wrapped_texel(image, level, x0, y0 + 1.0);
+    let c11 = wrapped_texel(image, level, x0 + 1.0, y0 + 1.0);
+    let mut c = [0.0; 4];
+    for i in 0..4 {
+        let top = c00[i] + (c10[i] - c00[i]) * tx;
+        let bottom = c01[i] + (c11[i] - c01[i]) * tx;
+        c[i] = top + (bottom - top) * ty;
+    }
+    c
+}
+
+/// Samples an image at normalized coordinates with the filters NanoVG's GL
+/// backend sets up: nearest or bilinear when magnified, and when minified
+/// trilinear, or the nearest texel of the nearest mip level with
+/// `ImageFlags::Nearest`, if the image has mipmaps.
+fn sample(image: &Image, u: f32, v: f32, lod: f32) -> [f32; 4] {
+    let v = if image.flags.contains(ImageFlags::Flipy) { 1.0 - v } else { v };
+    let nearest = image.flags.contains(ImageFlags::Nearest);
+    if lod <= 0.0 || image.mips.is_empty() {
+        return if nearest { sample_nearest(image, 0, u, v) } else { sample_bilinear(image, 0, u, v) };
+    }
+    let max = image.mips.len() as f32;
+    if nearest {
+        let level = (lod + 0.5).floor().min(max) as usize;
+        return sample_nearest(image, level, u, v);
+    }
+    let lod = lod.min(max);
+    let level = lod.floor();
+    let t = lod - level;
+    let a = sample_bilinear(image, level as usize, u, v);
+    if t == 0.0 {
+        return a;
+    }
+    let b = sample_bilinear(image, level as usize + 1, u, v);
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+/// Blends a premultiplied color scaled by `mask` into the pixel at byte
+/// offset `i`.
+fn blend(pixels: &mut [u8], i: usize, src: [f32; 4], mask: f32, composite: &CompositeOperationState) {
+    let src = [src[0] * mask, src[1] * mask, src[2] * mask, src[3] * mask];
+    let mut dst = [0.0; 4];
+    for c in 0..4 {
+        dst[c] = pixels[i + c] as f32 / 255.0;
+    }
+    let out = composite.blend(src, dst);
+    for c in 0..4 {
+        pixels[i + c] = (out[c] * 255.0 + 0.5) as u8;
+    }
+}
+
+/// Shades one pixel and blends it over the framebuffer.
+fn blend_pixel(pixels: &mut [u8], width: usize, shader: &Shader, ratio: f32,
+               x: usize, y: usize, coverage: f32) {
+    let wx = (x as f32 + 0.5) / ratio;
+    let wy = (y as f32 + 0.5) / ratio;
+    let mask = coverage * shader.scissor_mask(wx, wy);
+    if mask <= 0.0 {
+        return;
+    }
+    blend(pixels, (y * width + x) * 4, shader.shade(wx, wy), mask, &shader.composite);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CallKind {
+    Fill,
+    ConvexFill,
+    Stroke,
+    Triangles,
+}
+
+/// A queued draw call. The ranges index into the path and vertex
+/// buffers of the backend.
+struct Call {
+    kind:         CallKind,
+    paint:        Paint,
+    composite:    CompositeOperationState,
+    scissor:      Option<Scissor>,
+    fringe:       f32,
+    stroke_width: f32,
+    paths:        Range<usize>,
+    triangles:    Range<usize>,
+}
+
+/// Vertex ranges of a queued path.
+struct CallPath {
+    fill:   Range<usize>,
+    stroke: Range<usize>,
+}
+
+/// A `RenderBackend` which renders into an RGBA8 framebuffer on the CPU.
+///
+/// Draw calls are queued and rendered on `flush`, so a cancelled frame
+/// leaves the framebuffer untouched.
+pub struct SoftwareBackend {
+    width:        usize,
+    height:       usize,
+    pixels:       Vec<u8>,
+    stencil:      Stencil,
+    mask:         CoverageMask,
+    images:       ImageRegistry<Image>,
+    calls:        Vec<Call>,
+    paths:        Vec<CallPath>,
+    verts:        Vec<Vertex>,
+    device_ratio: f32,
+}
+
+impl SoftwareBackend {
+    /// Creates a backend with a framebuffer of the given size in pixels,
+    /// cleared to transparent black.
+    pub fn new(width: usize, height: usize) -> Self {
+        SoftwareBackend{
+            width:        width,
+            height:       height,
+            pixels:       vec![0; width * height * 4],
+            stencil:      Stencil::new(width, height),
+            mask:         CoverageMask::new(width, height),
+            images:       ImageRegistry::new(),
+            calls:        Vec::new(),
+            paths:        Vec::new(),
+            verts:        Vec::new(),
+            device_ratio: 1.0,
+        }
+    }
+
+    /// Returns the width of the framebuffer in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the framebuffer in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the framebuffer as rows of premultiplied RGBA8 pixels.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Fills the whole framebuffer with a color.
+    pub fn clear(&mut self, color: Color) {
+        let c = premul(color);
+        let px = [
+            (c[0] * 255.0 + 0.5) as u8,
+            (c[1] * 255.0 + 0.5) as u8,
+            (c[2] * 255.0 + 0.5) as u8,
+            (c[3] * 255.0 + 0.5) as u8,
+        ];
+        for chunk in self.pixels.chunks_mut(4) {
+            chunk.copy_from_slice(&px);
+        }
+    }
+
+    fn alloc_verts(&mut self, verts: &[Vertex]) -> Range<usize> {
+        let start = self.verts.len();
+        self.verts.extend_from_slice(verts);
+        start..self.verts.len()
+    }
+
+    fn push_call(&mut self, kind: CallKind, paint: &Paint, composite: CompositeOperationState,
+                 scissor: Option<&Scissor>, fringe: f32, stroke_width: f32, paths: &[RenderPath]) {
+        let start = self.paths.len();
+        for path in paths {
+            let fill = self.alloc_verts(path.fill);
+            let stroke = self.alloc_verts(path.stroke);
+            self.paths.push(CallPath{ fill: fill, stroke: stroke });
+        }
+        self.calls.push(Call{
+            kind:         kind,
+            paint:        *paint,
+            composite:    composite,
+            scissor:      scissor.cloned(),
+            fringe:       fringe,
+            stroke_width: stroke_width,
+            paths:        start..self.paths.len(),
+            triangles:    0..0,
+        });
+    }
+
+    fn render_fill(&mut self, call: &Call) {
+        let ratio = self.device_ratio;
+        let paths = &self.paths[call.paths.clone()];
+        let verts = &self.verts;
+        let fringe_coverage = |u: f32, v: f32| (1.0 - (u * 2.0 - 1.0).abs()).min(1.0) * v.min(1.0);
+        if call.kind == CallKind::ConvexFill {
+            for path in paths {
+                self.mask.add_fan(&verts[path.fill.clone()], ratio, |_, _, _, _| 1.0);
+                self.mask.add_strip(&verts[path.stroke.clone()], ratio, |_, _, u, v| fringe_coverage(u, v));
+            }
+        } else {
+            for path in paths {
+                self.stencil.add_fan(&verts[path.fill.clone()], ratio);
+            }
+            // Draw the antialiased fringes outside of the filled area.
+            let stencil = &self.stencil;
+            for path in paths {
+                self.mask.add_strip(&verts[path.stroke.clone()], ratio, |x, y, u, v| {
+                    if stencil.is_set(x, y) { 0.0 } else { fringe_coverage(u, v) }
+                });
+            }
+            self.mask.cover(&self.stencil);
+            self.stencil.clear();
+        }
+        self.composite_mask(call);
+    }
+
+    fn render_stroke(&mut self, call: &Call) {
+        // Same coverage as the GL shader's stroke mask.
+        let stroke_mult = (call.stroke_width * 0.5 + call.fringe * 0.5) / call.fringe;
+        let coverage = |u: f32, v: f32| {
+            f32::min(1.0, (1.0 - (u * 2.0 - 1.0).abs()) * stroke_mult) * f32::min(1.0, v)
+        };
+        for path in &self.paths[call.paths.clone()] {
+            self.mask.add_strip(&self.verts[path.stroke.clone()], self.device_ratio, |_, _, u, v| coverage(u, v));
+        }
+        self.composite_mask(call);
+    }
+
+    fn render_triangles(&mut self, call: &Call) {
+        let shader = Shader::new(call, &self.images, self.device_ratio);
+        let (pixels, width, height, ratio) = (&mut self.pixels, self.width, self.height, self.device_ratio);
+        for tri in self.verts[call.triangles.clone()].chunks(3) {
+            if tri.len() < 3 {
+                break;
+            }
+            let (a, b, c) = (&tri[0], &tri[1], &tri[2]);
+            let lod = match shader.image {
+                Some(image) => lod(image, triangle_uv_gradient(a, b, c, ratio)),
+                None => 0.0,
+            };
+            raster_triangle((a.x * ratio, a.y * ratio), (b.x * ratio, b.y * ratio), (c.x * ratio, c.y * ratio),
+                            width, height, |x, y, la, lb, lc| {
+                let wx = (x as f32 + 0.5) / ratio;
+                let wy = (y as f32 + 0.5) / ratio;
+                let mask = shader.scissor_mask(wx, wy);
+                if mask > 0.0 {
+                    let u = la * a.u + lb * b.u + lc * c.u;
+                    let v = la * a.v + lb * b.v + lc * c.v;
+                    blend(pixels, (y * width + x) * 4, shader.texture(u, v, lod), mask, &shader.composite);
+                }
+            });
+        }
+    }
+
+    /// Shades the coverage collected in the triangle mask.
+    fn composite_mask(&mut self, call: &Call) {
+        let shader = Shader::new(call, &self.images, self.device_ratio);
+        let (pixels, width, ratio) = (&mut self.pixels, self.width, self.device_ratio);
+        self.mask.sweep(|x, y, coverage| {
+            blend_pixel(pixels, width, &shader, ratio, x, y, coverage);
+        });
+    }
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn create_texture(&mut self, texture_type: TextureType, width: usize, height: usize,
+                      image_flags: ImageFlags, data: Option<&[u8]>) -> Option<ImageId> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let len = width.checked_mul(height)?.checked_mul(texture_type.bytes_per_pixel())?;
+        let data = match data {
+            Some(data) if data.len() >= len => data[..len].to_vec(),
+            Some(_) => return None,
+            None => vec![0; len],
+        };
+        let mut image = Image{
+            width:        width,
+            height:       height,
+            texture_type: texture_type,
+            flags:        image_flags,
+            data:         data,
+            mips:         Vec::new(),
+        };
+        image.build_mips();
+        Some(self.images.insert(image))
+    }
+
+    fn delete_texture(&mut self, image: ImageId) -> bool {
+        self.images.remove(image).is_some()
+    }
+
+    fn update_texture(&mut self, image: ImageId, x: usize, y: usize, width: usize, height: usize,
+                      data: &[u8], stride: usize) -> bool {
+        let img = match self.images.get_mut(image) {
+            Some(img) => img,
+            None => return false,
+        };
+        let bpp = img.texture_type.bytes_per_pixel();
+        let x1 = x.saturating_add(width).min(img.width);
+        let y1 = y.saturating_add(height).min(img.height);
+        if x >= x1 || y >= y1 {
+            return true;
+        }
+        let len = (x1 - x) * bpp;
+        for row in y..y1 {
+            let src = (row - y).checked_mul(stride).and_then(|src| data.get(src..)).and_then(|src| src.get(..len));
+            let dst = (row * img.width + x) * bpp;
+            match src {
+                Some(src) => img.data[dst..dst + len].copy_from_slice(src),
+                None => break,
+            }
+        }
+        img.build_mips();
+        true
+    }
+
+    fn texture_size(&self, image: ImageId) -> Option<(usize, usize)> {
+        self.images.get(image).map(|img| (img.width, img.height))
+    }
+
+    fn texture_type(&self, image: ImageId) -> Option<TextureType> {
+        self.images.get(image).map(|img| img.texture_type)
+    }
+
+    fn viewport(&mut self, width: f32, height: f32, device_pixel_ratio: f32) {
+        self.device_ratio = device_pixel_ratio;
+    }
+
+    fn cancel(&mut self) {
+        self.calls.clear();
+        self.paths.clear();
+        self.verts.clear();
+    }
+
+    fn flush(&mut self) {
+        let calls = std::mem::take(&mut self.calls);
+        for call in &calls {
+            match call.kind {
+                CallKind::Fill | CallKind::ConvexFill => self.render_fill(call),
+                CallKind::Stroke => self.render_stroke(call),
+                CallKind::Triangles => self.render_triangles(call),
+            }
+        }
+        self.calls = calls;
+        self.cancel();
+    }
+
+    fn fill(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+            fringe: f32, bounds: [f32; 4], paths: &[RenderPath]) {
+        let kind = if paths.len() == 1 && paths[0].convex { CallKind::ConvexFill } else { CallKind::Fill };
+        self.push_call(kind, paint, composite, scissor, fringe, fringe, paths);
+    }
+
+    fn stroke(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+              fringe: f32, stroke_width: f32, paths: &[RenderPath]) {
+        self.push_call(CallKind::Stroke, paint, composite, scissor, fringe, stroke_width, paths);
+    }
+
+    fn triangles(&mut self, paint: &Paint, composite: CompositeOperationState, scissor: Option<&Scissor>,
+                 verts: &[Vertex], fringe: f32) {
+        let triangles = self.alloc_verts(verts);
+        self.calls.push(Call{
+            kind:         CallKind::Triangles,
+            paint:        *paint,
+            composite:    composite,
+            scissor:      scissor.cloned(),
+            fringe:       fringe,
+            stroke_width: 1.0,
+            paths:        0..0,
+            triangles:    triangles,
+        });
+    }
+}
+
+/// A `Context` which renders into an RGBA8 framebuffer on the CPU.
+pub type SoftwareContext = Frontend<SoftwareBackend>;
+
+impl Frontend<SoftwareBackend> {
+    /// Creates a context with a framebuffer of the given size in pixels,
+    /// cleared to transparent black.
+    pub fn new(width: usize, height: usize) -> Self {
+        Frontend::with_backend(SoftwareBackend::new(width, height))
+    }
+
+    /// Returns the width of the framebuffer in pixels.
+    pub fn width(&self) -> usize {
+        self.backend().width()
+    }
+
+    /// Returns the height of the framebuffer in pixels.
+    pub fn height(&self) -> usize {
+        self.backend().height()
+    }
+
+    /// Returns the framebuffer as rows of premultiplied RGBA8 pixels.
+    pub fn pixels(&self) -> &[u8] {
+        self.backend().pixels()
+    }
+
+    /// Fills the whole framebuffer with a color.
+    pub fn clear(&mut self, color: Color) {
+        self.backend_mut().clear(color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::ImageError;
+    use crate::{Context, Winding};
+
+    /// Returns the data of an image of a context.
+    fn image_data(ctx: &SoftwareContext, image: ImageId) -> &[u8] {
+        &ctx.backend().images.get(image).unwrap().data
+    }
+
+    #[test]
+    fn updates_strided_regions() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let image = ctx.create_image_format(3, 2, TextureType::Alpha, ImageFlags::empty(), &[0; 6]).unwrap();
+        // Two rows of two texels, with a stride of three.
+        ctx.update_image_region(image, 1, 0, 2, 2, &[1, 2, 9, 3, 4], 3).unwrap();
+        assert_eq!(image_data(&ctx, image), [0, 1, 2, 0, 3, 4]);
+        ctx.update_image_region(image, 0, 1, 1, 1, &[5], 1).unwrap();
+        assert_eq!(image_data(&ctx, image), [0, 1, 2, 5, 3, 4]);
+        // Empty regions change nothing.
+        ctx.update_image_region(image, 3, 2, 0, 0, &[], 0).unwrap();
+    }
+
+    #[test]
+    fn rejects_regions_outside_the_image() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let image = ctx.create_image_r_g_b_a(2, 2, ImageFlags::empty(), &[0; 16]).unwrap();
+        for &(x, y, w, h) in &[(1, 0, 2, 1), (0, 2, 1, 1), (usize::MAX, 0, 2, 1), (0, 1, 1, usize::MAX)] {
+            match ctx.update_image_region(image, x, y, w, h, &[0; 16], 8) {
+                Err(ImageError::InvalidRegion) => (),
+                other => panic!("({}, {}, {}, {}): {:?}", x, y, w, h, other),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_short_data_and_strides() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let image = ctx.create_image_r_g_b_a(2, 2, ImageFlags::empty(), &[0; 16]).unwrap();
+        let cases: [(usize, usize); 5] = [(15, 8), (16, 4), (16, 7), (16, usize::MAX), (16, usize::MAX / 2 + 1)];
+        for &(len, stride) in &cases {
+            match ctx.update_image_region(image, 0, 0, 2, 2, &vec![0; len], stride) {
+                Err(ImageError::InvalidSize) => (),
+                other => panic!("{} bytes, stride {}: {:?}", len, stride, other),
+            }
+        }
+        assert!(ctx.update_image_region(image, 0, 0, 2, 2, &[7; 16], 8).is_ok());
+        assert_eq!(image_data(&ctx, image), [7; 16]);
+        assert!(ctx.update_image_region(image, 0, 0, 2, 1, &[8; 8], usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn rejects_overflowing_image_sizes() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let sizes = [(usize::MAX, usize::MAX), (usize::MAX, 2), (usize::MAX / 4 + 1, 1), (1 << 40, 1 << 40)];
+        for &(w, h) in &sizes {
+            match ctx.create_image_r_g_b_a(w, h, ImageFlags::empty(), &[0; 16]) {
+                Err(ImageError::InvalidSize) => (),
+                other => panic!("{}x{}: {:?}", w, h, other),
+            }
+            assert!(ctx.backend_mut().create_texture(TextureType::Rgba, w, h, ImageFlags::empty(), None).is_none());
+        }
+        assert!(ctx.backend_mut().create_texture(TextureType::Alpha, 0, usize::MAX, ImageFlags::empty(), None).is_none());
+    }
+
+    #[test]
+    fn wrap_modes_prefer_mirrored_then_repeat_then_border() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let all = ImageFlags::MirroredRepeatx | ImageFlags::Repeatx | ImageFlags::ClampToBorderx
+            | ImageFlags::Repeaty | ImageFlags::ClampToBordery;
+        let cases = [
+            (ImageFlags::empty(),                                      (Wrap::Clamp, Wrap::Clamp)),
+            (ImageFlags::Repeatx | ImageFlags::ClampToBordery,         (Wrap::Repeat, Wrap::Border)),
+            (ImageFlags::MirroredRepeatx | ImageFlags::Repeatx,        (Wrap::MirroredRepeat, Wrap::Clamp)),
+            (ImageFlags::Repeaty | ImageFlags::ClampToBordery,         (Wrap::Clamp, Wrap::Repeat)),
+            (ImageFlags::ClampToBorderx | ImageFlags::MirroredRepeaty, (Wrap::Border, Wrap::MirroredRepeat)),
+            (all,                                                      (Wrap::MirroredRepeat, Wrap::Repeat)),
+        ];
+        for &(flags, expected) in &cases {
+            let image = ctx.create_image_r_g_b_a(1, 1, flags, &[0; 4]).unwrap();
+            assert_eq!(wrap_modes(ctx.backend().images.get(image).unwrap()), expected, "{:?}", flags);
+        }
+    }
+
+    #[test]
+    fn wraps_texel_indices() {
+        let indices = [-5.0, -1.0, 0.0, 2.0, 3.0, 7.0];
+        let cases = [
+            (Wrap::Clamp,          [Some(0), Some(0), Some(0), Some(2), Some(2), Some(2)]),
+            (Wrap::Repeat,         [Some(1), Some(2), Some(0), Some(2), Some(0), Some(1)]),
+            (Wrap::MirroredRepeat, [Some(1), Some(0), Some(0), Some(2), Some(2), Some(1)]),
+            (Wrap::Border,         [None, None, Some(0), Some(2), None, None]),
+        ];
+        for &(wrap, expected) in &cases {
+            for (&i, &e) in indices.iter().zip(&expected) {
+                assert_eq!(wrap.apply(i, 3), e, "{:?} {}", wrap, i);
+            }
+        }
+        // Huge coordinates saturate instead of overflowing.
+        assert_eq!(Wrap::Clamp.apply(f32::MAX, 3), Some(2));
+        assert_eq!(Wrap::Border.apply(f32::MIN, 3), None);
+        assert!(Wrap::Repeat.apply(f32::MAX, 3).is_some());
+        assert!(Wrap::MirroredRepeat.apply(f32::MIN, 3).is_some());
+    }
+
+    /// Creates a luminance image with mipmaps and returns it with its context.
+    fn luminance_image(width: usize, height: usize, data: &[u8]) -> (SoftwareContext, ImageId) {
+        let mut ctx = SoftwareContext::new(4, 4);
+        let image = ctx.create_image_format(width, height, TextureType::Luminance,
+                                            ImageFlags::GenerateMipmaps, data).unwrap();
+        (ctx, image)
+    }
+
+    fn assert_gray(c: [f32; 4], v: f32) {
+        assert!(c[..3].iter().all(|&c| (c - v).abs() < 1e-5) && c[3] == 1.0, "{:?} != {}", c, v);
+    }
+
+    #[test]
+    fn builds_box_filtered_mips() {
+        let data: Vec<u8> = (0..16).map(|i| i * 16).collect();
+        let (ctx, image) = luminance_image(4, 4, &data);
+        let img = ctx.backend().images.get(image).unwrap();
+        assert_eq!(img.mips.len(), 2);
+        assert_eq!((0..3).map(|l| img.level_size(l)).collect::<Vec<_>>(), [(4, 4), (2, 2), (1, 1)]);
+
+        // Each texel averages 2x2 texels of the level above.
+        let level1 = [[0, 16, 64, 80], [32, 48, 96, 112], [128, 144, 192, 208], [160, 176, 224, 240]];
+        for (i, block) in level1.iter().enumerate() {
+            let mean = block.iter().sum::<i32>() as f32 / 4.0 / 255.0;
+            assert_gray(level_texel(img, 1, i % 2, i / 2), mean);
+        }
+        assert_gray(level_texel(img, 2, 0, 0), 120.0 / 255.0);
+
+        // Odd and non-square sizes round down to at least one texel.
+        let (ctx, image) = luminance_image(5, 2, &[0; 10]);
+        let img = ctx.backend().images.get(image).unwrap();
+        assert_eq!(img.mips.len(), 2);
+        assert_eq!((0..3).map(|l| img.level_size(l)).collect::<Vec<_>>(), [(5, 2), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn minification_selects_the_level_of_detail() {
+        let (ctx, image) = luminance_image(4, 4, &[0; 16]);
+        let img = ctx.backend().images.get(image).unwrap();
+        let q = 1.0 / 4.0;
+        assert_eq!(lod(img, [q, 0.0, 0.0, q]), 0.0);
+        assert_eq!(lod(img, [2.0 * q, 0.0, 0.0, 2.0 * q]), 1.0);
+        assert_eq!(lod(img, [4.0 * q, 0.0, 0.0, q]), 2.0);
+        assert_eq!(lod(img, [0.5 * q, 0.0, 0.0, 0.5 * q]), -1.0);
+        // Rotation does not change the scale.
+        let r = q * std::f32::consts::SQRT_2;
+        assert!((lod(img, [r, r, -r, r]) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn samples_bilinearly_between_texels() {
+        let (ctx, image) = luminance_image(2, 1, &[0, 255]);
+        let img = ctx.backend().images.get(image).unwrap();
+        // Texel centers are at u 0.25 and 0.75, clamped beyond them.
+        for &(u, v) in &[(0.0, 0.0), (0.25, 0.0), (0.375, 0.25), (0.5, 0.5), (0.625, 0.75), (0.75, 1.0), (1.0, 1.0)] {
+            assert_gray(sample_bilinear(img, 0, u, 0.5), v);
+        }
+        // Magnified images use the base level, minified ones blend levels.
+        assert_gray(sample(img, 0.375, 0.5, -1.0), 0.25);
+        assert_gray(sample(img, 0.25, 0.5, 1.0), 0.5);
+        assert_gray(sample(img, 0.25, 0.5, 0.5), 0.25);
+    }
+
+    /// Returns the pixels of a 16x16 context after drawing a frame.
+    fn draw<F: FnOnce(&mut SoftwareContext)>(f: F) -> SoftwareContext {
+        let mut ctx = SoftwareContext::new(16, 16);
+        ctx.begin_frame(16.0, 16.0, 1.0);
+        ctx.fill_color(Color::rgb(255, 0, 0));
+        ctx.stroke_color(Color::rgb(255, 0, 0));
+        f(&mut ctx);
+        ctx.end_frame();
+        ctx
+    }
+
+    /// Returns the alpha of a pixel.
+    fn alpha(ctx: &SoftwareContext, x: usize, y: usize) -> u8 {
+        ctx.pixels()[(y * ctx.width() + x) * 4 + 3]
+    }
+
+    /// Returns the covered pixels of each row, as a string of `#` and `.`.
+    fn coverage(ctx: &SoftwareContext) -> Vec<String> {
+        (0..ctx.height()).map(|y| (0..ctx.width()).map(|x| if alpha(ctx, x, y) > 127 { '#' } else { '.' }).collect()).collect()
+    }
+
+    #[test]
+    fn fills_rects() {
+        let ctx = draw(|ctx| {
+            ctx.begin_path();
+            ctx.rect(4.0, 4.0, 8.0, 6.0);
+            ctx.fill();
+        });
+        for y in 0..16 {
+            for x in 0..16 {
+                let inside = (4..12).contains(&x) && (4..10).contains(&y);
+                let expected = if inside { [255, 0, 0, 255] } else { [0; 4] };
+                let i = (y * 16 + x) * 4;
+                assert_eq!(ctx.pixels()[i..i + 4], expected, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn strokes_have_their_width() {
+        let ctx = draw(|ctx| {
+            ctx.begin_path();
+            ctx.move_to(2.0, 8.0);
+            ctx.line_to(14.0, 8.0);
+            ctx.stroke_width(4.0);
+            ctx.stroke();
+        });
+        for y in 0..16 {
+            for x in 0..16 {
+                let inside = (2..14).contains(&x) && (6..10).contains(&y);
+                assert_eq!(alpha(&ctx, x, y), if inside { 255 } else { 0 }, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn scissor_clips_drawing() {
+        let ctx = draw(|ctx| {
+            ctx.scissor(2.0, 0.0, 6.0, 16.0);
+            ctx.begin_path();
+            ctx.rect(0.0, 4.0, 16.0, 8.0);
+            ctx.fill();
+        });
+        for y in 0..16 {
+            for x in 0..16 {
+                let inside = (2..8).contains(&x) && (4..12).contains(&y);
+                assert_eq!(alpha(&ctx, x, y), if inside { 255 } else { 0 }, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn cancelled_frames_draw_nothing() {
+        let mut ctx = SoftwareContext::new(16, 16);
+        ctx.begin_frame(16.0, 16.0, 1.0);
+        ctx.fill_color(Color::rgb(255, 0, 0));
+        ctx.begin_path();
+        ctx.rect(0.0, 0.0, 16.0, 16.0);
+        ctx.fill();
+        ctx.stroke();
+        ctx.cancel_frame();
+        assert!(ctx.pixels().iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn draws_convex_fills_without_the_stencil() {
+        let mut ctx = SoftwareContext::new(16, 16);
+        ctx.begin_frame(16.0, 16.0, 1.0);
+        ctx.begin_path();
+        ctx.rect(2.0, 2.0, 8.0, 8.0);
+        ctx.fill();
+        ctx.begin_path();
+        ctx.move_to(2.0, 2.0);
+        for &(x, y) in &[(2.0, 10.0), (10.0, 10.0), (10.0, 6.0), (6.0, 6.0), (6.0, 2.0)] {
+            ctx.line_to(x, y);
+        }
+        ctx.fill();
+        ctx.begin_path();
+        ctx.rect(2.0, 2.0, 3.0, 3.0);
+        ctx.rect(8.0, 8.0, 3.0, 3.0);
+        ctx.fill();
+        let kinds: Vec<CallKind> = ctx.backend().calls.iter().map(|call| call.kind).collect();
+        assert_eq!(kinds, [CallKind::ConvexFill, CallKind::Fill, CallKind::Fill]);
+        ctx.cancel_frame();
+    }
+
+    #[test]
+    fn fills_with_the_nonzero_rule() {
+        // The path crosses itself and winds twice around the middle, which
+        // the nonzero rule fills and the even-odd rule would leave empty.
+        let ctx = draw(|ctx| {
+            ctx.begin_path();
+            ctx.move_to(2.0, 2.0);
+            for &(x, y) in &[(12.0, 2.0), (12.0, 12.0), (6.0, 12.0), (6.0, 6.0), (14.0, 6.0), (14.0, 14.0), (2.0, 14.0)] {
+                ctx.line_to(x, y);
+            }
+            ctx.close_path();
+            ctx.fill();
+        });
+        let rows = coverage(&ctx);
+        assert_eq!(rows[1], "................");
+        assert_eq!(rows[4], "..##########....");
+        assert_eq!(rows[8], "..############..");
+        assert_eq!(rows[13], "..############..");
+
+        // A clockwise sub-path winds the other way and cuts a hole.
+        let ctx = draw(|ctx| {
+            ctx.begin_path();
+            ctx.rect(2.0, 2.0, 12.0, 12.0);
+            ctx.rect(6.0, 6.0, 4.0, 4.0);
+            ctx.path_winding(Winding::CW);
+            ctx.fill();
+        });
+        let rows = coverage(&ctx);
+        assert_eq!(rows[4], "..############..");
+        assert_eq!(rows[7], "..####....####..");
+    }
+}
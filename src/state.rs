@@ -0,0 +1,329 @@
+//! Render state and the stack behind `save` and `restore`.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::backend::Scissor;
+use crate::color::Color;
+use crate::math::Transform;
+use crate::{Align, CompositeOperation, CompositeOperationState, Context, FontId, LineBreaking, LineCap, LineJoin,
+            Paint, TextDirection};
+
+/// Maximum depth of the render state stack.
+pub const MAX_STATES: usize = 32;
+
+/// Errors of the render state stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateError {
+    /// `save` was called with `MAX_STATES` states already on the stack.
+    Overflow,
+    /// `restore` was called without a matching `save`.
+    Underflow,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StateError::Overflow => write!(f, "render state stack is full ({} states)", MAX_STATES),
+            StateError::Underflow => write!(f, "render state restored without a matching save"),
+        }
+    }
+}
+
+impl Error for StateError {}
+
+/// Everything which affects how paths and text are rendered.
+#[derive(Debug, Clone, Copy)]
+pub struct State {
+    pub composite:        CompositeOperationState,
+    pub shape_anti_alias: bool,
+    pub fill:             Paint,
+    pub stroke:           Paint,
+    pub stroke_width:     f32,
+    pub miter_limit:      f32,
+    pub line_join:        LineJoin,
+    pub line_cap:         LineCap,
+    pub alpha:            f32,
+    pub xform:            Transform,
+    pub scissor:          Option<Scissor>,
+    pub font_size:        f32,
+    pub letter_spacing:   f32,
+    pub line_height:      f32,
+    pub font_blur:        f32,
+    /// Horizontal text alignment, one of `Left`, `Center` or `Right`.
+    pub text_halign:      Align,
+    /// Vertical text alignment, one of `Top`, `Middle`, `Bottom` or `Baseline`.
+    pub text_valign:      Align,
+    /// The current font, `None` after selecting a font name which was not found.
+    pub font_id:          Option<FontId>,
+    /// Whether text is shaped with the layout tables of the font.
+    pub text_shaping:     bool,
+    /// Base direction of paragraphs of text.
+    pub text_direction:   TextDirection,
+    /// Rules for breaking text into rows.
+    pub line_breaking:    LineBreaking,
+    /// Whether words which do not fit in a row are hyphenated.
+    pub text_hyphenation: bool,
+}
+
+impl State {
+    /// Returns the default state, which is also what `reset` restores.
+    pub fn new() -> Self {
+        State{
+            composite:        CompositeOperation::SourceOver.into(),
+            shape_anti_alias: true,
+            fill:             Paint::color(Color::rgba(255, 255, 255, 255)),
+            stroke:           Paint::color(Color::rgba(0, 0, 0, 255)),
+            stroke_width:     1.0,
+            miter_limit:      10.0,
+            line_join:        LineJoin::Miter,
+            line_cap:         LineCap::Butt,
+            alpha:            1.0,
+            xform:            Transform::identity(),
+            scissor:          None,
+            font_size:        16.0,
+            letter_spacing:   0.0,
+            line_height:      1.0,
+            font_blur:        0.0,
+            text_halign:      Align::Left,
+            text_valign:      Align::Baseline,
+            font_id:          Some(FontId{ index: 0 }),
+            text_shaping:     false,
+            text_direction:   TextDirection::Auto,
+            line_breaking:    LineBreaking::Unicode,
+            text_hyphenation: false,
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State::new()
+    }
+}
+
+/// Stack of render states. The top of the stack is the current state,
+/// and the stack is never empty.
+#[derive(Debug, Clone)]
+pub struct StateStack {
+    states: Vec<State>,
+}
+
+impl StateStack {
+    /// Creates a stack holding only the default state.
+    pub fn new() -> Self {
+        let mut states = Vec::with_capacity(MAX_STATES);
+        states.push(State::new());
+        StateStack{
+            states: states,
+        }
+    }
+
+    /// Returns the current state.
+    pub fn current(&self) -> &State {
+        self.states.last().unwrap()
+    }
+
+    /// Returns the current state for modification.
+    pub fn current_mut(&mut self) -> &mut State {
+        self.states.last_mut().unwrap()
+    }
+
+    /// Returns the number of states on the stack, including the current one.
+    pub fn depth(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Pushes a copy of the current state. Fails without changing the
+    /// stack when it already holds `MAX_STATES` states.
+    pub fn save(&mut self) -> Result<(), StateError> {
+        if self.states.len() >= MAX_STATES {
+            return Err(StateError::Overflow);
+        }
+        let state = *self.current();
+        self.states.push(state);
+        Ok(())
+    }
+
+    /// Pops the current state. Fails without changing the stack when
+    /// there is no saved state to return to.
+    pub fn restore(&mut self) -> Result<(), StateError> {
+        if self.states.len() <= 1 {
+            return Err(StateError::Underflow);
+        }
+        self.states.pop();
+        Ok(())
+    }
+
+    /// Sets the current state to the default values.
+    pub fn reset(&mut self) {
+        *self.current_mut() = State::new();
+    }
+
+    /// Drops every saved state and resets the current one.
+    pub fn clear(&mut self) {
+        self.states.truncate(1);
+        self.reset();
+    }
+}
+
+impl Default for StateStack {
+    fn default() -> Self {
+        StateStack::new()
+    }
+}
+
+/// Saves the render state of a context and restores it when dropped.
+///
+/// The guard dereferences to the context, so it can be drawn with
+/// directly:
+///
+/// ```ignore
+/// let mut ctx = SaveGuard::new(&mut ctx)?;
+/// ctx.translate(10.0, 0.0);
+/// // The transform is restored at the end of the scope.
+/// ```
+///
+/// Only the states saved since the guard was created are restored when
+/// it is dropped, so calling `restore` through the guard is harmless.
+pub struct SaveGuard<'a, C: Context + ?Sized> {
+    ctx:   &'a mut C,
+    /// Depth of the stack before the guard saved.
+    depth: usize,
+}
+
+impl<'a, C: Context + ?Sized> SaveGuard<'a, C> {
+    /// Saves the render state of `ctx`.
+    pub fn new(ctx: &'a mut C) -> Result<Self, StateError> {
+        let depth = ctx.state_depth();
+        ctx.save()?;
+        Ok(SaveGuard{
+            ctx:   ctx,
+            depth: depth,
+        })
+    }
+}
+
+impl<'a, C: Context + ?Sized> Deref for SaveGuard<'a, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.ctx
+    }
+}
+
+impl<'a, C: Context + ?Sized> DerefMut for SaveGuard<'a, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.ctx
+    }
+}
+
+impl<'a, C: Context + ?Sized> Drop for SaveGuard<'a, C> {
+    fn drop(&mut self) {
+        // Only the states saved since the guard was created are restored,
+        // so restoring through the guard cannot pop the states of the
+        // caller, and unbalanced saves inside the scope are undone.
+        while self.ctx.state_depth() > self.depth {
+            if self.ctx.restore().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::software::SoftwareContext;
+
+    #[test]
+    fn stack_overflows_at_max_states() {
+        let mut stack = StateStack::new();
+        for _ in 1..MAX_STATES {
+            stack.save().unwrap();
+        }
+        assert_eq!(stack.depth(), MAX_STATES);
+        assert_eq!(stack.save(), Err(StateError::Overflow));
+        assert_eq!(stack.depth(), MAX_STATES);
+    }
+
+    #[test]
+    fn stack_underflows_at_depth_one() {
+        let mut stack = StateStack::new();
+        stack.current_mut().alpha = 0.5;
+        assert_eq!(stack.restore(), Err(StateError::Underflow));
+        assert_eq!(stack.depth(), 1);
+        assert_eq!(stack.current().alpha, 0.5);
+    }
+
+    #[test]
+    fn restore_returns_to_saved_state() {
+        let mut stack = StateStack::new();
+        stack.current_mut().stroke_width = 3.0;
+        stack.save().unwrap();
+        stack.current_mut().stroke_width = 5.0;
+        stack.reset();
+        assert_eq!(stack.current().stroke_width, 1.0);
+        stack.restore().unwrap();
+        assert_eq!(stack.current().stroke_width, 3.0);
+        stack.save().unwrap();
+        stack.clear();
+        assert_eq!(stack.depth(), 1);
+        assert_eq!(stack.current().stroke_width, 1.0);
+    }
+
+    #[test]
+    fn guards_nest() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        ctx.translate(1.0, 0.0);
+        {
+            let mut outer = ctx.save_guard().unwrap();
+            outer.translate(2.0, 0.0);
+            {
+                let mut inner = outer.save_guard().unwrap();
+                inner.translate(4.0, 0.0);
+                assert_eq!(inner.state_depth(), 3);
+                assert_eq!(inner.current_transform().m[4], 7.0);
+            }
+            assert_eq!(outer.state_depth(), 2);
+            assert_eq!(outer.current_transform().m[4], 3.0);
+        }
+        assert_eq!(ctx.state_depth(), 1);
+        assert_eq!(ctx.current_transform().m[4], 1.0);
+    }
+
+    #[test]
+    fn guard_only_restores_its_own_states() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        ctx.save().unwrap();
+        ctx.translate(1.0, 0.0);
+        {
+            let mut guard = ctx.save_guard().unwrap();
+            guard.translate(2.0, 0.0);
+            // Restoring through the guard must not let it pop the outer state.
+            guard.restore().unwrap();
+        }
+        assert_eq!(ctx.state_depth(), 2);
+        assert_eq!(ctx.current_transform().m[4], 1.0);
+        {
+            // Unbalanced saves inside the scope are undone.
+            let mut guard = ctx.save_guard().unwrap();
+            guard.save().unwrap();
+            guard.save().unwrap();
+        }
+        assert_eq!(ctx.state_depth(), 2);
+        ctx.restore().unwrap();
+        assert_eq!(ctx.restore(), Err(StateError::Underflow));
+    }
+
+    #[test]
+    fn guard_reports_overflow() {
+        let mut ctx = SoftwareContext::new(4, 4);
+        for _ in 1..MAX_STATES {
+            ctx.save().unwrap();
+        }
+        assert_eq!(ctx.save_guard().err(), Some(StateError::Overflow));
+        assert_eq!(ctx.state_depth(), MAX_STATES);
+    }
+}
@@ -0,0 +1,414 @@
+//! Stroke expansion.
+//!
+//! Turns the flattened paths of a `PathCache` into triangle strips
+//! covering the stroke, including caps, joins and the antialiasing
+//! fringe along both edges.
+
+use std::f32::consts::PI;
+
+use crate::path::{normalize, PathCache, Point, Vertex, PT_BEVEL, PT_INNERBEVEL, PT_LEFT};
+use crate::{LineCap, LineJoin};
+
+/// Number of segments needed to approximate an arc of given radius
+/// within the tolerance.
+pub fn curve_divs(r: f32, arc: f32, tol: f32) -> usize {
+    let da = f32::acos(r / (r + tol)) * 2.0;
+    usize::max(2, (arc / da).ceil() as usize)
+}
+
+fn choose_bevel(bevel: bool, p0: &Point, p1: &Point, w: f32) -> (f32, f32, f32, f32) {
+    if bevel {
+        (p1.x + p0.dy * w, p1.y - p0.dx * w, p1.x + p1.dy * w, p1.y - p1.dx * w)
+    } else {
+        (p1.x + p1.dmx * w, p1.y + p1.dmy * w, p1.x + p1.dmx * w, p1.y + p1.dmy * w)
+    }
+}
+
+fn round_join(dst: &mut Vec<Vertex>, p0: &Point, p1: &Point,
+              lw: f32, rw: f32, lu: f32, ru: f32, ncap: usize) {
+    let dlx0 = p0.dy;
+    let dly0 = -p0.dx;
+    let dlx1 = p1.dy;
+    let dly1 = -p1.dx;
+
+    if p1.flags & PT_LEFT != 0 {
+        let (lx0, ly0, lx1, ly1) = choose_bevel(p1.flags & PT_INNERBEVEL != 0, p0, p1, lw);
+        let a0 = f32::atan2(-dly0, -dlx0);
+        let mut a1 = f32::atan2(-dly1, -dlx1);
+        if a1 > a0 {
+            a1 -= PI * 2.0;
+        }
+
+        dst.push(Vertex::new(lx0, ly0, lu, 1.0));
+        dst.push(Vertex::new(p1.x - dlx0 * rw, p1.y - dly0 * rw, ru, 1.0));
+
+        let n = ((((a0 - a1) / PI) * ncap as f32).ceil() as usize).max(2).min(ncap);
+        for i in 0..n {
+            let u = i as f32 / (n - 1) as f32;
+            let a = a0 + u * (a1 - a0);
+            let rx = p1.x + a.cos() * rw;
+            let ry = p1.y + a.sin() * rw;
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+            dst.push(Vertex::new(rx, ry, ru, 1.0));
+        }
+
+        dst.push(Vertex::new(lx1, ly1, lu, 1.0));
+        dst.push(Vertex::new(p1.x - dlx1 * rw, p1.y - dly1 * rw, ru, 1.0));
+    } else {
+        let (rx0, ry0, rx1, ry1) = choose_bevel(p1.flags & PT_INNERBEVEL != 0, p0, p1, -rw);
+        let a0 = f32::atan2(dly0, dlx0);
+        let mut a1 = f32::atan2(dly1, dlx1);
+        if a1 < a0 {
+            a1 += PI * 2.0;
+        }
+
+        dst.push(Vertex::new(p1.x + dlx0 * rw, p1.y + dly0 * rw, lu, 1.0));
+        dst.push(Vertex::new(rx0, ry0, ru, 1.0));
+
+        let n = ((((a1 - a0) / PI) * ncap as f32).ceil() as usize).max(2).min(ncap);
+        for i in 0..n {
+            let u = i as f32 / (n - 1) as f32;
+            let a = a0 + u * (a1 - a0);
+            let lx = p1.x + a.cos() * lw;
+            let ly = p1.y + a.sin() * lw;
+            dst.push(Vertex::new(lx, ly, lu, 1.0));
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+        }
+
+        dst.push(Vertex::new(p1.x + dlx1 * rw, p1.y + dly1 * rw, lu, 1.0));
+        dst.push(Vertex::new(rx1, ry1, ru, 1.0));
+    }
+}
+
+/// Emits a beveled join, or a miter whose inner side is beveled.
+pub fn bevel_join(dst: &mut Vec<Vertex>, p0: &Point, p1: &Point,
+                  lw: f32, rw: f32, lu: f32, ru: f32) {
+    let dlx0 = p0.dy;
+    let dly0 = -p0.dx;
+    let dlx1 = p1.dy;
+    let dly1 = -p1.dx;
+
+    if p1.flags & PT_LEFT != 0 {
+        let (lx0, ly0, lx1, ly1) = choose_bevel(p1.flags & PT_INNERBEVEL != 0, p0, p1, lw);
+
+        dst.push(Vertex::new(lx0, ly0, lu, 1.0));
+        dst.push(Vertex::new(p1.x - dlx0 * rw, p1.y - dly0 * rw, ru, 1.0));
+
+        if p1.flags & PT_BEVEL != 0 {
+            dst.push(Vertex::new(lx0, ly0, lu, 1.0));
+            dst.push(Vertex::new(p1.x - dlx0 * rw, p1.y - dly0 * rw, ru, 1.0));
+
+            dst.push(Vertex::new(lx1, ly1, lu, 1.0));
+            dst.push(Vertex::new(p1.x - dlx1 * rw, p1.y - dly1 * rw, ru, 1.0));
+        } else {
+            let rx0 = p1.x - p1.dmx * rw;
+            let ry0 = p1.y - p1.dmy * rw;
+
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+            dst.push(Vertex::new(p1.x - dlx0 * rw, p1.y - dly0 * rw, ru, 1.0));
+
+            dst.push(Vertex::new(rx0, ry0, ru, 1.0));
+            dst.push(Vertex::new(rx0, ry0, ru, 1.0));
+
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+            dst.push(Vertex::new(p1.x - dlx1 * rw, p1.y - dly1 * rw, ru, 1.0));
+        }
+
+        dst.push(Vertex::new(lx1, ly1, lu, 1.0));
+        dst.push(Vertex::new(p1.x - dlx1 * rw, p1.y - dly1 * rw, ru, 1.0));
+    } else {
+        let (rx0, ry0, rx1, ry1) = choose_bevel(p1.flags & PT_INNERBEVEL != 0, p0, p1, -rw);
+
+        dst.push(Vertex::new(p1.x + dlx0 * lw, p1.y + dly0 * lw, lu, 1.0));
+        dst.push(Vertex::new(rx0, ry0, ru, 1.0));
+
+        if p1.flags & PT_BEVEL != 0 {
+            dst.push(Vertex::new(p1.x + dlx0 * lw, p1.y + dly0 * lw, lu, 1.0));
+            dst.push(Vertex::new(rx0, ry0, ru, 1.0));
+
+            dst.push(Vertex::new(p1.x + dlx1 * lw, p1.y + dly1 * lw, lu, 1.0));
+            dst.push(Vertex::new(rx1, ry1, ru, 1.0));
+        } else {
+            let lx0 = p1.x + p1.dmx * lw;
+            let ly0 = p1.y + p1.dmy * lw;
+
+            dst.push(Vertex::new(p1.x + dlx0 * lw, p1.y + dly0 * lw, lu, 1.0));
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+
+            dst.push(Vertex::new(lx0, ly0, lu, 1.0));
+            dst.push(Vertex::new(lx0, ly0, lu, 1.0));
+
+            dst.push(Vertex::new(p1.x + dlx1 * lw, p1.y + dly1 * lw, lu, 1.0));
+            dst.push(Vertex::new(p1.x, p1.y, 0.5, 1.0));
+        }
+
+        dst.push(Vertex::new(p1.x + dlx1 * lw, p1.y + dly1 * lw, lu, 1.0));
+        dst.push(Vertex::new(rx1, ry1, ru, 1.0));
+    }
+}
+
+fn butt_cap_start(dst: &mut Vec<Vertex>, p: &Point, dx: f32, dy: f32, w: f32, d: f32,
+                  aa: f32, u0: f32, u1: f32) {
+    let px = p.x - dx * d;
+    let py = p.y - dy * d;
+    let dlx = dy;
+    let dly = -dx;
+    dst.push(Vertex::new(px + dlx * w - dx * aa, py + dly * w - dy * aa, u0, 0.0));
+    dst.push(Vertex::new(px - dlx * w - dx * aa, py - dly * w - dy * aa, u1, 0.0));
+    dst.push(Vertex::new(px + dlx * w, py + dly * w, u0, 1.0));
+    dst.push(Vertex::new(px - dlx * w, py - dly * w, u1, 1.0));
+}
+
+fn butt_cap_end(dst: &mut Vec<Vertex>, p: &Point, dx: f32, dy: f32, w: f32, d: f32,
+                aa: f32, u0: f32, u1: f32) {
+    let px = p.x + dx * d;
+    let py = p.y + dy * d;
+    let dlx = dy;
+    let dly = -dx;
+    dst.push(Vertex::new(px + dlx * w, py + dly * w, u0, 1.0));
+    dst.push(Vertex::new(px - dlx * w, py - dly * w, u1, 1.0));
+    dst.push(Vertex::new(px + dlx * w + dx * aa, py + dly * w + dy * aa, u0, 0.0));
+    dst.push(Vertex::new(px - dlx * w + dx * aa, py - dly * w + dy * aa, u1, 0.0));
+}
+
+fn round_cap_start(dst: &mut Vec<Vertex>, p: &Point, dx: f32, dy: f32, w: f32, ncap: usize,
+                   u0: f32, u1: f32) {
+    let dlx = dy;
+    let dly = -dx;
+    for i in 0..ncap {
+        let a = i as f32 / (ncap - 1) as f32 * PI;
+        let ax = a.cos() * w;
+        let ay = a.sin() * w;
+        dst.push(Vertex::new(p.x - dlx * ax - dx * ay, p.y - dly * ax - dy * ay, u0, 1.0));
+        dst.push(Vertex::new(p.x, p.y, 0.5, 1.0));
+    }
+    dst.push(Vertex::new(p.x + dlx * w, p.y + dly * w, u0, 1.0));
+    dst.push(Vertex::new(p.x - dlx * w, p.y - dly * w, u1, 1.0));
+}
+
+fn round_cap_end(dst: &mut Vec<Vertex>, p: &Point, dx: f32, dy: f32, w: f32, ncap: usize,
+                 u0: f32, u1: f32) {
+    let dlx = dy;
+    let dly = -dx;
+    dst.push(Vertex::new(p.x + dlx * w, p.y + dly * w, u0, 1.0));
+    dst.push(Vertex::new(p.x - dlx * w, p.y - dly * w, u1, 1.0));
+    for i in 0..ncap {
+        let a = i as f32 / (ncap - 1) as f32 * PI;
+        let ax = a.cos() * w;
+        let ay = a.sin() * w;
+        dst.push(Vertex::new(p.x, p.y, 0.5, 1.0));
+        dst.push(Vertex::new(p.x - dlx * ax + dx * ay, p.y - dly * ax + dy * ay, u0, 1.0));
+    }
+}
+
+impl PathCache {
+    /// Expands every flattened path into a stroke triangle strip of half
+    /// width `w`. A `fringe` of zero disables the antialiasing gradient.
+    pub fn expand_stroke(&mut self, w: f32, fringe: f32, line_cap: LineCap, line_join: LineJoin,
+                         miter_limit: f32, tess_tol: f32) {
+        let aa = fringe;
+        let (u0, u1) = if aa == 0.0 {
+            // Disable the gradient used for antialiasing when antialiasing is not used.
+            (0.5, 0.5)
+        } else {
+            (0.0, 1.0)
+        };
+        // Calculate divisions per half circle.
+        let ncap = curve_divs(w, PI, tess_tol);
+        let w = w + aa * 0.5;
+
+        self.calculate_joins(w, line_join, miter_limit);
+        self.verts.clear();
+
+        for i in 0..self.paths.len() {
+            let path = self.paths[i].clone();
+            let pts = &self.points[path.first..path.first + path.count];
+            let dst = &mut self.verts;
+            let start = dst.len();
+
+            self.paths[i].fill = start..start;
+            if pts.len() < 2 {
+                self.paths[i].stroke = start..start;
+                continue;
+            }
+
+            let n = pts.len();
+            let looped = path.closed;
+            let (mut p0, mut p1, s, e) = if looped {
+                (n - 1, 0, 0, n)
+            } else {
+                (0, 1, 1, n - 1)
+            };
+
+            if !looped {
+                // Add cap
+                let mut dx = pts[p1].x - pts[p0].x;
+                let mut dy = pts[p1].y - pts[p0].y;
+                normalize(&mut dx, &mut dy);
+                match line_cap {
+                    LineCap::Butt => butt_cap_start(dst, &pts[p0], dx, dy, w, -aa * 0.5, aa, u0, u1),
+                    LineCap::Square => butt_cap_start(dst, &pts[p0], dx, dy, w, w - aa, aa, u0, u1),
+                    LineCap::Round => round_cap_start(dst, &pts[p0], dx, dy, w, ncap, u0, u1),
+                }
+            }
+
+            for _ in s..e {
+                let (a, b) = (&pts[p0], &pts[p1]);
+                if b.flags & (PT_BEVEL | PT_INNERBEVEL) != 0 {
+                    if line_join == LineJoin::Round {
+                        round_join(dst, a, b, w, w, u0, u1, ncap);
+                    } else {
+                        bevel_join(dst, a, b, w, w, u0, u1);
+                    }
+                } else {
+                    dst.push(Vertex::new(b.x + b.dmx * w, b.y + b.dmy * w, u0, 1.0));
+                    dst.push(Vertex::new(b.x - b.dmx * w, b.y - b.dmy * w, u1, 1.0));
+                }
+                p0 = p1;
+                p1 += 1;
+            }
+
+            if looped {
+                // Loop it
+                let (v0, v1) = (dst[start], dst[start + 1]);
+                dst.push(Vertex::new(v0.x, v0.y, u0, 1.0));
+                dst.push(Vertex::new(v1.x, v1.y, u1, 1.0));
+            } else {
+                // Add cap
+                let mut dx = pts[p1].x - pts[p0].x;
+                let mut dy = pts[p1].y - pts[p0].y;
+                normalize(&mut dx, &mut dy);
+                match line_cap {
+                    LineCap::Butt => butt_cap_end(dst, &pts[p1], dx, dy, w, -aa * 0.5, aa, u0, u1),
+                    LineCap::Square => butt_cap_end(dst, &pts[p1], dx, dy, w, w - aa, aa, u0, u1),
+                    LineCap::Round => round_cap_end(dst, &pts[p1], dx, dy, w, ncap, u0, u1),
+                }
+            }
+
+            let end = dst.len();
+            self.paths[i].stroke = start..end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{Command, PT_CORNER};
+
+    /// Strokes a polyline and returns its cache.
+    fn stroke(points: &[(f32, f32)], closed: bool, w: f32, fringe: f32, cap: LineCap, join: LineJoin,
+              miter_limit: f32) -> PathCache {
+        let mut commands = vec![Command::MoveTo(points[0].0, points[0].1)];
+        commands.extend(points[1..].iter().map(|&(x, y)| Command::LineTo(x, y)));
+        if closed {
+            commands.push(Command::Close);
+        }
+        let mut cache = PathCache::new();
+        cache.flatten(&commands, 0.25, 0.01);
+        cache.expand_stroke(w, fringe, cap, join, miter_limit, 0.25);
+        cache
+    }
+
+    /// Returns the x extent of the vertices of the first path.
+    fn x_extent(cache: &PathCache) -> (f32, f32) {
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        (verts.iter().map(|v| v.x).fold(f32::MAX, f32::min), verts.iter().map(|v| v.x).fold(f32::MIN, f32::max))
+    }
+
+    const LINE: [(f32, f32); 2] = [(0.0, 0.0), (10.0, 0.0)];
+    const CORNER: [(f32, f32); 3] = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+
+    #[test]
+    fn divides_curves_by_tolerance() {
+        assert_eq!(curve_divs(1.0, PI, 0.25), 3);
+        assert_eq!(curve_divs(10.0, PI, 0.25), 8);
+        assert_eq!(curve_divs(0.01, PI, 0.25), 2);
+        assert!(curve_divs(100.0, PI, 0.25) > curve_divs(10.0, PI, 0.25));
+    }
+
+    #[test]
+    fn expands_caps() {
+        // Butt caps end at the end points, square caps half the width past them.
+        let cache = stroke(&LINE, false, 2.0, 0.0, LineCap::Butt, LineJoin::Miter, 10.0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 8);
+        assert_eq!(verts[2], Vertex::new(0.0, -2.0, 0.5, 1.0));
+        assert_eq!(verts[3], Vertex::new(0.0, 2.0, 0.5, 1.0));
+        assert_eq!(x_extent(&cache), (0.0, 10.0));
+        let cache = stroke(&LINE, false, 2.0, 0.0, LineCap::Square, LineJoin::Miter, 10.0);
+        assert_eq!(cache.stroke_verts(&cache.paths[0]).len(), 8);
+        assert_eq!(x_extent(&cache), (-2.0, 12.0));
+
+        // Round caps are half circles of curve_divs segments.
+        let cache = stroke(&LINE, false, 10.0, 0.0, LineCap::Round, LineJoin::Miter, 10.0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 2 * (2 * 8 + 2));
+        let (min, max) = x_extent(&cache);
+        assert!(min > -10.0 - 1e-4 && min < -9.5 && max < 20.0 + 1e-4 && max > 19.5, "{} {}", min, max);
+        for v in verts {
+            let d = (v.x.clamp(0.0, 10.0) - v.x).hypot(v.y);
+            assert!(d < 10.0 + 1e-4, "{:?}", v);
+        }
+    }
+
+    #[test]
+    fn antialiased_caps_have_a_fringe() {
+        // The stroke grows by half the fringe, which fades out past the ends.
+        let cache = stroke(&LINE, false, 2.0, 1.0, LineCap::Butt, LineJoin::Miter, 10.0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts[0], Vertex::new(-0.5, -2.5, 0.0, 0.0));
+        assert_eq!(verts[1], Vertex::new(-0.5, 2.5, 1.0, 0.0));
+        assert_eq!(verts[2], Vertex::new(0.5, -2.5, 0.0, 1.0));
+        assert_eq!(verts[7], Vertex::new(10.5, 2.5, 1.0, 0.0));
+    }
+
+    #[test]
+    fn miters_fall_back_to_bevels() {
+        // A right angle has a miter length of sqrt(2) times the width.
+        let cache = stroke(&CORNER, false, 2.0, 0.0, LineCap::Butt, LineJoin::Miter, 1.5);
+        assert_eq!(cache.points[1].flags & PT_BEVEL, 0);
+        assert_ne!(cache.points[1].flags & PT_CORNER, 0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 4 + 2 + 4);
+        assert!(verts[4..6].contains(&Vertex::new(12.0, -2.0, 0.5, 1.0)));
+        assert!(verts[4..6].contains(&Vertex::new(8.0, 2.0, 0.5, 1.0)));
+
+        let cache = stroke(&CORNER, false, 2.0, 0.0, LineCap::Butt, LineJoin::Miter, 1.4);
+        assert_ne!(cache.points[1].flags & PT_BEVEL, 0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 4 + 8 + 4);
+        // The outer corner is cut between the ends of both segments.
+        assert!(verts.contains(&Vertex::new(10.0, -2.0, 0.5, 1.0)));
+        assert!(verts.contains(&Vertex::new(12.0, 0.0, 0.5, 1.0)));
+        assert!(!verts.iter().any(|v| v.x > 12.0 || v.y < -2.0 || (v.x > 10.0 && v.y < 0.0)));
+
+        // Bevel joins are always beveled.
+        let cache = stroke(&CORNER, false, 2.0, 0.0, LineCap::Butt, LineJoin::Bevel, 10.0);
+        assert_eq!(cache.stroke_verts(&cache.paths[0]).len(), 4 + 8 + 4);
+    }
+
+    #[test]
+    fn round_joins_have_curve_divs_vertices() {
+        // A quarter turn takes half of the 8 divisions of a half circle.
+        let cache = stroke(&CORNER, false, 10.0, 0.0, LineCap::Butt, LineJoin::Round, 10.0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 4 + (2 + 2 * 4 + 2) + 4);
+        for v in &verts[4..verts.len() - 4] {
+            assert!(((v.x - 10.0).hypot(v.y) - 10.0).abs() < 1e-4 || (v.x, v.y) == (10.0, 0.0), "{:?}", v);
+        }
+        // A sharper turn takes more, but at most a half circle.
+        let cache = stroke(&[(0.0, 0.0), (10.0, 0.0), (0.0, 1.0)], false, 10.0, 0.0, LineCap::Butt, LineJoin::Round, 10.0);
+        assert_eq!(cache.stroke_verts(&cache.paths[0]).len(), 4 + (2 + 2 * 8 + 2) + 4);
+    }
+
+    #[test]
+    fn closed_paths_loop_without_caps() {
+        let square = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let cache = stroke(&square, true, 1.0, 0.0, LineCap::Round, LineJoin::Miter, 10.0);
+        let verts = cache.stroke_verts(&cache.paths[0]);
+        assert_eq!(verts.len(), 4 * 2 + 2);
+        assert_eq!((verts[8].x, verts[8].y), (verts[0].x, verts[0].y));
+        assert_eq!(x_extent(&cache), (-1.0, 11.0));
+    }
+}